@@ -58,6 +58,8 @@ interface RouteStationInfo {
   arrivalTime: number;
   // 离开该站点时间，用“距离始发时间的秒数”记录，始发站为 0，终到站为 0
   depatureTime: number;
+  // 距上一停靠站的里程（公里），始发站为 0；缺省为 0，此时按经停站数计价
+  distance?: number;
 }
 
 interface TrainNumberInfo {
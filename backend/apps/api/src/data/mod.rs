@@ -6,6 +6,7 @@ pub mod train;
 pub fn scoped_config(cfg: &mut web::ServiceConfig) {
     cfg.service(train::load_city_data)
         .service(train::load_station_data)
+        .service(train::load_intra_city_transfer_data)
         .service(train::load_train_type_data)
         .service(train::load_train_number_data)
         .service(train::load_dish_takeaway_data)
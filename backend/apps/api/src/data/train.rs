@@ -2,8 +2,8 @@ use crate::{ApiResponse, ApplicationErrorBox, parse_request_body};
 use actix_web::post;
 use actix_web::web::{Bytes, Data};
 use base::application::commands::train_data::{
-    LoadCityCommand, LoadDishTakeawayCommand, LoadStationCommand, LoadTrainNumberCommand,
-    LoadTrainTypeCommand,
+    LoadCityCommand, LoadDishTakeawayCommand, LoadIntraCityTransferCommand, LoadStationCommand,
+    LoadTrainNumberCommand, LoadTrainTypeCommand,
 };
 use base::application::service::train_data::TrainDataService;
 
@@ -31,6 +31,18 @@ async fn load_station_data(
     ApiResponse::ok(())
 }
 
+#[post("/intra_city_transfer")]
+async fn load_intra_city_transfer_data(
+    body: Bytes,
+    train_data_service: Data<dyn TrainDataService>,
+) -> Result<ApiResponse<()>, ApplicationErrorBox> {
+    let data: LoadIntraCityTransferCommand = parse_request_body(body)?;
+
+    train_data_service.load_intra_city_transfer(data).await?;
+
+    ApiResponse::ok(())
+}
+
 #[post("/train_type")]
 async fn load_train_type_data(
     body: Bytes,
@@ -1,4 +1,6 @@
-use crate::{ApiResponse, ApplicationErrorBox, get_session_id, parse_request_body};
+use crate::{
+    ApiResponse, ApplicationErrorBox, get_coupon_code, get_session_id, parse_request_body,
+};
 use actix_web::web::Bytes;
 use actix_web::{HttpRequest, post, web};
 use base::application::{
@@ -14,6 +16,7 @@ pub struct TransactionInfoDTO {
     pub transaction_id: String,
     pub amount: f64,
     pub status: String,
+    pub payment_deadline: Option<String>,
 }
 
 /// 创建酒店预订订单
@@ -23,6 +26,8 @@ pub struct TransactionInfoDTO {
 /// 提交订单后，订单为"未支付"状态，本接口将返回`TransactionInfo`，
 /// 需要根据`TransactionInfo`中的信息调用`支付订单`接口进行支付。
 /// 支付后订单才会真正被处理。
+///
+/// 可通过查询参数`couponCode`使用优惠券，返回的金额已扣除优惠金额。
 #[post("/order")]
 pub async fn create_hotel_order(
     req: HttpRequest,
@@ -33,7 +38,7 @@ pub async fn create_hotel_order(
     let hotel_orders: HotelOrderRequestsDTO = parse_request_body(body)?;
 
     let transaction_result = hotel_order_service
-        .process_hotel_orders(session_id, hotel_orders)
+        .process_hotel_orders(session_id, hotel_orders, get_coupon_code(&req))
         .await?;
 
     Ok(ApiResponse {
@@ -43,6 +48,7 @@ pub async fn create_hotel_order(
             transaction_id: transaction_result.transaction_id.to_string(),
             amount: transaction_result.amount,
             status: "unpaid".to_string(),
+            payment_deadline: transaction_result.payment_deadline,
         }),
     })
 }
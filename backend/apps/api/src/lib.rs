@@ -19,18 +19,22 @@ pub mod dish;
 
 use actix_web::body::BoxBody;
 use actix_web::http::header::ContentType;
-use actix_web::web::Bytes;
+use actix_web::web::Data;
+use actix_web::web::{Bytes, Query};
 use actix_web::{HttpRequest, HttpResponse, Responder, ResponseError};
-use base::application::ApplicationError;
+use base::application::commands::idempotency::BeginIdempotentRequestCommand;
+use base::application::service::idempotency::{IdempotencyApplicationService, IdempotentRequest};
+use base::application::{ApplicationError, GeneralError};
 use dyn_fmt::AsStrFormatExt;
-use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use shared::{
     API_BAD_REQUEST_MESSAGE_TEMPLATE, API_FORBIDDEN_CODE, API_FORBIDDEN_MESSAGE_TEMPLATE,
     API_SUCCESS_CODE, API_SUCCESS_MESSAGE,
 };
 use std::fmt::{Debug, Display, Formatter};
 use thiserror::Error;
+use tracing::error;
 
 pub const MAX_BODY_LENGTH: usize = 5 * 1024 * 1024 * 1024;
 
@@ -184,6 +188,20 @@ pub fn get_session_id(request: &HttpRequest) -> Result<String, Box<dyn Applicati
     }
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CouponCodeQuery {
+    coupon_code: Option<String>,
+}
+
+/// 从查询参数`couponCode`中获取优惠码，未提供时返回`None`
+pub fn get_coupon_code(request: &HttpRequest) -> Option<String> {
+    Query::<CouponCodeQuery>::from_query(request.query_string())
+        .ok()
+        .and_then(|query| query.into_inner().coupon_code)
+        .filter(|code| !code.is_empty())
+}
+
 #[derive(Error, Debug)]
 pub enum ParseRequestBodyError {
     #[error("invalid request body")]
@@ -206,3 +224,85 @@ pub fn parse_request_body<T: DeserializeOwned>(
     serde_json::from_slice(&raw_body)
         .map_err(|e| Box::new(ParseRequestBodyError::InvalidBody(e)) as Box<dyn ApplicationError>)
 }
+
+/// 客户端提供幂等键的请求头
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// 按`Idempotency-Key`请求头幂等地处理请求，未提供幂等键时直接处理
+///
+/// - 首次请求：处理请求，成功时保存响应数据，失败时释放幂等键以便客户端重试
+/// - 相同幂等键的重复请求：不再处理，直接返回首次请求的响应
+/// - 相同幂等键但请求方法、路径、查询参数或请求体不同：返回 16001
+/// - 相同请求仍在处理中：返回 16002，处理中的登记失效后相同请求可重新处理
+/// - 请求成功但未能保存响应：返回 500
+pub async fn handle_idempotent<T, F, Fut>(
+    request: &HttpRequest,
+    body: &Bytes,
+    idempotency_service: &Data<dyn IdempotencyApplicationService>,
+    handler: F,
+) -> Result<ApiResponse<T>, ApplicationErrorBox>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<ApiResponse<T>, ApplicationErrorBox>>,
+{
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return handler().await;
+    };
+
+    let key = key
+        .to_str()
+        .map_err(|_for_super_earth| {
+            Box::new(GeneralError::BadRequest(
+                "invalid idempotency key".to_string(),
+            )) as Box<dyn ApplicationError>
+        })?
+        .to_string();
+
+    let mut fingerprint = format!("{} {}\n", request.method(), request.uri()).into_bytes();
+    fingerprint.extend_from_slice(body);
+
+    let command = BeginIdempotentRequestCommand {
+        session_id: get_session_id(request)?,
+        key,
+        scope: request.path().to_string(),
+        request: fingerprint,
+    };
+
+    let reservation = match idempotency_service.begin(command).await? {
+        IdempotentRequest::Replay(data) => {
+            let data = serde_json::from_str::<Option<T>>(&data).map_err(|e| {
+                error!("Failed to parse saved idempotent response: {}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?;
+
+            return Ok(ApiResponse {
+                code: API_SUCCESS_CODE,
+                message: API_SUCCESS_MESSAGE.to_string(),
+                data,
+            });
+        }
+        IdempotentRequest::Proceed(reservation) => reservation,
+    };
+
+    let result = handler().await;
+
+    let data = result.as_ref().ok().and_then(|response| {
+        serde_json::to_string(&response.data)
+            .inspect_err(|e| error!("Failed to serialize idempotent response: {}", e))
+            .ok()
+    });
+
+    match data {
+        // 未能保存响应时，客户端重试无法得到本次的响应，不能返回成功
+        Some(data) => idempotency_service.complete(reservation, data).await?,
+        // 释放失败时登记在失效后仍可被相同请求接管，不影响本次响应
+        None => {
+            if let Err(e) = idempotency_service.release(reservation).await {
+                error!("Failed to release idempotency key: {}", e);
+            }
+        }
+    }
+
+    result
+}
@@ -38,6 +38,7 @@ use base::application::service::geo::GeoApplicationService;
 use base::application::service::hotel::HotelService;
 use base::application::service::hotel_data::HotelDataService;
 use base::application::service::hotel_order::HotelOrderService;
+use base::application::service::idempotency::IdempotencyApplicationService;
 use base::application::service::message::MessageApplicationService;
 use base::application::service::personal_info::PersonalInfoService;
 use base::application::service::train_data::TrainDataService;
@@ -47,22 +48,31 @@ use base::application::service::train_query::TrainQueryService;
 use base::application::service::transaction::TransactionApplicationService;
 use base::application::service::user_manager::UserManagerService;
 use base::application::service::user_profile::UserProfileService;
+use base::domain::model::refund_policy::{RefundPolicy, RefundPolicySet};
 use base::domain::model::session_config::SessionConfig;
 use base::domain::repository::session::SessionRepositoryConfig;
 use base::domain::repository::user::UserRepository;
+use base::domain::service::fare::FareRateTable;
+use base::domain::service::ledger::LedgerService;
 use base::domain::service::message::MessageListenerService;
 use base::domain::service::object_storage::ObjectStorageService;
 use base::domain::service::order_status::OrderStatusManagerService;
+use base::domain::service::payment_gateway::RechargeChannel;
+use base::domain::service::pricing::DynamicPricingPolicy;
 use base::domain::service::route::RouteService;
 use base::domain::service::session::SessionManagerService;
+use base::domain::service::train_booking::TrainBookingService;
 use base::domain::service::train_schedule::TrainScheduleService;
+use base::domain::service::train_seat::TrainSeatService;
 use base::domain::service::train_type::TrainTypeConfigurationService;
 use base::domain::service::user::UserService;
+use base::domain::service::waitlist::WaitlistService;
 use base::infrastructure::application::service::dish_query::DishQueryServiceImpl;
 use base::infrastructure::application::service::geo::GeoApplicationServiceImpl;
 use base::infrastructure::application::service::hotel::HotelServiceImpl;
 use base::infrastructure::application::service::hotel_data::HotelDataServiceImpl;
 use base::infrastructure::application::service::hotel_order::HotelOrderServiceImpl;
+use base::infrastructure::application::service::idempotency::IdempotencyApplicationServiceImpl;
 use base::infrastructure::application::service::message::MessageApplicationServiceImpl;
 use base::infrastructure::application::service::personal_info::PersonalInfoServiceImpl;
 use base::infrastructure::application::service::train_data::TrainDataServiceImpl;
@@ -76,9 +86,12 @@ use base::infrastructure::messaging::consumer::order_status::{
     TakeawayOrderStatusConsumer, TrainOrderStatusConsumer,
 };
 use base::infrastructure::repository::city::CityRepositoryImpl;
+use base::infrastructure::repository::coupon::CouponRepositoryImpl;
 use base::infrastructure::repository::dish::DishRepositoryImpl;
 use base::infrastructure::repository::hotel::HotelRepositoryImpl;
 use base::infrastructure::repository::hotel_rating::HotelRatingRepositoryImpl;
+use base::infrastructure::repository::idempotency::IdempotencyRepositoryImpl;
+use base::infrastructure::repository::ledger::LedgerRepositoryImpl;
 use base::infrastructure::repository::notify::NotifyRepositoryImpl;
 use base::infrastructure::repository::occupied_room::OccupiedRoomRepositoryImpl;
 use base::infrastructure::repository::order::OrderRepositoryImpl;
@@ -92,11 +105,14 @@ use base::infrastructure::repository::train::TrainRepositoryImpl;
 use base::infrastructure::repository::train_schedule::TrainScheduleRepositoryImpl;
 use base::infrastructure::repository::transaction::TransactionRepositoryImpl;
 use base::infrastructure::repository::user::UserRepositoryImpl;
+use base::infrastructure::repository::waitlist::WaitlistRepositoryImpl;
 use base::infrastructure::service::dish_booking::DishBookingServiceImpl;
+use base::infrastructure::service::fare::FareServiceImpl;
 use base::infrastructure::service::geo::GeoServiceImpl;
 use base::infrastructure::service::hotel_booking::HotelBookingServiceImpl;
 use base::infrastructure::service::hotel_query::HotelQueryServiceImpl;
 use base::infrastructure::service::hotel_rating::HotelRatingServiceImpl;
+use base::infrastructure::service::ledger::LedgerServiceImpl;
 use base::infrastructure::service::message::{MessageListenerServiceImpl, MessageServiceImpl};
 use base::infrastructure::service::object_storage::S3ObjectStorageServiceImpl;
 use base::infrastructure::service::order::OrderServiceImpl;
@@ -104,7 +120,10 @@ use base::infrastructure::service::order_status::OrderStatusManagerServiceImpl;
 use base::infrastructure::service::order_status_consumer_service::OrderStatusConsumerService;
 use base::infrastructure::service::order_status_producer_service::OrderStatusProducerService;
 use base::infrastructure::service::password::Argon2PasswordServiceImpl;
+use base::infrastructure::service::payment_gateway::MockPaymentGatewayImpl;
+use base::infrastructure::service::pricing::DynamicPricingServiceImpl;
 use base::infrastructure::service::route::RouteServiceImpl;
+use base::infrastructure::service::seat_assignment::BestFitSeatAssignmentStrategy;
 use base::infrastructure::service::session::SessionManagerServiceImpl;
 use base::infrastructure::service::station::StationServiceImpl;
 use base::infrastructure::service::takeaway_booking::TakeawayBookingServiceImpl;
@@ -114,8 +133,10 @@ use base::infrastructure::service::train_seat::TrainSeatServiceImpl;
 use base::infrastructure::service::train_type::TrainTypeConfigurationServiceImpl;
 use base::infrastructure::service::transaction::TransactionServiceImpl;
 use base::infrastructure::service::user::UserServiceImpl;
+use base::infrastructure::service::waitlist::WaitlistServiceImpl;
 use migration::MigratorTrait;
 use sea_orm::Database;
+use sea_orm::prelude::Decimal;
 use std::env::VarError;
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -123,6 +144,7 @@ use std::sync::Arc;
 use std::{env, fs};
 use tracing::{error, instrument, warn};
 use tracing_actix_web::TracingLogger;
+use uuid::Uuid;
 
 #[actix_web::main]
 
@@ -139,6 +161,20 @@ async fn main() -> std::io::Result<()> {
 
     let auto_schedule_days_str = read_file_env("AUTO_SCHEDULE_DAYS");
 
+    let dynamic_pricing_str = read_file_env("DYNAMIC_PRICING");
+    let dynamic_pricing_min_multiplier_str = read_file_env("DYNAMIC_PRICING_MIN_MULTIPLIER");
+    let dynamic_pricing_max_multiplier_str = read_file_env("DYNAMIC_PRICING_MAX_MULTIPLIER");
+
+    let refund_policy_train_str = read_file_env("REFUND_POLICY_TRAIN");
+    let refund_policy_hotel_str = read_file_env("REFUND_POLICY_HOTEL");
+    let refund_policy_dish_str = read_file_env("REFUND_POLICY_DISH");
+    let refund_policy_takeaway_str = read_file_env("REFUND_POLICY_TAKEAWAY");
+
+    let train_seat_hold_str = read_file_env("TRAIN_SEAT_HOLD");
+
+    let payment_gateway_str = read_file_env("PAYMENT_GATEWAY");
+    let mock_payment_secret_str = read_file_env("MOCK_PAYMENT_SECRET");
+
     let mini_io_endpoint = read_file_env("MINIO_ENDPOINT").expect("cannot get minio endpoint");
     let mini_io_access_key =
         read_file_env("MINIO_ACCESS_KEY").expect("cannot get minio access key");
@@ -164,6 +200,73 @@ async fn main() -> std::io::Result<()> {
         None => 14,
     };
 
+    let default_pricing_policy = DynamicPricingPolicy::default();
+
+    let dynamic_pricing_policy = DynamicPricingPolicy {
+        enabled: match dynamic_pricing_str {
+            Some(enabled_str) => enabled_str
+                .parse::<bool>()
+                .expect("cannot parse dynamic pricing"),
+            None => default_pricing_policy.enabled,
+        },
+        min_multiplier: match dynamic_pricing_min_multiplier_str {
+            Some(multiplier_str) => multiplier_str
+                .parse::<Decimal>()
+                .expect("cannot parse dynamic pricing min multiplier"),
+            None => default_pricing_policy.min_multiplier,
+        },
+        max_multiplier: match dynamic_pricing_max_multiplier_str {
+            Some(multiplier_str) => multiplier_str
+                .parse::<Decimal>()
+                .expect("cannot parse dynamic pricing max multiplier"),
+            None => default_pricing_policy.max_multiplier,
+        },
+        ..default_pricing_policy
+    };
+
+    assert!(
+        dynamic_pricing_policy.min_multiplier <= dynamic_pricing_policy.max_multiplier,
+        "dynamic pricing min multiplier should not be greater than max multiplier"
+    );
+
+    let default_refund_policy = RefundPolicySet::default();
+
+    // 格式见`RefundPolicy`的`FromStr`实现，例如："48h:5,2h:20"
+    let refund_policy = RefundPolicySet {
+        train: match refund_policy_train_str {
+            Some(policy_str) => policy_str
+                .parse::<RefundPolicy>()
+                .expect("cannot parse train refund policy"),
+            None => default_refund_policy.train,
+        },
+        hotel: match refund_policy_hotel_str {
+            Some(policy_str) => policy_str
+                .parse::<RefundPolicy>()
+                .expect("cannot parse hotel refund policy"),
+            None => default_refund_policy.hotel,
+        },
+        dish: match refund_policy_dish_str {
+            Some(policy_str) => policy_str
+                .parse::<RefundPolicy>()
+                .expect("cannot parse dish refund policy"),
+            None => default_refund_policy.dish,
+        },
+        takeaway: match refund_policy_takeaway_str {
+            Some(policy_str) => policy_str
+                .parse::<RefundPolicy>()
+                .expect("cannot parse takeaway refund policy"),
+            None => default_refund_policy.takeaway,
+        },
+    };
+
+    // 下单时暂留座位，支付后确认，默认关闭
+    let train_seat_hold = match train_seat_hold_str {
+        Some(enabled_str) => enabled_str
+            .parse::<bool>()
+            .expect("cannot parse train seat hold"),
+        None => false,
+    };
+
     let debug_mode = match env::var("DEBUG") {
         Ok(_) => true,
         Err(VarError::NotPresent) => false,
@@ -201,6 +304,7 @@ async fn main() -> std::io::Result<()> {
     let personal_info_repository_impl = Arc::new(PersonalInfoRepositoryImpl::new(conn.clone()));
     let hotel_repository_impl = Arc::new(HotelRepositoryImpl::new(conn.clone()));
     let hotel_rating_repository_impl = Arc::new(HotelRatingRepositoryImpl::new(conn.clone()));
+    let coupon_repository_impl = Arc::new(CouponRepositoryImpl::new(conn.clone()));
     let seat_availability_repository_impl =
         Arc::new(SeatAvailabilityRepositoryImpl::new(conn.clone()));
     let train_schedule_repository_impl = Arc::new(TrainScheduleRepositoryImpl::new(conn.clone()));
@@ -208,6 +312,9 @@ async fn main() -> std::io::Result<()> {
     let takeaway_repository_impl = Arc::new(TakeawayShopRepositoryImpl::new(conn.clone()));
     let notify_repository_impl = Arc::new(NotifyRepositoryImpl::new(conn.clone()));
     let occupied_room_repository_impl = Arc::new(OccupiedRoomRepositoryImpl::new(conn.clone()));
+    let waitlist_repository_impl = Arc::new(WaitlistRepositoryImpl::new(conn.clone()));
+    let ledger_repository_impl = Arc::new(LedgerRepositoryImpl::new(conn.clone()));
+    let idempotency_repository_impl = Arc::new(IdempotencyRepositoryImpl::new(conn.clone()));
 
     let s3_object_storage_service_impl = Arc::new(S3ObjectStorageServiceImpl::new(
         &mini_io_endpoint,
@@ -219,6 +326,45 @@ async fn main() -> std::io::Result<()> {
         error!("failed to initialize storage buckets: {}", e);
     }
 
+    // 可选值："mock"、"direct"、"disabled"，未配置时调试模式下使用模拟支付网关，否则充值直接入账；
+    // 模拟支付网关的支付意图只保存在内存中，重启后丢失，仅可在调试模式下使用
+    let payment_gateway = payment_gateway_str.unwrap_or_else(|| {
+        if debug_mode {
+            "mock".to_string()
+        } else {
+            "direct".to_string()
+        }
+    });
+
+    let recharge_channel = match payment_gateway.as_str() {
+        "mock" => {
+            assert!(
+                debug_mode,
+                "mock payment gateway is only available in debug mode"
+            );
+
+            // 回调签名密钥，未配置时随机生成，此时无法从外部构造合法回调
+            let mock_payment_secret = match mock_payment_secret_str {
+                Some(secret) => secret,
+                None => {
+                    warn!("MOCK_PAYMENT_SECRET not set, using a random secret");
+                    Uuid::new_v4().to_string()
+                }
+            };
+
+            RechargeChannel::Gateway(Arc::new(MockPaymentGatewayImpl::new(&mock_payment_secret)))
+        }
+        "direct" => {
+            warn!("no payment gateway configured, recharge credits the wallet directly");
+            RechargeChannel::Direct
+        }
+        "disabled" => {
+            warn!("recharge is disabled by PAYMENT_GATEWAY");
+            RechargeChannel::Disabled
+        }
+        other => panic!("unknown payment gateway: {}", other),
+    };
+
     let user_service_impl = Arc::new(UserServiceImpl::<_, Argon2PasswordServiceImpl>::new(
         Arc::clone(&user_repository_impl),
     ));
@@ -235,9 +381,26 @@ async fn main() -> std::io::Result<()> {
             .expect("Failed to start order status producer service"),
     );
 
+    let order_service_impl = Arc::new(OrderServiceImpl::new(
+        Arc::clone(&order_repository_impl),
+        tz_offset_hour,
+    ));
+
+    let message_listener_service_impl = Arc::new(MessageListenerServiceImpl::new(
+        MAX_CONCURRENT_WEBSOCKET_SESSION_PER_USER,
+    ));
+
+    let message_service_impl = Arc::new(MessageServiceImpl::new(
+        Arc::clone(&message_listener_service_impl),
+        Arc::clone(&notify_repository_impl),
+        Arc::clone(&order_service_impl),
+    ));
+
     let order_status_manager_service_impl = Arc::new(OrderStatusManagerServiceImpl::new(
         Arc::clone(&order_status_producer_service),
         Arc::clone(&order_repository_impl),
+        Arc::clone(&transaction_repository_impl),
+        Arc::clone(&message_service_impl),
     ));
 
     {
@@ -249,23 +412,20 @@ async fn main() -> std::io::Result<()> {
         });
     }
 
+    let ledger_service_impl = Arc::new(LedgerServiceImpl::new(
+        Arc::clone(&ledger_repository_impl),
+        Arc::clone(&transaction_repository_impl),
+    ));
+
+    actix_web::rt::spawn(async move {
+        ledger_service_impl.reconciliation_daemon().await;
+    });
+
     let user_profile_service_impl = Arc::new(UserProfileServiceImpl::new(
         Arc::clone(&session_manager_service_impl),
         Arc::clone(&user_repository_impl),
     ));
 
-    let train_data_service_impl = Arc::new(TrainDataServiceImpl::new(
-        debug_mode,
-        data_base_path.clone(),
-        Arc::clone(&city_repository_impl),
-        Arc::clone(&station_repository_impl),
-        Arc::clone(&train_repository_impl),
-        Arc::clone(&route_repository_impl),
-        Arc::clone(&dish_repository_impl),
-        Arc::clone(&takeaway_repository_impl),
-        Arc::clone(&s3_object_storage_service_impl),
-    ));
-
     let geo_service_impl = Arc::new(GeoServiceImpl::new(Arc::clone(&city_repository_impl)));
 
     let station_service_impl = Arc::new(StationServiceImpl::new(
@@ -277,16 +437,15 @@ async fn main() -> std::io::Result<()> {
         &train_repository_impl,
     )));
 
-    let order_service_impl = Arc::new(OrderServiceImpl::new(
-        Arc::clone(&order_repository_impl),
-        tz_offset_hour,
-    ));
-
     let transaction_service_impl = Arc::new(TransactionServiceImpl::new(
         Arc::clone(&user_repository_impl),
         Arc::clone(&transaction_repository_impl),
         Arc::clone(&order_service_impl),
         Arc::clone(&order_status_manager_service_impl),
+        Arc::clone(&coupon_repository_impl),
+        recharge_channel,
+        Arc::clone(&occupied_room_repository_impl),
+        refund_policy,
     ));
 
     let transaction_application_service_impl = Arc::new(TransactionApplicationServiceImpl::new(
@@ -296,6 +455,12 @@ async fn main() -> std::io::Result<()> {
         Arc::clone(&transaction_repository_impl),
         Arc::clone(&user_service_impl),
         Arc::clone(&user_repository_impl),
+        Arc::clone(&order_repository_impl),
+    ));
+
+    let idempotency_application_service_impl = Arc::new(IdempotencyApplicationServiceImpl::new(
+        Arc::clone(&session_manager_service_impl),
+        Arc::clone(&idempotency_repository_impl),
     ));
 
     let geo_application_service_impl = Arc::new(GeoApplicationServiceImpl::new(
@@ -315,7 +480,7 @@ async fn main() -> std::io::Result<()> {
 
     let hotel_data_service_impl = Arc::new(HotelDataServiceImpl::new(
         app_config.debug,
-        data_base_path,
+        data_base_path.clone(),
         Arc::clone(&city_repository_impl),
         Arc::clone(&station_repository_impl),
         Arc::clone(&s3_object_storage_service_impl),
@@ -369,6 +534,9 @@ async fn main() -> std::io::Result<()> {
         Arc::clone(&order_repository_impl),
         Arc::clone(&seat_availability_repository_impl),
         Arc::clone(&train_type_configuration_service_impl),
+        Arc::clone(&route_repository_impl),
+        Arc::new(BestFitSeatAssignmentStrategy),
+        Arc::clone(&waitlist_repository_impl),
     ));
 
     let dish_booking_service_impl = Arc::new(DishBookingServiceImpl::new(Arc::clone(
@@ -379,29 +547,36 @@ async fn main() -> std::io::Result<()> {
         &order_repository_impl,
     )));
 
-    let message_listener_service_impl = Arc::new(MessageListenerServiceImpl::new(
-        MAX_CONCURRENT_WEBSOCKET_SESSION_PER_USER,
-    ));
-
-    let message_service_impl = Arc::new(MessageServiceImpl::new(
-        Arc::clone(&message_listener_service_impl),
-        Arc::clone(&notify_repository_impl),
-        Arc::clone(&order_service_impl),
-    ));
-
     let message_application_service_impl = Arc::new(MessageApplicationServiceImpl::new(
         Arc::clone(&message_service_impl),
         Arc::clone(&session_manager_service_impl),
     ));
 
+    let fare_service_impl = Arc::new(FareServiceImpl::new(FareRateTable::default()));
+
     let train_schedule_service_impl = Arc::new(TrainScheduleServiceImpl::new(
         Arc::clone(&route_service_impl),
         Arc::clone(&train_repository_impl),
         Arc::clone(&train_schedule_repository_impl),
         Arc::clone(&route_repository_impl),
+        Arc::clone(&station_repository_impl),
+        Arc::clone(&fare_service_impl),
         tz_offset_hour,
     ));
 
+    let train_data_service_impl = Arc::new(TrainDataServiceImpl::new(
+        debug_mode,
+        data_base_path,
+        Arc::clone(&city_repository_impl),
+        Arc::clone(&station_repository_impl),
+        Arc::clone(&train_repository_impl),
+        Arc::clone(&route_repository_impl),
+        Arc::clone(&dish_repository_impl),
+        Arc::clone(&takeaway_repository_impl),
+        Arc::clone(&s3_object_storage_service_impl),
+        Arc::clone(&train_schedule_service_impl),
+    ));
+
     let dish_query_service_impl = Arc::new(DishQueryServiceImpl::new(
         Arc::clone(&dish_repository_impl),
         Arc::clone(&takeaway_repository_impl),
@@ -427,6 +602,12 @@ async fn main() -> std::io::Result<()> {
             tz_offset_hour as u32,
         ));
 
+    let pricing_service_impl = Arc::new(DynamicPricingServiceImpl::new(
+        dynamic_pricing_policy,
+        Arc::clone(&train_seat_service_impl),
+        tz_offset_hour,
+    ));
+
     let train_query_service_impl = Arc::new(TrainQueryServiceImpl::new(
         Arc::clone(&train_schedule_service_impl),
         Arc::clone(&station_service_impl),
@@ -435,7 +616,11 @@ async fn main() -> std::io::Result<()> {
         Arc::clone(&route_repository_impl),
         Arc::clone(&train_repository_impl),
         Arc::clone(&station_repository_impl),
+        Arc::clone(&train_seat_service_impl),
+        Arc::clone(&fare_service_impl),
+        Arc::clone(&pricing_service_impl),
         tz_offset_hour,
+        auto_schedule_days,
     ));
 
     let train_order_service_impl = Arc::new(TrainOrderServiceImpl::new(
@@ -449,8 +634,36 @@ async fn main() -> std::io::Result<()> {
         Arc::clone(&session_manager_service_impl),
         Arc::clone(&personal_info_repository_impl),
         Arc::clone(&train_schedule_service_impl),
+        Arc::clone(&fare_service_impl),
+        Arc::clone(&pricing_service_impl),
+        Arc::clone(&waitlist_repository_impl),
+        Arc::clone(&user_service_impl),
+        Arc::clone(&user_repository_impl),
+        train_seat_hold,
     ));
 
+    if train_seat_hold {
+        let train_booking_service_impl = Arc::clone(&train_booking_service_impl);
+        actix_web::rt::spawn(async move {
+            train_booking_service_impl.seat_hold_daemon().await;
+        });
+    }
+
+    let waitlist_service_impl = Arc::new(WaitlistServiceImpl::new(
+        Arc::clone(&train_booking_service_impl),
+        Arc::clone(&waitlist_repository_impl),
+        Arc::clone(&transaction_service_impl),
+        Arc::clone(&order_repository_impl),
+        Arc::clone(&message_service_impl),
+    ));
+
+    {
+        let waitlist_service_impl = Arc::clone(&waitlist_service_impl);
+        actix_web::rt::spawn(async move {
+            waitlist_service_impl.waitlist_daemon().await;
+        });
+    }
+
     let hotel_order_service_impl = Arc::new(HotelOrderServiceImpl::new(
         Arc::clone(&hotel_repository_impl),
         Arc::clone(&hotel_booking_service_impl),
@@ -493,6 +706,19 @@ async fn main() -> std::io::Result<()> {
             transaction_application_service_impl as Arc<dyn TransactionApplicationService>,
         );
 
+    {
+        let idempotency_application_service_impl =
+            Arc::clone(&idempotency_application_service_impl);
+        actix_web::rt::spawn(async move {
+            idempotency_application_service_impl.cleanup_daemon().await;
+        });
+    }
+
+    let idempotency_application_service: web::Data<dyn IdempotencyApplicationService> =
+        web::Data::from(
+            idempotency_application_service_impl as Arc<dyn IdempotencyApplicationService>,
+        );
+
     let personal_info_service: web::Data<dyn PersonalInfoService> =
         web::Data::from(personal_info_service_impl as Arc<dyn PersonalInfoService>);
 
@@ -523,6 +749,7 @@ async fn main() -> std::io::Result<()> {
     let train_order_status_consumer = Box::new(TrainOrderStatusConsumer::new(
         Arc::clone(&train_booking_service_impl),
         Arc::clone(&transaction_service_impl),
+        Arc::clone(&waitlist_service_impl),
     ));
 
     let hotel_order_status_consumer = Box::new(HotelOrderStatusConsumer::new(
@@ -565,6 +792,21 @@ async fn main() -> std::io::Result<()> {
         });
     }
 
+    {
+        let train_seat_service_impl = Arc::clone(&train_seat_service_impl);
+
+        actix_web::rt::spawn(async move {
+            let now_date = chrono::Local::now().naive_local().date();
+
+            if let Err(e) = train_seat_service_impl
+                .rebuild_seat_availability_cache(now_date, auto_schedule_days)
+                .await
+            {
+                error!("Failed to rebuild seat availability cache: {}", e);
+            }
+        });
+    }
+
     let train_query_service: web::Data<dyn TrainQueryService> =
         web::Data::from(train_query_service_impl as Arc<dyn TrainQueryService>);
 
@@ -582,6 +824,7 @@ async fn main() -> std::io::Result<()> {
             .app_data(geo_application_service.clone())
             .app_data(personal_info_service.clone())
             .app_data(transaction_application_service.clone())
+            .app_data(idempotency_application_service.clone())
             .app_data(object_storage_service.clone())
             .app_data(hotel_data_service.clone())
             .app_data(route_service.clone())
@@ -1,9 +1,14 @@
 use crate::{ApiResponse, ApplicationErrorBox, get_session_id, parse_request_body};
 use actix_web::web::{Bytes, Data};
 use actix_web::{HttpRequest, get, post, web};
-use base::application::commands::transaction::{CancelOrderCommand, TransactionDetailQuery};
-use base::application::service::transaction::{CancelOrderDTO, TransactionApplicationService};
+use base::application::commands::transaction::{
+    CancelOrderCommand, OrderTimelineQuery, PartialRefundCommand, TransactionDetailQuery,
+};
+use base::application::service::transaction::{
+    CancelOrderDTO, OrderStatusHistoryDTO, PartialRefundDTO, TransactionApplicationService,
+};
 use base::domain::service::order::order_dto::TransactionDataDto;
+use uuid::Uuid;
 
 #[get("/list")]
 pub async fn query_transaction_details(
@@ -43,6 +48,52 @@ pub async fn cancel_order(
     ApiResponse::ok(())
 }
 
+#[post("/refund")]
+pub async fn partial_refund(
+    requests: HttpRequest,
+    body: Bytes,
+    transaction_service: Data<dyn TransactionApplicationService>,
+) -> Result<ApiResponse<()>, ApplicationErrorBox> {
+    let session_id = get_session_id(&requests)?;
+
+    let partial_refund_dto: PartialRefundDTO = parse_request_body(body)?;
+
+    let partial_refund_command = PartialRefundCommand {
+        session_id,
+        order_id: partial_refund_dto.order_id,
+        quantity: partial_refund_dto.quantity,
+    };
+
+    transaction_service
+        .partial_refund(partial_refund_command)
+        .await?;
+
+    ApiResponse::ok(())
+}
+
+#[get("/timeline/{order_id}")]
+pub async fn query_order_timeline(
+    order_id: web::Path<Uuid>,
+    requests: HttpRequest,
+    transaction_service: Data<dyn TransactionApplicationService>,
+) -> Result<ApiResponse<Vec<OrderStatusHistoryDTO>>, ApplicationErrorBox> {
+    let session_id = get_session_id(&requests)?;
+
+    let order_timeline_query = OrderTimelineQuery {
+        session_id,
+        order_id: *order_id,
+    };
+
+    let timeline = transaction_service
+        .query_order_timeline(order_timeline_query)
+        .await?;
+
+    ApiResponse::ok(timeline)
+}
+
 pub fn scoped_config(cfg: &mut web::ServiceConfig) {
-    cfg.service(query_transaction_details).service(cancel_order);
+    cfg.service(query_transaction_details)
+        .service(cancel_order)
+        .service(partial_refund)
+        .service(query_order_timeline);
 }
@@ -1,33 +1,72 @@
-use crate::{ApiResponse, ApplicationErrorBox, get_session_id, parse_request_body};
-use actix_web::web::{Bytes, Data};
-use actix_web::{HttpRequest, get, post, web};
+use crate::{
+    ApiResponse, ApplicationErrorBox, get_session_id, handle_idempotent, parse_request_body,
+};
+use actix_web::http::header;
+use actix_web::web::{Bytes, Data, Query};
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
 use base::application::commands::transaction::{
-    BalanceQuery, GenerateDebugTransactionCommand, PayTransactionCommand, RechargeCommand,
-    SetPaymentPasswordCommand, TransactionQuery,
+    BalanceQuery, GenerateDebugTransactionCommand, PayTransactionCommand, PaymentCallbackCommand,
+    RechargeCommand, SetPaymentPasswordCommand, TransactionQuery, WalletStatementQuery,
 };
+use base::application::service::idempotency::IdempotencyApplicationService;
 use base::application::service::transaction::{
-    BalanceInfoDTO, PaymentConfirmationDTO, PaymentPasswordInfoDTO, RechargeDTO,
-    TransactionApplicationService, TransactionGenerateDTO, TransactionInfoDTO,
+    BalanceInfoDTO, PaymentConfirmationDTO, PaymentPasswordInfoDTO, RechargeDTO, RechargeIntentDTO,
+    TransactionApplicationService, TransactionGenerateDTO, TransactionInfoDTO, WalletStatementDTO,
 };
+use base::application::{ApplicationError, GeneralError};
 use sea_orm::prelude::Uuid;
 use serde::Deserialize;
 
+/// 支付网关回调携带签名的请求头
+const PAYMENT_SIGNATURE_HEADER: &str = "X-Payment-Signature";
+
+/// UTF-8 BOM，使电子表格软件正确识别导出 CSV 的编码
+const UTF8_BOM: &str = "\u{feff}";
+
 #[post("/recharge")]
 pub async fn recharge(
     requests: HttpRequest,
     body: Bytes,
     transaction_service: Data<dyn TransactionApplicationService>,
-) -> Result<ApiResponse<()>, ApplicationErrorBox> {
-    let session_id = get_session_id(&requests)?;
+    idempotency_service: Data<dyn IdempotencyApplicationService>,
+) -> Result<ApiResponse<RechargeIntentDTO>, ApplicationErrorBox> {
+    handle_idempotent(&requests, &body, &idempotency_service, || async {
+        let session_id = get_session_id(&requests)?;
 
-    let recharge_info_dto: RechargeDTO = parse_request_body(body)?;
+        let recharge_info_dto: RechargeDTO = parse_request_body(body.clone())?;
 
-    let recharge_command = RechargeCommand {
-        session_id,
-        amount: recharge_info_dto.amount,
+        let recharge_command = RechargeCommand {
+            session_id,
+            amount: recharge_info_dto.amount,
+        };
+
+        let recharge_intent_dto = transaction_service.recharge(recharge_command).await?;
+
+        ApiResponse::ok(recharge_intent_dto)
+    })
+    .await
+}
+
+#[post("/callback/{provider}")]
+pub async fn payment_callback(
+    provider: web::Path<String>,
+    requests: HttpRequest,
+    body: Bytes,
+    transaction_service: Data<dyn TransactionApplicationService>,
+) -> Result<ApiResponse<()>, ApplicationErrorBox> {
+    let signature = requests
+        .headers()
+        .get(PAYMENT_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    let command = PaymentCallbackCommand {
+        provider: provider.into_inner(),
+        payload: body.to_vec(),
+        signature,
     };
 
-    transaction_service.recharge(recharge_command).await?;
+    transaction_service.handle_payment_callback(command).await?;
 
     ApiResponse::ok(())
 }
@@ -46,6 +85,81 @@ pub async fn query_balance(
     ApiResponse::ok(balance_info_dto)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatementQueryParams {
+    begin_date: Option<String>,
+    end_date: Option<String>,
+    /// 逗号分隔的交易类型，如`recharge,refund`
+    types: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+}
+
+/// 从查询参数中解析钱包账单查询条件
+fn parse_statement_query(
+    requests: &HttpRequest,
+) -> Result<WalletStatementQuery, Box<dyn ApplicationError>> {
+    let session_id = get_session_id(requests)?;
+
+    let params = Query::<StatementQueryParams>::from_query(requests.query_string())
+        .map_err(|e| GeneralError::BadRequest(format!("invalid query parameters: {}", e)))?
+        .into_inner();
+
+    let types = params
+        .types
+        .map(|types| {
+            types
+                .split(',')
+                .map(str::trim)
+                .filter(|kind| !kind.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(WalletStatementQuery {
+        session_id,
+        begin_date: params.begin_date,
+        end_date: params.end_date,
+        types,
+        cursor: params.cursor,
+        limit: params.limit,
+    })
+}
+
+#[get("/statement")]
+pub async fn query_statement(
+    requests: HttpRequest,
+    transaction_service: Data<dyn TransactionApplicationService>,
+) -> Result<ApiResponse<WalletStatementDTO>, ApplicationErrorBox> {
+    let statement_query = parse_statement_query(&requests)?;
+
+    let statement = transaction_service.query_statement(statement_query).await?;
+
+    ApiResponse::ok(statement)
+}
+
+#[get("/statement/export")]
+pub async fn export_statement(
+    requests: HttpRequest,
+    transaction_service: Data<dyn TransactionApplicationService>,
+) -> Result<HttpResponse, ApplicationErrorBox> {
+    let statement_query = parse_statement_query(&requests)?;
+
+    let csv = transaction_service
+        .export_statement(statement_query)
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/csv; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"statement.csv\"",
+        ))
+        .body(format!("{}{}", UTF8_BOM, csv)))
+}
+
 #[get("/")]
 pub async fn query_transactions(
     requests: HttpRequest,
@@ -94,21 +208,25 @@ async fn pay_transaction(
     requests: HttpRequest,
     body: Bytes,
     transaction_service: Data<dyn TransactionApplicationService>,
+    idempotency_service: Data<dyn IdempotencyApplicationService>,
 ) -> Result<ApiResponse<()>, ApplicationErrorBox> {
-    let session_id = get_session_id(&requests)?;
+    handle_idempotent(&requests, &body, &idempotency_service, || async {
+        let session_id = get_session_id(&requests)?;
 
-    let payment_confirmation_dto: PaymentConfirmationDTO = parse_request_body(body)?;
+        let payment_confirmation_dto: PaymentConfirmationDTO = parse_request_body(body.clone())?;
 
-    let command = PayTransactionCommand {
-        session_id,
-        transaction_id: info.transaction_id,
-        user_password: payment_confirmation_dto.user_password,
-        payment_password: payment_confirmation_dto.payment_password,
-    };
+        let command = PayTransactionCommand {
+            session_id,
+            transaction_id: info.transaction_id,
+            user_password: payment_confirmation_dto.user_password,
+            payment_password: payment_confirmation_dto.payment_password,
+        };
 
-    transaction_service.pay_transaction(command).await?;
+        transaction_service.pay_transaction(command).await?;
 
-    ApiResponse::ok(())
+        ApiResponse::ok(())
+    })
+    .await
 }
 
 #[post("/generate")]
@@ -135,7 +253,10 @@ async fn generate_transaction(
 
 pub fn scoped_config(cfg: &mut web::ServiceConfig) {
     cfg.service(recharge)
+        .service(payment_callback)
         .service(query_balance)
+        .service(query_statement)
+        .service(export_statement)
         .service(query_transactions)
         .service(set_payment_password)
         .service(pay_transaction)
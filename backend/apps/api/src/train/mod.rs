@@ -2,6 +2,7 @@ use actix_web::web;
 
 pub mod order;
 pub mod schedule;
+pub mod waitlist;
 
 // Step 5: Register your endpoint
 // HINT: You may refer to `api/user/mod.rs` for example
@@ -10,4 +11,5 @@ pub mod schedule;
 pub fn scoped_config(cfg: &mut web::ServiceConfig) {
     cfg.service(web::scope("/schedule").configure(schedule::scoped_config));
     cfg.service(web::scope("/order").configure(order::scoped_config));
+    cfg.service(web::scope("/waitlist").configure(waitlist::scoped_config));
 }
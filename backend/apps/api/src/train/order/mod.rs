@@ -1,9 +1,12 @@
 pub mod new;
+pub mod rebook;
 
 use actix_web::web;
 
 use crate::train::order::new::create_train_order;
+use crate::train::order::rebook::rebook_train_order;
 
 pub fn scoped_config(cfg: &mut web::ServiceConfig) {
     cfg.service(create_train_order);
+    cfg.service(rebook_train_order);
 }
@@ -1,7 +1,11 @@
-use crate::{ApiResponse, ApplicationErrorBox, get_session_id, parse_request_body};
+use crate::{
+    ApiResponse, ApplicationErrorBox, get_coupon_code, get_session_id, handle_idempotent,
+    parse_request_body,
+};
 use actix_web::{HttpRequest, post, web::Bytes, web::Data};
 use base::application::{
     ApplicationError, GeneralError,
+    service::idempotency::IdempotencyApplicationService,
     service::train_order::{OrderPacksDTO, TrainOrderService},
 };
 use serde::{Deserialize, Serialize};
@@ -15,6 +19,7 @@ pub struct TransactionInfoDTO {
     pub transaction_id: String,
     pub amount: f64,
     pub status: String,
+    pub payment_deadline: Option<String>,
 }
 
 /// 创建火车票订单
@@ -24,33 +29,42 @@ pub struct TransactionInfoDTO {
 /// 提交订单后，订单为"未支付"状态，本接口将返回`TransactionInfo`，
 /// 需要根据`TransactionInfo`中的信息调用`支付订单`接口进行支付。
 /// 支付后订单才会真正被处理。
+///
+/// 可通过查询参数`couponCode`使用优惠券，返回的金额已扣除优惠金额。
+///
+/// 可通过请求头`Idempotency-Key`避免重复提交订单，见`handle_idempotent`。
 #[post("/new")]
 pub async fn create_train_order(
     req: HttpRequest,
     body: Bytes,
     train_order_service: Data<dyn TrainOrderService>,
+    idempotency_service: Data<dyn IdempotencyApplicationService>,
 ) -> Result<ApiResponse<TransactionInfoDTO>, ApplicationErrorBox> {
-    let session_id = get_session_id(&req)?;
-    let order_packs: OrderPacksDTO = parse_request_body(body)?;
+    handle_idempotent(&req, &body, &idempotency_service, || async {
+        let session_id = get_session_id(&req)?;
+        let order_packs: OrderPacksDTO = parse_request_body(body.clone())?;
 
-    let transaction_result = train_order_service
-        .process_train_order_packs(session_id, order_packs)
-        .await?;
+        let transaction_result = train_order_service
+            .process_train_order_packs(session_id, order_packs, get_coupon_code(&req))
+            .await?;
 
-    if transaction_result.transaction_id.is_nil() {
-        error!("No transaction was created");
-        return Err(ApplicationErrorBox::from(
-            Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>,
-        ));
-    }
+        if transaction_result.transaction_id.is_nil() {
+            error!("No transaction was created");
+            return Err(ApplicationErrorBox::from(
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>,
+            ));
+        }
 
-    Ok(ApiResponse {
-        code: API_SUCCESS_CODE,
-        message: API_SUCCESS_MESSAGE.to_string(),
-        data: Some(TransactionInfoDTO {
-            transaction_id: transaction_result.transaction_id.to_string(),
-            amount: transaction_result.amount,
-            status: "unpaid".to_string(),
-        }),
+        Ok(ApiResponse {
+            code: API_SUCCESS_CODE,
+            message: API_SUCCESS_MESSAGE.to_string(),
+            data: Some(TransactionInfoDTO {
+                transaction_id: transaction_result.transaction_id.to_string(),
+                amount: transaction_result.amount,
+                status: "unpaid".to_string(),
+                payment_deadline: transaction_result.payment_deadline,
+            }),
+        })
     })
+    .await
 }
@@ -0,0 +1,38 @@
+use crate::train::order::new::TransactionInfoDTO;
+use crate::{ApiResponse, ApplicationErrorBox, get_session_id, parse_request_body};
+use actix_web::{HttpRequest, post, web::Bytes, web::Data};
+use base::application::service::train_order::{RebookTrainOrderDTO, TrainOrderService};
+use shared::{API_SUCCESS_CODE, API_SUCCESS_MESSAGE};
+
+/// 改签火车票订单
+///
+/// POST /api/train/order/rebook
+///
+/// 将未出行的火车票订单改签至其他车次，无需先取消再重新购买。
+/// 本接口返回调整交易的`TransactionInfo`，其金额为新旧票价的差额：为正时补缴，为负时退还。
+/// 差价在新订单占座成功后自动结算，无需调用`支付订单`接口；
+/// 若新车次无票，调整交易将被丢弃，原订单保持不变。
+#[post("/rebook")]
+pub async fn rebook_train_order(
+    req: HttpRequest,
+    body: Bytes,
+    train_order_service: Data<dyn TrainOrderService>,
+) -> Result<ApiResponse<TransactionInfoDTO>, ApplicationErrorBox> {
+    let session_id = get_session_id(&req)?;
+    let rebook_dto: RebookTrainOrderDTO = parse_request_body(body)?;
+
+    let transaction_result = train_order_service
+        .rebook_train_order(session_id, rebook_dto)
+        .await?;
+
+    Ok(ApiResponse {
+        code: API_SUCCESS_CODE,
+        message: API_SUCCESS_MESSAGE.to_string(),
+        data: Some(TransactionInfoDTO {
+            transaction_id: transaction_result.transaction_id.to_string(),
+            amount: transaction_result.amount,
+            status: transaction_result.status,
+            payment_deadline: transaction_result.payment_deadline,
+        }),
+    })
+}
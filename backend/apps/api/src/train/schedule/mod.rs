@@ -2,7 +2,9 @@ use actix_web::web;
 
 pub mod query;
 
-use crate::train::schedule::query::{query_direct, query_indirect, query_train};
+use crate::train::schedule::query::{
+    query_calendar, query_direct, query_indirect, query_journey, query_train,
+};
 
 // Step 4: Register your endpoint
 // HINT: You may refer to `api/user/mod.rs` for example
@@ -11,5 +13,7 @@ use crate::train::schedule::query::{query_direct, query_indirect, query_train};
 pub fn scoped_config(cfg: &mut web::ServiceConfig) {
     cfg.service(query_direct)
         .service(query_indirect)
+        .service(query_journey)
+        .service(query_calendar)
         .service(query_train);
 }
@@ -3,12 +3,14 @@ use actix_web::web::Bytes;
 use actix_web::{HttpRequest, post, web};
 use base::application::GeneralError;
 use base::application::commands::train_query::{
-    DirectTrainQueryCommand, TrainScheduleQueryCommand, TransferTrainQueryCommand,
+    DirectTrainQueryCommand, DirectTrainSortKey, FareCalendarQueryCommand, JourneyQueryCommand,
+    TrainScheduleQueryCommand, TransferTrainQueryCommand,
 };
 use base::application::service::train_query::{
-    DirectTrainQueryDTO, TrainQueryResponseDTO, TrainQueryService, TransferTrainQueryDTO,
+    DirectTrainQueryDTO, FareCalendarDTO, JourneyQueryDTO, TrainQueryResponseDTO,
+    TrainQueryService, TransferTrainQueryDTO,
 };
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime};
 use serde::Deserialize;
 
 /// 列车查询请求体DTO
@@ -25,6 +27,93 @@ struct TrainScheduleQuery {
     pub arrival_city: Option<String>,
     /// 出发日期，格式：YYYY-MM-DD
     pub departure_date: String,
+    /// 出发时刻下限，格式：HH:MM
+    pub departure_time_from: Option<String>,
+    /// 出发时刻上限，格式：HH:MM
+    pub departure_time_to: Option<String>,
+    /// 到达时刻下限，格式：HH:MM
+    pub arrival_time_from: Option<String>,
+    /// 到达时刻上限，格式：HH:MM
+    pub arrival_time_to: Option<String>,
+    /// 车次号前缀，如：["G", "D"]
+    pub train_types: Option<Vec<String>>,
+    /// 必须均有余票的座位类型
+    pub seat_types: Option<Vec<String>>,
+    /// 排序方式
+    pub sort_by: Option<DirectTrainSortKey>,
+    /// 分页游标
+    pub cursor: Option<String>,
+    /// 每页数量
+    pub limit: Option<u32>,
+}
+
+/// 解析`HH:MM`格式的时刻
+fn parse_time(
+    time: Option<String>,
+) -> Result<Option<NaiveTime>, Box<dyn base::application::ApplicationError>> {
+    time.map(|time| {
+        NaiveTime::parse_from_str(&time, "%H:%M").map_err(|_| {
+            Box::new(GeneralError::BadRequest("Invalid time format".into()))
+                as Box<dyn base::application::ApplicationError>
+        })
+    })
+    .transpose()
+}
+
+/// 中转车次查询请求体DTO
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferTrainQuery {
+    /// 出发站点
+    pub departure_station: Option<String>,
+    /// 到达站点
+    pub arrival_station: Option<String>,
+    /// 出发城市
+    pub departure_city: Option<String>,
+    /// 到达城市
+    pub arrival_city: Option<String>,
+    /// 出发日期，格式：YYYY-MM-DD
+    pub departure_date: String,
+    /// 要求两段行程均有余票的座位类型
+    pub seat_type: Option<String>,
+    /// 两段行程该座位类型的最少余票数
+    pub min_seats: Option<u32>,
+}
+
+/// 多次换乘行程查询请求体DTO
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JourneyQuery {
+    /// 出发站点
+    pub departure_station: Option<String>,
+    /// 到达站点
+    pub arrival_station: Option<String>,
+    /// 出发城市
+    pub departure_city: Option<String>,
+    /// 到达城市
+    pub arrival_city: Option<String>,
+    /// 出发日期，格式：YYYY-MM-DD
+    pub departure_date: String,
+    /// 最多换乘次数
+    pub max_transfers: Option<u32>,
+}
+
+/// 低价日历查询请求体DTO
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FareCalendarQuery {
+    /// 出发站点
+    pub departure_station: Option<String>,
+    /// 到达站点
+    pub arrival_station: Option<String>,
+    /// 出发城市
+    pub departure_city: Option<String>,
+    /// 到达城市
+    pub arrival_city: Option<String>,
+    /// 起始日期，格式：YYYY-MM-DD
+    pub begin_date: String,
+    /// 结束日期（含），格式：YYYY-MM-DD
+    pub end_date: String,
 }
 
 #[derive(Deserialize)]
@@ -71,6 +160,15 @@ async fn query_direct(
                     as Box<dyn base::application::ApplicationError>
             },
         )?,
+        departure_time_from: parse_time(query_dto.departure_time_from)?,
+        departure_time_to: parse_time(query_dto.departure_time_to)?,
+        arrival_time_from: parse_time(query_dto.arrival_time_from)?,
+        arrival_time_to: parse_time(query_dto.arrival_time_to)?,
+        train_type_prefixes: query_dto.train_types,
+        seat_types: query_dto.seat_types,
+        sort_by: query_dto.sort_by,
+        cursor: query_dto.cursor,
+        limit: query_dto.limit,
     };
 
     ApiResponse::ok(train_query_service.query_direct_trains(command).await?)
@@ -84,7 +182,7 @@ async fn query_indirect(
 ) -> Result<ApiResponse<TransferTrainQueryDTO>, ApplicationErrorBox> {
     let session_id = get_session_id(&request)?;
 
-    let query_dto: TrainScheduleQuery = parse_request_body(body)?;
+    let query_dto: TransferTrainQuery = parse_request_body(body)?;
 
     let command = TransferTrainQueryCommand {
         session_id,
@@ -98,11 +196,71 @@ async fn query_indirect(
                     as Box<dyn base::application::ApplicationError>
             },
         )?,
+        seat_type: query_dto.seat_type,
+        min_seats: query_dto.min_seats,
     };
 
     ApiResponse::ok(train_query_service.query_transfer_trains(command).await?)
 }
 
+#[post("/query_journey")]
+async fn query_journey(
+    request: HttpRequest,
+    body: Bytes,
+    train_query_service: web::Data<dyn TrainQueryService>,
+) -> Result<ApiResponse<JourneyQueryDTO>, ApplicationErrorBox> {
+    let session_id = get_session_id(&request)?;
+
+    let query_dto: JourneyQuery = parse_request_body(body)?;
+
+    let command = JourneyQueryCommand {
+        session_id,
+        departure_station: query_dto.departure_station,
+        arrival_station: query_dto.arrival_station,
+        departure_city: query_dto.departure_city,
+        arrival_city: query_dto.arrival_city,
+        departure_time: NaiveDate::parse_from_str(&query_dto.departure_date, "%Y-%m-%d").map_err(
+            |_| {
+                Box::new(GeneralError::BadRequest("Invalid date format".into()))
+                    as Box<dyn base::application::ApplicationError>
+            },
+        )?,
+        max_transfers: query_dto.max_transfers,
+    };
+
+    ApiResponse::ok(train_query_service.query_journeys(command).await?)
+}
+
+#[post("/query_calendar")]
+async fn query_calendar(
+    request: HttpRequest,
+    body: Bytes,
+    train_query_service: web::Data<dyn TrainQueryService>,
+) -> Result<ApiResponse<FareCalendarDTO>, ApplicationErrorBox> {
+    let session_id = get_session_id(&request)?;
+
+    let query_dto: FareCalendarQuery = parse_request_body(body)?;
+
+    let parse_date = |date: &str| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            Box::new(GeneralError::BadRequest("Invalid date format".into()))
+                as Box<dyn base::application::ApplicationError>
+        })
+    };
+
+    let command = FareCalendarQueryCommand {
+        session_id,
+        departure_station: query_dto.departure_station,
+        arrival_station: query_dto.arrival_station,
+        departure_city: query_dto.departure_city,
+        arrival_city: query_dto.arrival_city,
+        begin_date: parse_date(&query_dto.begin_date)?,
+        end_date: parse_date(&query_dto.end_date)?,
+    };
+
+    ApiResponse::ok(train_query_service.query_fare_calendar(command).await?)
+}
+
 #[post("/")]
 
 async fn query_train(
@@ -139,6 +297,15 @@ mod tests {
             departure_city: None,
             arrival_city: None,
             departure_time: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            departure_time_from: None,
+            departure_time_to: None,
+            arrival_time_from: None,
+            arrival_time_to: None,
+            train_type_prefixes: None,
+            seat_types: None,
+            sort_by: None,
+            cursor: None,
+            limit: None,
         };
 
         // 验证站点是空的
@@ -153,7 +320,11 @@ mod tests {
                     as Box<dyn base::application::ApplicationError>,
             )
         } else {
-            Ok(DirectTrainQueryDTO { solutions: vec![] })
+            Ok(DirectTrainQueryDTO {
+                solutions: vec![],
+                total: 0,
+                next_cursor: None,
+            })
         };
 
         // 验证结果是错误
@@ -179,6 +350,15 @@ mod tests {
             departure_city: None,
             arrival_city: None,
             departure_time: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            departure_time_from: None,
+            departure_time_to: None,
+            arrival_time_from: None,
+            arrival_time_to: None,
+            train_type_prefixes: None,
+            seat_types: None,
+            sort_by: None,
+            cursor: None,
+            limit: None,
         };
 
         // 验证站点不为空
@@ -194,7 +374,11 @@ mod tests {
                     as Box<dyn base::application::ApplicationError>,
             )
         } else {
-            Ok(DirectTrainQueryDTO { solutions: vec![] })
+            Ok(DirectTrainQueryDTO {
+                solutions: vec![],
+                total: 0,
+                next_cursor: None,
+            })
         };
 
         // 验证结果是成功的
@@ -213,6 +397,8 @@ mod tests {
             departure_city: None,
             arrival_city: None,
             departure_time: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            seat_type: None,
+            min_seats: None,
         };
 
         // 验证出发站是空的
@@ -248,6 +434,8 @@ mod tests {
             departure_city: None,
             arrival_city: None,
             departure_time: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            seat_type: None,
+            min_seats: None,
         };
 
         // 验证出发站和到达站相同
@@ -296,6 +484,8 @@ mod tests {
             departure_city: None,
             arrival_city: None,
             departure_time: NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            seat_type: None,
+            min_seats: None,
         };
 
         // 验证站点不为空且不相同
@@ -0,0 +1,20 @@
+use crate::{ApiResponse, ApplicationErrorBox, get_session_id};
+use actix_web::{HttpRequest, get, web::Data};
+use base::application::service::train_order::{TrainOrderService, WaitlistInfoDTO};
+
+/// 查询当前用户的候补
+///
+/// GET /api/train/waitlist/list
+///
+/// 按提交时间倒序返回`WaitlistInfo`列表。
+#[get("/list")]
+pub async fn query_waitlist(
+    req: HttpRequest,
+    train_order_service: Data<dyn TrainOrderService>,
+) -> Result<ApiResponse<Vec<WaitlistInfoDTO>>, ApplicationErrorBox> {
+    let session_id = get_session_id(&req)?;
+
+    let waitlist_info_list = train_order_service.query_waitlist(session_id).await?;
+
+    ApiResponse::ok(waitlist_info_list)
+}
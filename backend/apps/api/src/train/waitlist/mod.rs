@@ -0,0 +1,12 @@
+pub mod list;
+pub mod new;
+
+use actix_web::web;
+
+use crate::train::waitlist::list::query_waitlist;
+use crate::train::waitlist::new::create_waitlist;
+
+pub fn scoped_config(cfg: &mut web::ServiceConfig) {
+    cfg.service(create_waitlist);
+    cfg.service(query_waitlist);
+}
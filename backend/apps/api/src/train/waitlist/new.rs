@@ -0,0 +1,29 @@
+use crate::{ApiResponse, ApplicationErrorBox, get_session_id, parse_request_body};
+use actix_web::{HttpRequest, post, web::Bytes, web::Data};
+use base::application::service::train_order::{
+    TrainOrderService, WaitlistInfoDTO, WaitlistRequestDTO,
+};
+
+/// 提交候补
+///
+/// POST /api/train/waitlist/new
+///
+/// 车次余票不足时，可提交候补并预付票款：预付金额为可接受座位类别中的最高票价。
+/// 本接口返回`WaitlistInfo`，需要根据其中的`transactionId`调用`支付订单`接口完成预付。
+/// 有车票被取消时按提交顺序兑现，以较低票价的座位类别兑现时退还差价；
+/// 截止时间前未能兑现则全额退款。兑现与退款结果均通过消息推送通知用户。
+#[post("/new")]
+pub async fn create_waitlist(
+    req: HttpRequest,
+    body: Bytes,
+    train_order_service: Data<dyn TrainOrderService>,
+) -> Result<ApiResponse<WaitlistInfoDTO>, ApplicationErrorBox> {
+    let session_id = get_session_id(&req)?;
+    let waitlist_dto: WaitlistRequestDTO = parse_request_body(body)?;
+
+    let waitlist_info = train_order_service
+        .create_waitlist(session_id, waitlist_dto)
+        .await?;
+
+    ApiResponse::ok(waitlist_info)
+}
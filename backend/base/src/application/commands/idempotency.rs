@@ -0,0 +1,10 @@
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BeginIdempotentRequestCommand {
+    pub session_id: String,
+    /// `Idempotency-Key`请求头的值
+    pub key: String,
+    /// 幂等键的作用范围，通常为请求路径
+    pub scope: String,
+    /// 用于计算请求摘要的请求内容
+    pub request: Vec<u8>,
+}
@@ -1,7 +1,11 @@
-use shared::data::{CityData, RawDishTakeawayData, StationData, TrainNumberData, TrainTypeData};
+use shared::data::{
+    CityData, IntraCityTransferData, RawDishTakeawayData, StationData, TrainNumberData,
+    TrainTypeData,
+};
 
 pub type LoadCityCommand = CityData;
 pub type LoadStationCommand = StationData;
+pub type LoadIntraCityTransferCommand = IntraCityTransferData;
 pub type LoadTrainTypeCommand = TrainTypeData;
 pub type LoadTrainNumberCommand = TrainNumberData;
 
@@ -1,4 +1,4 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, NaiveTime};
 use serde::{Deserialize, Serialize};
 
 use crate::application::service::train_query::TrainQueryServiceError;
@@ -27,6 +27,50 @@ pub struct DirectTrainQueryCommand {
     pub arrival_city: Option<String>,
     /// 乘车时间
     pub departure_time: NaiveDate,
+    /// 出发时刻下限（含）
+    pub departure_time_from: Option<NaiveTime>,
+    /// 出发时刻上限（含）
+    pub departure_time_to: Option<NaiveTime>,
+    /// 到达时刻下限（含），只比较时刻，不区分是否跨日
+    pub arrival_time_from: Option<NaiveTime>,
+    /// 到达时刻上限（含），只比较时刻，不区分是否跨日
+    pub arrival_time_to: Option<NaiveTime>,
+    /// 车次号前缀，如`["G", "D"]`，满足任一前缀即可，为`None`时不过滤
+    pub train_type_prefixes: Option<Vec<String>>,
+    /// 必须均有余票的座位类型，为`None`时不过滤
+    pub seat_types: Option<Vec<String>>,
+    /// 排序方式，为`None`时按出发时间排序
+    pub sort_by: Option<DirectTrainSortKey>,
+    /// 分页游标，为上一页响应中的`next_cursor`，为`None`时从第一条开始
+    pub cursor: Option<String>,
+    /// 每页数量，为`None`时返回全部结果
+    pub limit: Option<u32>,
+}
+
+/// 直达车次查询结果的排序方式（US1.2.2）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectTrainSortKey {
+    /// 按出发时间升序
+    #[default]
+    Departure,
+    /// 按到达时间升序
+    Arrival,
+    /// 按旅途总时长升序
+    Duration,
+    /// 按最低票价升序
+    Price,
+}
+
+impl From<DirectTrainSortKey> for &'static str {
+    fn from(value: DirectTrainSortKey) -> Self {
+        match value {
+            DirectTrainSortKey::Departure => "departure",
+            DirectTrainSortKey::Arrival => "arrival",
+            DirectTrainSortKey::Duration => "duration",
+            DirectTrainSortKey::Price => "price",
+        }
+    }
 }
 
 /// 中转车次查询（US3.1.1）——Query
@@ -44,6 +88,48 @@ pub struct TransferTrainQueryCommand {
     pub arrival_city: Option<String>,
     /// 乘车时间
     pub departure_time: NaiveDate,
+    /// 要求两段行程均有余票的座位类型，如“二等座”，为`None`时不检查余票
+    pub seat_type: Option<String>,
+    /// 两段行程该座位类型的最少余票数，为`None`时为 1
+    pub min_seats: Option<u32>,
+}
+
+/// 多次换乘行程查询——Query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JourneyQueryCommand {
+    /// 客户端会话，用于校验登录状态
+    pub session_id: String,
+    /// 始发站
+    pub departure_station: Option<String>,
+    /// 终到站
+    pub arrival_station: Option<String>,
+    /// 始发城市
+    pub departure_city: Option<String>,
+    /// 终到城市
+    pub arrival_city: Option<String>,
+    /// 乘车时间
+    pub departure_time: NaiveDate,
+    /// 最多换乘次数，为`None`时使用默认值
+    pub max_transfers: Option<u32>,
+}
+
+/// 按日期区间查询每日直达车次概况（低价日历）——Query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FareCalendarQueryCommand {
+    /// 客户端会话，用于校验登录状态
+    pub session_id: String,
+    /// 始发站
+    pub departure_station: Option<String>,
+    /// 终到站
+    pub arrival_station: Option<String>,
+    /// 始发城市
+    pub departure_city: Option<String>,
+    /// 终到城市
+    pub arrival_city: Option<String>,
+    /// 起始日期（含）
+    pub begin_date: NaiveDate,
+    /// 结束日期（含）
+    pub end_date: NaiveDate,
 }
 
 pub trait TrainQueryValidate {
@@ -116,3 +202,33 @@ impl TrainQueryValidate for TransferTrainQueryCommand {
         &self.arrival_city
     }
 }
+
+impl TrainQueryValidate for JourneyQueryCommand {
+    fn dep_station(&self) -> &Option<String> {
+        &self.departure_station
+    }
+    fn dep_city(&self) -> &Option<String> {
+        &self.departure_city
+    }
+    fn arr_station(&self) -> &Option<String> {
+        &self.arrival_station
+    }
+    fn arr_city(&self) -> &Option<String> {
+        &self.arrival_city
+    }
+}
+
+impl TrainQueryValidate for FareCalendarQueryCommand {
+    fn dep_station(&self) -> &Option<String> {
+        &self.departure_station
+    }
+    fn dep_city(&self) -> &Option<String> {
+        &self.departure_city
+    }
+    fn arr_station(&self) -> &Option<String> {
+        &self.arrival_station
+    }
+    fn arr_city(&self) -> &Option<String> {
+        &self.arrival_city
+    }
+}
@@ -6,6 +6,13 @@ pub struct RechargeCommand {
     pub amount: f64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PaymentCallbackCommand {
+    pub provider: String,
+    pub payload: Vec<u8>,
+    pub signature: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BalanceQuery {
     pub session_id: String,
@@ -16,6 +23,21 @@ pub struct TransactionQuery {
     pub session_id: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WalletStatementQuery {
+    pub session_id: String,
+    /// 起始日期（含），格式：`YYYY-MM-DD`，为`None`时从第一笔交易开始
+    pub begin_date: Option<String>,
+    /// 截止日期（含），格式：`YYYY-MM-DD`，为`None`时截至当前
+    pub end_date: Option<String>,
+    /// 交易类型：`recharge`、`pay`、`refund`，为空时不限类型
+    pub types: Vec<String>,
+    /// 分页游标，为上一页响应中的`next_cursor`，为`None`时从第一条开始
+    pub cursor: Option<String>,
+    /// 每页数量，为`None`时返回全部结果
+    pub limit: Option<u32>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TransactionDetailQuery {
     pub session_id: String,
@@ -52,3 +74,16 @@ pub struct CancelOrderCommand {
     pub session_id: String,
     pub order_id: Uuid,
 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialRefundCommand {
+    pub session_id: String,
+    pub order_id: Uuid,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrderTimelineQuery {
+    pub session_id: String,
+    pub order_id: Uuid,
+}
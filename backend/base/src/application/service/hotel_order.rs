@@ -8,9 +8,11 @@ pub trait HotelOrderService: 'static + Send + Sync {
     /// 处理酒店预订订单
     ///
     /// 此方法接收会话ID和酒店订单请求，验证并创建订单，然后创建交易
+    /// 若提供优惠码，将在创建交易时应用，返回的金额已扣除优惠金额
     async fn process_hotel_orders(
         &self,
         session_id: String,
         hotel_orders: HotelOrderRequestsDTO,
+        coupon_code: Option<String>,
     ) -> Result<TransactionInfoDTO, Box<dyn ApplicationError>>;
 }
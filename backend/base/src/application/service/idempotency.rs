@@ -0,0 +1,90 @@
+use crate::application::commands::idempotency::BeginIdempotentRequestCommand;
+use crate::application::{ApplicationError, GeneralError};
+use crate::domain::model::user::UserId;
+use async_trait::async_trait;
+use thiserror::Error;
+
+/// 幂等键的最大长度
+pub const IDEMPOTENCY_KEY_MAX_LENGTH: usize = 255;
+
+#[derive(Error, Debug)]
+pub enum IdempotencyError {
+    #[error("idempotency key is already used by a different request")]
+    KeyConflict,
+    #[error("a request with the same idempotency key is still being processed")]
+    RequestInProgress,
+}
+
+impl ApplicationError for IdempotencyError {
+    fn error_code(&self) -> u32 {
+        match self {
+            IdempotencyError::KeyConflict => 16001,
+            IdempotencyError::RequestInProgress => 16002,
+        }
+    }
+
+    fn error_message(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// 已登记的幂等键，请求处理完成后交回`complete`或`release`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyReservation {
+    pub(crate) user_id: UserId,
+    pub(crate) scope: String,
+    pub(crate) key: String,
+}
+
+/// 登记幂等键的结果
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IdempotentRequest {
+    /// 首次请求，需处理请求并保存响应
+    Proceed(IdempotencyReservation),
+    /// 重复请求，直接返回保存的响应数据
+    Replay(String),
+}
+
+#[async_trait]
+pub trait IdempotencyApplicationService: 'static + Send + Sync {
+    /// 登记幂等键。
+    ///
+    /// Returns:
+    /// - 首次请求返回`IdempotentRequest::Proceed`，已完成的相同请求返回`IdempotentRequest::Replay`。
+    /// - 相同键的请求内容不同时返回`IdempotencyError::KeyConflict`，
+    ///   相同请求仍在处理中时返回`IdempotencyError::RequestInProgress`。
+    async fn begin(
+        &self,
+        command: BeginIdempotentRequestCommand,
+    ) -> Result<IdempotentRequest, Box<dyn ApplicationError>>;
+
+    /// 请求成功后保存响应数据（JSON）。
+    async fn complete(
+        &self,
+        reservation: IdempotencyReservation,
+        response: String,
+    ) -> Result<(), Box<dyn ApplicationError>>;
+
+    /// 请求失败后删除登记，允许客户端使用同一个键重试。
+    async fn release(
+        &self,
+        reservation: IdempotencyReservation,
+    ) -> Result<(), Box<dyn ApplicationError>>;
+
+    /// 定期清理超过保留时间的幂等键记录。
+    async fn cleanup_daemon(&self);
+}
+
+/// 校验幂等键：非空、仅包含可见 ASCII 字符且不超过`IDEMPOTENCY_KEY_MAX_LENGTH`
+pub fn validate_idempotency_key(key: &str) -> Result<(), GeneralError> {
+    if key.is_empty()
+        || key.len() > IDEMPOTENCY_KEY_MAX_LENGTH
+        || !key.bytes().all(|b| b.is_ascii_graphic())
+    {
+        return Err(GeneralError::BadRequest(
+            "invalid idempotency key".to_string(),
+        ));
+    }
+
+    Ok(())
+}
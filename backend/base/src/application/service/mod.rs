@@ -6,6 +6,7 @@ pub mod geo;
 pub mod hotel;
 pub mod hotel_data;
 pub mod hotel_order;
+pub mod idempotency;
 pub mod message;
 pub mod train_data;
 pub mod train_dish;
@@ -3,6 +3,7 @@
 //! 该模块提供列车基础数据相关的服务接口，包括：
 //! - 城市数据的加载
 //! - 车站数据的加载
+//! - 同城换乘时间数据的加载
 //! - 列车类型数据的加载
 //! - 列车车次数据的加载
 //!
@@ -10,8 +11,8 @@
 //! 该服务由基础设施层实现，供应用层调用以初始化系统基础数据。
 use crate::application::ApplicationError;
 use crate::application::commands::train_data::{
-    LoadCityCommand, LoadDishTakeawayCommand, LoadStationCommand, LoadTrainNumberCommand,
-    LoadTrainTypeCommand,
+    LoadCityCommand, LoadDishTakeawayCommand, LoadIntraCityTransferCommand, LoadStationCommand,
+    LoadTrainNumberCommand, LoadTrainTypeCommand,
 };
 use async_trait::async_trait;
 
@@ -24,6 +25,7 @@ use async_trait::async_trait;
 /// - `is_debug_mode`: 检查服务是否处于调试模式
 /// - `load_city`: 加载城市数据
 /// - `load_station`: 加载车站数据
+/// - `load_intra_city_transfer`: 加载同城换乘时间数据
 /// - `load_train_type`: 加载列车类型数据
 /// - `load_train_number`: 加载列车车次数据
 #[async_trait]
@@ -50,6 +52,18 @@ pub trait TrainDataService: 'static + Send + Sync {
         command: LoadStationCommand,
     ) -> Result<(), Box<dyn ApplicationError>>;
 
+    /// 加载同城换乘时间数据
+    ///
+    /// 根据[`LoadIntraCityTransferCommand`]提供的信息创建或更新同城车站之间的换乘时间。
+    /// 城市与车站必须已存在。
+    ///
+    /// # Arguments
+    /// * `command` - 包含同城换乘时间数据的命令对象
+    async fn load_intra_city_transfer(
+        &self,
+        command: LoadIntraCityTransferCommand,
+    ) -> Result<(), Box<dyn ApplicationError>>;
+
     /// 加载列车类型数据
     ///
     /// 根据[`LoadTrainTypeCommand`]提供的信息创建或更新列车类型数据。
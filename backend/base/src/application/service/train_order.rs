@@ -2,6 +2,7 @@ use crate::{application::ApplicationError, domain::service::ServiceError};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use uuid::Uuid;
 
 use super::transaction::TransactionInfoDTO;
 
@@ -21,6 +22,9 @@ pub struct TrainOrderRequestDTO {
     pub personal_id: String,
     /// 座位类别，如：二等座
     pub seat_type: String,
+    /// 乘客类别：adult（成人）、child（儿童）、student（学生）、senior（老年）、disabled（优待），默认为成人
+    #[serde(default)]
+    pub ticket_category: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -43,6 +47,73 @@ pub struct CreateTrainOrderDTO {
     pub arrival_station: String,
     pub personal_id: String,
     pub seat_type: String,
+    pub ticket_category: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RebookTrainOrderDTO {
+    /// 要改签的火车票订单 Id
+    pub order_id: Uuid,
+    /// 新车次号，例如："G53"
+    pub train_number: String,
+    /// 新车次离开"始发站"的日期时间
+    pub origin_departure_time: String,
+    /// 起始站
+    pub departure_station: String,
+    /// 到达站
+    pub arrival_station: String,
+    /// 座位类别，如：二等座
+    pub seat_type: String,
+    /// 用户密码，与支付密码二选一，同时提供时使用用户密码
+    pub user_password: Option<String>,
+    /// 支付密码
+    pub payment_password: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistRequestDTO {
+    /// 车次号，例如："G53"
+    pub train_number: String,
+    /// 离开"始发站"的日期时间
+    pub origin_departure_time: String,
+    /// 起始站
+    pub departure_station: String,
+    /// 到达站
+    pub arrival_station: String,
+    /// 乘车人 Id（见`PersonalInfo`）
+    pub personal_id: String,
+    /// 可接受的座位类别，按优先顺序排列，如：["二等座", "一等座"]
+    pub seat_types: Vec<String>,
+    /// 乘客类别，同`TrainOrderRequestDTO`
+    #[serde(default)]
+    pub ticket_category: Option<String>,
+    /// 候补截止时间（RFC 3339），此前未能兑现则全额退款
+    pub deadline: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistInfoDTO {
+    /// 候补 Id
+    pub waitlist_id: Uuid,
+    /// 预付票款的火车票订单 Id
+    pub order_id: Uuid,
+    /// 预付票款的交易 Id，需支付该交易后候补才会兑现
+    pub transaction_id: Uuid,
+    /// 可接受的座位类别，按优先顺序排列
+    pub seat_types: Vec<String>,
+    /// 预付金额，即可接受座位类别中的最高票价
+    pub prepaid_amount: f64,
+    /// 候补截止时间
+    pub deadline: String,
+    /// 提交时间
+    pub create_time: String,
+    /// 候补状态：pending、fulfilled、expired、refunded、cancelled
+    pub status: String,
+    /// 兑现时分配的座位类别
+    pub fulfilled_seat_type: Option<String>,
 }
 
 #[derive(Error, Debug)]
@@ -62,6 +133,24 @@ pub enum TrainOrderServiceError {
     /// 乘车人 Id 不存在，或未与当前用户绑定
     #[error("invalid passenger id")]
     InvalidPassengerId,
+    /// 乘客类别不存在
+    #[error("invalid ticket category: {0}")]
+    InvalidTicketCategory(String),
+    /// 乘车人不满足乘客类别的条件，如购买儿童票的乘车人已满14周岁
+    #[error("passenger is not eligible for ticket category: {0}")]
+    IneligibleTicketCategory(String),
+    /// 订单 Id 不存在，或不属于当前用户
+    #[error("invalid order id: {0}")]
+    InvalidOrderId(Uuid),
+    /// 只有未出行的火车票订单可以改签
+    #[error("order {0} cannot be rebooked in status: {1}")]
+    InvalidRebookOrderStatus(Uuid, String),
+    /// 暂留座位模式下，下单时余票不足
+    #[error("no available tickets")]
+    NoAvailableTickets,
+    /// 候补截止时间须晚于当前时间，且不晚于乘车站的发车时间
+    #[error("invalid waitlist deadline: {0}")]
+    InvalidWaitlistDeadline(String),
 }
 
 impl ApplicationError for TrainOrderServiceError {
@@ -72,6 +161,12 @@ impl ApplicationError for TrainOrderServiceError {
             TrainOrderServiceError::InvalidTrainNumber => 404,
             TrainOrderServiceError::InvalidStationId => 404,
             TrainOrderServiceError::InvalidPassengerId => 404,
+            TrainOrderServiceError::InvalidTicketCategory(_) => 12002,
+            TrainOrderServiceError::IneligibleTicketCategory(_) => 12003,
+            TrainOrderServiceError::InvalidOrderId(_) => 404,
+            TrainOrderServiceError::InvalidRebookOrderStatus(_, _) => 12004,
+            TrainOrderServiceError::NoAvailableTickets => 12005,
+            TrainOrderServiceError::InvalidWaitlistDeadline(_) => 12006,
         }
     }
 
@@ -86,9 +181,41 @@ pub trait TrainOrderService: 'static + Send + Sync {
     ///
     /// 此方法接收会话ID和订单包列表，验证并创建订单，然后创建交易
     /// 注意会话ID用于获取用户ID，订单包中包含原子性设置
+    /// 若提供优惠码，将在创建交易时应用，返回的金额已扣除优惠金额
+    /// 启用暂留座位模式时，下单即暂留座位至支付截止时间，余票不足时不创建交易
     async fn process_train_order_packs(
         &self,
         session_id: String,
         order_packs: Vec<OrderPackDTO>,
+        coupon_code: Option<String>,
     ) -> Result<TransactionInfoDTO, Box<dyn ApplicationError>>;
+
+    /// 改签火车票订单
+    ///
+    /// 为原订单的乘车人和乘客类别创建新车次的订单，并创建一笔调整交易结算新旧票价的差额。
+    /// 占座在消息队列中异步完成：先为新订单占座，成功后释放原订单座位并结算差价；
+    /// 新订单占座失败时调整交易被丢弃，原订单保持不变。
+    /// 返回的金额为差价，为正时表示补缴，为负时表示退还
+    async fn rebook_train_order(
+        &self,
+        session_id: String,
+        rebook_dto: RebookTrainOrderDTO,
+    ) -> Result<TransactionInfoDTO, Box<dyn ApplicationError>>;
+
+    /// 提交候补
+    ///
+    /// 为每个可接受的座位类别计算票价，按其中的最高票价创建预付订单及交易。
+    /// 交易支付后订单保持已支付状态，不参与普通占座；有车票被取消时按提交顺序兑现，
+    /// 以较低票价的座位类别兑现时退还差价，截止时间前未能兑现则全额退款
+    async fn create_waitlist(
+        &self,
+        session_id: String,
+        waitlist_dto: WaitlistRequestDTO,
+    ) -> Result<WaitlistInfoDTO, Box<dyn ApplicationError>>;
+
+    /// 查询当前用户的候补，按提交时间倒序排列
+    async fn query_waitlist(
+        &self,
+        session_id: String,
+    ) -> Result<Vec<WaitlistInfoDTO>, Box<dyn ApplicationError>>;
 }
@@ -5,7 +5,8 @@ use thiserror::Error;
 
 use crate::application::ApplicationError;
 use crate::application::commands::train_query::{
-    DirectTrainQueryCommand, TrainScheduleQueryCommand, TransferTrainQueryCommand,
+    DirectTrainQueryCommand, FareCalendarQueryCommand, JourneyQueryCommand,
+    TrainScheduleQueryCommand, TransferTrainQueryCommand,
 };
 
 // Step 2: Define `TrainQueryServiceError` for possible errors
@@ -151,8 +152,13 @@ pub struct TrainInfoDTO {
 
 /// 直达查询的响应 DTO
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct DirectTrainQueryDTO {
     pub solutions: Vec<TrainInfoDTO>,
+    /// 满足过滤条件的车次总数
+    pub total: u32,
+    /// 下一页的分页游标，没有下一页时为`None`
+    pub next_cursor: Option<String>,
 }
 
 /// 中转方案（两段行程 + 中转站）
@@ -161,6 +167,10 @@ pub struct TransferSolutionDTO {
     pub first_ride: TrainInfoDTO,
     pub second_ride: TrainInfoDTO,
     pub relaxing_time: u32,
+    /// 换乘方式：same_station（同站换乘）或 same_city（同城换站换乘）
+    pub transfer_type: String,
+    /// 同城换站所需时间，单位：秒，同站换乘为 0
+    pub transfer_time: u32,
 }
 
 /// 中转查询的响应 DTO
@@ -169,6 +179,54 @@ pub struct TransferTrainQueryDTO {
     pub solutions: Vec<TransferSolutionDTO>,
 }
 
+/// 多次换乘行程方案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JourneySolutionDTO {
+    /// 各段乘车信息，按乘坐顺序排列
+    pub rides: Vec<TrainInfoDTO>,
+    /// 各次换乘的等待时间，单位：秒，长度为`rides.len() - 1`
+    pub relaxing_times: Vec<u32>,
+    /// 各次换乘的换乘方式：same_station（同站换乘）或 same_city（同城换站换乘）
+    pub transfer_types: Vec<String>,
+    /// 各次换乘中同城换站所需时间，单位：秒，同站换乘为 0
+    pub transfer_times: Vec<u32>,
+    /// 换乘次数
+    pub transfer_count: u32,
+    /// 总行程时间：到达终点的时间 - 离开起点的时间，单位：秒
+    pub total_time: u32,
+    /// 各段最低席别票价之和
+    pub price: u32,
+}
+
+/// 多次换乘行程查询的响应 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JourneyQueryDTO {
+    pub solutions: Vec<JourneySolutionDTO>,
+}
+
+/// 低价日历中某一天的直达车次概况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FareCalendarDayDTO {
+    /// 日期，格式：YYYY-MM-DD
+    pub date: String,
+    /// 仍有余票的直达车次数量
+    pub train_count: u32,
+    /// 仍有余票的直达车次中最早出发的日期时间，当天没有此类车次时为`None`
+    pub earliest_departure_time: Option<String>,
+    /// 座位类型 -> 仍有余票的车次中该座位类型的最低票价
+    pub lowest_price: HashMap<String, u32>,
+    /// 是否有任一车次的任一座位类型仍有余票
+    pub has_seats: bool,
+}
+
+/// 低价日历查询的响应 DTO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FareCalendarDTO {
+    pub days: Vec<FareCalendarDayDTO>,
+}
+
 // Thinking 1.2.1D - 3：DTO和CQRS结构的区别与联系是什么？它们中的数据是经过校验的，还是未经过校验的？
 
 #[async_trait]
@@ -198,4 +256,20 @@ pub trait TrainQueryService: 'static + Send + Sync {
         &self,
         cmd: TransferTrainQueryCommand,
     ) -> Result<TransferTrainQueryDTO, Box<dyn ApplicationError>>;
+
+    /// 查询多次换乘行程
+    ///
+    /// 返回到达时间、换乘次数、票价三者的Pareto最优方案
+    async fn query_journeys(
+        &self,
+        cmd: JourneyQueryCommand,
+    ) -> Result<JourneyQueryDTO, Box<dyn ApplicationError>>;
+
+    /// 查询日期区间内每天的直达车次概况
+    ///
+    /// 日期区间不超过自动排班的天数
+    async fn query_fare_calendar(
+        &self,
+        cmd: FareCalendarQueryCommand,
+    ) -> Result<FareCalendarDTO, Box<dyn ApplicationError>>;
 }
@@ -1,10 +1,14 @@
 use crate::application::commands::transaction::{
-    BalanceQuery, CancelOrderCommand, GenerateDebugTransactionCommand, PayTransactionCommand,
-    RechargeCommand, SetPaymentPasswordCommand, TransactionDetailQuery, TransactionQuery,
+    BalanceQuery, CancelOrderCommand, GenerateDebugTransactionCommand, OrderTimelineQuery,
+    PartialRefundCommand, PayTransactionCommand, PaymentCallbackCommand, RechargeCommand,
+    SetPaymentPasswordCommand, TransactionDetailQuery, TransactionQuery, WalletStatementQuery,
 };
 use crate::application::{ApplicationError, GeneralError};
+use crate::domain::model::coupon::CouponError;
+use crate::domain::model::order_state_machine::OrderStatusTransition;
 use crate::domain::model::transaction::Transaction;
-use crate::domain::service::order::order_dto::TransactionDataDto;
+use crate::domain::service::order::order_dto::{OrderInfoDto, TransactionDataDto};
+use crate::domain::service::payment_gateway::{PaymentGatewayError, PaymentIntent};
 use crate::domain::service::transaction::TransactionServiceError;
 use async_trait::async_trait;
 use rust_decimal::prelude::ToPrimitive;
@@ -19,6 +23,43 @@ pub struct RechargeDTO {
     pub external_payment_id: Option<Uuid>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RechargeIntentDTO {
+    /// 充值交易，通过支付网关充值时为未支付状态，收到支付网关的成功回调后完成
+    pub transaction_id: Uuid,
+    /// 支付网关名称，直接入账时为`None`
+    pub provider: Option<String>,
+    /// 支付网关分配的支付 ID，直接入账时为`None`
+    pub external_payment_id: Option<Uuid>,
+    pub amount: f64,
+    /// 用户完成付款的地址，无需跳转的支付网关或直接入账时为`None`
+    pub payment_url: Option<String>,
+}
+
+impl RechargeIntentDTO {
+    pub fn new(transaction: &Transaction, intent: Option<PaymentIntent>) -> Self {
+        match intent {
+            Some(intent) => RechargeIntentDTO {
+                transaction_id: transaction.uuid(),
+                provider: transaction
+                    .external_payment()
+                    .map(|payment| payment.provider().to_string()),
+                external_payment_id: Some(intent.external_payment_id),
+                amount: intent.amount.to_f64().unwrap(),
+                payment_url: intent.payment_url,
+            },
+            None => RechargeIntentDTO {
+                transaction_id: transaction.uuid(),
+                provider: None,
+                external_payment_id: None,
+                amount: transaction.raw_amount().abs().to_f64().unwrap(),
+                payment_url: None,
+            },
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PaymentConfirmationDTO {
     #[serde(rename = "userPassword")]
@@ -53,6 +94,43 @@ pub struct TransactionInfoDTO {
     pub transaction_id: Uuid,
     pub amount: f64,
     pub status: String,
+    /// 支付截止时间（RFC 3339），不会超时的交易为`None`
+    pub payment_deadline: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletStatementDTO {
+    /// 期初余额：起始日期前完成的全部交易后的余额
+    pub opening_balance: f64,
+    /// 期末余额：截止日期结束时的余额
+    pub closing_balance: f64,
+    /// 当前页的账单条目，按完成时间先后排序
+    pub entries: Vec<StatementEntryDTO>,
+    /// 满足过滤条件的条目总数
+    pub total: u32,
+    /// 下一页的分页游标，没有下一页时为`None`
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementEntryDTO {
+    pub transaction_id: Uuid,
+    /// 交易类型：`recharge`、`pay`或`refund`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// 交易完成时间，RFC 3339格式
+    pub time: String,
+    /// 余额变动，充值与退款为正，支付为负
+    pub amount: f64,
+    /// 退款扣除的手续费，其他交易为 0
+    pub refund_fee: f64,
+    /// 使用优惠券的优惠金额，未使用优惠券时为 0
+    pub coupon_discount: f64,
+    /// 人类可读的交易说明，包含交易中各订单的说明
+    pub description: String,
+    pub orders: Vec<OrderInfoDto>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -61,12 +139,46 @@ pub struct CancelOrderDTO {
     pub order_id: Uuid,
 }
 
+/// 部分退款请求，按数量退还订单的一部分
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialRefundDTO {
+    pub order_id: Uuid,
+    /// 退款数量，如酒店订单的房间数、火车餐/外卖订单的份数
+    pub quantity: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatusHistoryDTO {
+    pub from_status: String,
+    pub to_status: String,
+    /// 触发状态迁移的操作者：`user`或`system`
+    pub actor: String,
+    pub reason: String,
+    /// 状态迁移时间，RFC 3339格式
+    pub time: String,
+}
+
+impl From<OrderStatusTransition> for OrderStatusHistoryDTO {
+    fn from(value: OrderStatusTransition) -> Self {
+        OrderStatusHistoryDTO {
+            from_status: value.from().to_string(),
+            to_status: value.to().to_string(),
+            actor: value.actor().to_string(),
+            reason: value.reason().to_string(),
+            time: value.create_time().to_rfc3339(),
+        }
+    }
+}
+
 impl From<Transaction> for TransactionInfoDTO {
     fn from(value: Transaction) -> Self {
         TransactionInfoDTO {
             transaction_id: value.uuid(),
             amount: value.raw_amount().to_f64().unwrap(),
             status: value.status().to_string(),
+            payment_deadline: value.payment_deadline().map(|dt| dt.to_rfc3339()),
         }
     }
 }
@@ -87,6 +199,34 @@ pub enum TransactionApplicationServiceError {
     InvalidTransactionStatus(String),
     #[error("invalid payment password format")]
     InvalidPaymentPasswordFormat,
+    #[error("{0}")]
+    CouponNotFound(String),
+    #[error("{0}")]
+    CouponNotApplicable(String),
+    #[error("{0}")]
+    CouponLimitReached(String),
+    #[error("transaction payment expired")]
+    PaymentExpired,
+    #[error("invalid payment callback: {0}")]
+    InvalidPaymentCallback(String),
+    #[error("payment gateway not configured")]
+    PaymentGatewayNotConfigured,
+    #[error("recharge is disabled")]
+    RechargeDisabled,
+}
+
+impl From<CouponError> for TransactionApplicationServiceError {
+    fn from(value: CouponError) -> Self {
+        match value {
+            e @ CouponError::NotFound(_) => {
+                TransactionApplicationServiceError::CouponNotFound(e.to_string())
+            }
+            e @ (CouponError::GlobalLimitReached(_) | CouponError::UserLimitReached(_)) => {
+                TransactionApplicationServiceError::CouponLimitReached(e.to_string())
+            }
+            e => TransactionApplicationServiceError::CouponNotApplicable(e.to_string()),
+        }
+    }
 }
 
 impl From<TransactionServiceError> for Box<dyn ApplicationError> {
@@ -113,6 +253,27 @@ impl From<TransactionServiceError> for Box<dyn ApplicationError> {
             e @ TransactionServiceError::RefundError(..) => Box::new(
                 TransactionApplicationServiceError::RefundError(e.to_string()),
             ),
+            TransactionServiceError::CouponError(e) => {
+                Box::new(TransactionApplicationServiceError::from(e))
+            }
+            TransactionServiceError::PaymentExpired(_) => {
+                Box::new(TransactionApplicationServiceError::PaymentExpired)
+            }
+            TransactionServiceError::PaymentGatewayNotConfigured => {
+                Box::new(TransactionApplicationServiceError::PaymentGatewayNotConfigured)
+            }
+            TransactionServiceError::RechargeDisabled => {
+                Box::new(TransactionApplicationServiceError::RechargeDisabled)
+            }
+            TransactionServiceError::UnknownPaymentProvider(_) => Box::new(GeneralError::NotFound),
+            e @ (TransactionServiceError::PaymentGatewayError(
+                PaymentGatewayError::InvalidSignature
+                | PaymentGatewayError::InvalidPayload(_)
+                | PaymentGatewayError::PaymentNotFound(_),
+            )
+            | TransactionServiceError::PaymentAmountMismatch { .. }) => Box::new(
+                TransactionApplicationServiceError::InvalidPaymentCallback(e.to_string()),
+            ),
             _ => Box::new(GeneralError::InternalServerError),
         }
     }
@@ -128,6 +289,13 @@ impl ApplicationError for TransactionApplicationServiceError {
             TransactionApplicationServiceError::RefundError(_) => 11005,
             TransactionApplicationServiceError::InvalidTransactionStatus(_) => 11006,
             TransactionApplicationServiceError::InvalidPaymentPasswordFormat => 11007,
+            TransactionApplicationServiceError::CouponNotFound(_) => 11008,
+            TransactionApplicationServiceError::CouponNotApplicable(_) => 11009,
+            TransactionApplicationServiceError::CouponLimitReached(_) => 11010,
+            TransactionApplicationServiceError::PaymentExpired => 11011,
+            TransactionApplicationServiceError::InvalidPaymentCallback(_) => 11012,
+            TransactionApplicationServiceError::PaymentGatewayNotConfigured => 11013,
+            TransactionApplicationServiceError::RechargeDisabled => 11014,
         }
     }
 
@@ -138,13 +306,35 @@ impl ApplicationError for TransactionApplicationServiceError {
 
 #[async_trait]
 pub trait TransactionApplicationService: 'static + Send + Sync {
-    async fn recharge(&self, command: RechargeCommand) -> Result<(), Box<dyn ApplicationError>>;
+    /// 通过支付网关发起充值，余额在收到支付网关的成功回调后才会增加
+    async fn recharge(
+        &self,
+        command: RechargeCommand,
+    ) -> Result<RechargeIntentDTO, Box<dyn ApplicationError>>;
+
+    /// 处理支付网关的支付结果回调，无需会话
+    async fn handle_payment_callback(
+        &self,
+        command: PaymentCallbackCommand,
+    ) -> Result<(), Box<dyn ApplicationError>>;
 
     async fn query_balance(
         &self,
         query: BalanceQuery,
     ) -> Result<BalanceInfoDTO, Box<dyn ApplicationError>>;
 
+    /// 查询钱包账单：按日期与交易类型过滤、分页的已完成交易，以及期初与期末余额
+    async fn query_statement(
+        &self,
+        query: WalletStatementQuery,
+    ) -> Result<WalletStatementDTO, Box<dyn ApplicationError>>;
+
+    /// 导出钱包账单为 CSV，忽略分页条件，包含满足过滤条件的全部条目与期初、期末余额
+    async fn export_statement(
+        &self,
+        query: WalletStatementQuery,
+    ) -> Result<String, Box<dyn ApplicationError>>;
+
     async fn query_transactions(
         &self,
         query: TransactionQuery,
@@ -174,4 +364,16 @@ pub trait TransactionApplicationService: 'static + Send + Sync {
         &self,
         command: CancelOrderCommand,
     ) -> Result<(), Box<dyn ApplicationError>>;
+
+    /// 按数量部分退款订单并释放退还的房间，订单状态不变，按退款策略扣除手续费
+    async fn partial_refund(
+        &self,
+        command: PartialRefundCommand,
+    ) -> Result<(), Box<dyn ApplicationError>>;
+
+    /// 查询订单的状态迁移时间线，按迁移时间先后排序
+    async fn query_order_timeline(
+        &self,
+        query: OrderTimelineQuery,
+    ) -> Result<Vec<OrderStatusHistoryDTO>, Box<dyn ApplicationError>>;
 }
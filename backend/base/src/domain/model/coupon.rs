@@ -0,0 +1,508 @@
+//! # 优惠券实体模块
+//!
+//! 该模块定义了促销相关的实体数据结构及其相关操作。主要包含以下内容：
+//!
+//! - `CouponDiscount`: 枚举类型，表示优惠方式（满减 / 折扣）。
+//! - `CouponError`: 枚举类型，表示优惠券无法使用的原因。
+//! - `Coupon`: 结构体，表示优惠券聚合根。
+//! - `CouponRedemptionStatus`: 枚举类型，表示交易中优惠券的核销状态。
+//! - `CouponRedemption`: 结构体，表示交易上的优惠券使用记录。
+//!
+//! ## 关于优惠券使用的约定
+//!
+//! - 优惠券在创建交易时应用，优惠金额在此时确定并写入交易，交易金额为订单总价减去优惠金额。
+//! - 支付交易时核销优惠券，核销与支付在同一数据库事务中完成，并在其中检查使用次数限制。
+//! - 全额退款时返还优惠券（恢复使用次数）；部分退款时优惠券作废，退款金额按订单价格比例扣除优惠金额。
+use crate::domain::model::order::{Order, OrderType};
+use crate::domain::{Aggregate, Entity, Identifiable, Identifier};
+use id_macro::define_id_type;
+use sea_orm::prelude::{DateTimeWithTimeZone, Decimal};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+define_id_type!(Coupon);
+
+/// 枚举类型，表示优惠方式。
+///
+/// - `Fixed`: 固定金额优惠，例如：减 20 元。
+/// - `Percent`: 按百分比优惠，例如：`Percent(15)`表示优惠 15%（即八五折）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CouponDiscount {
+    Fixed(Decimal),
+    Percent(Decimal),
+}
+
+impl CouponDiscount {
+    /// 根据优惠类型字符串与数值创建优惠方式。
+    ///
+    /// Arguments:
+    /// - `discount_type`: 优惠类型，`fixed`或`percent`。
+    /// - `value`: 优惠数值，固定金额须大于 0，百分比须在`(0, 100]`内。
+    pub fn try_new(discount_type: &str, value: Decimal) -> Result<Self, CouponError> {
+        let discount = match discount_type {
+            "fixed" => CouponDiscount::Fixed(value),
+            "percent" => CouponDiscount::Percent(value),
+            x => {
+                return Err(CouponError::InvalidDiscount(format!(
+                    "invalid discount type: {}",
+                    x
+                )));
+            }
+        };
+
+        if value <= Decimal::ZERO {
+            return Err(CouponError::InvalidDiscount(format!(
+                "discount value should be positive: {}",
+                value
+            )));
+        }
+
+        if matches!(discount, CouponDiscount::Percent(_)) && value > Decimal::ONE_HUNDRED {
+            return Err(CouponError::InvalidDiscount(format!(
+                "discount percent should not exceed 100: {}",
+                value
+            )));
+        }
+
+        Ok(discount)
+    }
+
+    /// 获取优惠类型字符串：`fixed`或`percent`。
+    pub fn discount_type(&self) -> &'static str {
+        match self {
+            CouponDiscount::Fixed(_) => "fixed",
+            CouponDiscount::Percent(_) => "percent",
+        }
+    }
+
+    /// 获取优惠数值。
+    pub fn value(&self) -> Decimal {
+        match self {
+            CouponDiscount::Fixed(value) | CouponDiscount::Percent(value) => *value,
+        }
+    }
+}
+
+/// 枚举类型，表示优惠券无法使用的原因。
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CouponError {
+    #[error("coupon not found: {0}")]
+    NotFound(String),
+    #[error("invalid coupon discount: {0}")]
+    InvalidDiscount(String),
+    #[error("coupon {0} is not yet valid")]
+    NotStarted(String),
+    #[error("coupon {0} has expired")]
+    Expired(String),
+    #[error("coupon {0} is not applicable to any order in this transaction")]
+    NotApplicable(String),
+    #[error("coupon {code} requires a minimum spend of {min_spend}, but only {amount} applicable")]
+    BelowMinimumSpend {
+        code: String,
+        min_spend: Decimal,
+        amount: Decimal,
+    },
+    #[error("coupon {0} has reached its redemption limit")]
+    GlobalLimitReached(String),
+    #[error("coupon {0} has reached its redemption limit for this user")]
+    UserLimitReached(String),
+}
+
+/// 结构体，表示优惠券聚合根。
+///
+/// 包含以下字段：
+/// - `coupon_id`: 优惠券的唯一标识符，可以为空。
+/// - `code`: 优惠码，全局唯一。
+/// - `discount`: 优惠方式。
+/// - `min_spend`: 最低消费金额，按适用订单的总价计算。
+/// - `applicable_order_types`: 适用的订单类型，为空表示适用于所有订单类型。
+/// - `valid_from`: 生效时间。
+/// - `expire_time`: 过期时间。
+/// - `per_user_limit`: 每个用户最多使用次数，为空表示不限。
+/// - `global_limit`: 总使用次数上限，为空表示不限。
+/// - `redeemed_count`: 已核销次数。
+#[derive(Debug, Clone)]
+pub struct Coupon {
+    coupon_id: Option<CouponId>,
+    code: String,
+    discount: CouponDiscount,
+    min_spend: Decimal,
+    applicable_order_types: Vec<OrderType>,
+    valid_from: DateTimeWithTimeZone,
+    expire_time: DateTimeWithTimeZone,
+    per_user_limit: Option<u32>,
+    global_limit: Option<u32>,
+    redeemed_count: u32,
+}
+
+impl Identifiable for Coupon {
+    type ID = CouponId;
+
+    fn get_id(&self) -> Option<Self::ID> {
+        self.coupon_id
+    }
+
+    fn set_id(&mut self, id: Self::ID) {
+        self.coupon_id = Some(id);
+    }
+}
+
+impl Entity for Coupon {}
+
+impl Aggregate for Coupon {}
+
+impl Coupon {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        coupon_id: Option<CouponId>,
+        code: String,
+        discount: CouponDiscount,
+        min_spend: Decimal,
+        applicable_order_types: Vec<OrderType>,
+        valid_from: DateTimeWithTimeZone,
+        expire_time: DateTimeWithTimeZone,
+        per_user_limit: Option<u32>,
+        global_limit: Option<u32>,
+        redeemed_count: u32,
+    ) -> Self {
+        Coupon {
+            coupon_id,
+            code,
+            discount,
+            min_spend,
+            applicable_order_types,
+            valid_from,
+            expire_time,
+            per_user_limit,
+            global_limit,
+            redeemed_count,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn discount(&self) -> CouponDiscount {
+        self.discount
+    }
+
+    pub fn min_spend(&self) -> Decimal {
+        self.min_spend
+    }
+
+    pub fn applicable_order_types(&self) -> &[OrderType] {
+        &self.applicable_order_types
+    }
+
+    pub fn valid_from(&self) -> DateTimeWithTimeZone {
+        self.valid_from
+    }
+
+    pub fn expire_time(&self) -> DateTimeWithTimeZone {
+        self.expire_time
+    }
+
+    pub fn per_user_limit(&self) -> Option<u32> {
+        self.per_user_limit
+    }
+
+    pub fn global_limit(&self) -> Option<u32> {
+        self.global_limit
+    }
+
+    pub fn redeemed_count(&self) -> u32 {
+        self.redeemed_count
+    }
+
+    /// 判断优惠券是否适用于指定订单类型。
+    pub fn is_applicable_to(&self, order_type: OrderType) -> bool {
+        self.applicable_order_types.is_empty() || self.applicable_order_types.contains(&order_type)
+    }
+
+    /// 计算订单列表中适用本优惠券的订单总价。
+    pub fn applicable_amount(&self, orders: &[Box<dyn Order>]) -> Decimal {
+        orders
+            .iter()
+            .filter(|order| self.is_applicable_to(order.order_type()))
+            .map(|order| order.unit_price() * order.amount())
+            .sum()
+    }
+
+    /// 检查优惠券在指定时间是否处于有效期内。
+    pub fn check_valid_time(&self, now: DateTimeWithTimeZone) -> Result<(), CouponError> {
+        if now < self.valid_from {
+            return Err(CouponError::NotStarted(self.code.clone()));
+        }
+
+        if now >= self.expire_time {
+            return Err(CouponError::Expired(self.code.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// 检查使用次数限制。
+    ///
+    /// Arguments:
+    /// - `user_redeemed_count`: 该用户已核销（含已作废）本优惠券的次数。
+    pub fn check_redemption_limit(&self, user_redeemed_count: u32) -> Result<(), CouponError> {
+        if self
+            .global_limit
+            .is_some_and(|limit| self.redeemed_count >= limit)
+        {
+            return Err(CouponError::GlobalLimitReached(self.code.clone()));
+        }
+
+        if self
+            .per_user_limit
+            .is_some_and(|limit| user_redeemed_count >= limit)
+        {
+            return Err(CouponError::UserLimitReached(self.code.clone()));
+        }
+
+        Ok(())
+    }
+
+    /// 计算对指定订单列表的优惠金额。
+    ///
+    /// 优惠仅作用于适用的订单，优惠金额不超过适用订单总价，百分比优惠四舍五入到分。
+    ///
+    /// Returns:
+    /// - 成功时返回优惠金额。
+    /// - 不在有效期内、无适用订单或未达到最低消费时返回`CouponError`。
+    pub fn calculate_discount(
+        &self,
+        orders: &[Box<dyn Order>],
+        now: DateTimeWithTimeZone,
+    ) -> Result<Decimal, CouponError> {
+        self.check_valid_time(now)?;
+
+        let applicable_amount = self.applicable_amount(orders);
+
+        if applicable_amount <= Decimal::ZERO {
+            return Err(CouponError::NotApplicable(self.code.clone()));
+        }
+
+        if applicable_amount < self.min_spend {
+            return Err(CouponError::BelowMinimumSpend {
+                code: self.code.clone(),
+                min_spend: self.min_spend,
+                amount: applicable_amount,
+            });
+        }
+
+        let discount = match self.discount {
+            CouponDiscount::Fixed(value) => value,
+            CouponDiscount::Percent(percent) => {
+                (applicable_amount * percent / Decimal::ONE_HUNDRED).round_dp(2)
+            }
+        };
+
+        Ok(discount.min(applicable_amount))
+    }
+}
+
+/// 枚举类型，表示交易中优惠券的核销状态。
+///
+/// - `Pending`: 已应用于未支付的交易，尚未核销。
+/// - `Redeemed`: 交易已支付，优惠券已核销。
+/// - `Restored`: 交易全额退款或支付超时，优惠券已返还。
+/// - `Voided`: 交易部分退款，优惠券作废，不再返还。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CouponRedemptionStatus {
+    Pending,
+    Redeemed,
+    Restored,
+    Voided,
+}
+
+impl From<CouponRedemptionStatus> for &'static str {
+    fn from(value: CouponRedemptionStatus) -> Self {
+        match value {
+            CouponRedemptionStatus::Pending => "pending",
+            CouponRedemptionStatus::Redeemed => "redeemed",
+            CouponRedemptionStatus::Restored => "restored",
+            CouponRedemptionStatus::Voided => "voided",
+        }
+    }
+}
+
+impl TryFrom<&str> for CouponRedemptionStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "pending" => CouponRedemptionStatus::Pending,
+            "redeemed" => CouponRedemptionStatus::Redeemed,
+            "restored" => CouponRedemptionStatus::Restored,
+            "voided" => CouponRedemptionStatus::Voided,
+            x => return Err(format!("Invalid coupon redemption status: {}", x)),
+        })
+    }
+}
+
+impl Display for CouponRedemptionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            <CouponRedemptionStatus as Into<&'static str>>::into(*self)
+        )
+    }
+}
+
+/// 结构体，表示交易上的优惠券使用记录，属于交易聚合的一部分。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CouponRedemption {
+    coupon_id: CouponId,
+    discount: Decimal,
+    status: CouponRedemptionStatus,
+}
+
+impl CouponRedemption {
+    pub fn new(coupon_id: CouponId, discount: Decimal, status: CouponRedemptionStatus) -> Self {
+        CouponRedemption {
+            coupon_id,
+            discount,
+            status,
+        }
+    }
+
+    pub fn coupon_id(&self) -> CouponId {
+        self.coupon_id
+    }
+
+    /// 获取优惠金额。
+    pub fn discount(&self) -> Decimal {
+        self.discount
+    }
+
+    pub fn status(&self) -> CouponRedemptionStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: CouponRedemptionStatus) {
+        self.status = status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::test_support::{TestOrder, make_coupon};
+    use crate::domain::model::transaction::Transaction;
+    use chrono::Duration;
+
+    #[test]
+    fn test_coupon_discount_try_new() {
+        assert_eq!(
+            CouponDiscount::try_new("fixed", Decimal::from(20)),
+            Ok(CouponDiscount::Fixed(Decimal::from(20)))
+        );
+        assert_eq!(
+            CouponDiscount::try_new("percent", Decimal::from(15)),
+            Ok(CouponDiscount::Percent(Decimal::from(15)))
+        );
+        assert!(CouponDiscount::try_new("percent", Decimal::from(101)).is_err());
+        assert!(CouponDiscount::try_new("fixed", Decimal::ZERO).is_err());
+        assert!(CouponDiscount::try_new("gift", Decimal::ONE).is_err());
+    }
+
+    #[test]
+    fn test_check_valid_time() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(20)), Decimal::ZERO);
+        let now = Transaction::now();
+
+        assert!(coupon.check_valid_time(now).is_ok());
+        assert_eq!(
+            coupon.check_valid_time(now - Duration::days(2)),
+            Err(CouponError::NotStarted("SPRING".to_string()))
+        );
+        assert_eq!(
+            coupon.check_valid_time(now + Duration::days(2)),
+            Err(CouponError::Expired("SPRING".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_check_redemption_limit() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(20)), Decimal::ZERO);
+
+        assert!(coupon.check_redemption_limit(0).is_ok());
+        assert_eq!(
+            coupon.check_redemption_limit(1),
+            Err(CouponError::UserLimitReached("SPRING".to_string()))
+        );
+
+        let exhausted = Coupon {
+            redeemed_count: 10,
+            ..coupon
+        };
+
+        assert_eq!(
+            exhausted.check_redemption_limit(0),
+            Err(CouponError::GlobalLimitReached("SPRING".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_applicable_order_types() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(20)), Decimal::ZERO);
+
+        assert!(coupon.is_applicable_to(OrderType::Train));
+        assert!(!coupon.is_applicable_to(OrderType::Hotel));
+
+        let all = Coupon {
+            applicable_order_types: Vec::new(),
+            ..coupon
+        };
+
+        assert!(all.is_applicable_to(OrderType::Hotel));
+        assert!(all.is_applicable_to(OrderType::Takeaway));
+    }
+
+    #[test]
+    fn test_calculate_discount_without_orders() {
+        let coupon = make_coupon(CouponDiscount::Percent(Decimal::from(15)), Decimal::ZERO);
+
+        assert_eq!(
+            coupon.calculate_discount(&[], Transaction::now()),
+            Err(CouponError::NotApplicable("SPRING".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_calculate_discount() {
+        let orders = vec![
+            TestOrder::new_boxed(OrderType::Train, 300),
+            TestOrder::new_boxed(OrderType::Hotel, 500),
+        ];
+        let now = Transaction::now();
+
+        // 仅火车票订单适用
+        let percent = make_coupon(CouponDiscount::Percent(Decimal::new(125, 1)), Decimal::ZERO);
+        assert_eq!(
+            percent.calculate_discount(&orders, now),
+            Ok(Decimal::new(3750, 2))
+        );
+
+        // 优惠金额不超过适用订单总价
+        let fixed = make_coupon(CouponDiscount::Fixed(Decimal::from(400)), Decimal::ZERO);
+        assert_eq!(
+            fixed.calculate_discount(&orders, now),
+            Ok(Decimal::from(300))
+        );
+
+        // 最低消费按适用订单总价计算
+        let min_spend = make_coupon(CouponDiscount::Fixed(Decimal::from(20)), Decimal::from(500));
+        assert_eq!(
+            min_spend.calculate_discount(&orders, now),
+            Err(CouponError::BelowMinimumSpend {
+                code: "SPRING".to_string(),
+                min_spend: Decimal::from(500),
+                amount: Decimal::from(300),
+            })
+        );
+    }
+}
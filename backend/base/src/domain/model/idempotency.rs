@@ -0,0 +1,104 @@
+//! # 幂等键模块
+//!
+//! 该模块定义了客户端通过`Idempotency-Key`请求头提交的幂等键记录。主要包含以下内容：
+//!
+//! - `IdempotencyRecord`: 结构体，表示一个幂等键及其请求摘要与响应。
+//!
+//! ## 约定
+//!
+//! - 幂等键按用户及接口（`scope`）隔离，不同用户或不同接口可使用相同的键。
+//! - 处理请求前先以“处理中”状态（响应为空）登记幂等键，同一个键的并发请求只有一个能登记成功。
+//! - 请求成功后保存响应，之后相同键、相同请求的重试直接返回保存的响应；请求失败时删除登记，允许客户端使用同一个键重试。
+//! - 处理中的登记在`IDEMPOTENCY_KEY_LOCK_SECONDS`秒后失效（如处理请求时进程退出），之后相同请求可重新登记并处理。
+//! - 幂等键记录保留`IDEMPOTENCY_KEY_RETENTION_HOURS`小时，之后被定期清理。
+//! - 相同键但请求内容不同（请求摘要不一致）的请求将被拒绝。
+use crate::IDEMPOTENCY_KEY_LOCK_SECONDS;
+use crate::domain::model::user::UserId;
+use chrono::{Duration, Local};
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+/// 结构体，表示一个幂等键及其请求摘要与响应。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyRecord {
+    user_id: UserId,
+    scope: String,
+    key: String,
+    request_hash: String,
+    response: Option<String>,
+    create_time: DateTimeWithTimeZone,
+    locked_until: DateTimeWithTimeZone,
+}
+
+impl IdempotencyRecord {
+    pub fn new(
+        user_id: UserId,
+        scope: String,
+        key: String,
+        request_hash: String,
+        response: Option<String>,
+        create_time: DateTimeWithTimeZone,
+        locked_until: DateTimeWithTimeZone,
+    ) -> Self {
+        Self {
+            user_id,
+            scope,
+            key,
+            request_hash,
+            response,
+            create_time,
+            locked_until,
+        }
+    }
+
+    /// 创建处理中的幂等键记录
+    pub fn new_pending(user_id: UserId, scope: String, key: String, request_hash: String) -> Self {
+        let local_now = Local::now();
+        let offset = *local_now.offset();
+        let create_time = local_now.with_timezone(&offset);
+
+        Self::new(
+            user_id,
+            scope,
+            key,
+            request_hash,
+            None,
+            create_time,
+            create_time + Duration::seconds(IDEMPOTENCY_KEY_LOCK_SECONDS),
+        )
+    }
+
+    pub fn user_id(&self) -> UserId {
+        self.user_id
+    }
+
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn request_hash(&self) -> &str {
+        &self.request_hash
+    }
+
+    /// 保存的响应数据，请求仍在处理中时为空
+    pub fn response(&self) -> Option<&str> {
+        self.response.as_deref()
+    }
+
+    pub fn create_time(&self) -> DateTimeWithTimeZone {
+        self.create_time
+    }
+
+    /// 处理中的登记的失效时间，失效后相同请求可重新登记
+    pub fn locked_until(&self) -> DateTimeWithTimeZone {
+        self.locked_until
+    }
+
+    /// 判断请求摘要是否与登记时一致
+    pub fn matches(&self, request_hash: &str) -> bool {
+        self.request_hash == request_hash
+    }
+}
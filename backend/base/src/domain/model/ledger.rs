@@ -0,0 +1,446 @@
+//! # 复式记账模块
+//!
+//! 该模块定义了用户钱包背后的复式记账账本。主要包含以下内容：
+//!
+//! - `LedgerAccount`: 枚举类型，表示记账科目。
+//! - `JournalEntryKind`: 枚举类型，表示记账凭证的业务类型。
+//! - `JournalLine`: 结构体，表示记账凭证中的一条分录。
+//! - `JournalEntry`: 结构体，表示一张记账凭证。
+//! - `LedgerError`: 枚举类型，表示记账错误。
+//!
+//! ## 记账约定
+//!
+//! - 分录金额为对应科目余额的变动，增加为正、减少为负；每张凭证的分录金额之和必须为 0。
+//! - 用户钱包科目的余额即用户余额，应与`balance`视图中的余额一致，由对账任务定期核对。
+//! - 交易完成（进入“已支付”状态）时生成凭证，随交易在同一数据库事务中持久化：
+//!
+//! | 业务 | 分录 |
+//! | --- | --- |
+//! | 充值 `x` | 外部资金 −x，用户钱包 +x |
+//! | 支付 `x`，优惠 `d` | 用户钱包 −x，营销费用 −d，平台收入 +(x+d) |
+//! | 退款 `x`，手续费 `f` | 平台收入 −(x+f)，退款清算 +(x+f)；退款清算 −x，用户钱包 +x；退款清算 −f，平台收入 +f |
+//!
+//! - 上线账本前已存在的余额以“期初余额”凭证导入，对方科目为`OpeningBalance`。
+use crate::domain::model::user::UserId;
+use chrono::Local;
+use rust_decimal::Decimal;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LedgerError {
+    #[error("unbalanced journal entry for transaction {transaction_uuid:?}: lines sum to {sum}")]
+    Unbalanced {
+        transaction_uuid: Option<Uuid>,
+        sum: Decimal,
+    },
+}
+
+/// 记账科目
+///
+/// - `UserWallet`: 用户钱包，余额即用户可用余额。
+/// - `PlatformRevenue`: 平台收入。
+/// - `RefundClearing`: 退款清算，退款在此科目中转，完成后余额为 0。
+/// - `Promotion`: 营销费用，优惠券抵扣的金额由平台承担。
+/// - `ExternalFunds`: 外部资金，充值资金的来源。
+/// - `OpeningBalance`: 期初余额，导入上线账本前已存在的余额。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LedgerAccount {
+    UserWallet(UserId),
+    PlatformRevenue,
+    RefundClearing,
+    Promotion,
+    ExternalFunds,
+    OpeningBalance,
+}
+
+impl LedgerAccount {
+    /// 科目类型的字符串表示，用于持久化
+    pub fn kind(&self) -> &'static str {
+        match self {
+            LedgerAccount::UserWallet(_) => "user_wallet",
+            LedgerAccount::PlatformRevenue => "platform_revenue",
+            LedgerAccount::RefundClearing => "refund_clearing",
+            LedgerAccount::Promotion => "promotion",
+            LedgerAccount::ExternalFunds => "external_funds",
+            LedgerAccount::OpeningBalance => "opening_balance",
+        }
+    }
+
+    /// 科目为用户钱包时返回用户Id
+    pub fn user_id(&self) -> Option<UserId> {
+        match self {
+            LedgerAccount::UserWallet(user_id) => Some(*user_id),
+            _ => None,
+        }
+    }
+
+    /// 由科目类型及用户Id还原科目
+    pub fn from_parts(kind: &str, user_id: Option<UserId>) -> Result<Self, String> {
+        match (kind, user_id) {
+            ("user_wallet", Some(user_id)) => Ok(LedgerAccount::UserWallet(user_id)),
+            ("platform_revenue", None) => Ok(LedgerAccount::PlatformRevenue),
+            ("refund_clearing", None) => Ok(LedgerAccount::RefundClearing),
+            ("promotion", None) => Ok(LedgerAccount::Promotion),
+            ("external_funds", None) => Ok(LedgerAccount::ExternalFunds),
+            ("opening_balance", None) => Ok(LedgerAccount::OpeningBalance),
+            (kind, user_id) => Err(format!(
+                "Invalid ledger account: {}, user id: {:?}",
+                kind, user_id
+            )),
+        }
+    }
+}
+
+impl Display for LedgerAccount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerAccount::UserWallet(user_id) => write!(f, "{}:{}", self.kind(), user_id),
+            _ => write!(f, "{}", self.kind()),
+        }
+    }
+}
+
+/// 枚举类型，表示记账凭证的业务类型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JournalEntryKind {
+    Recharge,
+    Payment,
+    Refund,
+    Opening,
+}
+
+impl From<JournalEntryKind> for &'static str {
+    fn from(value: JournalEntryKind) -> Self {
+        match value {
+            JournalEntryKind::Recharge => "recharge",
+            JournalEntryKind::Payment => "payment",
+            JournalEntryKind::Refund => "refund",
+            JournalEntryKind::Opening => "opening",
+        }
+    }
+}
+
+impl TryFrom<&str> for JournalEntryKind {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "recharge" => Ok(JournalEntryKind::Recharge),
+            "payment" => Ok(JournalEntryKind::Payment),
+            "refund" => Ok(JournalEntryKind::Refund),
+            "opening" => Ok(JournalEntryKind::Opening),
+            _ => Err(format!("Invalid journal entry kind: {}", value)),
+        }
+    }
+}
+
+impl Display for JournalEntryKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <JournalEntryKind as Into<&str>>::into(*self))
+    }
+}
+
+/// 记账凭证中的一条分录
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JournalLine {
+    uuid: Uuid,
+    account: LedgerAccount,
+    amount: Decimal,
+}
+
+impl JournalLine {
+    pub fn new(uuid: Uuid, account: LedgerAccount, amount: Decimal) -> Self {
+        Self {
+            uuid,
+            account,
+            amount,
+        }
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn account(&self) -> LedgerAccount {
+        self.account
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// 一张记账凭证
+///
+/// 凭证及其分录均具有唯一的`uuid`，重复持久化同一张凭证不会产生重复的分录。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JournalEntry {
+    uuid: Uuid,
+    transaction_uuid: Option<Uuid>,
+    kind: JournalEntryKind,
+    lines: Vec<JournalLine>,
+    create_time: DateTimeWithTimeZone,
+}
+
+impl JournalEntry {
+    /// 创建记账凭证，并校验借贷平衡。
+    ///
+    /// Arguments:
+    /// - `uuid`: 凭证的 UUID。
+    /// - `transaction_uuid`: 凭证对应的交易的 UUID，期初余额凭证为空。
+    /// - `kind`: 凭证的业务类型。
+    /// - `lines`: 凭证的分录。
+    /// - `create_time`: 记账时间。
+    ///
+    /// Returns:
+    /// - 分录金额之和不为 0 时返回`LedgerError::Unbalanced`。
+    pub fn new(
+        uuid: Uuid,
+        transaction_uuid: Option<Uuid>,
+        kind: JournalEntryKind,
+        lines: Vec<JournalLine>,
+        create_time: DateTimeWithTimeZone,
+    ) -> Result<Self, LedgerError> {
+        let sum = lines.iter().map(|line| line.amount).sum::<Decimal>();
+
+        if !sum.is_zero() {
+            return Err(LedgerError::Unbalanced {
+                transaction_uuid,
+                sum,
+            });
+        }
+
+        Ok(Self {
+            uuid,
+            transaction_uuid,
+            kind,
+            lines,
+            create_time,
+        })
+    }
+
+    /// 按`(科目, 金额)`列表生成交易的记账凭证，金额为 0 的分录被忽略
+    fn post(
+        transaction_uuid: Uuid,
+        kind: JournalEntryKind,
+        lines: &[(LedgerAccount, Decimal)],
+    ) -> Self {
+        let local_now = Local::now();
+        let offset = *local_now.offset();
+
+        let lines = lines
+            .iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|(account, amount)| JournalLine::new(Uuid::new_v4(), *account, *amount))
+            .collect();
+
+        Self::new(
+            Uuid::new_v4(),
+            Some(transaction_uuid),
+            kind,
+            lines,
+            local_now.with_timezone(&offset),
+        )
+        .expect("posted journal entry should be balanced")
+    }
+
+    /// 生成充值凭证：外部资金 −amount，用户钱包 +amount
+    pub fn recharge(transaction_uuid: Uuid, user_id: UserId, amount: Decimal) -> Self {
+        Self::post(
+            transaction_uuid,
+            JournalEntryKind::Recharge,
+            &[
+                (LedgerAccount::ExternalFunds, -amount),
+                (LedgerAccount::UserWallet(user_id), amount),
+            ],
+        )
+    }
+
+    /// 生成支付凭证：用户钱包 −amount，营销费用 −discount，平台收入 +(amount+discount)
+    pub fn payment(
+        transaction_uuid: Uuid,
+        user_id: UserId,
+        amount: Decimal,
+        discount: Decimal,
+    ) -> Self {
+        Self::post(
+            transaction_uuid,
+            JournalEntryKind::Payment,
+            &[
+                (LedgerAccount::UserWallet(user_id), -amount),
+                (LedgerAccount::Promotion, -discount),
+                (LedgerAccount::PlatformRevenue, amount + discount),
+            ],
+        )
+    }
+
+    /// 生成退款凭证，退款经退款清算科目返还用户钱包，手续费转回平台收入
+    ///
+    /// Arguments:
+    /// - `amount`: 实际退还用户的金额。
+    /// - `fee`: 扣除的手续费。
+    pub fn refund(transaction_uuid: Uuid, user_id: UserId, amount: Decimal, fee: Decimal) -> Self {
+        Self::post(
+            transaction_uuid,
+            JournalEntryKind::Refund,
+            &[
+                (LedgerAccount::PlatformRevenue, -(amount + fee)),
+                (LedgerAccount::RefundClearing, amount + fee),
+                (LedgerAccount::RefundClearing, -amount),
+                (LedgerAccount::UserWallet(user_id), amount),
+                (LedgerAccount::RefundClearing, -fee),
+                (LedgerAccount::PlatformRevenue, fee),
+            ],
+        )
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn transaction_uuid(&self) -> Option<Uuid> {
+        self.transaction_uuid
+    }
+
+    pub fn kind(&self) -> JournalEntryKind {
+        self.kind
+    }
+
+    pub fn lines(&self) -> &[JournalLine] {
+        &self.lines
+    }
+
+    pub fn create_time(&self) -> DateTimeWithTimeZone {
+        self.create_time
+    }
+
+    /// 计算凭证中指定科目的余额变动
+    pub fn account_change(&self, account: LedgerAccount) -> Decimal {
+        self.lines
+            .iter()
+            .filter(|line| line.account == account)
+            .map(|line| line.amount)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posted_entries_are_balanced() {
+        let user_id = UserId::from(1);
+        let transaction_uuid = Uuid::new_v4();
+        let wallet = LedgerAccount::UserWallet(user_id);
+
+        let recharge = JournalEntry::recharge(transaction_uuid, user_id, Decimal::from(100));
+        assert_eq!(recharge.account_change(wallet), Decimal::from(100));
+        assert_eq!(
+            recharge.account_change(LedgerAccount::ExternalFunds),
+            Decimal::from(-100)
+        );
+
+        let payment = JournalEntry::payment(
+            transaction_uuid,
+            user_id,
+            Decimal::from(80),
+            Decimal::from(20),
+        );
+        assert_eq!(payment.account_change(wallet), Decimal::from(-80));
+        assert_eq!(
+            payment.account_change(LedgerAccount::Promotion),
+            Decimal::from(-20)
+        );
+        assert_eq!(
+            payment.account_change(LedgerAccount::PlatformRevenue),
+            Decimal::from(100)
+        );
+
+        let refund = JournalEntry::refund(
+            transaction_uuid,
+            user_id,
+            Decimal::from(70),
+            Decimal::from(10),
+        );
+        assert_eq!(refund.account_change(wallet), Decimal::from(70));
+        assert_eq!(
+            refund.account_change(LedgerAccount::RefundClearing),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            refund.account_change(LedgerAccount::PlatformRevenue),
+            Decimal::from(-70)
+        );
+    }
+
+    #[test]
+    fn test_zero_lines_skipped() {
+        let payment = JournalEntry::payment(
+            Uuid::new_v4(),
+            UserId::from(1),
+            Decimal::from(50),
+            Decimal::ZERO,
+        );
+
+        assert_eq!(payment.lines().len(), 2);
+        assert!(
+            payment
+                .lines()
+                .iter()
+                .all(|line| line.account() != LedgerAccount::Promotion)
+        );
+    }
+
+    #[test]
+    fn test_unbalanced_entry_rejected() {
+        let transaction_uuid = Uuid::new_v4();
+
+        let err = JournalEntry::new(
+            Uuid::new_v4(),
+            Some(transaction_uuid),
+            JournalEntryKind::Payment,
+            vec![
+                JournalLine::new(
+                    Uuid::new_v4(),
+                    LedgerAccount::UserWallet(UserId::from(1)),
+                    Decimal::from(-10),
+                ),
+                JournalLine::new(
+                    Uuid::new_v4(),
+                    LedgerAccount::PlatformRevenue,
+                    Decimal::from(9),
+                ),
+            ],
+            Local::now().fixed_offset(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            LedgerError::Unbalanced {
+                transaction_uuid: Some(transaction_uuid),
+                sum: Decimal::from(-1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_account_from_parts() {
+        let user_id = UserId::from(1);
+
+        assert_eq!(
+            LedgerAccount::from_parts("user_wallet", Some(user_id)),
+            Ok(LedgerAccount::UserWallet(user_id))
+        );
+        assert_eq!(
+            LedgerAccount::from_parts("promotion", None),
+            Ok(LedgerAccount::Promotion)
+        );
+        assert!(LedgerAccount::from_parts("user_wallet", None).is_err());
+        assert!(LedgerAccount::from_parts("promotion", Some(user_id)).is_err());
+    }
+}
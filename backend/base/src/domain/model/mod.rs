@@ -1,16 +1,24 @@
 pub mod city;
+pub mod coupon;
 pub mod dish;
 pub mod hotel;
+pub mod idempotency;
+pub mod ledger;
 pub mod message;
 pub mod order;
+pub mod order_state_machine;
 pub mod password;
 pub mod personal_info;
+pub mod refund_policy;
 pub mod route;
 pub mod session;
 pub mod session_config;
 pub mod station;
 pub mod takeaway;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod train;
 pub mod train_schedule;
 pub mod transaction;
 pub mod user;
+pub mod waitlist;
@@ -8,13 +8,18 @@
 //! - `Order`: 特性，定义了订单的基本操作。
 //! - `BaseOrder`: 结构体，表示基础订单信息。
 //! - `TrainOrder`: 结构体，表示火车票订单。
+//! - `GroupSeatingStrategy`: 枚举类型，表示同行订单的座位分配方式。
+//! - `TicketCategory`: 枚举类型，表示火车票的乘客类别。
 //! - `HotelOrder`: 结构体，表示酒店预订订单。
 //! - `DishOrder`: 结构体，表示火车餐订单。
 //! - `TakeawayOrder`: 结构体，表示外卖订单。
 use super::personal_info::PreferredSeatLocation;
 use crate::Verified;
 use crate::domain::model::dish::DishId;
-use crate::domain::model::hotel::{HotelDateRange, HotelId, HotelRoomTypeId};
+use crate::domain::model::hotel::{HotelDateRange, HotelId, HotelRoomTypeId, OccupiedRoom};
+use crate::domain::model::order_state_machine::{
+    OrderStateError, OrderStateMachine, OrderStatusActor, OrderStatusTransition,
+};
 use crate::domain::model::personal_info::PersonalInfoId;
 use crate::domain::model::takeaway::TakeawayDishId;
 use crate::domain::model::train::SeatTypeName;
@@ -191,15 +196,19 @@ impl OrderTimeInfo {
 ///
 /// 包含以下字段：
 /// - `pay_transaction_id`: 支付交易的唯一标识符，可能为空。
-/// - `refund_transaction_id`: 退款交易的唯一标识符，可能为空。
+/// - `refund_transaction_id`: 全额退款（取消订单）的退款交易的唯一标识符，可能为空。
+/// - `refunded_amount`: 累计已退还的实付金额（含退款手续费）。
+/// - `refunded_quantity`: 累计已退还的数量。
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct PaymentInfo {
     pay_transaction_id: Option<TransactionId>,
     refund_transaction_id: Option<TransactionId>,
+    refunded_amount: Decimal,
+    refunded_quantity: Decimal,
 }
 
 impl PaymentInfo {
-    /// 创建一个新的 `PaymentInfo` 实例。
+    /// 创建一个新的 `PaymentInfo` 实例，累计退款为 0。
     ///
     /// Arguments:
     /// - `pay_transaction_id`: 支付交易的唯一标识符，可能为空。
@@ -210,10 +219,36 @@ impl PaymentInfo {
     pub fn new(
         pay_transaction_id: Option<TransactionId>,
         refund_transaction_id: Option<TransactionId>,
+    ) -> Self {
+        Self::new_full(
+            pay_transaction_id,
+            refund_transaction_id,
+            Decimal::ZERO,
+            Decimal::ZERO,
+        )
+    }
+
+    /// 创建一个新的完整 `PaymentInfo` 实例。
+    ///
+    /// Arguments:
+    /// - `pay_transaction_id`: 支付交易的唯一标识符，可能为空。
+    /// - `refund_transaction_id`: 退款交易的唯一标识符，可能为空。
+    /// - `refunded_amount`: 累计已退还的实付金额。
+    /// - `refunded_quantity`: 累计已退还的数量。
+    ///
+    /// Returns:
+    /// - 新创建的 `PaymentInfo` 实例。
+    pub fn new_full(
+        pay_transaction_id: Option<TransactionId>,
+        refund_transaction_id: Option<TransactionId>,
+        refunded_amount: Decimal,
+        refunded_quantity: Decimal,
     ) -> Self {
         Self {
             pay_transaction_id,
             refund_transaction_id,
+            refunded_amount,
+            refunded_quantity,
         }
     }
 
@@ -233,6 +268,16 @@ impl PaymentInfo {
         self.refund_transaction_id
     }
 
+    /// 获取累计已退还的实付金额（含退款手续费）。
+    pub fn refunded_amount(&self) -> Decimal {
+        self.refunded_amount
+    }
+
+    /// 获取累计已退还的数量。
+    pub fn refunded_quantity(&self) -> Decimal {
+        self.refunded_quantity
+    }
+
     pub fn set_pay_transaction_id(&mut self, tx_id: TransactionId) {
         self.pay_transaction_id = Some(tx_id);
     }
@@ -240,6 +285,12 @@ impl PaymentInfo {
     pub fn set_refund_transaction_id(&mut self, tx_id: TransactionId) {
         self.refund_transaction_id = Some(tx_id);
     }
+
+    /// 累加一次退款的金额与数量
+    pub fn record_refund(&mut self, amount: Decimal, quantity: Decimal) {
+        self.refunded_amount += amount;
+        self.refunded_quantity += quantity;
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -277,6 +328,20 @@ impl Display for OrderType {
     }
 }
 
+impl TryFrom<&str> for OrderType {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "train" => OrderType::Train,
+            "hotel" => OrderType::Hotel,
+            "dish" => OrderType::Dish,
+            "takeaway" => OrderType::Takeaway,
+            x => return Err(format!("Invalid order type: {}", x)),
+        })
+    }
+}
+
 /// 特性，定义了订单的基本操作。
 ///
 /// 包含以下方法：
@@ -287,8 +352,11 @@ impl Display for OrderType {
 /// - `order_time_info`: 获取订单的时间信息。
 /// - `unit_price`: 获取订单的单价。
 /// - `amount`: 获取订单的数量。
+/// - `remaining_quantity`: 获取订单扣除已部分退还数量后的剩余数量。
 /// - `payment_info`: 获取订单的支付信息。
 /// - `personal_info_id`: 获取订单关联的个人信息唯一标识符。
+/// - `transition_status`: 通过订单状态机迁移订单状态。
+/// - `status_transitions`: 获取尚未持久化的状态迁移记录。
 pub trait Order: DynClone + Debug + Send + Sync + 'static + Any {
     /// 获取订单的唯一标识符。
     ///
@@ -334,6 +402,11 @@ pub trait Order: DynClone + Debug + Send + Sync + 'static + Any {
     /// - 订单的数量。
     fn amount(&self) -> Decimal;
 
+    /// 获取订单扣除已部分退还数量后的剩余数量。
+    fn remaining_quantity(&self) -> Decimal {
+        self.amount() - self.payment_info().refunded_quantity()
+    }
+
     /// 获取订单的支付信息。
     ///
     /// Returns:
@@ -348,7 +421,24 @@ pub trait Order: DynClone + Debug + Send + Sync + 'static + Any {
     /// - 订单关联的个人信息唯一标识符。
     fn personal_info_id(&self) -> PersonalInfoId;
 
-    fn set_status(&mut self, status: OrderStatus);
+    /// 通过订单状态机迁移订单状态，并记录本次迁移。
+    ///
+    /// Arguments:
+    /// - `to`: 目标状态。
+    /// - `actor`: 触发迁移的操作者。
+    /// - `reason`: 迁移原因。
+    ///
+    /// Returns:
+    /// - 迁移不合法时返回`OrderStateError`，订单状态保持不变。
+    fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError>;
+
+    /// 获取订单自加载以来的状态迁移记录，由仓储在保存订单时持久化。
+    fn status_transitions(&self) -> &[OrderStatusTransition];
 }
 
 clone_trait_object!(Order);
@@ -364,6 +454,7 @@ clone_trait_object!(Order);
 /// - `amount`: 订单的数量。
 /// - `payment_info`: 订单的支付信息。
 /// - `personal_info_id`: 订单关联的个人信息唯一标识符。
+/// - `status_transitions`: 尚未持久化的状态迁移记录。
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BaseOrder {
     pub order_id: Option<OrderId>,
@@ -374,6 +465,8 @@ pub struct BaseOrder {
     pub amount: Decimal,
     pub payment_info: PaymentInfo,
     pub personal_info_id: PersonalInfoId,
+    /// 尚未持久化的状态迁移记录
+    pub status_transitions: Vec<OrderStatusTransition>,
 }
 
 impl BaseOrder {
@@ -411,10 +504,146 @@ impl BaseOrder {
             amount,
             payment_info,
             personal_info_id,
+            status_transitions: Vec::new(),
+        }
+    }
+
+    /// 通过订单状态机迁移订单状态，并记录本次迁移
+    pub fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError> {
+        let transition =
+            OrderStateMachine::transition(self.uuid, self.order_status, to, actor, reason)?;
+
+        self.order_status = to;
+        self.status_transitions.push(transition);
+
+        Ok(())
+    }
+}
+
+/// 枚举类型，表示同行（原子订单包）火车票订单最终采用的座位分配方式。
+///
+/// 分配时按以下顺序依次尝试：
+/// - `SameRow`: 所有乘客位于同一车厢的同一排。
+/// - `AdjacentRows`: 所有乘客位于同一车厢的相邻若干排。
+/// - `SameCarriage`: 所有乘客位于同一车厢。
+/// - `Scattered`: 无法安排在同一车厢，任意分配座位。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GroupSeatingStrategy {
+    SameRow,
+    AdjacentRows,
+    SameCarriage,
+    Scattered,
+}
+
+impl TryFrom<&str> for GroupSeatingStrategy {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "same_row" => GroupSeatingStrategy::SameRow,
+            "adjacent_rows" => GroupSeatingStrategy::AdjacentRows,
+            "same_carriage" => GroupSeatingStrategy::SameCarriage,
+            "scattered" => GroupSeatingStrategy::Scattered,
+            x => return Err(format!("Invalid group seating strategy: {}", x)),
+        })
+    }
+}
+
+impl From<GroupSeatingStrategy> for &'static str {
+    fn from(value: GroupSeatingStrategy) -> Self {
+        match value {
+            GroupSeatingStrategy::SameRow => "same_row",
+            GroupSeatingStrategy::AdjacentRows => "adjacent_rows",
+            GroupSeatingStrategy::SameCarriage => "same_carriage",
+            GroupSeatingStrategy::Scattered => "scattered",
+        }
+    }
+}
+
+impl Display for GroupSeatingStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            <GroupSeatingStrategy as Into<&'static str>>::into(*self)
+        )
+    }
+}
+
+/// 枚举类型，表示火车票的乘客类别。
+///
+/// - `Adult`: 成人票，任何乘客均可购买。
+/// - `Child`: 儿童票，乘车日未满14周岁。
+/// - `Student`: 学生票，需凭学生证乘车，无法通过身份证校验。
+/// - `Senior`: 老年票，乘车日年满60周岁。
+/// - `Disabled`: 残疾军人、伤残人民警察优待票，需凭相关证件乘车，无法通过身份证校验。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum TicketCategory {
+    #[default]
+    Adult,
+    Child,
+    Student,
+    Senior,
+    Disabled,
+}
+
+impl TicketCategory {
+    /// 儿童票的年龄上限（不含）
+    pub const CHILD_MAX_AGE: u32 = 14;
+    /// 老年票的年龄下限（含）
+    pub const SENIOR_MIN_AGE: u32 = 60;
+
+    /// 判断乘车日周岁年龄为`age`的乘客能否购买该类别车票
+    ///
+    /// 无法从身份证号码取得年龄时（`age`为`None`），不能购买儿童票和老年票；
+    /// 学生票和优待票的资格无法通过年龄校验，由乘车时查验证件。
+    pub fn is_eligible(&self, age: Option<u32>) -> bool {
+        match self {
+            TicketCategory::Child => age.is_some_and(|age| age < Self::CHILD_MAX_AGE),
+            TicketCategory::Senior => age.is_some_and(|age| age >= Self::SENIOR_MIN_AGE),
+            TicketCategory::Adult | TicketCategory::Student | TicketCategory::Disabled => true,
         }
     }
 }
 
+impl TryFrom<&str> for TicketCategory {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Ok(match value {
+            "adult" => TicketCategory::Adult,
+            "child" => TicketCategory::Child,
+            "student" => TicketCategory::Student,
+            "senior" => TicketCategory::Senior,
+            "disabled" => TicketCategory::Disabled,
+            x => return Err(format!("Invalid ticket category: {}", x)),
+        })
+    }
+}
+
+impl From<TicketCategory> for &'static str {
+    fn from(value: TicketCategory) -> Self {
+        match value {
+            TicketCategory::Adult => "adult",
+            TicketCategory::Child => "child",
+            TicketCategory::Student => "student",
+            TicketCategory::Senior => "senior",
+            TicketCategory::Disabled => "disabled",
+        }
+    }
+}
+
+impl Display for TicketCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <TicketCategory as Into<&'static str>>::into(*self))
+    }
+}
+
 /// 结构体，表示火车票订单。
 ///
 /// 包含以下字段：
@@ -422,6 +651,9 @@ impl BaseOrder {
 /// - `train_schedule_id`: 火车时刻表的唯一标识符。
 /// - `seat`: 座位信息。
 /// - `station_range`: 站点范围。
+/// - `group_seating`: 同行订单的座位分配方式，单独订票时为`None`。
+/// - `ticket_category`: 乘客类别，票价已按该类别优惠。
+/// - `seat_hold_expire_time`: 暂留座位的过期时间，仅在支付前暂留座位时为`Some`。
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TrainOrder {
     base: BaseOrder,
@@ -430,6 +662,9 @@ pub struct TrainOrder {
     order_seat_type_name: SeatTypeName<Verified>,
     preferred_seat_location: Option<PreferredSeatLocation>,
     station_range: StationRange<Verified>,
+    group_seating: Option<GroupSeatingStrategy>,
+    ticket_category: TicketCategory,
+    seat_hold_expire_time: Option<DateTimeWithTimeZone>,
 }
 
 impl TrainOrder {
@@ -458,6 +693,9 @@ impl TrainOrder {
             order_seat_type_name,
             preferred_seat_location,
             station_range,
+            group_seating: None,
+            ticket_category: TicketCategory::Adult,
+            seat_hold_expire_time: None,
         }
     }
 
@@ -516,6 +754,76 @@ impl TrainOrder {
     pub fn order_seat_type_name(&self) -> &SeatTypeName<Verified> {
         &self.order_seat_type_name
     }
+
+    /// 设置订单的座位类型
+    ///
+    /// 候补兑现时，订单改为实际分配的座位类型
+    ///
+    /// Arguments:
+    /// - `order_seat_type_name`: 座位类型名称
+    pub fn set_order_seat_type_name(&mut self, order_seat_type_name: SeatTypeName<Verified>) {
+        self.order_seat_type_name = order_seat_type_name;
+    }
+
+    /// 设置订单单价
+    ///
+    /// 候补兑现时，订单单价改为实际分配座位类型的票价
+    ///
+    /// Arguments:
+    /// - `unit_price`: 单价
+    pub fn set_unit_price(&mut self, unit_price: Decimal) {
+        self.base.unit_price = unit_price;
+    }
+
+    /// 获取同行订单的座位分配方式。
+    pub fn group_seating(&self) -> Option<GroupSeatingStrategy> {
+        self.group_seating
+    }
+
+    /// 设置同行订单的座位分配方式
+    ///
+    /// Arguments:
+    /// - `group_seating`: 座位分配方式
+    pub fn set_group_seating(&mut self, group_seating: Option<GroupSeatingStrategy>) {
+        self.group_seating = group_seating;
+    }
+
+    /// 获取乘客类别。
+    pub fn ticket_category(&self) -> TicketCategory {
+        self.ticket_category
+    }
+
+    /// 设置乘客类别
+    ///
+    /// Arguments:
+    /// - `ticket_category`: 乘客类别
+    pub fn set_ticket_category(&mut self, ticket_category: TicketCategory) {
+        self.ticket_category = ticket_category;
+    }
+
+    /// 获取暂留座位的过期时间。
+    ///
+    /// 订单未支付时即已分配座位（`seat`），支付后确认该座位；
+    /// 超过该时间仍未支付，座位将被释放。
+    pub fn seat_hold_expire_time(&self) -> Option<DateTimeWithTimeZone> {
+        self.seat_hold_expire_time
+    }
+
+    /// 设置暂留座位的过期时间
+    ///
+    /// Arguments:
+    /// - `seat_hold_expire_time`: 过期时间，`None`表示未暂留座位
+    pub fn set_seat_hold_expire_time(
+        &mut self,
+        seat_hold_expire_time: Option<DateTimeWithTimeZone>,
+    ) {
+        self.seat_hold_expire_time = seat_hold_expire_time;
+    }
+
+    /// 订单是否持有暂留的座位
+    pub fn is_seat_held(&self) -> bool {
+        self.seat_hold_expire_time.is_some() && self.seat.is_some()
+    }
 }
 
 impl Order for TrainOrder {
@@ -563,8 +871,17 @@ impl Order for TrainOrder {
         self.base.personal_info_id
     }
 
-    fn set_status(&mut self, status: OrderStatus) {
-        self.base.order_status = status;
+    fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError> {
+        self.base.transition_status(to, actor, reason)
+    }
+
+    fn status_transitions(&self) -> &[OrderStatusTransition] {
+        &self.base.status_transitions
     }
 }
 
@@ -647,6 +964,35 @@ impl HotelOrder {
         self.booking_date_range
     }
 
+    /// 从已占用的房间中选出按数量部分退款时释放的房间。
+    ///
+    /// 只选取与本订单的酒店、房型、日期范围及入住人均一致的房间。
+    ///
+    /// Arguments:
+    /// - `occupied_rooms`: 可能属于本订单的已占用房间。
+    /// - `quantity`: 退还的房间数。
+    ///
+    /// Returns:
+    /// - 属于本订单的房间不足`quantity`间时返回`None`。
+    pub fn rooms_to_release(
+        &self,
+        occupied_rooms: Vec<OccupiedRoom>,
+        quantity: usize,
+    ) -> Option<Vec<OccupiedRoom>> {
+        let rooms = occupied_rooms
+            .into_iter()
+            .filter(|room| {
+                room.hotel_id() == self.hotel_id
+                    && room.hotel_room_type_id() == self.room_id
+                    && *room.booking_date_range() == self.booking_date_range
+                    && room.personal_info() == self.base.personal_info_id
+            })
+            .take(quantity)
+            .collect::<Vec<_>>();
+
+        (rooms.len() == quantity).then_some(rooms)
+    }
+
     pub fn base(&self) -> &BaseOrder {
         &self.base
     }
@@ -712,8 +1058,17 @@ impl Order for HotelOrder {
         self.base.personal_info_id
     }
 
-    fn set_status(&mut self, status: OrderStatus) {
-        self.base.order_status = status;
+    fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError> {
+        self.base.transition_status(to, actor, reason)
+    }
+
+    fn status_transitions(&self) -> &[OrderStatusTransition] {
+        &self.base.status_transitions
     }
 }
 
@@ -859,8 +1214,17 @@ impl Order for DishOrder {
         self.base.personal_info_id
     }
 
-    fn set_status(&mut self, status: OrderStatus) {
-        self.base.order_status = status;
+    fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError> {
+        self.base.transition_status(to, actor, reason)
+    }
+
+    fn status_transitions(&self) -> &[OrderStatusTransition] {
+        &self.base.status_transitions
     }
 }
 
@@ -991,8 +1355,17 @@ impl Order for TakeawayOrder {
         self.base.personal_info_id
     }
 
-    fn set_status(&mut self, status: OrderStatus) {
-        self.base.order_status = status;
+    fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError> {
+        self.base.transition_status(to, actor, reason)
+    }
+
+    fn status_transitions(&self) -> &[OrderStatusTransition] {
+        &self.base.status_transitions
     }
 }
 
@@ -0,0 +1,330 @@
+//! # 订单状态机模块
+//!
+//! 该模块集中定义了订单状态之间的合法迁移。主要包含以下内容：
+//!
+//! - `OrderStateMachine`: 结构体，定义订单状态的合法迁移并执行迁移校验。
+//! - `OrderStatusActor`: 枚举类型，表示触发订单状态迁移的操作者。
+//! - `OrderStatusTransition`: 结构体，表示一次订单状态迁移记录。
+//! - `OrderStateError`: 枚举类型，表示订单状态迁移失败的原因。
+//!
+//! ## 合法迁移
+//!
+//! ```text
+//! Unpaid ──> Paid ──> Ongoing ──> Active ──> Completed
+//!   │         │  │       │  │                   ^
+//!   │         │  │       │  └───────────────────┘
+//!   │         │  └─> Failed
+//!   └─────────┴───────┴──> Cancelled
+//! ```
+//!
+//! - 行程尚未开始即已结束时（如状态更新守护进程未能在行程期间运行），允许由`Ongoing`直接迁移至`Completed`。
+//! - `Completed`、`Failed`、`Cancelled`为终止状态，不能再迁移。
+use crate::domain::model::order::OrderStatus;
+use crate::domain::model::user::UserId;
+use chrono::Local;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStateError {
+    #[error("illegal status transition of order {order_uuid}: {from} -> {to}")]
+    IllegalTransition {
+        order_uuid: Uuid,
+        from: OrderStatus,
+        to: OrderStatus,
+    },
+}
+
+/// 触发订单状态迁移的操作者
+///
+/// - `User`: 用户主动操作，如支付订单。
+/// - `System`: 系统自动处理，如订票、支付超时取消、行程状态更新。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderStatusActor {
+    User(UserId),
+    System,
+}
+
+impl OrderStatusActor {
+    /// 操作者类型的字符串表示，用于持久化
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OrderStatusActor::User(_) => "user",
+            OrderStatusActor::System => "system",
+        }
+    }
+
+    /// 操作者为用户时返回用户Id
+    pub fn user_id(&self) -> Option<UserId> {
+        match self {
+            OrderStatusActor::User(user_id) => Some(*user_id),
+            OrderStatusActor::System => None,
+        }
+    }
+
+    /// 由操作者类型及用户Id还原操作者
+    pub fn from_parts(kind: &str, user_id: Option<UserId>) -> Result<Self, String> {
+        match (kind, user_id) {
+            ("user", Some(user_id)) => Ok(OrderStatusActor::User(user_id)),
+            ("system", None) => Ok(OrderStatusActor::System),
+            (kind, user_id) => Err(format!(
+                "Invalid order status actor: {}, user id: {:?}",
+                kind, user_id
+            )),
+        }
+    }
+}
+
+impl Display for OrderStatusActor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind())
+    }
+}
+
+/// 一次订单状态迁移记录
+///
+/// 每条记录具有唯一的`uuid`，重复持久化同一条记录不会产生重复的历史。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrderStatusTransition {
+    uuid: Uuid,
+    order_uuid: Uuid,
+    from: OrderStatus,
+    to: OrderStatus,
+    actor: OrderStatusActor,
+    reason: String,
+    create_time: DateTimeWithTimeZone,
+}
+
+impl OrderStatusTransition {
+    pub fn new(
+        uuid: Uuid,
+        order_uuid: Uuid,
+        from: OrderStatus,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: String,
+        create_time: DateTimeWithTimeZone,
+    ) -> Self {
+        Self {
+            uuid,
+            order_uuid,
+            from,
+            to,
+            actor,
+            reason,
+            create_time,
+        }
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn order_uuid(&self) -> Uuid {
+        self.order_uuid
+    }
+
+    pub fn from(&self) -> OrderStatus {
+        self.from
+    }
+
+    pub fn to(&self) -> OrderStatus {
+        self.to
+    }
+
+    pub fn actor(&self) -> OrderStatusActor {
+        self.actor
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn create_time(&self) -> DateTimeWithTimeZone {
+        self.create_time
+    }
+}
+
+/// 订单状态机，定义订单状态之间的合法迁移
+pub struct OrderStateMachine;
+
+impl OrderStateMachine {
+    /// 获取从`from`状态出发的所有合法目标状态
+    pub fn next_statuses(from: OrderStatus) -> &'static [OrderStatus] {
+        match from {
+            OrderStatus::Unpaid => &[OrderStatus::Paid, OrderStatus::Cancelled],
+            OrderStatus::Paid => &[
+                OrderStatus::Ongoing,
+                OrderStatus::Failed,
+                OrderStatus::Cancelled,
+            ],
+            OrderStatus::Ongoing => &[
+                OrderStatus::Active,
+                OrderStatus::Completed,
+                OrderStatus::Cancelled,
+            ],
+            OrderStatus::Active => &[OrderStatus::Completed],
+            OrderStatus::Completed | OrderStatus::Failed | OrderStatus::Cancelled => &[],
+        }
+    }
+
+    /// 判断订单状态能否由`from`迁移至`to`
+    pub fn can_transition(from: OrderStatus, to: OrderStatus) -> bool {
+        Self::next_statuses(from).contains(&to)
+    }
+
+    /// 判断`status`是否为终止状态
+    pub fn is_terminal(status: OrderStatus) -> bool {
+        Self::next_statuses(status).is_empty()
+    }
+
+    /// 校验订单状态迁移，合法时生成迁移记录
+    ///
+    /// Arguments:
+    /// - `order_uuid`: 订单的 UUID。
+    /// - `from`: 订单的当前状态。
+    /// - `to`: 订单的目标状态。
+    /// - `actor`: 触发迁移的操作者。
+    /// - `reason`: 迁移原因。
+    ///
+    /// Returns:
+    /// - 成功时返回迁移记录。
+    /// - 迁移不合法时返回`OrderStateError::IllegalTransition`。
+    pub fn transition(
+        order_uuid: Uuid,
+        from: OrderStatus,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<OrderStatusTransition, OrderStateError> {
+        if !Self::can_transition(from, to) {
+            return Err(OrderStateError::IllegalTransition {
+                order_uuid,
+                from,
+                to,
+            });
+        }
+
+        let local_now = Local::now();
+        let offset = *local_now.offset();
+
+        Ok(OrderStatusTransition::new(
+            Uuid::new_v4(),
+            order_uuid,
+            from,
+            to,
+            actor,
+            reason.to_string(),
+            local_now.with_timezone(&offset),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_happy_path_transitions() {
+        let path = [
+            OrderStatus::Unpaid,
+            OrderStatus::Paid,
+            OrderStatus::Ongoing,
+            OrderStatus::Active,
+            OrderStatus::Completed,
+        ];
+
+        for pair in path.windows(2) {
+            assert!(OrderStateMachine::can_transition(pair[0], pair[1]));
+        }
+
+        assert!(OrderStateMachine::can_transition(
+            OrderStatus::Ongoing,
+            OrderStatus::Completed
+        ));
+        assert!(OrderStateMachine::can_transition(
+            OrderStatus::Paid,
+            OrderStatus::Failed
+        ));
+    }
+
+    #[test]
+    fn test_cancel_only_before_trip() {
+        for status in [OrderStatus::Unpaid, OrderStatus::Paid, OrderStatus::Ongoing] {
+            assert!(OrderStateMachine::can_transition(
+                status,
+                OrderStatus::Cancelled
+            ));
+        }
+
+        for status in [
+            OrderStatus::Active,
+            OrderStatus::Completed,
+            OrderStatus::Failed,
+            OrderStatus::Cancelled,
+        ] {
+            assert!(!OrderStateMachine::can_transition(
+                status,
+                OrderStatus::Cancelled
+            ));
+        }
+    }
+
+    #[test]
+    fn test_illegal_transition_rejected() {
+        let order_uuid = Uuid::new_v4();
+
+        let err = OrderStateMachine::transition(
+            order_uuid,
+            OrderStatus::Unpaid,
+            OrderStatus::Ongoing,
+            OrderStatusActor::System,
+            "订票成功",
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err,
+            OrderStateError::IllegalTransition {
+                order_uuid,
+                from: OrderStatus::Unpaid,
+                to: OrderStatus::Ongoing,
+            }
+        );
+
+        assert!(OrderStateMachine::is_terminal(OrderStatus::Completed));
+        assert!(!OrderStateMachine::can_transition(
+            OrderStatus::Paid,
+            OrderStatus::Paid
+        ));
+    }
+
+    #[test]
+    fn test_transition_record() {
+        let order_uuid = Uuid::new_v4();
+        let user_id = UserId::from(1);
+
+        let transition = OrderStateMachine::transition(
+            order_uuid,
+            OrderStatus::Unpaid,
+            OrderStatus::Paid,
+            OrderStatusActor::User(user_id),
+            "订单支付成功",
+        )
+        .unwrap();
+
+        assert_eq!(transition.order_uuid(), order_uuid);
+        assert_eq!(transition.from(), OrderStatus::Unpaid);
+        assert_eq!(transition.to(), OrderStatus::Paid);
+        assert_eq!(transition.actor().user_id(), Some(user_id));
+        assert_eq!(transition.reason(), "订单支付成功");
+
+        assert_eq!(
+            OrderStatusActor::from_parts("user", Some(user_id)),
+            Ok(OrderStatusActor::User(user_id))
+        );
+        assert!(OrderStatusActor::from_parts("system", Some(user_id)).is_err());
+    }
+}
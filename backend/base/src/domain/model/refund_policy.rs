@@ -0,0 +1,362 @@
+//! # 退款策略模块
+//!
+//! 该模块定义了取消订单时的退款手续费策略。主要包含以下内容：
+//!
+//! - `RefundFeeTier`: 结构体，表示一档退款手续费。
+//! - `RefundPolicy`: 结构体，表示一种订单类型的退款策略。
+//! - `RefundPolicySet`: 结构体，表示各订单类型的退款策略集合。
+//!
+//! ## 关于退款手续费的约定
+//!
+//! - 手续费按订单的生效时间（`OrderTimeInfo::active_time`，如火车发车时间、酒店入住时间）计算：
+//!   距生效时间不足某档的时长时，按该档的费率收取手续费；同时满足多档时取费率最高的一档。
+//! - 手续费按订单的应退金额（已按比例扣除优惠金额）计算，四舍五入到分，不超过应退金额。
+//! - 仅用户主动取消订单时收取手续费，因订票失败等系统原因产生的退款不收取手续费。
+use crate::domain::model::order::{Order, OrderType};
+use chrono::TimeDelta;
+use sea_orm::prelude::{DateTimeWithTimeZone, Decimal};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RefundPolicyError {
+    #[error("invalid refund fee tier: {0}")]
+    InvalidTier(String),
+    #[error("invalid refund fee rate: {0}, should be in [0, 100]")]
+    InvalidFeeRate(Decimal),
+}
+
+/// 一档退款手续费：距订单生效时间不足`within`时，按`fee_percent`收取手续费
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefundFeeTier {
+    within: TimeDelta,
+    fee_percent: Decimal,
+}
+
+impl RefundFeeTier {
+    /// 创建一档退款手续费
+    ///
+    /// Arguments:
+    /// - `within`: 距订单生效时间的时长，不能为负。
+    /// - `fee_percent`: 手续费占应退金额的百分比，须在`[0, 100]`内。
+    pub fn new(within: TimeDelta, fee_percent: Decimal) -> Result<Self, RefundPolicyError> {
+        if within < TimeDelta::zero() {
+            return Err(RefundPolicyError::InvalidTier(format!(
+                "negative duration: {}",
+                within
+            )));
+        }
+
+        if fee_percent < Decimal::ZERO || fee_percent > Decimal::ONE_HUNDRED {
+            return Err(RefundPolicyError::InvalidFeeRate(fee_percent));
+        }
+
+        Ok(Self {
+            within,
+            fee_percent,
+        })
+    }
+
+    pub fn within(&self) -> TimeDelta {
+        self.within
+    }
+
+    pub fn fee_percent(&self) -> Decimal {
+        self.fee_percent
+    }
+}
+
+/// 一种订单类型的退款策略，不含任何档位时免费退款
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefundPolicy {
+    tiers: Vec<RefundFeeTier>,
+}
+
+impl RefundPolicy {
+    pub fn new(tiers: Vec<RefundFeeTier>) -> Self {
+        Self { tiers }
+    }
+
+    /// 免费退款的策略
+    pub fn free() -> Self {
+        Self::default()
+    }
+
+    pub fn tiers(&self) -> &[RefundFeeTier] {
+        &self.tiers
+    }
+
+    /// 计算在`now`取消生效时间为`active_time`的订单时的手续费百分比
+    pub fn fee_percent(
+        &self,
+        active_time: DateTimeWithTimeZone,
+        now: DateTimeWithTimeZone,
+    ) -> Decimal {
+        let remaining = active_time - now;
+
+        self.tiers
+            .iter()
+            .filter(|tier| remaining < tier.within)
+            .map(|tier| tier.fee_percent)
+            .max()
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// 计算退款手续费，结果四舍五入到分且不超过应退金额
+    ///
+    /// Arguments:
+    /// - `refund_amount`: 应退金额。
+    /// - `active_time`: 订单生效时间。
+    /// - `now`: 取消时间。
+    pub fn refund_fee(
+        &self,
+        refund_amount: Decimal,
+        active_time: DateTimeWithTimeZone,
+        now: DateTimeWithTimeZone,
+    ) -> Decimal {
+        if refund_amount <= Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let fee =
+            (refund_amount * self.fee_percent(active_time, now) / Decimal::ONE_HUNDRED).round_dp(2);
+
+        fee.min(refund_amount)
+    }
+}
+
+/// 从配置字符串解析退款策略
+///
+/// 格式为以逗号分隔的档位列表，每档为`时长:手续费百分比`，时长单位支持`d`（天）、`h`（小时）、`m`（分钟），
+/// 例如：`48h:5,2h:20`表示发车前 48 小时内收取 5% 手续费，2 小时内收取 20% 手续费。空字符串表示免费退款。
+impl FromStr for RefundPolicy {
+    type Err = RefundPolicyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tiers = s
+            .split(',')
+            .map(str::trim)
+            .filter(|tier| !tier.is_empty())
+            .map(|tier| {
+                let (within, fee_percent) = tier
+                    .split_once(':')
+                    .ok_or_else(|| RefundPolicyError::InvalidTier(tier.to_string()))?;
+
+                let within = within.trim();
+                let unit_index = within
+                    .char_indices()
+                    .last()
+                    .map(|(index, _)| index)
+                    .ok_or_else(|| RefundPolicyError::InvalidTier(tier.to_string()))?;
+                let (value, unit) = within.split_at(unit_index);
+
+                let value = value
+                    .parse::<i64>()
+                    .map_err(|_for_super_earth| RefundPolicyError::InvalidTier(tier.to_string()))?;
+
+                let within = match unit {
+                    "d" => TimeDelta::try_days(value),
+                    "h" => TimeDelta::try_hours(value),
+                    "m" => TimeDelta::try_minutes(value),
+                    _ => None,
+                }
+                .ok_or_else(|| RefundPolicyError::InvalidTier(tier.to_string()))?;
+
+                let fee_percent = Decimal::from_str(fee_percent.trim())
+                    .map_err(|_for_super_earth| RefundPolicyError::InvalidTier(tier.to_string()))?;
+
+                RefundFeeTier::new(within, fee_percent)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(RefundPolicy::new(tiers))
+    }
+}
+
+/// 各订单类型的退款策略
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefundPolicySet {
+    pub train: RefundPolicy,
+    pub hotel: RefundPolicy,
+    pub dish: RefundPolicy,
+    pub takeaway: RefundPolicy,
+}
+
+impl Default for RefundPolicySet {
+    /// 默认策略：
+    /// - 火车票：发车前 48 小时内收取 5% 手续费，2 小时内收取 20% 手续费
+    /// - 酒店：入住前一天（入住时间前 24 小时）之前免费取消，此后不予退款
+    /// - 火车餐、外卖：免费退款
+    fn default() -> Self {
+        Self {
+            train: RefundPolicy::from_str("48h:5,2h:20").unwrap(),
+            hotel: RefundPolicy::from_str("1d:100").unwrap(),
+            dish: RefundPolicy::free(),
+            takeaway: RefundPolicy::free(),
+        }
+    }
+}
+
+impl RefundPolicySet {
+    /// 所有订单类型均免费退款的策略
+    pub fn free() -> Self {
+        Self {
+            train: RefundPolicy::free(),
+            hotel: RefundPolicy::free(),
+            dish: RefundPolicy::free(),
+            takeaway: RefundPolicy::free(),
+        }
+    }
+
+    /// 获取指定订单类型的退款策略
+    pub fn policy(&self, order_type: OrderType) -> &RefundPolicy {
+        match order_type {
+            OrderType::Train => &self.train,
+            OrderType::Hotel => &self.hotel,
+            OrderType::Dish => &self.dish,
+            OrderType::Takeaway => &self.takeaway,
+        }
+    }
+
+    /// 计算在`now`取消订单时的退款手续费
+    ///
+    /// Arguments:
+    /// - `order`: 要取消的订单。
+    /// - `refund_amount`: 该订单的应退金额。
+    /// - `now`: 取消时间。
+    pub fn refund_fee(
+        &self,
+        order: &dyn Order,
+        refund_amount: Decimal,
+        now: DateTimeWithTimeZone,
+    ) -> Decimal {
+        self.policy(order.order_type()).refund_fee(
+            refund_amount,
+            order.order_time_info().active_time(),
+            now,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, TimeZone};
+
+    fn time(hour: u32) -> DateTimeWithTimeZone {
+        FixedOffset::east_opt(8 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2025, 6, 20, hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_refund_policy() {
+        let policy = RefundPolicy::from_str("48h:5, 2h:20,1d:7.5").unwrap();
+
+        assert_eq!(policy.tiers().len(), 3);
+        assert_eq!(policy.tiers()[0].within(), TimeDelta::hours(48));
+        assert_eq!(policy.tiers()[1].fee_percent(), Decimal::from(20));
+        assert_eq!(policy.tiers()[2].within(), TimeDelta::days(1));
+        assert_eq!(policy.tiers()[2].fee_percent(), Decimal::new(75, 1));
+
+        assert_eq!(RefundPolicy::from_str("").unwrap(), RefundPolicy::free());
+        assert_eq!(
+            RefundPolicy::from_str("30m:10").unwrap().tiers()[0].within(),
+            TimeDelta::minutes(30)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_refund_policy() {
+        assert!(RefundPolicy::from_str("48h").is_err());
+        assert!(RefundPolicy::from_str("48x:5").is_err());
+        assert!(RefundPolicy::from_str("h:5").is_err());
+        assert!(RefundPolicy::from_str("48h:abc").is_err());
+        assert_eq!(
+            RefundPolicy::from_str("48h:120"),
+            Err(RefundPolicyError::InvalidFeeRate(Decimal::from(120)))
+        );
+        assert!(RefundPolicy::from_str("-1h:5").is_err());
+    }
+
+    #[test]
+    fn test_fee_percent_takes_highest_matching_tier() {
+        let policy = RefundPolicy::from_str("48h:5,2h:20").unwrap();
+        let departure = time(20);
+
+        // 距发车超过 48 小时
+        assert_eq!(
+            policy.fee_percent(departure, departure - TimeDelta::hours(49)),
+            Decimal::ZERO
+        );
+        // 恰好 48 小时不收取手续费
+        assert_eq!(
+            policy.fee_percent(departure, departure - TimeDelta::hours(48)),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            policy.fee_percent(departure, departure - TimeDelta::hours(10)),
+            Decimal::from(5)
+        );
+        assert_eq!(
+            policy.fee_percent(departure, departure - TimeDelta::minutes(90)),
+            Decimal::from(20)
+        );
+        // 已过生效时间
+        assert_eq!(
+            policy.fee_percent(departure, departure + TimeDelta::hours(1)),
+            Decimal::from(20)
+        );
+    }
+
+    #[test]
+    fn test_refund_fee_rounding_and_cap() {
+        let policy = RefundPolicy::from_str("48h:5,2h:20").unwrap();
+        let departure = time(20);
+        let now = departure - TimeDelta::hours(10);
+
+        assert_eq!(
+            policy.refund_fee(Decimal::new(55350, 2), departure, now),
+            Decimal::new(2768, 2)
+        );
+        assert_eq!(
+            policy.refund_fee(Decimal::ZERO, departure, now),
+            Decimal::ZERO
+        );
+
+        let no_refund = RefundPolicy::from_str("1d:100").unwrap();
+        assert_eq!(
+            no_refund.refund_fee(Decimal::from(300), departure, now),
+            Decimal::from(300)
+        );
+        assert_eq!(
+            RefundPolicy::free().refund_fee(Decimal::from(300), departure, now),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_default_policy_set() {
+        let policy_set = RefundPolicySet::default();
+
+        assert_eq!(
+            policy_set.policy(OrderType::Train).tiers().len(),
+            2,
+            "train tickets should have two fee tiers"
+        );
+        assert_eq!(
+            policy_set
+                .policy(OrderType::Hotel)
+                .fee_percent(time(14), time(14) - TimeDelta::hours(25)),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            policy_set
+                .policy(OrderType::Hotel)
+                .fee_percent(time(14), time(14) - TimeDelta::hours(23)),
+            Decimal::ONE_HUNDRED
+        );
+        assert_eq!(policy_set.policy(OrderType::Dish), &RefundPolicy::free());
+    }
+}
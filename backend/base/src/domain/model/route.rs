@@ -26,14 +26,16 @@
 //!     StationId::from(101u64), // 北京站
 //!     0,     // 始发站到达时间(发车时间)
 //!     120,   // 北京站出发时间(2分钟后)
-//!     1      // 第一站
+//!     1,     // 第一站
+//!     0      // 始发站里程为0
 //! );
 //! route.add_stop(
 //!     Some(StopId::from(2u64)),
 //!     StationId::from(102u64), // 天津站
 //!     1800,  // 30分钟后到达
 //!     1860,  // 31分钟后发车
-//!     2      // 第二站
+//!     2,     // 第二站
+//!     120    // 距北京站120公里
 //! );
 //! ```
 //!
@@ -62,6 +64,7 @@ define_id_type!(Stop);
 /// - `arrival_time`: 到达时间(相对于始发站发车的秒数)
 /// - `departure_time`: 出发时间(相对于始发站发车的秒数)
 /// - `order`: 停靠顺序(从0开始)
+/// - `distance`: 距上一停靠站的里程(公里)，始发站为0
 ///
 /// # 不变量
 /// - `arrival_time` <= `departure_time`
@@ -74,6 +77,7 @@ pub struct Stop {
     arrival_time: u32,
     departure_time: u32,
     order: u32,
+    distance: u32,
 }
 
 impl Identifiable for Stop {
@@ -100,6 +104,7 @@ impl Stop {
     /// * `arrival_time`: 到达时间(秒)
     /// * `departure_time`: 出发时间(秒)
     /// * `order`: 停靠顺序
+    /// * `distance`: 距上一停靠站的里程(公里)
     pub fn new(
         id: Option<StopId>,
         route_id: Option<RouteId>,
@@ -107,6 +112,7 @@ impl Stop {
         arrival_time: u32,
         departure_time: u32,
         order: u32,
+        distance: u32,
     ) -> Self {
         Stop {
             stop_id: id,
@@ -115,6 +121,7 @@ impl Stop {
             arrival_time,
             departure_time,
             order,
+            distance,
         }
     }
 
@@ -137,6 +144,11 @@ impl Stop {
     pub fn order(&self) -> u32 {
         self.order
     }
+
+    /// 获取距上一停靠站的里程(公里)
+    pub fn distance(&self) -> u32 {
+        self.distance
+    }
 }
 
 define_id_type!(Route);
@@ -216,6 +228,7 @@ impl Route {
     /// * `arrival_time`: 到达时间(秒)
     /// * `departure_time`: 出发时间(秒)
     /// * `order`: 停靠顺序
+    /// * `distance`: 距上一停靠站的里程(公里)
     pub fn add_stop(
         &mut self,
         stop_id: Option<StopId>,
@@ -223,6 +236,7 @@ impl Route {
         arrival_time: u32,
         departure_time: u32,
         order: u32,
+        distance: u32,
     ) {
         let stop = Stop::new(
             stop_id,
@@ -231,7 +245,20 @@ impl Route {
             arrival_time,
             departure_time,
             order,
+            distance,
         );
         self.stops.push(stop);
     }
+
+    /// 计算第`from_index`个停靠站到第`to_index`个停靠站之间的里程(公里)
+    ///
+    /// 下标为`stops()`中的位置，`from_index >= to_index`时返回0。
+    pub fn distance_between(&self, from_index: usize, to_index: usize) -> u32 {
+        self.stops
+            .iter()
+            .take(to_index + 1)
+            .skip(from_index + 1)
+            .map(|stop| stop.distance)
+            .sum()
+    }
 }
@@ -0,0 +1,133 @@
+//! 领域模型单元测试共用的测试数据
+use crate::domain::model::coupon::{Coupon, CouponDiscount, CouponId};
+use crate::domain::model::order::{
+    Order, OrderId, OrderStatus, OrderTimeInfo, OrderType, PaymentInfo,
+};
+use crate::domain::model::order_state_machine::{
+    OrderStateError, OrderStateMachine, OrderStatusActor, OrderStatusTransition,
+};
+use crate::domain::model::personal_info::PersonalInfoId;
+use crate::domain::model::transaction::Transaction;
+use crate::domain::model::user::UserId;
+use chrono::Duration;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// 仅包含计价与状态信息的订单
+#[derive(Debug, Clone)]
+pub struct TestOrder {
+    pub uuid: Uuid,
+    pub order_type: OrderType,
+    pub price: Decimal,
+    pub amount: Decimal,
+    pub status: OrderStatus,
+    pub payment_info: PaymentInfo,
+}
+
+impl TestOrder {
+    pub fn new_boxed(order_type: OrderType, price: i64) -> Box<dyn Order> {
+        Box::new(TestOrder {
+            uuid: Uuid::new_v4(),
+            order_type,
+            price: Decimal::from(price),
+            amount: Decimal::ONE,
+            status: OrderStatus::Unpaid,
+            payment_info: PaymentInfo::new(None, None),
+        })
+    }
+}
+
+impl Order for TestOrder {
+    fn order_id(&self) -> Option<OrderId> {
+        None
+    }
+
+    fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    fn already_refund(&self) -> bool {
+        self.payment_info.refund_transaction_id().is_some()
+    }
+
+    fn order_status(&self) -> OrderStatus {
+        self.status
+    }
+
+    fn order_type(&self) -> OrderType {
+        self.order_type
+    }
+
+    fn order_time_info(&self) -> OrderTimeInfo {
+        let now = Transaction::now();
+        OrderTimeInfo::new(now, now, now)
+    }
+
+    fn unit_price(&self) -> Decimal {
+        self.price
+    }
+
+    fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    fn payment_info(&self) -> PaymentInfo {
+        self.payment_info
+    }
+
+    fn payment_info_mut(&mut self) -> &mut PaymentInfo {
+        &mut self.payment_info
+    }
+
+    fn personal_info_id(&self) -> PersonalInfoId {
+        PersonalInfoId::from(1)
+    }
+
+    fn transition_status(
+        &mut self,
+        to: OrderStatus,
+        actor: OrderStatusActor,
+        reason: &str,
+    ) -> Result<(), OrderStateError> {
+        OrderStateMachine::transition(self.uuid, self.status, to, actor, reason)?;
+        self.status = to;
+        Ok(())
+    }
+
+    fn status_transitions(&self) -> &[OrderStatusTransition] {
+        &[]
+    }
+}
+
+/// 使用优惠券支付后订单均已取消的交易
+pub fn make_paid_transaction(coupon: &Coupon, orders: Vec<Box<dyn Order>>) -> Transaction {
+    let mut tx = Transaction::new(UserId::from(1), orders, true);
+    tx.apply_coupon(coupon, 0).unwrap();
+    tx.pay().unwrap();
+
+    for order in tx.orders_mut() {
+        order
+            .transition_status(OrderStatus::Cancelled, OrderStatusActor::System, "测试")
+            .unwrap();
+    }
+
+    tx
+}
+
+/// 仅适用于火车票订单、当前有效的优惠券
+pub fn make_coupon(discount: CouponDiscount, min_spend: Decimal) -> Coupon {
+    let now = Transaction::now();
+
+    Coupon::new(
+        Some(CouponId::from(1)),
+        "SPRING".to_string(),
+        discount,
+        min_spend,
+        vec![OrderType::Train],
+        now - Duration::days(1),
+        now + Duration::days(1),
+        Some(1),
+        Some(10),
+        0,
+    )
+}
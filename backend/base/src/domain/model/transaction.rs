@@ -4,10 +4,17 @@
 //!
 //! - `TransactionStatus`: 枚举类型，表示交易的状态。
 //! - `TransactionStatusError`: 枚举类型，表示交易状态错误。
+//! - `TransactionKind`: 枚举类型，表示交易的类型。
 //! - `TransactionAmountError`: 枚举类型，表示交易金额错误。
 //! - `TransactionError`: 枚举类型，表示交易错误。
 //! - `RefundError`: 枚举类型，表示退款错误。
+//! - `RefundQuote`: 结构体，表示取消订单前的退款预估。
+//! - `RefundPortion`: 枚举类型，表示部分退款按金额或按数量退还。
+//! - `PartialRefundRequest`: 结构体，表示对一个订单的部分退款请求。
+//! - `RefundLine`: 结构体，表示退款交易中一个订单的退款明细。
+//! - `OrderRefundSummary`: 结构体，表示订单的实付、已退与剩余金额。
 //! - `TransactionAmountAbs`: 结构体，表示交易金额的绝对值。
+//! - `ExternalPayment`: 结构体，表示交易关联的外部支付。
 //! - `Transaction`: 结构体，表示交易实体。
 //!
 //! ## 关于交易和订单的约定
@@ -16,10 +23,22 @@
 //! - 交易对应一笔支付，一个交易可包含多个订单，例如：添加多个乘车人后点击“预订”，产生多个订单，但只有一个交易；交易有“未支付”、“已支付”两种状态。
 //! - 只能取消“订单”，而不能直接取消“交易”。若需“取消”交易，需通过退款交易实现。
 //! - 取消订单、失败订单的退款通过新的退款交易返还，原始支付交易不变。
+//! - 订单可按金额或数量部分退款（例如酒店提前退房、多份火车餐退掉其中几份）。退款交易为每个订单记录一条退款明细，
+//!   订单的支付信息中累计已退金额与数量，累计退款不能超过订单实付金额；部分退款须保留订单的一部分，退还剩余全部金额需取消订单。
+//! - 交易可使用一张优惠券，交易金额为订单总价减去优惠金额，见`coupon`模块。
+//! - 用户主动取消订单时按退款策略收取手续费，手续费记录在退款交易上，见`refund_policy`模块。
+//! - 包含订单的交易需在支付截止时间（创建后`TRANSACTION_PAYMENT_TIMEOUT_MINUTES`分钟）前支付，超时后订单被自动取消，交易不能再支付。
+//! - 交易完成时生成借贷平衡的记账凭证，与交易在同一数据库事务中持久化，见`ledger`模块。
+//! - 通过外部支付网关充值时，充值交易以“未支付”状态创建，只有收到经验证的支付回调后才会完成，见`payment_gateway`模块。
+use crate::TRANSACTION_PAYMENT_TIMEOUT_MINUTES;
+use crate::domain::model::coupon::{Coupon, CouponError, CouponRedemption, CouponRedemptionStatus};
+use crate::domain::model::ledger::JournalEntry;
 use crate::domain::model::order::{Order, OrderStatus};
+use crate::domain::model::order_state_machine::OrderStatusActor;
+use crate::domain::model::refund_policy::RefundPolicySet;
 use crate::domain::model::user::UserId;
 use crate::domain::{Aggregate, Entity, Identifiable, Identifier};
-use chrono::Local;
+use chrono::{Local, TimeDelta};
 use id_macro::define_id_type;
 use sea_orm::prelude::{DateTimeWithTimeZone, Decimal};
 use std::collections::HashSet;
@@ -32,10 +51,14 @@ use uuid::Uuid;
 /// 主要包含以下状态：
 /// - `Unpaid`: 交易尚未支付。
 /// - `Paid`: 交易已支付。
+/// - `Expired`: 交易超过支付截止时间未支付，已关闭。
+/// - `Failed`: 外部支付失败，交易已关闭。
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum TransactionStatus {
     Unpaid,
     Paid,
+    Expired,
+    Failed,
 }
 
 /// 枚举类型，表示交易状态错误。
@@ -50,6 +73,8 @@ impl Display for TransactionStatus {
         match self {
             TransactionStatus::Unpaid => write!(f, "unpaid"),
             TransactionStatus::Paid => write!(f, "paid"),
+            TransactionStatus::Expired => write!(f, "expired"),
+            TransactionStatus::Failed => write!(f, "failed"),
         }
     }
 }
@@ -63,6 +88,8 @@ impl From<TransactionStatus> for &'static str {
         match status {
             TransactionStatus::Unpaid => "unpaid",
             TransactionStatus::Paid => "paid",
+            TransactionStatus::Expired => "expired",
+            TransactionStatus::Failed => "failed",
         }
     }
 }
@@ -82,13 +109,84 @@ impl TryFrom<&str> for TransactionStatus {
         match value {
             "unpaid" => Ok(TransactionStatus::Unpaid),
             "paid" => Ok(TransactionStatus::Paid),
+            "expired" => Ok(TransactionStatus::Expired),
+            "failed" => Ok(TransactionStatus::Failed),
             _ => Err("Invalid transaction status"),
         }
     }
 }
 
+/// 枚举类型，表示交易的类型，即交易对用户余额的影响。
+///
+/// 主要包含以下类型：
+/// - `Recharge`: 充值，增加余额。
+/// - `Pay`: 支付订单（包括改签补缴差价），减少余额。
+/// - `Refund`: 退款（包括取消订单、部分退款、退还差价），增加余额。
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransactionKind {
+    Recharge,
+    Pay,
+    Refund,
+}
+
+impl Display for TransactionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <&'static str>::from(*self))
+    }
+}
+
+impl From<TransactionKind> for &'static str {
+    fn from(kind: TransactionKind) -> Self {
+        match kind {
+            TransactionKind::Recharge => "recharge",
+            TransactionKind::Pay => "pay",
+            TransactionKind::Refund => "refund",
+        }
+    }
+}
+
+impl TryFrom<&str> for TransactionKind {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "recharge" => Ok(TransactionKind::Recharge),
+            "pay" => Ok(TransactionKind::Pay),
+            "refund" => Ok(TransactionKind::Refund),
+            _ => Err("Invalid transaction kind"),
+        }
+    }
+}
+
 define_id_type!(Transaction);
 
+/// 结构体，表示交易关联的外部支付。
+///
+/// - `provider`: 支付网关名称。
+/// - `external_payment_id`: 支付网关分配的支付 ID。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExternalPayment {
+    provider: String,
+    external_payment_id: Uuid,
+}
+
+impl ExternalPayment {
+    pub fn new(provider: String, external_payment_id: Uuid) -> Self {
+        Self {
+            provider,
+            external_payment_id,
+        }
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    pub fn external_payment_id(&self) -> Uuid {
+        self.external_payment_id
+    }
+}
+
 /// 结构体，表示交易实体。
 ///
 /// 包含以下字段：
@@ -98,8 +196,15 @@ define_id_type!(Transaction);
 /// - `finish_time`: 交易完成时间，可能为空。
 /// - `amount`: 交易金额。
 /// - `status`: 交易状态。
+/// - `kind`: 交易类型。
 /// - `user_id`: 用户的唯一标识符。
 /// - `orders`: 交易包含的订单列表。
+/// - `coupon_redemption`: 交易使用的优惠券，可能为空。
+/// - `refund_fee`: 退款交易扣除的手续费，其他交易为 0。
+/// - `payment_deadline`: 支付截止时间，为空表示不会超时（如充值交易、改签调整交易）。
+/// - `external_payment`: 通过外部支付网关完成支付的交易关联的外部支付，其他交易为空。
+/// - `journal_entries`: 交易完成时生成的记账凭证，随交易一同持久化，不会从数据库加载。
+/// - `refund_lines`: 退款交易中各订单的退款明细，随交易一同持久化，不会从数据库加载。
 #[derive(Debug, Clone)]
 pub struct Transaction {
     transaction_id: Option<TransactionId>,
@@ -108,9 +213,16 @@ pub struct Transaction {
     finish_time: Option<DateTimeWithTimeZone>,
     amount: Decimal,
     status: TransactionStatus,
+    kind: TransactionKind,
     user_id: UserId,
     orders: Vec<Box<dyn Order>>,
     atomic: bool,
+    coupon_redemption: Option<CouponRedemption>,
+    refund_fee: Decimal,
+    payment_deadline: Option<DateTimeWithTimeZone>,
+    external_payment: Option<ExternalPayment>,
+    journal_entries: Vec<JournalEntry>,
+    refund_lines: Vec<RefundLine>,
 }
 
 impl Identifiable for Transaction {
@@ -142,10 +254,20 @@ pub enum TransactionError {
     AlreadyPaid(Uuid),
     #[error("Cannot refund transaction: {0}")]
     RefundError(#[from] RefundError),
+    #[error("Coupon already applied to transaction: {0}")]
+    CouponAlreadyApplied(Uuid),
+    #[error("Cannot apply coupon: {0}")]
+    CouponError(#[from] CouponError),
+    #[error("Transaction payment expired: {0}")]
+    PaymentExpired(Uuid),
+    #[error("Transaction must be paid via external payment: {0}")]
+    ExternalPaymentRequired(Uuid),
+    #[error("Transaction has no external payment: {0}")]
+    NoExternalPayment(Uuid),
 }
 
 /// 枚举类型，表示退款错误。
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq)]
 pub enum RefundError {
     #[error("transaction not paid: {0}")]
     NotPaid(Uuid),
@@ -153,8 +275,136 @@ pub enum RefundError {
     RechargeTransaction(Uuid),
     #[error("transaction already (partial) fulfilled. Fulfilled orders: {0:?}")]
     AlreadyFulfilled(Vec<Uuid>),
-    #[error("transaction already (partial) refunded. Refunded orders: {0:?}")]
+    #[error("orders already refunded: {0:?}")]
     AlreadyRefunded(Vec<Uuid>),
+    #[error("order {order_uuid} with status {status} cannot be partially refunded")]
+    NotPartiallyRefundable {
+        order_uuid: Uuid,
+        status: OrderStatus,
+    },
+    #[error("invalid refund amount {amount} for order {order_uuid}")]
+    InvalidAmount { order_uuid: Uuid, amount: Decimal },
+    #[error("invalid refund quantity {quantity} for order {order_uuid}")]
+    InvalidQuantity { order_uuid: Uuid, quantity: Decimal },
+    #[error(
+        "refund amount {requested} exceeds remaining paid amount {remaining} of order {order_uuid}"
+    )]
+    ExceedsPaidAmount {
+        order_uuid: Uuid,
+        requested: Decimal,
+        remaining: Decimal,
+    },
+    #[error(
+        "refund quantity {requested} exceeds remaining quantity {remaining} of order {order_uuid}"
+    )]
+    ExceedsQuantity {
+        order_uuid: Uuid,
+        requested: Decimal,
+        remaining: Decimal,
+    },
+    #[error("partial refund covers the whole remaining order {0}, cancel the order instead")]
+    WholeOrder(Uuid),
+}
+
+/// 结构体，表示取消订单前的退款预估。
+///
+/// - `refund_amount`: 应退金额（已按比例扣除优惠金额）。
+/// - `refund_fee`: 按退款策略收取的手续费。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefundQuote {
+    pub refund_amount: Decimal,
+    pub refund_fee: Decimal,
+}
+
+impl RefundQuote {
+    /// 实际可退金额
+    pub fn refundable_amount(&self) -> Decimal {
+        self.refund_amount - self.refund_fee
+    }
+}
+
+/// 枚举类型，表示部分退款按金额或按数量退还。
+///
+/// - `Amount`: 退还指定的实付金额，不释放座位或房间，仅供运营人员处理售后（如酒店提前退房）时使用。
+/// - `Quantity`: 退还指定的数量，退款金额按订单实付金额的比例计算，例如多份火车餐退掉其中几份。
+///   调用者需同时释放退还数量对应的座位或房间。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefundPortion {
+    Amount(Decimal),
+    Quantity(Decimal),
+}
+
+/// 结构体，表示对一个订单的部分退款请求。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialRefundRequest {
+    pub order_uuid: Uuid,
+    pub portion: RefundPortion,
+}
+
+/// 结构体，表示退款交易中一个订单的退款明细。
+///
+/// - `uuid`: 退款明细的 UUID。
+/// - `order_uuid`: 退款订单的 UUID。
+/// - `quantity`: 退还的数量，按金额部分退款时为空。
+/// - `amount`: 退还的实付金额（含手续费）。
+/// - `fee`: 从退还金额中扣除的手续费。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RefundLine {
+    uuid: Uuid,
+    order_uuid: Uuid,
+    quantity: Option<Decimal>,
+    amount: Decimal,
+    fee: Decimal,
+}
+
+impl RefundLine {
+    pub fn new(
+        uuid: Uuid,
+        order_uuid: Uuid,
+        quantity: Option<Decimal>,
+        amount: Decimal,
+        fee: Decimal,
+    ) -> Self {
+        Self {
+            uuid,
+            order_uuid,
+            quantity,
+            amount,
+            fee,
+        }
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn order_uuid(&self) -> Uuid {
+        self.order_uuid
+    }
+
+    pub fn quantity(&self) -> Option<Decimal> {
+        self.quantity
+    }
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    pub fn fee(&self) -> Decimal {
+        self.fee
+    }
+}
+
+/// 结构体，表示订单的实付、已退与剩余金额。
+///
+/// - `paid_amount`: 订单实付金额（已按比例扣除优惠金额）。
+/// - `refunded_amount`: 累计已退还的实付金额（含手续费）。
+/// - `remaining_amount`: 尚未退还的实付金额。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderRefundSummary {
+    pub paid_amount: Decimal,
+    pub refunded_amount: Decimal,
+    pub remaining_amount: Decimal,
 }
 
 /// 结构体，表示交易金额的绝对值。
@@ -234,16 +484,103 @@ impl Transaction {
     /// Returns:
     /// - 新创建的充值交易实例。
     pub fn new_recharge(user_id: UserId, recharge_amount: TransactionAmountAbs) -> Transaction {
+        let uuid = Uuid::new_v4();
+
         Transaction {
             transaction_id: None,
-            uuid: Uuid::new_v4(),
+            uuid,
             create_time: Self::now(),
             finish_time: Some(Self::now()),
             amount: -Decimal::from(recharge_amount),
             status: TransactionStatus::Paid,
+            kind: TransactionKind::Recharge,
             user_id,
             orders: vec![],
             atomic: false,
+            coupon_redemption: None,
+            refund_fee: Decimal::ZERO,
+            payment_deadline: None,
+            external_payment: None,
+            journal_entries: vec![JournalEntry::recharge(
+                uuid,
+                user_id,
+                Decimal::from(recharge_amount),
+            )],
+            refund_lines: vec![],
+        }
+    }
+
+    /// 创建一个新的外部支付充值交易实例。
+    ///
+    /// 交易以未支付状态创建，收到支付网关的成功回调后通过`complete_external_payment`完成。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `recharge_amount`: 充值金额的绝对值。
+    /// - `external_payment`: 支付网关创建的外部支付。
+    ///
+    /// Returns:
+    /// - 新创建的未支付充值交易实例。
+    pub fn new_external_recharge(
+        user_id: UserId,
+        recharge_amount: TransactionAmountAbs,
+        external_payment: ExternalPayment,
+    ) -> Transaction {
+        Transaction {
+            transaction_id: None,
+            uuid: Uuid::new_v4(),
+            create_time: Self::now(),
+            finish_time: None,
+            amount: -Decimal::from(recharge_amount),
+            status: TransactionStatus::Unpaid,
+            kind: TransactionKind::Recharge,
+            user_id,
+            orders: vec![],
+            atomic: false,
+            coupon_redemption: None,
+            refund_fee: Decimal::ZERO,
+            payment_deadline: None,
+            external_payment: Some(external_payment),
+            journal_entries: vec![],
+            refund_lines: vec![],
+        }
+    }
+
+    /// 创建一个新的差价退款交易实例，不关联订单。
+    ///
+    /// 用于候补以低于预付金额的座位类型兑现后退还差价。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `refund_amount`: 退还金额的绝对值。
+    ///
+    /// Returns:
+    /// - 新创建的已完成退款交易实例。
+    pub fn new_fare_refund(user_id: UserId, refund_amount: TransactionAmountAbs) -> Transaction {
+        let uuid = Uuid::new_v4();
+
+        Transaction {
+            transaction_id: None,
+            uuid,
+            create_time: Self::now(),
+            finish_time: Some(Self::now()),
+            amount: -Decimal::from(refund_amount),
+            status: TransactionStatus::Paid,
+            kind: TransactionKind::Refund,
+            user_id,
+            orders: vec![],
+            atomic: false,
+            coupon_redemption: None,
+            refund_fee: Decimal::ZERO,
+            payment_deadline: None,
+            external_payment: None,
+            journal_entries: vec![JournalEntry::refund(
+                uuid,
+                user_id,
+                Decimal::from(refund_amount),
+                Decimal::ZERO,
+            )],
+            refund_lines: vec![],
         }
     }
 
@@ -263,9 +600,16 @@ impl Transaction {
             finish_time: Some(Self::now()),
             amount: Decimal::from(amount),
             status: TransactionStatus::Unpaid,
+            kind: TransactionKind::Pay,
             user_id,
             orders: vec![],
             atomic: false,
+            coupon_redemption: None,
+            refund_fee: Decimal::ZERO,
+            payment_deadline: None,
+            external_payment: None,
+            journal_entries: vec![],
+            refund_lines: vec![],
         }
     }
 
@@ -276,23 +620,75 @@ impl Transaction {
     /// - `orders`: 交易包含的订单列表。
     ///
     /// Returns:
-    /// - 新创建的交易实例。
+    /// - 新创建的交易实例，支付截止时间为创建后`TRANSACTION_PAYMENT_TIMEOUT_MINUTES`分钟。
     pub fn new(user_id: UserId, orders: Vec<Box<dyn Order>>, atomic: bool) -> Transaction {
         let total_amount = orders
             .iter()
             .map(|order| order.unit_price() * order.amount())
             .sum::<Decimal>();
 
+        let create_time = Self::now();
+
         Transaction {
             transaction_id: None,
             uuid: Uuid::new_v4(),
-            create_time: Self::now(),
+            create_time,
             finish_time: None,
             amount: total_amount,
             status: TransactionStatus::Unpaid,
+            kind: TransactionKind::Pay,
             user_id,
             orders,
             atomic,
+            coupon_redemption: None,
+            refund_fee: Decimal::ZERO,
+            payment_deadline: Some(
+                create_time + TimeDelta::minutes(TRANSACTION_PAYMENT_TIMEOUT_MINUTES),
+            ),
+            external_payment: None,
+            journal_entries: vec![],
+            refund_lines: vec![],
+        }
+    }
+
+    /// 创建一个新的改签调整交易实例，交易仅包含改签后的新订单。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `replaced_order`: 被改签的原订单。
+    /// - `new_order`: 改签后的新订单。
+    ///
+    /// Returns:
+    /// - 新创建的调整交易实例，金额为新旧订单的票价差额：为正时补缴差价，为负时退还差价。
+    pub fn new_adjustment(
+        user_id: UserId,
+        replaced_order: &dyn Order,
+        new_order: Box<dyn Order>,
+    ) -> Transaction {
+        let fare_difference = new_order.unit_price() * new_order.amount()
+            - replaced_order.unit_price() * replaced_order.amount();
+
+        Transaction {
+            transaction_id: None,
+            uuid: Uuid::new_v4(),
+            create_time: Self::now(),
+            finish_time: None,
+            amount: fare_difference,
+            status: TransactionStatus::Unpaid,
+            kind: if fare_difference.is_sign_negative() {
+                TransactionKind::Refund
+            } else {
+                TransactionKind::Pay
+            },
+            user_id,
+            orders: vec![new_order],
+            atomic: true,
+            coupon_redemption: None,
+            refund_fee: Decimal::ZERO,
+            payment_deadline: None,
+            external_payment: None,
+            journal_entries: vec![],
+            refund_lines: vec![],
         }
     }
 
@@ -305,8 +701,13 @@ impl Transaction {
     /// - `finish_time`: 交易完成时间，可能为空。
     /// - `amount`: 交易金额。
     /// - `status`: 交易状态。
+    /// - `kind`: 交易类型。
     /// - `user_id`: 用户的唯一标识符。
     /// - `orders`: 交易包含的订单列表。
+    /// - `coupon_redemption`: 交易使用的优惠券，可能为空。
+    /// - `refund_fee`: 退款交易扣除的手续费。
+    /// - `payment_deadline`: 支付截止时间，可能为空。
+    /// - `external_payment`: 关联的外部支付，可能为空。
     ///
     /// Returns:
     /// - 新创建的完整交易实例。
@@ -318,9 +719,14 @@ impl Transaction {
         finish_time: Option<DateTimeWithTimeZone>,
         amount: Decimal,
         status: TransactionStatus,
+        kind: TransactionKind,
         user_id: UserId,
         orders: Vec<Box<dyn Order>>,
         atomic: bool,
+        coupon_redemption: Option<CouponRedemption>,
+        refund_fee: Decimal,
+        payment_deadline: Option<DateTimeWithTimeZone>,
+        external_payment: Option<ExternalPayment>,
     ) -> Transaction {
         Transaction {
             transaction_id,
@@ -329,12 +735,65 @@ impl Transaction {
             finish_time,
             amount,
             status,
+            kind,
             user_id,
             orders,
             atomic,
+            coupon_redemption,
+            refund_fee,
+            payment_deadline,
+            external_payment,
+            journal_entries: vec![],
+            refund_lines: vec![],
         }
     }
 
+    /// 对未支付的交易应用优惠券，交易金额减去优惠金额。
+    ///
+    /// Arguments:
+    /// - `coupon`: 要应用的优惠券，需已持久化。
+    /// - `user_redeemed_count`: 该用户已核销本优惠券的次数。
+    ///
+    /// Returns:
+    /// - 成功时返回优惠金额。
+    /// - 失败时返回 `TransactionError`。
+    ///
+    /// Notes:
+    /// 此处的次数检查仅用于尽早拒绝，最终以支付时在数据库事务中的检查为准
+    pub fn apply_coupon(
+        &mut self,
+        coupon: &Coupon,
+        user_redeemed_count: u32,
+    ) -> Result<Decimal, TransactionError> {
+        if self.status == TransactionStatus::Paid {
+            return Err(TransactionError::AlreadyPaid(self.uuid));
+        }
+
+        if self.is_payment_expired(Self::now()) {
+            return Err(TransactionError::PaymentExpired(self.uuid));
+        }
+
+        if self.coupon_redemption.is_some() {
+            return Err(TransactionError::CouponAlreadyApplied(self.uuid));
+        }
+
+        let coupon_id = coupon
+            .get_id()
+            .expect("coupon should be persisted before applying");
+
+        let discount = coupon.calculate_discount(&self.orders, Self::now())?;
+        coupon.check_redemption_limit(user_redeemed_count)?;
+
+        self.amount -= discount;
+        self.coupon_redemption = Some(CouponRedemption::new(
+            coupon_id,
+            discount,
+            CouponRedemptionStatus::Pending,
+        ));
+
+        Ok(discount)
+    }
+
     /// 标记交易为已支付。
     ///
     /// Returns:
@@ -348,12 +807,129 @@ impl Transaction {
             return Err(TransactionError::AlreadyPaid(self.uuid));
         }
 
+        if self.external_payment.is_some() {
+            return Err(TransactionError::ExternalPaymentRequired(self.uuid));
+        }
+
+        if self.is_payment_expired(Self::now()) {
+            return Err(TransactionError::PaymentExpired(self.uuid));
+        }
+
+        self.status = TransactionStatus::Paid;
+        self.finish_time = Some(Self::now());
+
+        if let Some(redemption) = &mut self.coupon_redemption {
+            redemption.set_status(CouponRedemptionStatus::Redeemed);
+        }
+
+        // 改签调整交易金额为负时，支付即为退还差价
+        let entry = if self.amount.is_sign_negative() {
+            JournalEntry::refund(self.uuid, self.user_id, -self.amount, Decimal::ZERO)
+        } else {
+            JournalEntry::payment(self.uuid, self.user_id, self.amount, self.coupon_discount())
+        };
+        self.journal_entries.push(entry);
+
+        Ok(())
+    }
+
+    /// 判断交易在`now`时是否已不能支付：已超时关闭，或已超过支付截止时间仍未支付。
+    pub fn is_payment_expired(&self, now: DateTimeWithTimeZone) -> bool {
+        match self.status {
+            TransactionStatus::Unpaid => self
+                .payment_deadline
+                .is_some_and(|deadline| now >= deadline),
+            TransactionStatus::Paid => false,
+            TransactionStatus::Expired | TransactionStatus::Failed => true,
+        }
+    }
+
+    /// 收到支付网关的成功回调后，标记外部支付交易为已支付。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Ok(())`。
+    /// - 交易未关联外部支付、已支付或已关闭时返回 `TransactionError`。
+    pub fn complete_external_payment(&mut self) -> Result<(), TransactionError> {
+        self.check_external_payment_pending()?;
+
         self.status = TransactionStatus::Paid;
         self.finish_time = Some(Self::now());
+        self.journal_entries.push(JournalEntry::recharge(
+            self.uuid,
+            self.user_id,
+            -self.amount,
+        ));
 
         Ok(())
     }
 
+    /// 收到支付网关的失败回调后，关闭外部支付交易。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Ok(())`。
+    /// - 交易未关联外部支付、已支付或已关闭时返回 `TransactionError`。
+    pub fn fail_external_payment(&mut self) -> Result<(), TransactionError> {
+        self.check_external_payment_pending()?;
+
+        self.status = TransactionStatus::Failed;
+        self.finish_time = Some(Self::now());
+
+        Ok(())
+    }
+
+    fn check_external_payment_pending(&self) -> Result<(), TransactionError> {
+        if self.external_payment.is_none() {
+            return Err(TransactionError::NoExternalPayment(self.uuid));
+        }
+
+        match self.status {
+            TransactionStatus::Unpaid => Ok(()),
+            TransactionStatus::Paid => Err(TransactionError::AlreadyPaid(self.uuid)),
+            TransactionStatus::Expired | TransactionStatus::Failed => {
+                Err(TransactionError::PaymentExpired(self.uuid))
+            }
+        }
+    }
+
+    /// 支付超时后关闭交易：交易进入`Expired`状态，取消所有未支付的订单，并返还待核销的优惠券。
+    ///
+    /// Returns:
+    /// - 成功时返回被取消的订单列表。
+    /// - 交易已支付时返回 `TransactionError`。
+    ///
+    /// Notes:
+    /// 不会检查是否已超过支付截止时间，调用者需先通过`is_payment_expired`判断
+    pub fn expire(&mut self) -> Result<Vec<Box<dyn Order>>, TransactionError> {
+        if self.status == TransactionStatus::Paid {
+            return Err(TransactionError::AlreadyPaid(self.uuid));
+        }
+
+        self.status = TransactionStatus::Expired;
+
+        let mut cancelled_orders = Vec::new();
+
+        for order in &mut self.orders {
+            if order.order_status() == OrderStatus::Unpaid {
+                order
+                    .transition_status(
+                        OrderStatus::Cancelled,
+                        OrderStatusActor::System,
+                        "支付超时，订单自动取消",
+                    )
+                    .expect("unpaid order should be able to be cancelled");
+                cancelled_orders.push(order.clone());
+            }
+        }
+
+        if let Some(redemption) = &mut self.coupon_redemption
+            && redemption.status() == CouponRedemptionStatus::Pending
+        {
+            redemption.set_status(CouponRedemptionStatus::Restored);
+        }
+
+        Ok(cancelled_orders)
+    }
+
     /// 创建一个新的退款交易实例。
     ///
     /// Returns:
@@ -383,6 +959,34 @@ impl Transaction {
     pub fn refund_transaction_partial(
         &mut self,
         to_refund_orders: &[Box<dyn Order>],
+    ) -> Result<Transaction, RefundError> {
+        self.refund_transaction_partial_with_fee(
+            to_refund_orders,
+            &RefundPolicySet::free(),
+            Self::now(),
+        )
+    }
+
+    /// 创建一个新的部分退款交易实例，按退款策略扣除手续费，手续费记录在退款交易上。
+    ///
+    /// 退还各订单剩余的全部实付金额与数量（订单此前已部分退款时只退还剩余部分）。
+    ///
+    /// Arguments:
+    /// - `to_refund_orders`: 要退款的订单列表。
+    /// - `refund_policy`: 各订单类型的退款策略。
+    /// - `now`: 取消时间。
+    ///
+    /// Returns:
+    /// - 成功时返回新的部分退款交易实例，退款金额为应退金额减去手续费。
+    /// - 失败时返回 `RefundError`。
+    ///
+    /// Notes:
+    /// 调用者需要保证传入的订单是当前交易的订单
+    pub fn refund_transaction_partial_with_fee(
+        &mut self,
+        to_refund_orders: &[Box<dyn Order>],
+        refund_policy: &RefundPolicySet,
+        now: DateTimeWithTimeZone,
     ) -> Result<Transaction, RefundError> {
         let transaction_order_uuid_set =
             self.orders.iter().map(|e| e.uuid()).collect::<HashSet<_>>();
@@ -393,29 +997,31 @@ impl Transaction {
             }
         }
 
-        if self.status == TransactionStatus::Unpaid {
+        if self.status != TransactionStatus::Paid {
             return Err(RefundError::NotPaid(self.uuid));
         }
 
-        let mut fulfilled_order_list = Vec::new();
-        let mut refunded_order_list = Vec::new();
-
-        for order in &self.orders {
-            if order.order_status() == OrderStatus::Active
-                || order.order_status() == OrderStatus::Completed
-            {
-                fulfilled_order_list.push(order.uuid());
-            }
-
-            if order.already_refund() {
-                refunded_order_list.push(order.uuid());
-            }
-        }
+        let fulfilled_order_list = self
+            .orders
+            .iter()
+            .filter(|order| {
+                order.order_status() == OrderStatus::Active
+                    || order.order_status() == OrderStatus::Completed
+            })
+            .map(|order| order.uuid())
+            .collect::<Vec<_>>();
 
         if !fulfilled_order_list.is_empty() {
             return Err(RefundError::AlreadyFulfilled(fulfilled_order_list));
         }
 
+        let refunded_order_list = self
+            .orders
+            .iter()
+            .filter(|order| order.already_refund())
+            .map(|order| order.uuid())
+            .collect::<Vec<_>>();
+
         let refunded_order_uuid_set = refunded_order_list.iter().copied().collect::<HashSet<_>>();
 
         for order in to_refund_orders {
@@ -423,24 +1029,375 @@ impl Transaction {
                 return Err(RefundError::AlreadyRefunded(refunded_order_list));
             }
         }
-        let refund_amount_abs = to_refund_orders
+
+        let refund_amount_abs = self.refund_amount(to_refund_orders);
+
+        let mut refund_lines = to_refund_orders
             .iter()
-            .map(|order| order.unit_price() * order.amount())
-            .sum::<Decimal>();
+            .map(|order| {
+                let order_refund_amount = self.refund_amount(std::slice::from_ref(order));
+                RefundLine::new(
+                    Uuid::new_v4(),
+                    order.uuid(),
+                    Some(order.remaining_quantity()),
+                    order_refund_amount,
+                    refund_policy.refund_fee(order.as_ref(), order_refund_amount, now),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // 分摊优惠金额的舍入差额计入最后一条明细，保证明细合计等于退款金额
+        if let Some(last) = refund_lines.last_mut() {
+            let lines_total = to_refund_orders
+                .iter()
+                .map(|order| self.refund_amount(std::slice::from_ref(order)))
+                .sum::<Decimal>();
+
+            last.amount += refund_amount_abs - lines_total;
+        }
+
+        let refund_fee = refund_lines
+            .iter()
+            .map(|line| line.fee())
+            .sum::<Decimal>()
+            .min(refund_amount_abs.max(Decimal::ZERO));
+
+        let full_refund =
+            refunded_order_uuid_set.is_empty() && to_refund_orders.len() == self.orders.len();
+
+        self.void_coupon_on_refund(full_refund);
+
+        for line in &refund_lines {
+            self.record_order_refund(line);
+        }
+
+        Ok(self.make_refund_transaction(refund_amount_abs, refund_fee, refund_lines))
+    }
+
+    /// 创建一个新的部分退款交易实例，按金额或数量退还订单的一部分，按退款策略扣除手续费。
+    ///
+    /// Arguments:
+    /// - `requests`: 部分退款请求列表，同一订单的多个请求依次累计。
+    /// - `refund_policy`: 各订单类型的退款策略。
+    /// - `now`: 退款时间。
+    ///
+    /// Returns:
+    /// - 成功时返回新的退款交易实例，各订单的累计退款随之更新。
+    /// - 订单状态不允许部分退款、退款超过订单剩余的实付金额或数量、或退还了订单剩余的全部金额或数量时返回 `RefundError`，交易保持不变。
+    ///
+    /// Notes:
+    /// 调用者需要保证请求的订单是当前交易的订单，并在保存退款交易的同时释放按数量退还的座位或房间
+    pub fn refund_transaction_lines_with_fee(
+        &mut self,
+        requests: &[PartialRefundRequest],
+        refund_policy: &RefundPolicySet,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Transaction, RefundError> {
+        if self.amount.is_sign_negative() {
+            return Err(RefundError::RechargeTransaction(self.uuid));
+        }
+
+        if self.status != TransactionStatus::Paid {
+            return Err(RefundError::NotPaid(self.uuid));
+        }
+
+        let mut orders = self.orders.clone();
+        let mut refund_lines = Vec::with_capacity(requests.len());
+
+        for request in requests {
+            let order = orders
+                .iter_mut()
+                .find(|order| order.uuid() == request.order_uuid)
+                .unwrap_or_else(|| {
+                    panic!(
+                        "Order {} not in transaction {}",
+                        request.order_uuid, self.uuid
+                    )
+                });
+
+            let line = self.partial_refund_line(order.as_ref(), request, refund_policy, now)?;
+
+            order
+                .payment_info_mut()
+                .record_refund(line.amount(), line.quantity().unwrap_or(Decimal::ZERO));
+            refund_lines.push(line);
+        }
+
+        self.orders = orders;
+        self.void_coupon_on_refund(false);
+
+        let refund_amount_abs = refund_lines.iter().map(|line| line.amount()).sum();
+        let refund_fee = refund_lines.iter().map(|line| line.fee()).sum();
+
+        Ok(self.make_refund_transaction(refund_amount_abs, refund_fee, refund_lines))
+    }
+
+    /// 校验部分退款请求并计算退款明细
+    fn partial_refund_line(
+        &self,
+        order: &dyn Order,
+        request: &PartialRefundRequest,
+        refund_policy: &RefundPolicySet,
+        now: DateTimeWithTimeZone,
+    ) -> Result<RefundLine, RefundError> {
+        let order_uuid = order.uuid();
+
+        // 按数量退款会释放座位或房间，只能在行程开始前进行
+        let refundable = match request.portion {
+            RefundPortion::Amount(_) => matches!(
+                order.order_status(),
+                OrderStatus::Ongoing | OrderStatus::Active
+            ),
+            RefundPortion::Quantity(_) => order.order_status() == OrderStatus::Ongoing,
+        };
+
+        if order.already_refund() || !refundable {
+            return Err(RefundError::NotPartiallyRefundable {
+                order_uuid,
+                status: order.order_status(),
+            });
+        }
+
+        let paid_amount = self.order_paid_amount(order);
+        let remaining = paid_amount - order.payment_info().refunded_amount();
+        let remaining_quantity = order.remaining_quantity();
+
+        let (quantity, amount) = match request.portion {
+            RefundPortion::Amount(amount) => {
+                if amount <= Decimal::ZERO || amount.round_dp(2) != amount {
+                    return Err(RefundError::InvalidAmount { order_uuid, amount });
+                }
+
+                (None, amount)
+            }
+            RefundPortion::Quantity(quantity) => {
+                if quantity <= Decimal::ZERO || !quantity.fract().is_zero() {
+                    return Err(RefundError::InvalidQuantity {
+                        order_uuid,
+                        quantity,
+                    });
+                }
+
+                if quantity > remaining_quantity {
+                    return Err(RefundError::ExceedsQuantity {
+                        order_uuid,
+                        requested: quantity,
+                        remaining: remaining_quantity,
+                    });
+                }
+
+                if quantity == remaining_quantity {
+                    return Err(RefundError::WholeOrder(order_uuid));
+                }
+
+                (
+                    Some(quantity),
+                    (paid_amount * quantity / order.amount()).round_dp(2),
+                )
+            }
+        };
+
+        if amount > remaining {
+            return Err(RefundError::ExceedsPaidAmount {
+                order_uuid,
+                requested: amount,
+                remaining,
+            });
+        }
+
+        if amount == remaining {
+            return Err(RefundError::WholeOrder(order_uuid));
+        }
+
+        Ok(RefundLine::new(
+            Uuid::new_v4(),
+            order_uuid,
+            quantity,
+            amount,
+            refund_policy.refund_fee(order, amount, now),
+        ))
+    }
+
+    /// 退款后作废已核销的优惠券，全额退款时返还优惠券
+    fn void_coupon_on_refund(&mut self, full_refund: bool) {
+        if let Some(redemption) = &mut self.coupon_redemption
+            && redemption.status() == CouponRedemptionStatus::Redeemed
+        {
+            redemption.set_status(if full_refund {
+                CouponRedemptionStatus::Restored
+            } else {
+                CouponRedemptionStatus::Voided
+            });
+        }
+    }
+
+    /// 在订单的支付信息中累计退款明细的金额与数量
+    fn record_order_refund(&mut self, line: &RefundLine) {
+        if let Some(order) = self
+            .orders
+            .iter_mut()
+            .find(|order| order.uuid() == line.order_uuid())
+        {
+            order
+                .payment_info_mut()
+                .record_refund(line.amount(), line.quantity().unwrap_or(Decimal::ZERO));
+        }
+    }
+
+    fn make_refund_transaction(
+        &self,
+        refund_amount_abs: Decimal,
+        refund_fee: Decimal,
+        refund_lines: Vec<RefundLine>,
+    ) -> Transaction {
+        let uuid = Uuid::new_v4();
 
-        Ok(Transaction {
+        Transaction {
             transaction_id: None,
-            uuid: Uuid::new_v4(),
+            uuid,
             create_time: Self::now(),
             finish_time: Some(Self::now()),
-            amount: -refund_amount_abs,
+            amount: -(refund_amount_abs - refund_fee),
             status: TransactionStatus::Paid,
+            kind: TransactionKind::Refund,
             user_id: self.user_id,
-            orders: self.orders.clone(),
+            orders: vec![],
             atomic: false,
+            coupon_redemption: None,
+            refund_fee,
+            payment_deadline: None,
+            external_payment: None,
+            journal_entries: vec![JournalEntry::refund(
+                uuid,
+                self.user_id,
+                refund_amount_abs - refund_fee,
+                refund_fee,
+            )],
+            refund_lines,
+        }
+    }
+
+    /// 计算在`now`取消指定订单时的应退金额与手续费，供用户确认取消前查看。
+    ///
+    /// Arguments:
+    /// - `order_uuid`: 要取消的订单的 UUID。
+    /// - `refund_policy`: 各订单类型的退款策略。
+    /// - `now`: 取消时间。
+    ///
+    /// Returns:
+    /// - 交易未支付、订单不属于本交易或订单已退款时返回`None`。
+    pub fn refund_quote(
+        &self,
+        order_uuid: Uuid,
+        refund_policy: &RefundPolicySet,
+        now: DateTimeWithTimeZone,
+    ) -> Option<RefundQuote> {
+        if self.status != TransactionStatus::Paid {
+            return None;
+        }
+
+        let order = self
+            .orders
+            .iter()
+            .find(|order| order.uuid() == order_uuid)?;
+
+        if order.already_refund() {
+            return None;
+        }
+
+        let refund_amount = self.refund_amount(std::slice::from_ref(order));
+        let refund_fee = refund_policy.refund_fee(order.as_ref(), refund_amount, now);
+
+        Some(RefundQuote {
+            refund_amount,
+            refund_fee,
         })
     }
 
+    /// 计算订单的实付、已退与剩余金额。
+    ///
+    /// Returns:
+    /// - 交易未支付或订单不属于本交易时返回`None`。
+    pub fn order_refund_summary(&self, order_uuid: Uuid) -> Option<OrderRefundSummary> {
+        if self.status != TransactionStatus::Paid {
+            return None;
+        }
+
+        let order = self
+            .orders
+            .iter()
+            .find(|order| order.uuid() == order_uuid)?;
+
+        let paid_amount = self.order_paid_amount(order.as_ref());
+        let refunded_amount = self.order_refunded_amount(order.as_ref());
+
+        Some(OrderRefundSummary {
+            paid_amount,
+            refunded_amount,
+            remaining_amount: (paid_amount - refunded_amount).max(Decimal::ZERO),
+        })
+    }
+
+    /// 计算订单的实付金额，使用优惠券时按订单价格比例扣除优惠金额，四舍五入到分。
+    fn order_paid_amount(&self, order: &dyn Order) -> Decimal {
+        let price = order.unit_price() * order.amount();
+        let discount = self.coupon_discount();
+
+        let gross_amount = self
+            .orders
+            .iter()
+            .map(|order| order.unit_price() * order.amount())
+            .sum::<Decimal>();
+
+        if discount.is_zero() || gross_amount.is_zero() {
+            price
+        } else {
+            price - (discount * price / gross_amount).round_dp(2)
+        }
+    }
+
+    /// 计算订单累计已退还的实付金额，已全额退款的订单视为退还了全部实付金额。
+    fn order_refunded_amount(&self, order: &dyn Order) -> Decimal {
+        if order.already_refund() {
+            self.order_paid_amount(order)
+        } else {
+            order.payment_info().refunded_amount()
+        }
+    }
+
+    /// 计算退还指定订单剩余全部金额时的退款金额，使用优惠券时按订单价格比例扣除优惠金额。
+    ///
+    /// 每个订单分摊的优惠金额四舍五入到分，最后一笔退款取剩余的实付金额，保证退款总额等于实付金额。
+    fn refund_amount(&self, to_refund_orders: &[Box<dyn Order>]) -> Decimal {
+        let to_refund_uuid_set = to_refund_orders
+            .iter()
+            .map(|order| order.uuid())
+            .collect::<HashSet<_>>();
+
+        let remaining_orders = self
+            .orders
+            .iter()
+            .filter(|order| !order.already_refund() && !to_refund_uuid_set.contains(&order.uuid()))
+            .count();
+
+        if !self.coupon_discount().is_zero() && remaining_orders == 0 {
+            let previous_refund = self
+                .orders
+                .iter()
+                .map(|order| self.order_refunded_amount(order.as_ref()))
+                .sum::<Decimal>();
+
+            return self.amount - previous_refund;
+        }
+
+        to_refund_orders
+            .iter()
+            .map(|order| {
+                self.order_paid_amount(order.as_ref()) - order.payment_info().refunded_amount()
+            })
+            .sum()
+    }
+
     /// 获取交易的 UUID。
     ///
     /// Returns:
@@ -501,6 +1458,14 @@ impl Transaction {
         self.status
     }
 
+    /// 获取交易类型。
+    ///
+    /// Returns:
+    /// - 交易类型。
+    pub fn kind(&self) -> TransactionKind {
+        self.kind
+    }
+
     pub fn amount(&self) -> Decimal {
         self.amount
     }
@@ -516,4 +1481,655 @@ impl Transaction {
     pub fn atomic(&self) -> bool {
         self.atomic
     }
+
+    /// 获取交易使用的优惠券。
+    ///
+    /// Returns:
+    /// - 交易使用的优惠券，可能为空。
+    pub fn coupon_redemption(&self) -> Option<CouponRedemption> {
+        self.coupon_redemption
+    }
+
+    /// 获取交易的优惠金额，未使用优惠券时为 0。
+    pub fn coupon_discount(&self) -> Decimal {
+        self.coupon_redemption
+            .map(|redemption| redemption.discount())
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// 获取退款交易扣除的手续费，其他交易为 0。
+    pub fn refund_fee(&self) -> Decimal {
+        self.refund_fee
+    }
+
+    /// 获取交易的支付截止时间。
+    ///
+    /// Returns:
+    /// - 支付截止时间，为空表示不会超时。
+    pub fn payment_deadline(&self) -> Option<DateTimeWithTimeZone> {
+        self.payment_deadline
+    }
+
+    /// 获取交易关联的外部支付。
+    ///
+    /// Returns:
+    /// - 关联的外部支付，不经过支付网关的交易为空。
+    pub fn external_payment(&self) -> Option<&ExternalPayment> {
+        self.external_payment.as_ref()
+    }
+
+    /// 获取交易完成时生成、尚未持久化的记账凭证。
+    ///
+    /// Returns:
+    /// - 记账凭证列表，从数据库加载的交易为空。
+    pub fn journal_entries(&self) -> &[JournalEntry] {
+        &self.journal_entries
+    }
+
+    /// 获取退款交易中尚未持久化的退款明细。
+    ///
+    /// Returns:
+    /// - 退款明细列表，从数据库加载的交易为空。
+    pub fn refund_lines(&self) -> &[RefundLine] {
+        &self.refund_lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::coupon::{CouponDiscount, CouponId};
+    use crate::domain::model::hotel::{
+        HotelDateRange, HotelId, HotelRoomTypeId, OccupiedRoom, OccupiedRoomId,
+    };
+    use crate::domain::model::ledger::{JournalEntryKind, LedgerAccount};
+    use crate::domain::model::order::{
+        BaseOrder, HotelOrder, OrderTimeInfo, OrderType, PaymentInfo,
+    };
+    use crate::domain::model::personal_info::PersonalInfoId;
+    use crate::domain::model::test_support::{TestOrder, make_coupon, make_paid_transaction};
+    use chrono::{Duration, NaiveDate};
+    use std::any::Any;
+
+    fn make_ongoing_transaction(coupon: &Coupon, orders: Vec<Box<dyn Order>>) -> Transaction {
+        let mut tx = Transaction::new(UserId::from(1), orders, true);
+        tx.apply_coupon(coupon, 0).unwrap();
+        tx.pay().unwrap();
+
+        for order in tx.orders_mut() {
+            for status in [OrderStatus::Paid, OrderStatus::Ongoing] {
+                order
+                    .transition_status(status, OrderStatusActor::System, "测试")
+                    .unwrap();
+            }
+        }
+
+        tx
+    }
+
+    #[test]
+    fn test_transaction_apply_coupon() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(20)), Decimal::ZERO);
+        let mut tx = Transaction::new(
+            UserId::from(1),
+            vec![TestOrder::new_boxed(OrderType::Train, 100)],
+            true,
+        );
+
+        assert_eq!(tx.apply_coupon(&coupon, 0).unwrap(), Decimal::from(20));
+        assert_eq!(tx.raw_amount(), Decimal::from(80));
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Pending
+        );
+        assert!(tx.apply_coupon(&coupon, 0).is_err());
+
+        tx.pay().unwrap();
+
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Redeemed
+        );
+    }
+
+    #[test]
+    fn test_full_refund_restores_coupon() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(20)), Decimal::ZERO);
+        let mut tx = make_paid_transaction(
+            &coupon,
+            vec![
+                TestOrder::new_boxed(OrderType::Train, 100),
+                TestOrder::new_boxed(OrderType::Train, 200),
+            ],
+        );
+
+        let refund_tx = tx.refund_transaction().unwrap();
+
+        assert_eq!(refund_tx.raw_amount(), Decimal::from(-280));
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Restored
+        );
+    }
+
+    #[test]
+    fn test_partial_refund_voids_coupon() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(10)), Decimal::ZERO);
+        let mut tx = make_paid_transaction(
+            &coupon,
+            vec![
+                TestOrder::new_boxed(OrderType::Train, 100),
+                TestOrder::new_boxed(OrderType::Train, 100),
+                TestOrder::new_boxed(OrderType::Train, 100),
+            ],
+        );
+
+        let first = vec![tx.orders()[0].clone()];
+        let refund_tx = tx.refund_transaction_partial(&first).unwrap();
+
+        // 分摊优惠 10 * 100 / 300 = 3.33
+        assert_eq!(refund_tx.raw_amount(), Decimal::new(-9667, 2));
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Voided
+        );
+
+        tx.orders_mut()[0]
+            .payment_info_mut()
+            .set_refund_transaction_id(TransactionId::from(2));
+
+        let rest = tx.orders()[1..].to_vec();
+        let refund_tx = tx.refund_transaction_partial(&rest).unwrap();
+
+        // 最后一笔退款取剩余实付金额，退款总额等于实付金额 290
+        assert_eq!(refund_tx.raw_amount(), Decimal::new(-19333, 2));
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Voided
+        );
+    }
+
+    #[test]
+    fn test_expired_transaction_restores_coupon() {
+        let now = Transaction::now();
+        let mut tx = Transaction::new_full(
+            None,
+            Uuid::new_v4(),
+            now - Duration::minutes(20),
+            None,
+            Decimal::from(80),
+            TransactionStatus::Unpaid,
+            TransactionKind::Pay,
+            UserId::from(1),
+            vec![TestOrder::new_boxed(OrderType::Train, 100)],
+            true,
+            Some(CouponRedemption::new(
+                CouponId::from(1),
+                Decimal::from(20),
+                CouponRedemptionStatus::Pending,
+            )),
+            Decimal::ZERO,
+            Some(now - Duration::minutes(5)),
+            None,
+        );
+
+        assert!(tx.is_payment_expired(now));
+        assert!(matches!(tx.pay(), Err(TransactionError::PaymentExpired(_))));
+
+        let cancelled_orders = tx.expire().unwrap();
+
+        assert_eq!(cancelled_orders.len(), 1);
+        assert_eq!(tx.status(), TransactionStatus::Expired);
+        assert_eq!(tx.orders()[0].order_status(), OrderStatus::Cancelled);
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Restored
+        );
+        assert!(tx.pay().is_err());
+        assert!(tx.refund_transaction().is_err());
+    }
+
+    #[test]
+    fn test_new_transaction_payment_deadline() {
+        let tx = Transaction::new(
+            UserId::from(1),
+            vec![TestOrder::new_boxed(OrderType::Train, 100)],
+            true,
+        );
+
+        assert_eq!(
+            tx.payment_deadline(),
+            Some(tx.create_time() + Duration::minutes(TRANSACTION_PAYMENT_TIMEOUT_MINUTES))
+        );
+        assert!(!tx.is_payment_expired(Transaction::now()));
+
+        let recharge = Transaction::new_recharge(UserId::from(1), Decimal::from(100).into());
+
+        assert_eq!(recharge.payment_deadline(), None);
+    }
+
+    #[test]
+    fn test_external_recharge_settled_only_by_callback() {
+        let new_recharge = || {
+            Transaction::new_external_recharge(
+                UserId::from(1),
+                TransactionAmountAbs::from(Decimal::from(100)),
+                ExternalPayment::new("mock".to_string(), Uuid::new_v4()),
+            )
+        };
+
+        let mut tx = new_recharge();
+
+        assert_eq!(tx.status(), TransactionStatus::Unpaid);
+        assert_eq!(tx.raw_amount(), Decimal::from(-100));
+        assert!(matches!(
+            tx.pay(),
+            Err(TransactionError::ExternalPaymentRequired(_))
+        ));
+
+        tx.complete_external_payment().unwrap();
+
+        assert_eq!(tx.status(), TransactionStatus::Paid);
+        assert!(tx.finish_time().is_some());
+        assert!(matches!(
+            tx.complete_external_payment(),
+            Err(TransactionError::AlreadyPaid(_))
+        ));
+
+        let mut failed_tx = new_recharge();
+        failed_tx.fail_external_payment().unwrap();
+
+        assert_eq!(failed_tx.status(), TransactionStatus::Failed);
+        assert!(failed_tx.is_payment_expired(Transaction::now()));
+        assert!(failed_tx.complete_external_payment().is_err());
+
+        let mut plain_tx = Transaction::new_recharge(
+            UserId::from(1),
+            TransactionAmountAbs::from(Decimal::from(100)),
+        );
+        assert!(matches!(
+            plain_tx.complete_external_payment(),
+            Err(TransactionError::NoExternalPayment(_))
+        ));
+    }
+
+    #[test]
+    fn test_completed_transaction_posts_journal_entry() {
+        let user_id = UserId::from(1);
+        let wallet = LedgerAccount::UserWallet(user_id);
+        let wallet_change = |tx: &Transaction| {
+            tx.journal_entries()
+                .iter()
+                .map(|entry| entry.account_change(wallet))
+                .sum::<Decimal>()
+        };
+
+        let recharge = Transaction::new_recharge(user_id, Decimal::from(100).into());
+        assert_eq!(recharge.journal_entries().len(), 1);
+        assert_eq!(
+            recharge.journal_entries()[0].kind(),
+            JournalEntryKind::Recharge
+        );
+        assert_eq!(wallet_change(&recharge), -recharge.raw_amount());
+
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(10)), Decimal::ZERO);
+        let mut tx = make_paid_transaction(
+            &coupon,
+            vec![
+                TestOrder::new_boxed(OrderType::Train, 100),
+                TestOrder::new_boxed(OrderType::Train, 100),
+            ],
+        );
+
+        let entry = &tx.journal_entries()[0];
+        assert_eq!(entry.kind(), JournalEntryKind::Payment);
+        assert_eq!(wallet_change(&tx), Decimal::from(-190));
+        assert_eq!(
+            entry.account_change(LedgerAccount::Promotion),
+            Decimal::from(-10)
+        );
+        assert_eq!(
+            entry.account_change(LedgerAccount::PlatformRevenue),
+            Decimal::from(200)
+        );
+
+        let first = vec![tx.orders()[0].clone()];
+        let refund_tx = tx.refund_transaction_partial(&first).unwrap();
+        assert_eq!(
+            refund_tx.journal_entries()[0].kind(),
+            JournalEntryKind::Refund
+        );
+        assert_eq!(wallet_change(&refund_tx), -refund_tx.raw_amount());
+
+        let mut external = Transaction::new_external_recharge(
+            user_id,
+            TransactionAmountAbs::from(Decimal::from(50)),
+            ExternalPayment::new("mock".to_string(), Uuid::new_v4()),
+        );
+        assert!(external.journal_entries().is_empty());
+
+        external.complete_external_payment().unwrap();
+        assert_eq!(wallet_change(&external), Decimal::from(50));
+    }
+
+    fn partial_refund(
+        tx: &mut Transaction,
+        order_uuid: Uuid,
+        portion: RefundPortion,
+    ) -> Result<Transaction, RefundError> {
+        tx.refund_transaction_lines_with_fee(
+            &[PartialRefundRequest {
+                order_uuid,
+                portion,
+            }],
+            &RefundPolicySet::free(),
+            Transaction::now(),
+        )
+    }
+
+    #[test]
+    fn test_partial_refund_by_quantity_and_amount() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(30)), Decimal::ZERO);
+        let mut tx = make_ongoing_transaction(
+            &coupon,
+            vec![Box::new(TestOrder {
+                uuid: Uuid::new_v4(),
+                order_type: OrderType::Train,
+                price: Decimal::from(100),
+                amount: Decimal::from(3),
+                status: OrderStatus::Unpaid,
+                payment_info: PaymentInfo::new(None, None),
+            })],
+        );
+        let order_uuid = tx.orders()[0].uuid();
+
+        // 实付 300 - 30 = 270，退还 1 份为 90
+        let refund_tx =
+            partial_refund(&mut tx, order_uuid, RefundPortion::Quantity(Decimal::ONE)).unwrap();
+
+        assert_eq!(refund_tx.raw_amount(), Decimal::from(-90));
+        assert!(refund_tx.orders().is_empty());
+        assert_eq!(refund_tx.refund_lines().len(), 1);
+        assert_eq!(refund_tx.refund_lines()[0].quantity(), Some(Decimal::ONE));
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Voided
+        );
+
+        let refund_tx = partial_refund(
+            &mut tx,
+            order_uuid,
+            RefundPortion::Amount(Decimal::new(505, 1)),
+        )
+        .unwrap();
+
+        assert_eq!(refund_tx.raw_amount(), Decimal::new(-505, 1));
+        assert_eq!(refund_tx.refund_lines()[0].quantity(), None);
+        assert_eq!(
+            tx.order_refund_summary(order_uuid),
+            Some(OrderRefundSummary {
+                paid_amount: Decimal::from(270),
+                refunded_amount: Decimal::new(1405, 1),
+                remaining_amount: Decimal::new(1295, 1),
+            })
+        );
+        assert_eq!(tx.orders()[0].order_status(), OrderStatus::Ongoing);
+        assert!(!tx.orders()[0].already_refund());
+    }
+
+    #[test]
+    fn test_partial_refund_limits() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(30)), Decimal::ZERO);
+        let mut tx = make_ongoing_transaction(
+            &coupon,
+            vec![Box::new(TestOrder {
+                uuid: Uuid::new_v4(),
+                order_type: OrderType::Train,
+                price: Decimal::from(100),
+                amount: Decimal::from(3),
+                status: OrderStatus::Unpaid,
+                payment_info: PaymentInfo::new(None, None),
+            })],
+        );
+        let order_uuid = tx.orders()[0].uuid();
+
+        partial_refund(
+            &mut tx,
+            order_uuid,
+            RefundPortion::Amount(Decimal::from(100)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Amount(Decimal::from(200))
+            )
+            .unwrap_err(),
+            RefundError::ExceedsPaidAmount {
+                order_uuid,
+                requested: Decimal::from(200),
+                remaining: Decimal::from(170),
+            }
+        );
+        assert_eq!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Amount(Decimal::from(170))
+            )
+            .unwrap_err(),
+            RefundError::WholeOrder(order_uuid)
+        );
+        assert_eq!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Quantity(Decimal::from(3))
+            )
+            .unwrap_err(),
+            RefundError::WholeOrder(order_uuid)
+        );
+        assert_eq!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Quantity(Decimal::from(4))
+            )
+            .unwrap_err(),
+            RefundError::ExceedsQuantity {
+                order_uuid,
+                requested: Decimal::from(4),
+                remaining: Decimal::from(3),
+            }
+        );
+        // 按数量折算的金额 180 超过剩余实付金额 170
+        assert_eq!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Quantity(Decimal::from(2))
+            )
+            .unwrap_err(),
+            RefundError::ExceedsPaidAmount {
+                order_uuid,
+                requested: Decimal::from(180),
+                remaining: Decimal::from(170),
+            }
+        );
+        assert_eq!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Amount(Decimal::new(1, 3))
+            )
+            .unwrap_err(),
+            RefundError::InvalidAmount {
+                order_uuid,
+                amount: Decimal::new(1, 3),
+            }
+        );
+
+        // 取消订单时只退还剩余的实付金额
+        let refund_tx = tx
+            .refund_transaction_partial(&[tx.orders()[0].clone()])
+            .unwrap();
+
+        assert_eq!(refund_tx.raw_amount(), Decimal::from(-170));
+        assert_eq!(
+            refund_tx.refund_lines()[0].quantity(),
+            Some(Decimal::from(3))
+        );
+
+        tx.orders_mut()[0]
+            .payment_info_mut()
+            .set_refund_transaction_id(TransactionId::from(2));
+
+        assert_eq!(
+            tx.order_refund_summary(order_uuid),
+            Some(OrderRefundSummary {
+                paid_amount: Decimal::from(270),
+                refunded_amount: Decimal::from(270),
+                remaining_amount: Decimal::ZERO,
+            })
+        );
+        assert_eq!(
+            partial_refund(&mut tx, order_uuid, RefundPortion::Amount(Decimal::ONE)).unwrap_err(),
+            RefundError::NotPartiallyRefundable {
+                order_uuid,
+                status: OrderStatus::Ongoing,
+            }
+        );
+    }
+
+    #[test]
+    fn test_partial_refund_requires_ongoing_order() {
+        let coupon = make_coupon(CouponDiscount::Fixed(Decimal::from(10)), Decimal::ZERO);
+        let mut tx =
+            make_paid_transaction(&coupon, vec![TestOrder::new_boxed(OrderType::Train, 100)]);
+        let order_uuid = tx.orders()[0].uuid();
+
+        assert_eq!(
+            partial_refund(&mut tx, order_uuid, RefundPortion::Amount(Decimal::ONE)).unwrap_err(),
+            RefundError::NotPartiallyRefundable {
+                order_uuid,
+                status: OrderStatus::Cancelled,
+            }
+        );
+        assert_eq!(
+            tx.coupon_redemption().unwrap().status(),
+            CouponRedemptionStatus::Redeemed
+        );
+    }
+
+    #[test]
+    fn test_partial_refund_by_quantity_releases_hotel_rooms() {
+        let personal_info_id = PersonalInfoId::from(1);
+        let hotel_id = HotelId::from(1);
+        let room_type_id = HotelRoomTypeId::from(1);
+        let date_range = HotelDateRange::new(
+            NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 5, 3).unwrap(),
+        )
+        .unwrap();
+        let now = Transaction::now();
+
+        let order = HotelOrder::new(
+            BaseOrder::new(
+                None,
+                Uuid::new_v4(),
+                OrderStatus::Unpaid,
+                OrderTimeInfo::new(now, now, now),
+                Decimal::from(200),
+                Decimal::from(3),
+                PaymentInfo::new(None, None),
+                personal_info_id,
+            ),
+            hotel_id,
+            room_type_id,
+            date_range,
+        );
+        let order_uuid = order.uuid();
+
+        let mut tx = Transaction::new(UserId::from(1), vec![Box::new(order)], true);
+        tx.pay().unwrap();
+
+        for status in [OrderStatus::Paid, OrderStatus::Ongoing] {
+            tx.orders_mut()[0]
+                .transition_status(status, OrderStatusActor::System, "测试")
+                .unwrap();
+        }
+
+        // 同一酒店、房型和日期下还有其他入住人占用的房间
+        let occupied_rooms = (1..=4)
+            .map(|id| {
+                OccupiedRoom::new(
+                    Some(OccupiedRoomId::from(id)),
+                    hotel_id,
+                    room_type_id,
+                    date_range,
+                    if id == 1 {
+                        PersonalInfoId::from(2)
+                    } else {
+                        personal_info_id
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let refund_tx =
+            partial_refund(&mut tx, order_uuid, RefundPortion::Quantity(Decimal::ONE)).unwrap();
+
+        assert_eq!(refund_tx.raw_amount(), Decimal::from(-200));
+        assert_eq!(tx.orders()[0].order_status(), OrderStatus::Ongoing);
+        assert!(!tx.orders()[0].already_refund());
+        assert_eq!(tx.orders()[0].remaining_quantity(), Decimal::from(2));
+
+        let hotel_order = (tx.orders()[0].as_ref() as &dyn Any)
+            .downcast_ref::<HotelOrder>()
+            .unwrap();
+
+        let released = hotel_order
+            .rooms_to_release(occupied_rooms.clone(), 1)
+            .unwrap();
+
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].personal_info(), personal_info_id);
+
+        // 释放后剩余的房间与订单剩余数量一致，取消订单时全部释放
+        let remaining_rooms = occupied_rooms
+            .into_iter()
+            .filter(|room| room.get_id() != released[0].get_id())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            hotel_order
+                .rooms_to_release(remaining_rooms.clone(), 2)
+                .map(|rooms| rooms.len()),
+            Some(2)
+        );
+        assert!(hotel_order.rooms_to_release(remaining_rooms, 3).is_none());
+
+        // 入住后不能再按数量退款，只能由运营人员按金额退款
+        tx.orders_mut()[0]
+            .transition_status(OrderStatus::Active, OrderStatusActor::System, "测试")
+            .unwrap();
+
+        assert_eq!(
+            partial_refund(&mut tx, order_uuid, RefundPortion::Quantity(Decimal::ONE)).unwrap_err(),
+            RefundError::NotPartiallyRefundable {
+                order_uuid,
+                status: OrderStatus::Active,
+            }
+        );
+        assert!(
+            partial_refund(
+                &mut tx,
+                order_uuid,
+                RefundPortion::Amount(Decimal::from(50))
+            )
+            .is_ok()
+        );
+    }
 }
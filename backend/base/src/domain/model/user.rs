@@ -71,6 +71,7 @@
 //! - 密码尝试次数有上限控制
 use crate::domain::model::password::HashedPassword;
 use crate::domain::{Aggregate, Entity, Identifiable, Identifier};
+use chrono::NaiveDate;
 use email_address::EmailAddress;
 use id_macro::define_id_type;
 use shared::{PHONE_PREFIX_SET, PHONE_REGEX};
@@ -406,6 +407,36 @@ impl IdentityCardId {
             Ok(())
         }
     }
+
+    /// 获取身份证号码中的出生日期，出生日期不是合法日期时返回`None`
+    ///
+    /// # Examples
+    /// ```
+    /// use base::domain::model::user::IdentityCardId;
+    /// use chrono::NaiveDate;
+    ///
+    /// let id = IdentityCardId::try_from("11010519491231002X".to_string()).unwrap();
+    /// assert_eq!(id.birth_date(), NaiveDate::from_ymd_opt(1949, 12, 31));
+    /// ```
+    pub fn birth_date(&self) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(&self.0[6..14], "%Y%m%d").ok()
+    }
+
+    /// 计算在`date`当天的周岁年龄，出生日期无效或晚于`date`时返回`None`
+    ///
+    /// # Examples
+    /// ```
+    /// use base::domain::model::user::IdentityCardId;
+    /// use chrono::NaiveDate;
+    ///
+    /// let id = IdentityCardId::try_from("11010519491231002X".to_string()).unwrap();
+    /// assert_eq!(id.age_on(NaiveDate::from_ymd_opt(2009, 12, 30).unwrap()), Some(59));
+    /// assert_eq!(id.age_on(NaiveDate::from_ymd_opt(2009, 12, 31).unwrap()), Some(60));
+    /// ```
+    pub fn age_on(&self, date: NaiveDate) -> Option<u32> {
+        self.birth_date()
+            .and_then(|birth_date| date.years_since(birth_date))
+    }
 }
 
 impl TryFrom<String> for IdentityCardId {
@@ -1135,6 +1166,25 @@ mod tests {
             }
         }
 
+        #[test]
+        fn age_on() {
+            let id = IdentityCardId::try_from("110108200811088252".to_string()).unwrap();
+
+            assert_eq!(id.birth_date(), NaiveDate::from_ymd_opt(2008, 11, 8));
+            assert_eq!(
+                id.age_on(NaiveDate::from_ymd_opt(2022, 11, 7).unwrap()),
+                Some(13)
+            );
+            assert_eq!(
+                id.age_on(NaiveDate::from_ymd_opt(2022, 11, 8).unwrap()),
+                Some(14)
+            );
+            assert_eq!(
+                id.age_on(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
+                None
+            );
+        }
+
         #[test]
         fn invalid_check_code() {
             let case = "110105194912310020"; // 错误校验码
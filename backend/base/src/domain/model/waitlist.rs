@@ -0,0 +1,471 @@
+//! # 候补实体模块
+//!
+//! 车次的座位售罄时，用户可提交候补：预付票款后排队，在截止时间前有车票被取消时按提交顺序兑现。
+//! 主要包含以下内容：
+//!
+//! - `WaitlistStatus`: 枚举类型，表示候补状态。
+//! - `WaitlistSeatOption`: 结构体，表示候补可接受的座位类型及其票价。
+//! - `WaitlistError`: 枚举类型，表示候补状态变更失败的原因。
+//! - `Waitlist`: 结构体，表示候补聚合根。
+//!
+//! ## 关于候补的约定
+//!
+//! - 提交候补时创建一个火车票订单及其交易，订单票价为可接受座位类型中的最高票价，即预付金额；
+//!   订单支付后保持`Paid`状态，不参与普通的占座流程。
+//! - 有车票被取消时，按提交时间先后为同一车次的候补分配座位，并按可接受座位类型的顺序尝试；
+//!   以较低票价的座位类型兑现时，订单票价改为该座位类型的票价，并退还差价。
+//! - 截止时间前未能兑现的候补先标记为`Expired`，随后全额退款并标记为`Refunded`。
+use crate::Verified;
+use crate::domain::model::train::SeatTypeName;
+use crate::domain::model::train_schedule::{StationRange, TrainScheduleId};
+use crate::domain::model::user::UserId;
+use crate::domain::{Aggregate, Entity, Identifiable, Identifier};
+use chrono::Local;
+use id_macro::define_id_type;
+use sea_orm::prelude::{DateTimeWithTimeZone, Decimal};
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use thiserror::Error;
+use uuid::Uuid;
+
+define_id_type!(Waitlist);
+
+/// 枚举类型，表示候补状态。
+///
+/// - `Pending`: 等待兑现，包括尚未完成预付的候补。
+/// - `Fulfilled`: 已分配座位，订单进入`Ongoing`状态。
+/// - `Expired`: 已过截止时间未能兑现，等待退款。
+/// - `Refunded`: 已全额退还预付款。
+/// - `Cancelled`: 截止时间前未完成预付，无需退款。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WaitlistStatus {
+    Pending,
+    Fulfilled,
+    Expired,
+    Refunded,
+    Cancelled,
+}
+
+impl From<WaitlistStatus> for &'static str {
+    fn from(value: WaitlistStatus) -> Self {
+        match value {
+            WaitlistStatus::Pending => "pending",
+            WaitlistStatus::Fulfilled => "fulfilled",
+            WaitlistStatus::Expired => "expired",
+            WaitlistStatus::Refunded => "refunded",
+            WaitlistStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl TryFrom<&str> for WaitlistStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(WaitlistStatus::Pending),
+            "fulfilled" => Ok(WaitlistStatus::Fulfilled),
+            "expired" => Ok(WaitlistStatus::Expired),
+            "refunded" => Ok(WaitlistStatus::Refunded),
+            "cancelled" => Ok(WaitlistStatus::Cancelled),
+            x => Err(format!("invalid waitlist status: {}", x)),
+        }
+    }
+}
+
+impl Display for WaitlistStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <WaitlistStatus as Into<&'static str>>::into(*self))
+    }
+}
+
+/// 结构体，表示候补可接受的座位类型及其票价。
+///
+/// 票价在提交候补时按乘客类别与动态定价计算，兑现时不再重新计算。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WaitlistSeatOption {
+    pub seat_type_name: SeatTypeName<Verified>,
+    pub price: Decimal,
+}
+
+/// 枚举类型，表示候补状态变更失败的原因。
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum WaitlistError {
+    #[error("waitlist {0} is in invalid status: {1}")]
+    InvalidStatus(Uuid, WaitlistStatus),
+    #[error("seat type {1} is not acceptable for waitlist {0}")]
+    InvalidSeatType(Uuid, String),
+}
+
+/// 结构体，表示候补聚合根。
+///
+/// 包含以下字段：
+/// - `waitlist_id`: 候补的唯一标识符，可以为空。
+/// - `uuid`: 候补的 UUID。
+/// - `user_id`: 提交候补的用户。
+/// - `train_order_uuid`: 预付票款的火车票订单。
+/// - `transaction_uuid`: 预付票款的交易。
+/// - `train_schedule_id`: 候补的车次排班。
+/// - `station_range`: 候补的站点范围。
+/// - `seat_options`: 可接受的座位类型，按优先顺序排列。
+/// - `deadline`: 截止时间，此前未能兑现则退款。
+/// - `create_time`: 提交时间，兑现按提交时间先后进行。
+/// - `status`: 候补状态。
+/// - `fulfilled_seat_type`: 兑现时分配的座位类型。
+/// - `fare_difference_refunded`: 兑现后的差价是否已退还，无差价时兑现即视为已退还。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Waitlist {
+    waitlist_id: Option<WaitlistId>,
+    uuid: Uuid,
+    user_id: UserId,
+    train_order_uuid: Uuid,
+    transaction_uuid: Uuid,
+    train_schedule_id: TrainScheduleId,
+    station_range: StationRange<Verified>,
+    seat_options: Vec<WaitlistSeatOption>,
+    deadline: DateTimeWithTimeZone,
+    create_time: DateTimeWithTimeZone,
+    status: WaitlistStatus,
+    fulfilled_seat_type: Option<SeatTypeName<Verified>>,
+    fare_difference_refunded: bool,
+}
+
+impl Identifiable for Waitlist {
+    type ID = WaitlistId;
+
+    fn get_id(&self) -> Option<Self::ID> {
+        self.waitlist_id
+    }
+
+    fn set_id(&mut self, id: Self::ID) {
+        self.waitlist_id = Some(id);
+    }
+}
+
+impl Entity for Waitlist {}
+
+impl Aggregate for Waitlist {}
+
+impl Waitlist {
+    /// 创建一个新的候补，状态为`Pending`。
+    ///
+    /// Arguments:
+    /// - `user_id`: 提交候补的用户。
+    /// - `train_order_uuid`: 预付票款的火车票订单。
+    /// - `transaction_uuid`: 预付票款的交易。
+    /// - `train_schedule_id`: 候补的车次排班。
+    /// - `station_range`: 候补的站点范围。
+    /// - `seat_options`: 可接受的座位类型，按优先顺序排列，不能为空。
+    /// - `deadline`: 截止时间。
+    pub fn new(
+        user_id: UserId,
+        train_order_uuid: Uuid,
+        transaction_uuid: Uuid,
+        train_schedule_id: TrainScheduleId,
+        station_range: StationRange<Verified>,
+        seat_options: Vec<WaitlistSeatOption>,
+        deadline: DateTimeWithTimeZone,
+    ) -> Self {
+        Waitlist {
+            waitlist_id: None,
+            uuid: Uuid::new_v4(),
+            user_id,
+            train_order_uuid,
+            transaction_uuid,
+            train_schedule_id,
+            station_range,
+            seat_options,
+            deadline,
+            create_time: Local::now().fixed_offset(),
+            status: WaitlistStatus::Pending,
+            fulfilled_seat_type: None,
+            fare_difference_refunded: false,
+        }
+    }
+
+    /// 从持久化数据重建候补。
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_full(
+        waitlist_id: Option<WaitlistId>,
+        uuid: Uuid,
+        user_id: UserId,
+        train_order_uuid: Uuid,
+        transaction_uuid: Uuid,
+        train_schedule_id: TrainScheduleId,
+        station_range: StationRange<Verified>,
+        seat_options: Vec<WaitlistSeatOption>,
+        deadline: DateTimeWithTimeZone,
+        create_time: DateTimeWithTimeZone,
+        status: WaitlistStatus,
+        fulfilled_seat_type: Option<SeatTypeName<Verified>>,
+        fare_difference_refunded: bool,
+    ) -> Self {
+        Waitlist {
+            waitlist_id,
+            uuid,
+            user_id,
+            train_order_uuid,
+            transaction_uuid,
+            train_schedule_id,
+            station_range,
+            seat_options,
+            deadline,
+            create_time,
+            status,
+            fulfilled_seat_type,
+            fare_difference_refunded,
+        }
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn user_id(&self) -> UserId {
+        self.user_id
+    }
+
+    pub fn train_order_uuid(&self) -> Uuid {
+        self.train_order_uuid
+    }
+
+    pub fn transaction_uuid(&self) -> Uuid {
+        self.transaction_uuid
+    }
+
+    pub fn train_schedule_id(&self) -> TrainScheduleId {
+        self.train_schedule_id
+    }
+
+    pub fn station_range(&self) -> StationRange<Verified> {
+        self.station_range
+    }
+
+    pub fn seat_options(&self) -> &[WaitlistSeatOption] {
+        &self.seat_options
+    }
+
+    pub fn deadline(&self) -> DateTimeWithTimeZone {
+        self.deadline
+    }
+
+    pub fn create_time(&self) -> DateTimeWithTimeZone {
+        self.create_time
+    }
+
+    pub fn status(&self) -> WaitlistStatus {
+        self.status
+    }
+
+    pub fn fulfilled_seat_type(&self) -> Option<&SeatTypeName<Verified>> {
+        self.fulfilled_seat_type.as_ref()
+    }
+
+    pub fn fare_difference_refunded(&self) -> bool {
+        self.fare_difference_refunded
+    }
+
+    /// 预付金额，即可接受座位类型中的最高票价。
+    pub fn prepaid_amount(&self) -> Decimal {
+        self.seat_options
+            .iter()
+            .map(|option| option.price)
+            .max()
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// 兑现时应退还的差价，即预付金额与所分配座位类型票价之差；未兑现时为`None`。
+    pub fn fare_difference(&self) -> Option<Decimal> {
+        let seat_type_name = self.fulfilled_seat_type.as_ref()?;
+
+        self.seat_options
+            .iter()
+            .find(|option| &option.seat_type_name == seat_type_name)
+            .map(|option| self.prepaid_amount() - option.price)
+    }
+
+    /// 已兑现但差价尚未退还时，返回应退还的差价。
+    pub fn pending_fare_difference(&self) -> Option<Decimal> {
+        if self.status != WaitlistStatus::Fulfilled || self.fare_difference_refunded {
+            return None;
+        }
+
+        self.fare_difference()
+            .filter(|difference| *difference > Decimal::ZERO)
+    }
+
+    /// 判断候补在`now`是否已过截止时间且仍未兑现。
+    pub fn is_expired(&self, now: DateTimeWithTimeZone) -> bool {
+        self.status == WaitlistStatus::Pending && now >= self.deadline
+    }
+
+    /// 以指定座位类型兑现候补。
+    ///
+    /// Arguments:
+    /// - `seat_type_name`: 分配的座位类型，须为可接受的座位类型之一。
+    ///
+    /// Returns:
+    /// - 成功时返回该座位类型的票价。
+    pub fn fulfil(&mut self, seat_type_name: &str) -> Result<Decimal, WaitlistError> {
+        if self.status != WaitlistStatus::Pending {
+            return Err(WaitlistError::InvalidStatus(self.uuid, self.status));
+        }
+
+        let option = self
+            .seat_options
+            .iter()
+            .find(|option| option.seat_type_name.deref() == seat_type_name)
+            .ok_or_else(|| WaitlistError::InvalidSeatType(self.uuid, seat_type_name.to_string()))?;
+
+        let price = option.price;
+
+        self.fulfilled_seat_type = Some(option.seat_type_name.clone());
+        self.status = WaitlistStatus::Fulfilled;
+        self.fare_difference_refunded = price >= self.prepaid_amount();
+
+        Ok(price)
+    }
+
+    /// 兑现后的差价已退还。
+    pub fn mark_fare_difference_refunded(&mut self) -> Result<(), WaitlistError> {
+        if self.status != WaitlistStatus::Fulfilled {
+            return Err(WaitlistError::InvalidStatus(self.uuid, self.status));
+        }
+
+        self.fare_difference_refunded = true;
+
+        Ok(())
+    }
+
+    /// 截止时间前未能兑现，标记为等待退款。
+    pub fn expire(&mut self) -> Result<(), WaitlistError> {
+        if self.status != WaitlistStatus::Pending {
+            return Err(WaitlistError::InvalidStatus(self.uuid, self.status));
+        }
+
+        self.status = WaitlistStatus::Expired;
+
+        Ok(())
+    }
+
+    /// 已退还预付款。
+    pub fn refund(&mut self) -> Result<(), WaitlistError> {
+        if self.status != WaitlistStatus::Expired {
+            return Err(WaitlistError::InvalidStatus(self.uuid, self.status));
+        }
+
+        self.status = WaitlistStatus::Refunded;
+
+        Ok(())
+    }
+
+    /// 截止时间前未完成预付，无需退款。
+    pub fn cancel(&mut self) -> Result<(), WaitlistError> {
+        if !matches!(
+            self.status,
+            WaitlistStatus::Pending | WaitlistStatus::Expired
+        ) {
+            return Err(WaitlistError::InvalidStatus(self.uuid, self.status));
+        }
+
+        self.status = WaitlistStatus::Cancelled;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::station::StationId;
+    use chrono::Duration;
+
+    fn make_waitlist(deadline: DateTimeWithTimeZone) -> Waitlist {
+        Waitlist::new(
+            UserId::from(1u64),
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            TrainScheduleId::from(1u64),
+            StationRange::from_unchecked(StationId::from(1u64), StationId::from(2u64)),
+            vec![
+                WaitlistSeatOption {
+                    seat_type_name: SeatTypeName::from_unchecked("二等座".to_string()),
+                    price: Decimal::from(300),
+                },
+                WaitlistSeatOption {
+                    seat_type_name: SeatTypeName::from_unchecked("一等座".to_string()),
+                    price: Decimal::from(500),
+                },
+            ],
+            deadline,
+        )
+    }
+
+    #[test]
+    fn test_fulfil_with_cheaper_seat_type() {
+        let mut waitlist = make_waitlist(Local::now().fixed_offset() + Duration::hours(1));
+
+        assert_eq!(waitlist.prepaid_amount(), Decimal::from(500));
+        assert_eq!(waitlist.fare_difference(), None);
+
+        assert_eq!(
+            waitlist.fulfil("商务座"),
+            Err(WaitlistError::InvalidSeatType(
+                waitlist.uuid(),
+                "商务座".to_string()
+            ))
+        );
+
+        assert_eq!(waitlist.fulfil("二等座"), Ok(Decimal::from(300)));
+        assert_eq!(waitlist.status(), WaitlistStatus::Fulfilled);
+        assert_eq!(waitlist.fare_difference(), Some(Decimal::from(200)));
+        assert!(!waitlist.fare_difference_refunded());
+        assert_eq!(waitlist.pending_fare_difference(), Some(Decimal::from(200)));
+
+        waitlist.mark_fare_difference_refunded().unwrap();
+        assert_eq!(waitlist.pending_fare_difference(), None);
+
+        // 已兑现的候补不能再次兑现或过期
+        assert_eq!(
+            waitlist.fulfil("一等座"),
+            Err(WaitlistError::InvalidStatus(
+                waitlist.uuid(),
+                WaitlistStatus::Fulfilled
+            ))
+        );
+        assert!(waitlist.expire().is_err());
+    }
+
+    #[test]
+    fn test_fulfil_with_most_expensive_seat_type_needs_no_refund() {
+        let mut waitlist = make_waitlist(Local::now().fixed_offset() + Duration::hours(1));
+
+        assert_eq!(waitlist.pending_fare_difference(), None);
+
+        assert_eq!(waitlist.fulfil("一等座"), Ok(Decimal::from(500)));
+        assert_eq!(waitlist.fare_difference(), Some(Decimal::ZERO));
+        assert!(waitlist.fare_difference_refunded());
+        assert_eq!(waitlist.pending_fare_difference(), None);
+    }
+
+    #[test]
+    fn test_expire_and_refund() {
+        let now = Local::now().fixed_offset();
+        let mut waitlist = make_waitlist(now + Duration::hours(1));
+
+        assert!(!waitlist.is_expired(now));
+        assert!(waitlist.is_expired(now + Duration::hours(1)));
+
+        // 未过期的候补不能直接退款
+        assert!(waitlist.refund().is_err());
+
+        waitlist.expire().unwrap();
+        assert_eq!(waitlist.status(), WaitlistStatus::Expired);
+        assert!(!waitlist.is_expired(now + Duration::hours(1)));
+        assert!(waitlist.fulfil("二等座").is_err());
+
+        waitlist.refund().unwrap();
+        assert_eq!(waitlist.status(), WaitlistStatus::Refunded);
+        assert!(waitlist.cancel().is_err());
+    }
+}
@@ -0,0 +1,16 @@
+use crate::domain::model::coupon::{Coupon, CouponId};
+use crate::domain::model::user::UserId;
+use crate::domain::{Repository, RepositoryError};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait CouponRepository: Repository<Coupon> {
+    async fn find_by_code(&self, code: &str) -> Result<Option<Coupon>, RepositoryError>;
+
+    /// 查询用户已核销指定优惠券的次数（含已作废，不含已返还和未支付）
+    async fn count_user_redemptions(
+        &self,
+        coupon_id: CouponId,
+        user_id: UserId,
+    ) -> Result<u32, RepositoryError>;
+}
@@ -0,0 +1,55 @@
+//! # 幂等键仓储模块
+//!
+//! 该模块定义了幂等键记录的仓储接口。主要包含以下内容：
+//!
+//! - `IdempotencyRepository`: 异步 trait，定义了幂等键记录的操作。
+use crate::domain::RepositoryError;
+use crate::domain::model::idempotency::IdempotencyRecord;
+use crate::domain::model::user::UserId;
+use async_trait::async_trait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+
+/// 异步 trait，定义了幂等键记录的操作。
+///
+/// 幂等键记录由`(user_id, scope, key)`唯一确定。
+#[async_trait]
+pub trait IdempotencyRepository: 'static + Send + Sync {
+    /// 查找幂等键记录。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Option<IdempotencyRecord>`，如果未找到则返回 `None`。
+    /// - 失败时返回 `RepositoryError`。
+    async fn find(
+        &self,
+        user_id: UserId,
+        scope: &str,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, RepositoryError>;
+
+    /// 登记幂等键。幂等键已存在时不做修改，除非已有登记仍在处理中、已失效且请求摘要相同，
+    /// 此时由本次请求接管。
+    ///
+    /// Returns:
+    /// - 成功登记或接管时返回`true`，否则返回`false`。
+    /// - 失败时返回 `RepositoryError`。
+    async fn reserve(&self, record: &IdempotencyRecord) -> Result<bool, RepositoryError>;
+
+    /// 保存幂等键对应请求的响应。
+    async fn save_response(
+        &self,
+        user_id: UserId,
+        scope: &str,
+        key: &str,
+        response: &str,
+    ) -> Result<(), RepositoryError>;
+
+    /// 删除幂等键记录。
+    async fn remove(&self, user_id: UserId, scope: &str, key: &str) -> Result<(), RepositoryError>;
+
+    /// 删除`time`之前登记的幂等键记录。
+    ///
+    /// Returns:
+    /// - 成功时返回删除的记录数。
+    /// - 失败时返回 `RepositoryError`。
+    async fn remove_before(&self, time: DateTimeWithTimeZone) -> Result<u64, RepositoryError>;
+}
@@ -0,0 +1,42 @@
+//! # 账本仓储模块
+//!
+//! 该模块定义了复式记账账本的查询接口。主要包含以下内容：
+//!
+//! - `LedgerRepository`: 异步 trait，定义了账本的查询操作。
+//!
+//! 记账凭证随交易一同持久化（见`TransactionRepository`），本仓储只负责查询。
+use crate::domain::RepositoryError;
+use crate::domain::model::ledger::JournalEntry;
+use crate::domain::model::user::UserId;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// 异步 trait，定义了账本的查询操作。
+///
+/// 包含以下方法：
+/// - `find_by_transaction_uuid`: 查找交易对应的记账凭证。
+/// - `get_wallet_balances`: 获取所有用户钱包科目的余额。
+#[async_trait]
+pub trait LedgerRepository: 'static + Send + Sync {
+    /// 查找交易对应的记账凭证。
+    ///
+    /// Arguments:
+    /// - `transaction_uuid`: 交易的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回按记账时间排序的凭证列表。
+    /// - 失败时返回 `RepositoryError`。
+    async fn find_by_transaction_uuid(
+        &self,
+        transaction_uuid: Uuid,
+    ) -> Result<Vec<JournalEntry>, RepositoryError>;
+
+    /// 获取所有用户钱包科目的余额。
+    ///
+    /// Returns:
+    /// - 成功时返回用户 ID 到钱包余额的映射，没有分录的用户不包含在内。
+    /// - 失败时返回 `RepositoryError`。
+    async fn get_wallet_balances(&self) -> Result<HashMap<UserId, Decimal>, RepositoryError>;
+}
@@ -1,7 +1,10 @@
 pub mod city;
+pub mod coupon;
 pub mod dish;
 pub mod hotel;
 pub mod hotel_rating;
+pub mod idempotency;
+pub mod ledger;
 pub mod notify;
 pub mod occupied_room;
 pub mod order;
@@ -15,3 +18,4 @@ pub mod train;
 pub mod train_schedule;
 pub mod transaction;
 pub mod user;
+pub mod waitlist;
@@ -3,11 +3,13 @@ use crate::domain::model::hotel::HotelId;
 use crate::domain::model::order::{
     DishOrder, HotelOrder, Order, OrderId, TakeawayOrder, TrainOrder,
 };
+use crate::domain::model::order_state_machine::OrderStatusTransition;
 use crate::domain::model::train_schedule::TrainScheduleId;
 use crate::domain::model::user::UserId;
 use async_trait::async_trait;
 use chrono::NaiveDate;
 use sea_orm::FromQueryResult;
+use sea_orm::prelude::DateTimeWithTimeZone;
 use uuid::Uuid;
 /*
 c
@@ -217,4 +219,16 @@ pub trait OrderRepository: 'static + Send + Sync {
         origin_departure_date: NaiveDate,
         origin_departure_time_second: i32,
     ) -> Result<bool, RepositoryError>;
+
+    /// 查找暂留座位已过期（过期时间不晚于`now`）的火车订单UUID
+    async fn find_expired_seat_hold_train_order_uuids(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Uuid>, RepositoryError>;
+
+    /// 查询订单的状态迁移历史，按迁移时间先后排序
+    async fn find_status_history(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<Vec<OrderStatusTransition>, RepositoryError>;
 }
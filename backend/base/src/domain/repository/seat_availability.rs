@@ -20,6 +20,14 @@ pub trait SeatAvailabilityRepository: Repository<SeatAvailability> {
         train_schedule_id: TrainScheduleId,
     ) -> Result<OccupiedSeatInfoMap, RepositoryError>;
 
+    /// 批量获取多个车次的已占用座位信息，单次查询完成
+    ///
+    /// 没有已占用座位的车次不会出现在返回值中。
+    async fn get_train_schedules_occupied_seat(
+        &self,
+        train_schedule_ids: &[TrainScheduleId],
+    ) -> Result<HashMap<TrainScheduleId, OccupiedSeatInfoMap>, RepositoryError>;
+
     async fn find_by_schedule_seat_type_station_range(
         &self,
         train_schedule_id: TrainScheduleId,
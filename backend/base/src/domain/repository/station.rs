@@ -7,10 +7,11 @@
 //!
 //! 注意：具体实现应放在基础设施层(`infrastructure::repository`)。
 use crate::domain::model::city::CityId;
-use crate::domain::model::station::Station;
+use crate::domain::model::station::{Station, StationId};
 use crate::domain::{Repository, RepositoryError};
 use async_trait::async_trait;
-use shared::data::StationData;
+use shared::data::{IntraCityTransferData, StationData};
+use std::collections::HashMap;
 
 /// 火车站仓储接口
 ///
@@ -33,6 +34,8 @@ use shared::data::StationData;
 /// - `find_by_city`: 根据城市ID查找火车站。
 /// - `find_by_name`: 根据车站名称查找火车站。
 /// - `save_raw`: 保存原始火车站数据。
+/// - `load_transfer_times`: 加载同城换乘时间表。
+/// - `save_raw_transfer`: 保存原始同城换乘时间数据。
 ///
 /// # Errors
 ///
@@ -71,4 +74,26 @@ pub trait StationRepository: Repository<Station> + 'static + Send + Sync {
     /// # Returns
     /// 成功时返回`()`；失败时返回`RepositoryError`。
     async fn save_raw(&self, station_data: StationData) -> Result<(), RepositoryError>;
+
+    /// 加载同城换乘时间表。
+    ///
+    /// # Returns
+    /// 成功时返回`(出发车站ID, 目标车站ID) -> 换乘时间（秒）`的映射；失败时返回`RepositoryError`。
+    async fn load_transfer_times(
+        &self,
+    ) -> Result<HashMap<(StationId, StationId), u32>, RepositoryError>;
+
+    /// 保存原始同城换乘时间数据。
+    ///
+    /// 城市的默认换乘时间将展开为该城市内所有未单独配置的车站对。
+    ///
+    /// # Arguments
+    /// * `transfer_data` - 原始同城换乘时间数据。
+    ///
+    /// # Returns
+    /// 成功时返回`()`；失败时返回`RepositoryError`。
+    async fn save_raw_transfer(
+        &self,
+        transfer_data: IntraCityTransferData,
+    ) -> Result<(), RepositoryError>;
 }
@@ -2,20 +2,65 @@
 //!
 //! 该模块定义了火车票订购系统中的交易仓储接口。主要包含以下内容：
 //!
+//! - `StatementFilter`: 结构体，表示账单的查询条件。
+//! - `StatementCursor`: 结构体，表示账单分页的位置。
+//! - `RebookSettlement`: 枚举类型，表示改签调整交易的结算结果。
 //! - `TransactionRepository`: 异步 trait，定义了交易仓储的操作。
-use crate::domain::model::transaction::Transaction;
+use crate::domain::model::hotel::OccupiedRoom;
+use crate::domain::model::transaction::{Transaction, TransactionId, TransactionKind};
 use crate::domain::model::user::UserId;
 use crate::domain::{Repository, RepositoryError};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+/// 结构体，表示账单的查询条件，账单仅包含已完成（已支付）的交易，按完成时间筛选。
+///
+/// - `begin`: 起始时间（含），为空时不限。
+/// - `end`: 截止时间（不含）。
+/// - `kinds`: 交易类型，为空时不限。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatementFilter {
+    pub begin: Option<DateTimeWithTimeZone>,
+    pub end: DateTimeWithTimeZone,
+    pub kinds: Vec<TransactionKind>,
+}
+
+/// 结构体，表示账单分页的位置，即上一页最后一笔交易的完成时间与 ID。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatementCursor {
+    pub finish_time: DateTimeWithTimeZone,
+    pub transaction_id: TransactionId,
+}
+
+/// 枚举类型，表示改签调整交易的结算结果。
+///
+/// - `Settled`: 已结算。
+/// - `InsufficientBalance`: 加锁后读取的余额`balance`不足以补缴差价，未做任何修改。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebookSettlement {
+    Settled,
+    InsufficientBalance { balance: Decimal },
+}
+
 /// 异步 trait，定义了交易仓储的操作。
 ///
 /// 包含以下方法：
 /// - `find_by_uuid`: 根据 UUID 查找交易。
 /// - `find_by_user_id`: 根据用户 ID 查找所有交易。
+/// - `find_by_order_uuid`: 根据订单 UUID 查找支付该订单的交易。
+/// - `find_expired_unpaid`: 查找已超过支付截止时间仍未支付的交易。
+/// - `find_by_external_payment_id`: 根据外部支付 ID 查找交易。
 /// - `get_user_balance`: 获取用户的余额。
+/// - `get_all_user_balances`: 获取所有用户的余额。
+/// - `find_statement`: 分页查询用户账单中的交易。
+/// - `count_statement`: 统计用户账单中的交易数量。
+/// - `get_user_balance_before`: 获取用户在指定时间之前的余额。
+/// - `save_partial_refund`: 在同一数据库事务中保存部分退款及其释放的房间。
+/// - `save_rebook_settlement`: 在同一数据库事务中保存改签调整交易的结算。
+/// - `save_fare_difference_refund`: 在同一数据库事务中保存候补差价退款并标记候补差价已退还。
 #[async_trait]
 pub trait TransactionRepository: Repository<Transaction> {
     /// 根据 UUID 查找交易。
@@ -38,6 +83,47 @@ pub trait TransactionRepository: Repository<Transaction> {
     /// - 失败时返回 `RepositoryError`。
     async fn find_by_user_id(&self, user_id: UserId) -> Result<Vec<Transaction>, RepositoryError>;
 
+    /// 根据订单 UUID 查找支付该订单的交易。
+    ///
+    /// Arguments:
+    /// - `order_uuid`: 订单的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Option<Transaction>`，如果订单不存在或尚未关联交易则返回 `None`。
+    /// - 失败时返回 `RepositoryError`。
+    async fn find_by_order_uuid(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<Option<Transaction>, RepositoryError>;
+
+    /// 查找在`now`时已超过支付截止时间仍未支付的交易。
+    ///
+    /// Arguments:
+    /// - `now`: 当前时间。
+    ///
+    /// Returns:
+    /// - 成功时返回交易列表。
+    /// - 失败时返回 `RepositoryError`。
+    async fn find_expired_unpaid(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Transaction>, RepositoryError>;
+
+    /// 根据支付网关名称及外部支付 ID 查找交易。
+    ///
+    /// Arguments:
+    /// - `provider`: 支付网关名称。
+    /// - `external_payment_id`: 支付网关分配的支付 ID。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Option<Transaction>`，如果未找到则返回 `None`。
+    /// - 失败时返回 `RepositoryError`。
+    async fn find_by_external_payment_id(
+        &self,
+        provider: &str,
+        external_payment_id: Uuid,
+    ) -> Result<Option<Transaction>, RepositoryError>;
+
     /// 获取用户的余额。
     ///
     /// Arguments:
@@ -47,4 +133,120 @@ pub trait TransactionRepository: Repository<Transaction> {
     /// - 成功时返回用户的余额，可能为空。
     /// - 失败时返回 `RepositoryError`。
     async fn get_user_balance(&self, user_id: UserId) -> Result<Option<Decimal>, RepositoryError>;
+
+    /// 获取所有用户的余额。
+    ///
+    /// Returns:
+    /// - 成功时返回用户 ID 到余额的映射，没有已支付交易的用户不包含在内。
+    /// - 失败时返回 `RepositoryError`。
+    async fn get_all_user_balances(&self) -> Result<HashMap<UserId, Decimal>, RepositoryError>;
+
+    /// 分页查询用户账单中的交易，按完成时间先后排序，完成时间相同时按 ID 排序。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `filter`: 账单的查询条件。
+    /// - `after`: 从该位置之后开始查询，为空时从第一笔交易开始。
+    /// - `limit`: 最多返回的交易数量，为空时返回全部。
+    ///
+    /// Returns:
+    /// - 成功时返回交易列表。
+    /// - 失败时返回 `RepositoryError`。
+    async fn find_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+        after: Option<StatementCursor>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Transaction>, RepositoryError>;
+
+    /// 统计用户账单中满足查询条件的交易数量。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `filter`: 账单的查询条件。
+    ///
+    /// Returns:
+    /// - 成功时返回交易数量。
+    /// - 失败时返回 `RepositoryError`。
+    async fn count_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+    ) -> Result<u64, RepositoryError>;
+
+    /// 获取用户在`time`之前（不含）完成的全部交易后的余额。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `time`: 截止时间。
+    ///
+    /// Returns:
+    /// - 成功时返回余额，没有已完成交易时为 0。
+    /// - 失败时返回 `RepositoryError`。
+    async fn get_user_balance_before(
+        &self,
+        user_id: UserId,
+        time: DateTimeWithTimeZone,
+    ) -> Result<Decimal, RepositoryError>;
+
+    /// 在同一数据库事务中保存部分退款：插入退款交易及其退款明细，更新原交易中各订单的累计退款
+    /// 及优惠券状态，并删除退还数量对应的已占用房间。
+    ///
+    /// 订单的累计退款、状态在数据库中与加载`transaction`时不一致（如并发退款或取消），
+    /// 或待释放的房间已不存在时，整个事务回滚。
+    ///
+    /// Arguments:
+    /// - `transaction`: 已记录本次部分退款的原交易。
+    /// - `refund_transaction`: 部分退款生成的退款交易。
+    /// - `released_rooms`: 随部分退款释放的已占用房间。
+    ///
+    /// Returns:
+    /// - 成功时返回退款交易的 ID。
+    /// - 失败时返回 `RepositoryError`。
+    async fn save_partial_refund(
+        &self,
+        transaction: &mut Transaction,
+        refund_transaction: &mut Transaction,
+        released_rooms: &[OccupiedRoom],
+    ) -> Result<TransactionId, RepositoryError>;
+
+    /// 在同一数据库事务中保存改签调整交易的结算：将调整交易更新为已支付并记账，
+    /// 将新订单更新为已支付，同时将原交易中被改签的订单标记为已退款至调整交易。
+    ///
+    /// 结算期间锁定用户的全部交易行并重新读取余额，余额不足以补缴差价时不做任何修改；
+    /// 调整交易在数据库中已不是未支付状态、新订单已不是未支付状态，
+    /// 或原订单已不处于`Ongoing`状态、已被退款时，整个事务回滚。
+    ///
+    /// Arguments:
+    /// - `adjustment_transaction`: 已支付且新订单已进入`Paid`状态的调整交易。
+    /// - `replaced_transaction`: 已将原订单标记为退款至调整交易的原交易。
+    /// - `replaced_order_uuid`: 被改签的原订单的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回 `RebookSettlement`。
+    /// - 失败时返回 `RepositoryError`。
+    async fn save_rebook_settlement(
+        &self,
+        adjustment_transaction: &mut Transaction,
+        replaced_transaction: &mut Transaction,
+        replaced_order_uuid: Uuid,
+    ) -> Result<RebookSettlement, RepositoryError>;
+
+    /// 在同一数据库事务中保存候补兑现后的差价退款：插入退款交易，并将候补标记为差价已退还。
+    ///
+    /// 候补在数据库中已不是`Fulfilled`状态，或差价已退还时，整个事务回滚，避免重复退款。
+    ///
+    /// Arguments:
+    /// - `refund_transaction`: 差价退款生成的退款交易。
+    /// - `waitlist_uuid`: 已兑现候补的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回退款交易的 ID。
+    /// - 失败时返回 `RepositoryError`。
+    async fn save_fare_difference_refund(
+        &self,
+        refund_transaction: &mut Transaction,
+        waitlist_uuid: Uuid,
+    ) -> Result<TransactionId, RepositoryError>;
 }
@@ -0,0 +1,44 @@
+use crate::domain::model::train_schedule::TrainScheduleId;
+use crate::domain::model::user::UserId;
+use crate::domain::model::waitlist::{Waitlist, WaitlistStatus};
+use crate::domain::{Repository, RepositoryError};
+use async_trait::async_trait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait WaitlistRepository: Repository<Waitlist> {
+    async fn find_by_uuid(&self, uuid: Uuid) -> Result<Option<Waitlist>, RepositoryError>;
+
+    async fn find_by_train_order_uuid(
+        &self,
+        train_order_uuid: Uuid,
+    ) -> Result<Option<Waitlist>, RepositoryError>;
+
+    async fn find_by_user_id(&self, user_id: UserId) -> Result<Vec<Waitlist>, RepositoryError>;
+
+    /// 查询指定车次排班等待兑现的候补，按提交时间先后排序
+    async fn find_pending_by_train_schedule(
+        &self,
+        train_schedule_id: TrainScheduleId,
+    ) -> Result<Vec<Waitlist>, RepositoryError>;
+
+    /// 查询存在等待兑现候补的车次排班
+    async fn find_pending_train_schedule_ids(
+        &self,
+    ) -> Result<Vec<TrainScheduleId>, RepositoryError>;
+
+    /// 查询截止时间不晚于`now`且仍等待兑现的候补
+    async fn find_expired_pending(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Waitlist>, RepositoryError>;
+
+    async fn find_by_status(
+        &self,
+        status: WaitlistStatus,
+    ) -> Result<Vec<Waitlist>, RepositoryError>;
+
+    /// 查询已兑现但差价尚未退还的候补
+    async fn find_fare_difference_unrefunded(&self) -> Result<Vec<Waitlist>, RepositoryError>;
+}
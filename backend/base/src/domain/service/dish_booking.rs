@@ -1,5 +1,6 @@
 use crate::domain::RepositoryError;
 use crate::domain::model::order::{DishOrder, OrderStatus};
+use crate::domain::model::order_state_machine::OrderStateError;
 use crate::domain::model::transaction::TransactionStatus;
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
@@ -21,6 +22,16 @@ pub enum DishBookingServiceError {
     InvalidTransactionStatus(Uuid, TransactionStatus),
 }
 
+impl From<OrderStateError> for DishBookingServiceError {
+    fn from(value: OrderStateError) -> Self {
+        match value {
+            OrderStateError::IllegalTransition {
+                order_uuid, from, ..
+            } => DishBookingServiceError::InvalidOrderStatus(order_uuid, from),
+        }
+    }
+}
+
 impl From<RepositoryError> for DishBookingServiceError {
     fn from(value: RepositoryError) -> Self {
         DishBookingServiceError::InfrastructureError(ServiceError::RepositoryError(value))
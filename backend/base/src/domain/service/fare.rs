@@ -0,0 +1,145 @@
+//! 票价计算领域服务模块
+//!
+//! 按乘车区间的里程计算票价，查询报价与下单扣款均通过本服务计算，保证二者一致：
+//! - 票价 = 区间里程 × 座位类型每公里费率 × 列车类型系数
+//! - 不低于最低票价，并四舍五入到计价单位的整数倍（默认为整数，与查询结果中的整数票价一致）
+//! - 线路未录入里程（区间里程为0）时，退回按经停站数计价，即`SeatType::unit_price` × 区间数
+//! - 儿童、学生等乘客类别在上述票价基础上按比例优惠，见`FareService::apply_ticket_category`
+use crate::domain::model::order::TicketCategory;
+use crate::domain::model::route::Route;
+use crate::domain::model::station::StationId;
+use crate::domain::model::train::SeatType;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum FareServiceError {
+    #[error("station {0} is not in route")]
+    StationNotInRoute(StationId),
+    #[error("invalid station range: {0} -> {1}")]
+    InvalidStationRange(usize, usize),
+}
+
+/// 票价费率表
+#[derive(Debug, Clone, PartialEq)]
+pub struct FareRateTable {
+    /// 座位类型名称 -> 每公里费率(SC/km)
+    pub seat_type_rate: HashMap<String, Decimal>,
+    /// 未在`seat_type_rate`中配置的座位类型的每公里费率
+    pub default_seat_type_rate: Decimal,
+    /// 列车类型 -> 费率系数，未配置的列车类型系数为1
+    pub train_type_factor: HashMap<String, Decimal>,
+    /// 最低票价
+    pub min_fare: Decimal,
+    /// 计价单位，票价四舍五入到该单位的整数倍
+    pub rounding_unit: Decimal,
+    /// 乘客类别 -> 应付票价占成人票价的比例，未配置的乘客类别按成人票计价
+    pub ticket_category_rate: HashMap<TicketCategory, Decimal>,
+}
+
+impl Default for FareRateTable {
+    fn default() -> Self {
+        let seat_type_rate = [
+            ("商务座", Decimal::new(150, 2)),
+            ("一等", Decimal::new(80, 2)),
+            ("二等", Decimal::new(50, 2)),
+            ("软座", Decimal::new(35, 2)),
+            ("硬座", Decimal::new(20, 2)),
+        ]
+        .into_iter()
+        .map(|(name, rate)| (name.to_string(), rate))
+        .collect();
+
+        let train_type_factor = [
+            ("G", Decimal::new(120, 2)),
+            ("D", Decimal::ONE),
+            ("C", Decimal::ONE),
+            ("S", Decimal::new(90, 2)),
+            ("Z", Decimal::ONE),
+            ("T", Decimal::new(90, 2)),
+            ("K", Decimal::new(80, 2)),
+            ("Y", Decimal::new(110, 2)),
+            ("P", Decimal::new(70, 2)),
+        ]
+        .into_iter()
+        .map(|(train_type, factor)| (train_type.to_string(), factor))
+        .collect();
+
+        let ticket_category_rate = [
+            (TicketCategory::Child, Decimal::new(50, 2)),
+            (TicketCategory::Student, Decimal::new(75, 2)),
+            (TicketCategory::Senior, Decimal::new(80, 2)),
+            (TicketCategory::Disabled, Decimal::new(50, 2)),
+        ]
+        .into_iter()
+        .collect();
+
+        Self {
+            seat_type_rate,
+            default_seat_type_rate: Decimal::new(50, 2),
+            train_type_factor,
+            min_fare: Decimal::from(5),
+            rounding_unit: Decimal::ONE,
+            ticket_category_rate,
+        }
+    }
+}
+
+pub trait FareService: 'static + Send + Sync {
+    /// 计算乘坐`route`上第`from_index`个停靠站至第`to_index`个停靠站的票价
+    ///
+    /// # Arguments
+    /// * `train_type` - 列车类型，如"G"
+    /// * `seat_type` - 座位类型
+    /// * `route` - 车次线路
+    /// * `from_index`/`to_index` - 上下车站在`route.stops()`中的下标
+    ///
+    /// # Errors
+    /// * `InvalidStationRange` - `from_index >= to_index`或下标越界
+    fn calculate_fare(
+        &self,
+        train_type: &str,
+        seat_type: &SeatType,
+        route: &Route,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<Decimal, FareServiceError>;
+
+    /// 按乘客类别计算优惠后的票价，四舍五入到计价单位的整数倍
+    ///
+    /// # Arguments
+    /// * `fare` - 成人票价
+    /// * `ticket_category` - 乘客类别
+    fn apply_ticket_category(&self, fare: Decimal, ticket_category: TicketCategory) -> Decimal;
+
+    /// 按上下车站ID计算票价，见`calculate_fare`
+    ///
+    /// # Errors
+    /// * `StationNotInRoute` - 车站不在线路上
+    /// * `InvalidStationRange` - 上车站不在下车站之前
+    fn calculate_fare_by_station(
+        &self,
+        train_type: &str,
+        seat_type: &SeatType,
+        route: &Route,
+        from_station: StationId,
+        to_station: StationId,
+    ) -> Result<Decimal, FareServiceError> {
+        let position = |station_id: StationId| {
+            route
+                .stops()
+                .iter()
+                .position(|stop| stop.station_id() == station_id)
+                .ok_or(FareServiceError::StationNotInRoute(station_id))
+        };
+
+        self.calculate_fare(
+            train_type,
+            seat_type,
+            route,
+            position(from_station)?,
+            position(to_station)?,
+        )
+    }
+}
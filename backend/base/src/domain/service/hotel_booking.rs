@@ -1,6 +1,7 @@
 use crate::domain::RepositoryError;
 use crate::domain::model::hotel::{HotelDateRange, HotelId, HotelRoomStatus, HotelRoomTypeId};
 use crate::domain::model::order::{HotelOrder, OrderStatus};
+use crate::domain::model::order_state_machine::OrderStateError;
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -22,6 +23,16 @@ pub enum HotelBookingServiceError {
     InvalidHotelId(HotelId),
 }
 
+impl From<OrderStateError> for HotelBookingServiceError {
+    fn from(value: OrderStateError) -> Self {
+        match value {
+            OrderStateError::IllegalTransition {
+                order_uuid, from, ..
+            } => HotelBookingServiceError::InvalidOrderStatus(order_uuid, from),
+        }
+    }
+}
+
 impl From<RepositoryError> for HotelBookingServiceError {
     fn from(value: RepositoryError) -> Self {
         HotelBookingServiceError::InfrastructureError(ServiceError::RepositoryError(value))
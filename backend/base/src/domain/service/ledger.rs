@@ -0,0 +1,51 @@
+//! # 账本对账领域服务模块
+//!
+//! 该模块定义了复式记账账本与交易余额之间的对账接口。主要包含以下内容：
+//!
+//! - `LedgerServiceError`: 枚举类型，表示对账服务错误。
+//! - `WalletDiscrepancy`: 结构体，表示一个用户的对账差异。
+//! - `LedgerService`: 异步 trait，定义了对账操作。
+//!
+//! 用户钱包科目的余额应与`TransactionRepository::get_user_balance`（即`balance`视图）一致，
+//! 不一致说明存在未记账或重复记账的交易，需人工核查。
+use crate::domain::RepositoryError;
+use crate::domain::model::user::UserId;
+use crate::domain::service::ServiceError;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LedgerServiceError {
+    /// 底层基础设施错误（如数据库访问失败）
+    #[error("an infrastructure error occurred: {0}")]
+    InfrastructureError(ServiceError),
+}
+
+impl From<RepositoryError> for LedgerServiceError {
+    fn from(value: RepositoryError) -> Self {
+        LedgerServiceError::InfrastructureError(ServiceError::RepositoryError(value))
+    }
+}
+
+/// 一个用户的对账差异，缺失的余额视为 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WalletDiscrepancy {
+    pub user_id: UserId,
+    /// 用户钱包科目的余额
+    pub ledger_balance: Decimal,
+    /// 由交易计算的余额
+    pub transaction_balance: Decimal,
+}
+
+#[async_trait]
+pub trait LedgerService: 'static + Send + Sync {
+    /// 核对所有用户的钱包科目余额与交易余额。
+    ///
+    /// Returns:
+    /// - 成功时返回存在差异的用户，按用户 ID 排序；账实相符时为空。
+    async fn reconcile(&self) -> Result<Vec<WalletDiscrepancy>, LedgerServiceError>;
+
+    /// 定期对账，并记录存在差异的用户。
+    async fn reconciliation_daemon(&self);
+}
@@ -88,16 +88,21 @@
 //! - 大规模聚合根集合应考虑分片管理
 
 pub mod dish_booking;
+pub mod fare;
 pub mod geo;
 pub mod hotel_booking;
 pub mod hotel_query;
 pub mod hotel_rating;
+pub mod ledger;
 pub mod message;
 pub mod object_storage;
 pub mod order;
 pub mod order_status;
 pub mod password;
+pub mod payment_gateway;
+pub mod pricing;
 pub mod route;
+pub mod seat_assignment;
 pub mod session;
 pub mod station;
 pub mod takeaway_booking;
@@ -107,6 +112,7 @@ pub mod train_seat;
 pub mod train_type;
 pub mod transaction;
 pub mod user;
+pub mod waitlist;
 
 use crate::domain::{Aggregate, AggregateManager, MultiEntityDiff, RepositoryError};
 use std::collections::HashMap;
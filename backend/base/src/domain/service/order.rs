@@ -16,8 +16,11 @@ pub mod order_dto {
         pub status: String,
         pub create_time: String,
         pub pay_time: Option<String>,
+        /// 支付截止时间，不会超时的交易为`None`
+        pub payment_deadline: Option<String>,
         pub orders: Vec<OrderInfoDto>,
         pub amount: f64,
+        pub coupon_discount: f64,
     }
 
     #[derive(Serialize, Clone)]
@@ -67,6 +70,12 @@ pub mod order_dto {
 
         pub name: String,
         pub seat: Option<SeatLocationInfoDTO>,
+        /// 同行订单的座位分配方式，如"same_row"，单独订票时为`None`
+        pub group_seating: Option<String>,
+        /// 乘客类别，如"adult"、"child"
+        pub ticket_category: String,
+        /// 暂留座位的过期时间（RFC 3339），仅在支付前已暂留座位时有值
+        pub seat_hold_expire_time: Option<String>,
     }
 
     #[derive(Serialize, Clone)]
@@ -120,6 +129,54 @@ pub mod order_dto {
         pub can_cancel: bool,
         pub reason: Option<String>,
         pub order_type: String,
+        /// 当前取消订单可退还的金额（已扣除手续费），不可取消时为`None`
+        pub refundable_amount: Option<f64>,
+        /// 当前取消订单需收取的手续费，不可取消时为`None`
+        pub refund_fee: Option<f64>,
+        /// 订单已累计退还的金额，交易未支付时为`None`
+        pub refunded_amount: Option<f64>,
+        /// 订单剩余可退的实付金额，交易未支付时为`None`
+        pub remaining_amount: Option<f64>,
+    }
+
+    impl OrderInfoDto {
+        /// 人类可读的订单说明，用于账单等场景
+        ///
+        /// 例如：`火车票 G1 北京南-上海虹桥 张三`、`酒店 如家 大床房 2025-06-01至2025-06-03 ×1 张三`
+        pub fn description(&self) -> String {
+            match self {
+                OrderInfoDto::Train(dto) => format!(
+                    "火车票 {} {}-{} {}",
+                    dto.train_number, dto.departure_station, dto.arrival_station, dto.name
+                ),
+                OrderInfoDto::Hotel(dto) => format!(
+                    "酒店 {} {} {}至{} ×{} {}",
+                    dto.hotel_name,
+                    dto.room_type,
+                    dto.begin_date,
+                    dto.end_date,
+                    dto.base.amount,
+                    dto.name
+                ),
+                OrderInfoDto::Dish(dto) => format!(
+                    "火车餐 {} {} ×{} {}",
+                    dto.train_number, dto.dish_name, dto.base.amount, dto.name
+                ),
+                OrderInfoDto::Takeaway(dto) => format!(
+                    "外卖 {} {} {} ×{} {}",
+                    dto.station, dto.shop_name, dto.takeaway_name, dto.base.amount, dto.name
+                ),
+            }
+        }
+
+        pub fn base_mut(&mut self) -> &mut BaseOrderDto {
+            match self {
+                OrderInfoDto::Train(dto) => &mut dto.base,
+                OrderInfoDto::Hotel(dto) => &mut dto.base,
+                OrderInfoDto::Dish(dto) => &mut dto.base,
+                OrderInfoDto::Takeaway(dto) => &mut dto.base,
+            }
+        }
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::domain::model::order::{Order, OrderStatus, OrderType};
+use crate::domain::model::user::UserId;
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -23,6 +24,17 @@ pub trait OrderStatusManagerService: 'static + Send + Sync {
         new_status: OrderStatus,
     );
 
+    /// 通知改签：新订单保持`Unpaid`状态，消费者为其暂留座位并结算差价后释放被改签的原订单
+    async fn notify_rebook(
+        &self,
+        transaction_uuid: Uuid,
+        replaced_order: &dyn Order,
+        new_order: &dyn Order,
+    );
+
+    /// 通知用户订单已部分退款，订单状态不变
+    async fn notify_partial_refund(&self, user_id: UserId, order: &dyn Order);
+
     async fn order_status_daemon(&self);
 }
 
@@ -38,6 +50,9 @@ pub struct OrderStatusMessage {
     pub order_id: Uuid,
     pub order_type: OrderType,
     pub new_status: OrderStatus,
+    /// 改签时被替换的原订单，为空表示普通的状态变更
+    #[serde(default)]
+    pub replaced_order_id: Option<Uuid>,
 }
 
 #[async_trait]
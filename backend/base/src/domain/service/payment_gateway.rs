@@ -0,0 +1,158 @@
+//! 外部支付网关领域服务模块
+//!
+//! 充值等需要用户向平台实际付款的操作通过外部支付网关完成：
+//! - 平台调用`create_intent`创建支付意图，用户在支付网关完成付款
+//! - 支付网关通过回调（`/api/payment/callback/{provider}`）通知支付结果，
+//!   平台使用`verify_webhook`校验回调签名后才会完成对应的交易
+//! - `query_intent`用于主动查询支付状态，`refund`用于原路退款
+//!
+//! 每个支付网关实现以`provider`名称区分，交易上记录支付网关名称及其分配的外部支付 ID。
+use crate::domain::model::transaction::TransactionAmountAbs;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum PaymentGatewayError {
+    #[error("payment gateway error: {0}")]
+    GatewayError(anyhow::Error),
+    #[error("invalid webhook signature")]
+    InvalidSignature,
+    #[error("invalid webhook payload: {0}")]
+    InvalidPayload(String),
+    #[error("external payment not found: {0}")]
+    PaymentNotFound(Uuid),
+    #[error("invalid status {status} for op {op} for external payment {external_payment_id}")]
+    InvalidPaymentStatus {
+        op: &'static str,
+        status: PaymentIntentStatus,
+        external_payment_id: Uuid,
+    },
+}
+
+/// 枚举类型，表示外部支付的状态。
+///
+/// - `Pending`: 等待用户付款。
+/// - `Succeeded`: 付款成功。
+/// - `Failed`: 付款失败或被用户取消。
+/// - `Refunded`: 已原路退款。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaymentIntentStatus {
+    Pending,
+    Succeeded,
+    Failed,
+    Refunded,
+}
+
+impl From<PaymentIntentStatus> for &'static str {
+    fn from(value: PaymentIntentStatus) -> Self {
+        match value {
+            PaymentIntentStatus::Pending => "pending",
+            PaymentIntentStatus::Succeeded => "succeeded",
+            PaymentIntentStatus::Failed => "failed",
+            PaymentIntentStatus::Refunded => "refunded",
+        }
+    }
+}
+
+impl TryFrom<&str> for PaymentIntentStatus {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(PaymentIntentStatus::Pending),
+            "succeeded" => Ok(PaymentIntentStatus::Succeeded),
+            "failed" => Ok(PaymentIntentStatus::Failed),
+            "refunded" => Ok(PaymentIntentStatus::Refunded),
+            _ => Err(format!("Invalid payment intent status: {}", value)),
+        }
+    }
+}
+
+impl Display for PaymentIntentStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <PaymentIntentStatus as Into<&str>>::into(*self))
+    }
+}
+
+/// 支付网关创建的支付意图
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PaymentIntent {
+    /// 支付网关分配的支付 ID
+    pub external_payment_id: Uuid,
+    /// 应付金额
+    pub amount: Decimal,
+    pub status: PaymentIntentStatus,
+    /// 用户完成付款的地址，无需跳转的支付网关为`None`
+    pub payment_url: Option<String>,
+}
+
+/// 经过签名校验的支付结果回调
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PaymentCallback {
+    pub external_payment_id: Uuid,
+    /// 实际支付金额
+    pub amount: Decimal,
+    pub status: PaymentIntentStatus,
+}
+
+/// 充值方式。
+///
+/// - `Gateway`: 通过支付网关充值，收到成功回调后入账。
+/// - `Direct`: 未配置支付网关，充值直接入账。
+/// - `Disabled`: 已显式关闭充值。
+pub enum RechargeChannel<G: PaymentGateway> {
+    Gateway(Arc<G>),
+    Direct,
+    Disabled,
+}
+
+#[async_trait]
+pub trait PaymentGateway: 'static + Send + Sync {
+    /// 支付网关名称，与回调地址中的`provider`对应
+    fn provider(&self) -> &'static str;
+
+    /// 创建支付意图。
+    ///
+    /// Arguments:
+    /// - `amount`: 应付金额的绝对值。
+    /// - `description`: 展示给用户的支付说明。
+    ///
+    /// Returns:
+    /// - 成功时返回状态为`Pending`的支付意图。
+    async fn create_intent(
+        &self,
+        amount: TransactionAmountAbs,
+        description: &str,
+    ) -> Result<PaymentIntent, PaymentGatewayError>;
+
+    /// 查询支付意图的当前状态。
+    async fn query_intent(
+        &self,
+        external_payment_id: Uuid,
+    ) -> Result<PaymentIntent, PaymentGatewayError>;
+
+    /// 原路退款，仅能对付款成功的支付全额退款。
+    ///
+    /// Returns:
+    /// - 成功时返回状态为`Refunded`的支付意图。
+    async fn refund(&self, external_payment_id: Uuid)
+    -> Result<PaymentIntent, PaymentGatewayError>;
+
+    /// 校验回调签名并解析回调内容。
+    ///
+    /// Arguments:
+    /// - `payload`: 原始回调请求体，签名基于原始字节计算。
+    /// - `signature`: 回调请求携带的签名。
+    ///
+    /// Returns:
+    /// - 签名不匹配时返回`InvalidSignature`。
+    fn verify_webhook(
+        &self,
+        payload: &[u8],
+        signature: &str,
+    ) -> Result<PaymentCallback, PaymentGatewayError>;
+}
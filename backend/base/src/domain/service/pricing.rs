@@ -0,0 +1,114 @@
+//! 动态定价领域服务模块
+//!
+//! 在`FareService`计算出的基础票价之上，按上座率与距发车天数对票价进行上浮或折扣：
+//! - 上座率不低于`high_load_threshold`时，随上座率线性上浮，满座时上浮`max_load_markup`
+//! - 上座率不高于`low_load_threshold`时，随上座率线性折扣，空座时折扣`max_load_discount`
+//! - 距发车不少于`early_bird_days`天时折扣`early_bird_discount`，
+//!   不超过`last_minute_days`天时上浮`last_minute_markup`
+//! - 调整系数限制在`[min_multiplier, max_multiplier]`内，结果四舍五入到计价单位的整数倍
+//!
+//! 下单时的报价写入`TrainOrder`，此后支付金额不再随上座率变化。
+use crate::Verified;
+use crate::domain::model::route::RouteId;
+use crate::domain::model::train::SeatType;
+use crate::domain::model::train_schedule::{StationRange, TrainScheduleId};
+use crate::domain::service::train_seat::TrainSeatServiceError;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PricingServiceError {
+    #[error("failed to get seat availability: {0}")]
+    SeatAvailabilityError(#[from] TrainSeatServiceError),
+}
+
+/// 动态定价策略
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicPricingPolicy {
+    /// 是否启用动态定价，未启用时票价即为基础票价
+    pub enabled: bool,
+    /// 开始上浮的上座率
+    pub high_load_threshold: Decimal,
+    /// 满座时的最大上浮比例
+    pub max_load_markup: Decimal,
+    /// 开始折扣的上座率
+    pub low_load_threshold: Decimal,
+    /// 空座时的最大折扣比例
+    pub max_load_discount: Decimal,
+    /// 距发车不少于该天数时给予提前购票折扣
+    pub early_bird_days: i64,
+    /// 提前购票折扣比例
+    pub early_bird_discount: Decimal,
+    /// 距发车不超过该天数时临近发车上浮
+    pub last_minute_days: i64,
+    /// 临近发车上浮比例
+    pub last_minute_markup: Decimal,
+    /// 调整系数下限
+    pub min_multiplier: Decimal,
+    /// 调整系数上限
+    pub max_multiplier: Decimal,
+    /// 计价单位，调整后的票价四舍五入到该单位的整数倍
+    pub rounding_unit: Decimal,
+}
+
+impl Default for DynamicPricingPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            high_load_threshold: Decimal::new(70, 2),
+            max_load_markup: Decimal::new(20, 2),
+            low_load_threshold: Decimal::new(30, 2),
+            max_load_discount: Decimal::new(10, 2),
+            early_bird_days: 7,
+            early_bird_discount: Decimal::new(5, 2),
+            last_minute_days: 1,
+            last_minute_markup: Decimal::new(5, 2),
+            min_multiplier: Decimal::new(80, 2),
+            max_multiplier: Decimal::new(130, 2),
+            rounding_unit: Decimal::ONE,
+        }
+    }
+}
+
+/// 票价报价查询中的一项
+#[derive(Debug, Clone)]
+pub struct PriceQuoteQuery {
+    pub train_schedule_id: TrainScheduleId,
+    pub route_id: RouteId,
+    /// 车次日期（始发站发车日期）
+    pub date: NaiveDate,
+    pub seat_type: SeatType,
+    pub station_range: StationRange<Verified>,
+    /// 由`FareService`计算的基础票价
+    pub base_fare: Decimal,
+}
+
+#[async_trait]
+pub trait PricingService: 'static + Send + Sync {
+    /// 按上座率与距发车天数调整基础票价，未启用动态定价时原样返回
+    ///
+    /// # Arguments
+    /// * `base_fare` - 基础票价
+    /// * `occupied_seats_count` - 已占用座位数
+    /// * `total_seats_count` - 座位总数
+    /// * `days_to_departure` - 距车次日期的天数，当天为0
+    fn adjust_fare(
+        &self,
+        base_fare: Decimal,
+        occupied_seats_count: u32,
+        total_seats_count: u32,
+        days_to_departure: i64,
+    ) -> Decimal;
+
+    /// 批量计算当前报价，返回值与`queries`一一对应
+    ///
+    /// # Note
+    /// - 上座率按查询区间内的余票计算，距发车天数按服务所在时区的当前日期计算
+    /// - 未启用动态定价时直接返回基础票价，不查询余票
+    async fn quote_fares(
+        &self,
+        queries: &[PriceQuoteQuery],
+    ) -> Result<Vec<Decimal>, PricingServiceError>;
+}
@@ -0,0 +1,47 @@
+//! 座位分配策略领域服务模块
+//!
+//! 定义从候选空闲座位中挑选具体座位的策略接口。
+//! 不同策略对区间碎片化的影响不同，例如总是选择第一个空闲座位会使短途订单分散在各个座位上，
+//! 导致全程座位过早售罄。
+use crate::domain::model::train_schedule::{SeatId, SeatLocationInfo};
+
+/// 候选座位
+///
+/// `occupied_bitmap`为座位的区间占用位图（下标为停靠顺序，`true`表示该站到下一站的区间已被占用），
+/// 为`None`表示该座位全程空闲。
+#[derive(Debug, Clone, Copy)]
+pub struct SeatCandidate<'a> {
+    pub seat_id: SeatId,
+    pub location_info: SeatLocationInfo,
+    pub occupied_bitmap: Option<&'a [bool]>,
+}
+
+/// 座位分配请求
+///
+/// 停靠顺序区间均为左闭右开，`[begin_order, end_order)`为乘客乘坐的区间，
+/// `[first_order, last_order)`为整条路线的区间。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeatAssignmentRequest {
+    pub begin_order: u32,
+    pub end_order: u32,
+    pub first_order: u32,
+    pub last_order: u32,
+    /// 乘客偏好的座位位置，如'A'
+    pub preferred_location: Option<char>,
+}
+
+/// 座位分配策略接口
+///
+/// 调用方保证`candidates`中的座位在请求区间内均空闲。
+pub trait SeatAssignmentStrategy: 'static + Send + Sync {
+    /// 从候选座位中选择一个座位
+    ///
+    /// # Returns
+    /// * `Some(index)` - 选中座位在`candidates`中的下标
+    /// * `None` - 没有可用座位
+    fn select_seat(
+        &self,
+        candidates: &[SeatCandidate<'_>],
+        request: &SeatAssignmentRequest,
+    ) -> Option<usize>;
+}
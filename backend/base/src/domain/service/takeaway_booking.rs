@@ -1,5 +1,6 @@
 use crate::domain::RepositoryError;
 use crate::domain::model::order::{OrderStatus, TakeawayOrder};
+use crate::domain::model::order_state_machine::OrderStateError;
 use crate::domain::model::transaction::TransactionStatus;
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
@@ -21,6 +22,16 @@ pub enum TakeawayBookingServiceError {
     InvalidTransactionStatus(Uuid, TransactionStatus),
 }
 
+impl From<OrderStateError> for TakeawayBookingServiceError {
+    fn from(value: OrderStateError) -> Self {
+        match value {
+            OrderStateError::IllegalTransition {
+                order_uuid, from, ..
+            } => TakeawayBookingServiceError::InvalidOrderStatus(order_uuid, from),
+        }
+    }
+}
+
 impl From<RepositoryError> for TakeawayBookingServiceError {
     fn from(value: RepositoryError) -> Self {
         TakeawayBookingServiceError::InfrastructureError(ServiceError::RepositoryError(value))
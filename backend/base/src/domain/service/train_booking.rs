@@ -1,7 +1,11 @@
 use crate::domain::model::order::{OrderStatus, TrainOrder};
+use crate::domain::model::order_state_machine::OrderStateError;
+use crate::domain::model::train_schedule::TrainScheduleId;
 use crate::domain::model::transaction::TransactionStatus;
+use crate::domain::model::waitlist::Waitlist;
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
+use sea_orm::prelude::DateTimeWithTimeZone;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -20,17 +24,85 @@ pub enum TrainBookingServiceError {
     InvalidTransaction(Uuid),
     #[error("invalid transaction status for transaction uuid: {0}, status: {1}")]
     InvalidTransactionStatus(Uuid, TransactionStatus),
+    /// 原子订单组占座失败并已回滚，回滚释放的座位已分配给候补，调用方需结算这些候补
+    #[error("{source}")]
+    RolledBackWithFulfilledWaitlists {
+        source: Box<TrainBookingServiceError>,
+        fulfilled_waitlists: Vec<Waitlist>,
+    },
+}
+
+impl From<OrderStateError> for TrainBookingServiceError {
+    fn from(value: OrderStateError) -> Self {
+        match value {
+            OrderStateError::IllegalTransition {
+                order_uuid, from, ..
+            } => TrainBookingServiceError::InvalidOrderStatus(order_uuid, from),
+        }
+    }
 }
 
 #[async_trait]
 pub trait TrainBookingService: 'static + Send + Sync {
     async fn booking_ticket(&self, order_uuid: Uuid) -> Result<(), TrainBookingServiceError>;
-    async fn cancel_ticket(&self, order_uuid: Uuid) -> Result<(), TrainBookingServiceError>;
 
-    // 返回要退款的订单
+    /// 取消订单并释放座位，释放座位后按提交顺序为可接受该座位类型的候补分配座位
+    ///
+    /// Returns:
+    /// - 因本次释放座位而兑现的候补，调用方需结算差价并通知用户。
+    async fn cancel_ticket(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError>;
+
+    /// 改签：为未支付的新订单暂留座位，原订单需处于`Ongoing`状态
+    ///
+    /// 原订单保持不变，调用方结算差价后通过`booking_ticket`确认新订单暂留的座位，
+    /// 再通过`cancel_ticket`释放原订单的座位（随即为候补分配座位）；
+    /// 结算失败时通过`cancel_ticket`取消新订单并释放暂留的座位，原订单不受影响。
+    async fn rebook_ticket(
+        &self,
+        replaced_order_uuid: Uuid,
+        new_order_uuid: Uuid,
+    ) -> Result<(), TrainBookingServiceError>;
+
+    /// 为一组已支付的订单分配座位，返回要退款的订单
+    ///
+    /// 原子操作中任一订单占座失败时取消同组已占座的订单，释放座位后按提交顺序为候补分配座位；
+    /// 有候补兑现时返回`TrainBookingServiceError::RolledBackWithFulfilledWaitlists`。
     async fn booking_group(
         &self,
         order_uuid_list: Vec<Uuid>,
         atomic: bool,
     ) -> Result<Vec<TrainOrder>, TrainBookingServiceError>;
+
+    /// 为未支付的订单暂留座位，暂留至`hold_expire_time`
+    ///
+    /// 订单保持`Unpaid`状态，但已分配座位；支付后`booking_ticket`/`booking_group`直接确认暂留的座位。
+    /// `order_packs`中每项为一组订单及其是否为原子操作，分组规则与`booking_group`相同：
+    /// - 原子的订单组暂留失败时释放已暂留的全部座位并返回错误；
+    /// - 非原子的订单组中仅暂留失败的订单不暂留座位，支付后按常规流程分配座位，分配失败时该订单单独失败并退款。
+    ///
+    /// Returns:
+    /// - 暂留失败的非原子订单
+    async fn hold_group(
+        &self,
+        order_packs: Vec<(Vec<Uuid>, bool)>,
+        hold_expire_time: DateTimeWithTimeZone,
+    ) -> Result<Vec<Uuid>, TrainBookingServiceError>;
+
+    /// 定期释放已过期且订单已取消的暂留座位
+    async fn seat_hold_daemon(&self);
+
+    /// 按提交顺序为车次排班中等待兑现的候补分配座位，返回已兑现的候补
+    async fn fulfil_waitlist(
+        &self,
+        train_schedule_id: TrainScheduleId,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError>;
+
+    /// 将截止时间不晚于`now`且仍未兑现的候补标记为等待退款，返回这些候补
+    async fn expire_waitlist(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError>;
 }
@@ -5,6 +5,7 @@ use crate::domain::model::train_schedule::{TrainSchedule, TrainScheduleId};
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
 use chrono::NaiveDate;
+use rust_decimal::Decimal;
 use sea_orm::prelude::DateTimeWithTimeZone;
 use thiserror::Error;
 
@@ -21,6 +22,85 @@ pub enum TrainScheduleServiceError {
     InvalidTrainNumber(String),
 }
 
+/// 换乘方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferType {
+    /// 同站换乘
+    SameStation,
+    /// 同城换站换乘
+    SameCity,
+}
+
+impl From<TransferType> for &'static str {
+    fn from(value: TransferType) -> Self {
+        match value {
+            TransferType::SameStation => "same_station",
+            TransferType::SameCity => "same_city",
+        }
+    }
+}
+
+impl std::fmt::Display for TransferType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", <&'static str>::from(*self))
+    }
+}
+
+/// 换乘点
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TransferPoint {
+    /// 前一程的到达站
+    pub arrival_station: StationId,
+    /// 后一程的出发站
+    pub departure_station: StationId,
+    /// 两站之间的换乘时间，单位：秒，同站换乘为 0
+    pub transfer_time: u32,
+}
+
+impl TransferPoint {
+    pub fn transfer_type(&self) -> TransferType {
+        if self.arrival_station == self.departure_station {
+            TransferType::SameStation
+        } else {
+            TransferType::SameCity
+        }
+    }
+}
+
+/// 行程中的一段乘车
+///
+/// 时间均为相对查询日期零点的秒数（允许 > 86400）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JourneyLeg {
+    pub train_schedule_id: TrainScheduleId,
+    pub departure_station: StationId,
+    pub departure_time: u32,
+    pub arrival_station: StationId,
+    pub arrival_time: u32,
+}
+
+/// 由若干段乘车组成的行程
+#[derive(Debug, Clone, PartialEq)]
+pub struct Journey {
+    pub legs: Vec<JourneyLeg>,
+    /// 各次换乘的换乘点，长度为`legs.len() - 1`
+    pub transfers: Vec<TransferPoint>,
+    /// 各段最低席别基础票价（由`FareService`计算）之和，不含动态调价
+    pub price: Decimal,
+}
+
+impl Journey {
+    /// 换乘次数
+    pub fn transfer_count(&self) -> u32 {
+        self.legs.len().saturating_sub(1) as u32
+    }
+
+    /// 到达终点的时间
+    pub fn arrival_time(&self) -> u32 {
+        self.legs.last().map(|leg| leg.arrival_time).unwrap_or(0)
+    }
+}
+
 #[async_trait]
 pub trait TrainScheduleService: 'static + Send + Sync {
     async fn add_schedule(
@@ -48,6 +128,14 @@ pub trait TrainScheduleService: 'static + Send + Sync {
 
     async fn auto_plan_schedule_daemon(&self, days: i32);
 
+    /// 使内存时刻表缓存失效
+    ///
+    /// `date`为`None`时使全部日期的时刻表及同城换站索引失效，下次查询时重新构建。
+    fn invalidate_timetable(&self, date: Option<NaiveDate>);
+
+    /// 线路、车站或列车数据变更后，立即重建当前已缓存日期的时刻表
+    async fn rebuild_timetable(&self) -> Result<(), TrainScheduleServiceError>;
+
     // async fn find_schedules(
     //     &self,
     //     date: NaiveDate,
@@ -65,7 +153,27 @@ pub trait TrainScheduleService: 'static + Send + Sync {
         &self,
         date: chrono::NaiveDate,
         pairs: &[(StationId, StationId)],
-    ) -> Result<Vec<(Vec<TrainScheduleId>, StationId, StationId, Option<StationId>)>, TrainScheduleServiceError>;
+    ) -> Result<
+        Vec<(
+            Vec<TrainScheduleId>,
+            StationId,
+            StationId,
+            Option<TransferPoint>,
+        )>,
+        TrainScheduleServiceError,
+    >;
+
+    /// 多次换乘行程规划
+    ///
+    /// 返回从`origins`中任一车站出发、到达`destinations`中任一车站、换乘不超过`max_transfers`次的
+    /// 所有Pareto最优行程（到达时间、换乘次数、票价三者不可同时改进），按到达时间升序排列。
+    async fn plan_journeys(
+        &self,
+        date: NaiveDate,
+        origins: &[StationId],
+        destinations: &[StationId],
+        max_transfers: u32,
+    ) -> Result<Vec<Journey>, TrainScheduleServiceError>;
 
     async fn get_station_arrival_time(
         &self,
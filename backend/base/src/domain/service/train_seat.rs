@@ -1,11 +1,13 @@
 use crate::Verified;
 use crate::domain::model::personal_info::PersonalInfoId;
+use crate::domain::model::route::RouteId;
 use crate::domain::model::train::SeatType;
 use crate::domain::model::train_schedule::{
-    Seat, SeatAvailabilityId, SeatLocationInfo, StationRange, TrainSchedule,
+    Seat, SeatAvailabilityId, SeatLocationInfo, StationRange, TrainSchedule, TrainScheduleId,
 };
 use crate::domain::service::ServiceError;
 use async_trait::async_trait;
+use chrono::NaiveDate;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -21,17 +23,37 @@ pub enum TrainSeatServiceError {
     InvalidSeatAvailability(SeatAvailabilityId),
 }
 
+/// 可用座位数批量查询中的一项
+#[derive(Debug, Clone)]
+pub struct SeatCountQuery {
+    pub train_schedule_id: TrainScheduleId,
+    pub route_id: RouteId,
+    pub seat_type: SeatType,
+    pub station_range: StationRange<Verified>,
+}
+
 #[async_trait]
 pub trait TrainSeatService: 'static + Send + Sync {
     /// 获取指定区间和座位类型的可用座位数
     ///
     /// # Note
     /// - 处理了部分占用问题
+    /// - 优先读取余票缓存，未命中时从数据库加载并写入缓存
     async fn available_seats_count(
         &self,
         seat_availability_id: SeatAvailabilityId,
     ) -> Result<u32, TrainSeatServiceError>;
 
+    /// 批量获取多个车次指定区间和座位类型的可用座位数
+    ///
+    /// # Note
+    /// - 与`available_seats_count`计算方式相同
+    /// - 未命中余票缓存的车次的占用信息通过一次查询获取，返回值与`queries`一一对应
+    async fn available_seats_count_batch(
+        &self,
+        queries: &[SeatCountQuery],
+    ) -> Result<Vec<u32>, TrainSeatServiceError>;
+
     /// 添加座位占用记录
     ///
     /// # Note
@@ -53,4 +75,14 @@ pub trait TrainSeatService: 'static + Send + Sync {
         seat_availability_id: SeatAvailabilityId,
         seat: Seat,
     ) -> Result<(), TrainSeatServiceError>;
+
+    /// 从数据库重建`[begin_date, begin_date + days)`内各车次的余票缓存
+    ///
+    /// # Note
+    /// - 启动时调用；此范围外的车次在首次查询时按需加载
+    async fn rebuild_seat_availability_cache(
+        &self,
+        begin_date: NaiveDate,
+        days: i32,
+    ) -> Result<(), TrainSeatServiceError>;
 }
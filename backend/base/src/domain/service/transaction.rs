@@ -5,6 +5,7 @@
 //! - `TransactionServiceError`: 枚举类型，表示交易领域服务错误。
 //! - `TransactionService`: 异步 trait，定义了交易领域的操作。
 use crate::domain::RepositoryError;
+use crate::domain::model::coupon::CouponError;
 use crate::domain::model::order::{Order, OrderStatus};
 use crate::domain::model::transaction::{
     RefundError, Transaction, TransactionAmountAbs, TransactionStatus,
@@ -12,6 +13,7 @@ use crate::domain::model::transaction::{
 use crate::domain::model::user::UserId;
 use crate::domain::service::ServiceError;
 use crate::domain::service::order::order_dto::TransactionDataDto;
+use crate::domain::service::payment_gateway::{PaymentGatewayError, PaymentIntent};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use thiserror::Error;
@@ -55,8 +57,28 @@ pub enum TransactionServiceError {
         balance: Decimal,
         amount: TransactionAmountAbs,
     },
+    #[error("transaction {0} payment expired")]
+    PaymentExpired(Uuid),
     #[error(transparent)]
     RefundError(#[from] RefundError),
+    #[error(transparent)]
+    CouponError(#[from] CouponError),
+    #[error("payment gateway not configured")]
+    PaymentGatewayNotConfigured,
+    #[error("recharge is disabled")]
+    RechargeDisabled,
+    #[error("unknown payment provider: {0}")]
+    UnknownPaymentProvider(String),
+    #[error(transparent)]
+    PaymentGatewayError(#[from] PaymentGatewayError),
+    #[error(
+        "payment amount mismatch for transaction {transaction_id}: expected {expected} but got {actual}"
+    )]
+    PaymentAmountMismatch {
+        transaction_id: Uuid,
+        expected: Decimal,
+        actual: Decimal,
+    },
 }
 
 impl From<RepositoryError> for TransactionServiceError {
@@ -68,27 +90,60 @@ impl From<RepositoryError> for TransactionServiceError {
 /// 异步 trait，定义了交易领域的操作。
 ///
 /// 包含以下方法：
-/// - `recharge`: 为用户充值。
+/// - `recharge`: 为用户充值，配置了支付网关时通过支付网关发起充值。
+/// - `handle_payment_callback`: 处理支付网关的支付结果回调。
 /// - `get_balance`: 获取用户的余额。
 /// - `new_transaction`: 创建新的交易。
 /// - `pay_transaction`: 支付交易。
 /// - `refund_transaction`: 退款交易。
+/// - `refund_transaction_with_fee`: 用户取消订单时退款交易，扣除手续费。
+/// - `partial_refund`: 按数量部分退款订单并释放退还的房间，扣除手续费。
+/// - `adjust_order_refund`: 运营人员按金额部分退款订单，扣除手续费。
+/// - `new_rebook_transaction`: 创建改签调整交易。
+/// - `settle_rebook_transaction`: 结算改签调整交易。
+/// - `discard_transaction`: 丢弃未支付的交易。
+/// - `refund_fare_difference`: 退还候补兑现后的差价。
 #[async_trait]
 pub trait TransactionService: 'static + Send + Sync {
     /// 为用户充值。
     ///
+    /// 配置了支付网关时创建未支付的充值交易，收到成功回调后余额才会增加；
+    /// 未配置支付网关时充值直接入账。
+    ///
     /// Arguments:
     /// - `user_id`: 用户的唯一标识符。
     /// - `amount`: 充值金额的绝对值。
     ///
     /// Returns:
-    /// - 成功时返回充值交易的 UUID。
+    /// - 成功时返回充值交易及支付网关创建的支付意图，直接入账时支付意图为`None`。
+    /// - 已关闭充值时返回`RechargeDisabled`。
     /// - 失败时返回 `TransactionServiceError`。
     async fn recharge(
         &self,
         user_id: UserId,
         amount: TransactionAmountAbs,
-    ) -> Result<Uuid, TransactionServiceError>;
+    ) -> Result<(Transaction, Option<PaymentIntent>), TransactionServiceError>;
+
+    /// 处理支付网关的支付结果回调，校验签名后完成或关闭对应的充值交易。
+    ///
+    /// 重复的回调不会重复入账。
+    ///
+    /// Arguments:
+    /// - `provider`: 回调地址中的支付网关名称。
+    /// - `payload`: 原始回调请求体。
+    /// - `signature`: 回调请求携带的签名。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Ok(())`。
+    /// - 支付网关不存在时返回`UnknownPaymentProvider`。
+    /// - 签名校验失败时返回`PaymentGatewayError`。
+    /// - 支付金额与交易金额不符时返回`PaymentAmountMismatch`。
+    async fn handle_payment_callback(
+        &self,
+        provider: &str,
+        payload: &[u8],
+        signature: &str,
+    ) -> Result<(), TransactionServiceError>;
 
     /// 获取用户的余额。
     ///
@@ -105,28 +160,31 @@ pub trait TransactionService: 'static + Send + Sync {
     /// Arguments:
     /// - `user_id`: 用户的唯一标识符。
     /// - `orders`: 交易包含的订单列表。
+    /// - `coupon_code`: 要使用的优惠码，可能为空。
     ///
     /// Returns:
-    /// - 成功时返回新创建交易的 UUID。
-    /// - 失败时返回 `TransactionServiceError`。c
+    /// - 成功时返回新创建的交易，交易金额已扣除优惠金额。
+    /// - 失败时返回 `TransactionServiceError`。
     async fn new_transaction(
         &self,
         user_id: UserId,
         orders: Vec<Box<dyn Order>>,
         atomic: bool,
-    ) -> Result<Uuid, TransactionServiceError>;
+        coupon_code: Option<String>,
+    ) -> Result<Transaction, TransactionServiceError>;
 
-    /// 支付交易。
+    /// 支付交易，交易使用了优惠券时同时核销优惠券。
     ///
     /// Arguments:
     /// - `transaction_id`: 交易的 UUID。
     ///
     /// Returns:
     /// - 成功时返回 `Ok(())`。
+    /// - 已超过支付截止时间时返回`PaymentExpired`。
     /// - 失败时返回 `TransactionServiceError`。
     async fn pay_transaction(&self, transaction_id: Uuid) -> Result<(), TransactionServiceError>;
 
-    /// 退款交易。
+    /// 退款交易，全额退款时返还优惠券，部分退款时优惠券作废。
     ///
     /// Arguments:
     /// - `transaction_id`: 交易的 UUID。
@@ -141,6 +199,133 @@ pub trait TransactionService: 'static + Send + Sync {
         to_refund_orders: &[Box<dyn Order>],
     ) -> Result<Uuid, TransactionServiceError>;
 
+    /// 用户主动取消订单时退款，按订单类型的退款策略扣除手续费，手续费记录在退款交易上。
+    ///
+    /// 因订票失败等系统原因产生的退款应使用`refund_transaction`，不收取手续费。
+    ///
+    /// Arguments:
+    /// - `transaction_id`: 交易的 UUID。
+    /// - `to_refund_orders`: 要退款的订单列表。
+    ///
+    /// Returns:
+    /// - 成功时返回新的退款交易的 UUID。
+    /// - 失败时返回 `TransactionServiceError`。
+    async fn refund_transaction_with_fee(
+        &self,
+        transaction_id: Uuid,
+        to_refund_orders: &[Box<dyn Order>],
+    ) -> Result<Uuid, TransactionServiceError>;
+
+    /// 按数量部分退款交易中的订单，按订单类型的退款策略扣除手续费，供用户退掉订单的一部分。
+    ///
+    /// 退款交易、订单的累计退款及释放退还数量对应的房间在同一数据库事务中保存，完成后通知用户。
+    /// 订单保持`Ongoing`状态，剩余数量随之减少；退还订单的全部剩余数量应通过取消订单完成。
+    ///
+    /// Arguments:
+    /// - `transaction_id`: 交易的 UUID。
+    /// - `order_uuid`: 要部分退款的订单的 UUID。
+    /// - `quantity`: 退还的数量。
+    ///
+    /// Returns:
+    /// - 成功时返回新的退款交易的 UUID。
+    /// - 失败时返回 `TransactionServiceError`。
+    async fn partial_refund(
+        &self,
+        transaction_id: Uuid,
+        order_uuid: Uuid,
+        quantity: Decimal,
+    ) -> Result<Uuid, TransactionServiceError>;
+
+    /// 按金额部分退款交易中的订单，不释放座位或房间，不改变订单状态与数量。
+    ///
+    /// 仅供运营人员处理售后（如酒店提前退房时退还未入住的房费）使用，不对用户开放。
+    ///
+    /// Arguments:
+    /// - `transaction_id`: 交易的 UUID。
+    /// - `order_uuid`: 要部分退款的订单的 UUID。
+    /// - `amount`: 退还的实付金额。
+    ///
+    /// Returns:
+    /// - 成功时返回新的退款交易的 UUID。
+    /// - 失败时返回 `TransactionServiceError`。
+    async fn adjust_order_refund(
+        &self,
+        transaction_id: Uuid,
+        order_uuid: Uuid,
+        amount: Decimal,
+    ) -> Result<Uuid, TransactionServiceError>;
+
+    /// 创建改签调整交易，交易在改签完成前保持未支付状态。
+    ///
+    /// 交易包含状态为`Unpaid`的新订单，保存后通过订单状态消息通知消费者先为新订单暂留座位，结算差价后再释放原订单。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `replaced_order`: 被改签的原订单。
+    /// - `new_order`: 改签后的新订单。
+    ///
+    /// Returns:
+    /// - 成功时返回新创建的调整交易。
+    /// - 需补缴差价且余额不足时返回`InsufficientFunds`。
+    async fn new_rebook_transaction(
+        &self,
+        user_id: UserId,
+        replaced_order: &dyn Order,
+        new_order: Box<dyn Order>,
+    ) -> Result<Transaction, TransactionServiceError>;
+
+    /// 改签占座成功后结算调整交易：收取或退还差价，将新订单更新为`Paid`状态，并将原订单标记为已退款至调整交易。
+    ///
+    /// Arguments:
+    /// - `transaction_id`: 调整交易的 UUID。
+    /// - `replaced_order_id`: 被改签的原订单的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Ok(())`。
+    /// - 结算时余额不足以补缴差价返回`InsufficientFunds`，交易与订单均保持不变。
+    /// - 失败时返回 `TransactionServiceError`。
+    async fn settle_rebook_transaction(
+        &self,
+        transaction_id: Uuid,
+        replaced_order_id: Uuid,
+    ) -> Result<(), TransactionServiceError>;
+
+    /// 丢弃未支付的交易及其订单。
+    ///
+    /// 用于改签占座失败后丢弃调整交易（原订单不受影响），以及下单时暂留座位失败后丢弃新交易。
+    ///
+    /// Arguments:
+    /// - `transaction_id`: 交易的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Ok(())`。
+    /// - 失败时返回 `TransactionServiceError`。
+    async fn discard_transaction(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<(), TransactionServiceError>;
+
+    /// 向用户退还差价，生成一笔不关联订单的已完成退款交易。
+    ///
+    /// 用于候补以低于预付金额的座位类型兑现后退还差价。退款交易与候补的差价已退还标记
+    /// 在同一数据库事务中保存，候补的差价已退还时返回错误。
+    ///
+    /// Arguments:
+    /// - `user_id`: 用户的唯一标识符。
+    /// - `amount`: 退还金额的绝对值。
+    /// - `waitlist_uuid`: 已兑现候补的 UUID。
+    ///
+    /// Returns:
+    /// - 成功时返回退款交易的 UUID。
+    /// - 失败时返回 `TransactionServiceError`。
+    async fn refund_fare_difference(
+        &self,
+        user_id: UserId,
+        amount: TransactionAmountAbs,
+        waitlist_uuid: Uuid,
+    ) -> Result<Uuid, TransactionServiceError>;
+
+    /// 将交易转换为 DTO，可取消的订单附带当前取消时的可退金额与手续费。
     async fn convert_transaction_to_dto(
         &self,
         transaction: Transaction,
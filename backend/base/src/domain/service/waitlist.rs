@@ -0,0 +1,54 @@
+//! # 候补领域服务模块
+//!
+//! 该模块定义了候补兑现后的结算及候补到期处理的领域服务接口。主要包含以下内容：
+//!
+//! - `WaitlistServiceError`: 枚举类型，表示候补领域服务错误。
+//! - `WaitlistService`: 异步 trait，定义了候补结算与到期处理的操作。
+//!
+//! 座位分配由`TrainBookingService`在持有车次排班锁时完成，本服务负责退还差价、到期退款及通知用户。
+use crate::domain::RepositoryError;
+use crate::domain::model::waitlist::{Waitlist, WaitlistError};
+use crate::domain::service::ServiceError;
+use crate::domain::service::train_booking::TrainBookingServiceError;
+use crate::domain::service::transaction::TransactionServiceError;
+use async_trait::async_trait;
+use thiserror::Error;
+use uuid::Uuid;
+
+/// 枚举类型，表示候补领域服务错误。
+#[derive(Debug, Error)]
+pub enum WaitlistServiceError {
+    /// 底层基础设施错误（如数据库访问失败）
+    #[error("an infrastructure error occurred: {0}")]
+    InfrastructureError(ServiceError),
+    #[error("no order found for order uuid: {0}")]
+    InvalidOrder(Uuid),
+    #[error(transparent)]
+    WaitlistError(#[from] WaitlistError),
+    #[error(transparent)]
+    TrainBookingServiceError(#[from] TrainBookingServiceError),
+    #[error(transparent)]
+    TransactionServiceError(#[from] TransactionServiceError),
+}
+
+impl From<RepositoryError> for WaitlistServiceError {
+    fn from(value: RepositoryError) -> Self {
+        WaitlistServiceError::InfrastructureError(ServiceError::RepositoryError(value))
+    }
+}
+
+/// 异步 trait，定义了候补结算与到期处理的操作。
+#[async_trait]
+pub trait WaitlistService: 'static + Send + Sync {
+    /// 结算已兑现的候补：退还预付金额与所分配座位类型票价的差价，并通知用户。
+    ///
+    /// Arguments:
+    /// - `waitlists`: `TrainBookingService`返回的已兑现候补。
+    ///
+    /// Returns:
+    /// - 成功时返回 `Ok(())`，单个候补结算失败不影响其他候补，差价未能退还的候补由守护任务重试。
+    async fn settle_fulfilled(&self, waitlists: Vec<Waitlist>) -> Result<(), WaitlistServiceError>;
+
+    /// 定期为等待兑现的候补分配座位，重试此前未能退还的差价，并对已过截止时间的候补全额退款、通知用户。
+    async fn waitlist_daemon(&self);
+}
@@ -15,7 +15,7 @@ use crate::domain::repository::order::OrderRepository;
 use crate::domain::repository::personal_info::PersonalInfoRepository;
 use crate::domain::service::hotel_booking::HotelBookingService;
 use crate::domain::service::session::SessionManagerService;
-use crate::domain::service::transaction::TransactionService;
+use crate::domain::service::transaction::{TransactionService, TransactionServiceError};
 use async_trait::async_trait;
 use chrono::{Datelike, NaiveDate, TimeZone};
 use rust_decimal::Decimal;
@@ -318,6 +318,7 @@ where
         &self,
         session_id: String,
         hotel_orders: HotelOrderRequestsDTO,
+        coupon_code: Option<String>,
     ) -> Result<TransactionInfoDTO, Box<dyn ApplicationError>> {
         if hotel_orders.is_empty() {
             return Err(
@@ -347,28 +348,23 @@ where
             orders.push(order);
         }
 
-        let total_amount = orders
-            .iter()
-            .map(|order| {
-                (order.unit_price() * order.amount())
-                    .to_f64()
-                    .unwrap_or(0.0)
-            })
-            .sum::<f64>();
-
-        let transaction_id = self
+        let transaction = self
             .transaction_service
-            .new_transaction(user_id, orders, true)
+            .new_transaction(user_id, orders, true, coupon_code)
             .await
-            .map_err(|e| {
-                error!("Failed to create transaction: {:?}", e);
-                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            .map_err(|e| match e {
+                e @ TransactionServiceError::CouponError(_) => e.into(),
+                e => {
+                    error!("Failed to create transaction: {:?}", e);
+                    Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+                }
             })?;
 
         Ok(TransactionInfoDTO {
-            transaction_id,
-            amount: total_amount,
+            transaction_id: transaction.uuid(),
+            amount: transaction.raw_amount().to_f64().unwrap_or(0.0),
             status: "unpaid".to_string(),
+            payment_deadline: transaction.payment_deadline().map(|dt| dt.to_rfc3339()),
         })
     }
 }
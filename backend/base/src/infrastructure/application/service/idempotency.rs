@@ -0,0 +1,395 @@
+use crate::application::commands::idempotency::BeginIdempotentRequestCommand;
+use crate::application::service::idempotency::{
+    IdempotencyApplicationService, IdempotencyError, IdempotencyReservation, IdempotentRequest,
+    validate_idempotency_key,
+};
+use crate::application::{ApplicationError, GeneralError};
+use crate::domain::model::idempotency::IdempotencyRecord;
+use crate::domain::model::session::SessionId;
+use crate::domain::model::user::UserId;
+use crate::domain::repository::idempotency::IdempotencyRepository;
+use crate::domain::service::session::SessionManagerService;
+use crate::{IDEMPOTENCY_KEY_CLEANUP_INTERVAL_SECONDS, IDEMPOTENCY_KEY_RETENTION_HOURS};
+use async_trait::async_trait;
+use blake2::{Blake2b512, Digest};
+use chrono::{Duration, Local};
+use std::sync::Arc;
+use tracing::{error, info, instrument};
+
+pub struct IdempotencyApplicationServiceImpl<S, IR>
+where
+    S: SessionManagerService,
+    IR: IdempotencyRepository,
+{
+    session_manager: Arc<S>,
+    idempotency_repository: Arc<IR>,
+}
+
+impl<S, IR> IdempotencyApplicationServiceImpl<S, IR>
+where
+    S: SessionManagerService,
+    IR: IdempotencyRepository,
+{
+    pub fn new(session_manager: Arc<S>, idempotency_repository: Arc<IR>) -> Self {
+        Self {
+            session_manager,
+            idempotency_repository,
+        }
+    }
+
+    async fn get_user_id_by_session_id(
+        &self,
+        session_id: &str,
+    ) -> Result<UserId, Box<dyn ApplicationError>> {
+        let session_id = SessionId::try_from(session_id)
+            .map_err(|_for_super_earth| GeneralError::InvalidSessionId)?;
+
+        let user_id = self
+            .session_manager
+            .get_user_id_by_session(session_id)
+            .await
+            .map_err(|_for_super_earth| GeneralError::InternalServerError)?
+            .ok_or(GeneralError::InvalidSessionId)?;
+
+        Ok(user_id)
+    }
+
+    fn request_hash(request: &[u8]) -> String {
+        Blake2b512::digest(request)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+}
+
+#[async_trait]
+impl<S, IR> IdempotencyApplicationService for IdempotencyApplicationServiceImpl<S, IR>
+where
+    S: SessionManagerService,
+    IR: IdempotencyRepository,
+{
+    #[instrument(skip(self, command), fields(scope = %command.scope))]
+    async fn begin(
+        &self,
+        command: BeginIdempotentRequestCommand,
+    ) -> Result<IdempotentRequest, Box<dyn ApplicationError>> {
+        validate_idempotency_key(&command.key)?;
+
+        let user_id = self.get_user_id_by_session_id(&command.session_id).await?;
+        let request_hash = Self::request_hash(&command.request);
+
+        let record = IdempotencyRecord::new_pending(
+            user_id,
+            command.scope.clone(),
+            command.key.clone(),
+            request_hash.clone(),
+        );
+
+        let reserved = self
+            .idempotency_repository
+            .reserve(&record)
+            .await
+            .map_err(|e| {
+                error!("Failed to reserve idempotency key: {}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        if reserved {
+            return Ok(IdempotentRequest::Proceed(IdempotencyReservation {
+                user_id,
+                scope: command.scope,
+                key: command.key,
+            }));
+        }
+
+        let existing = self
+            .idempotency_repository
+            .find(user_id, &command.scope, &command.key)
+            .await
+            .map_err(|e| {
+                error!("Failed to load idempotency key: {}", e);
+                GeneralError::InternalServerError
+            })?
+            // 登记后、查询前被并发失败的请求删除，视为仍在处理中，由客户端重试
+            .ok_or(IdempotencyError::RequestInProgress)?;
+
+        if !existing.matches(&request_hash) {
+            return Err(IdempotencyError::KeyConflict.into());
+        }
+
+        match existing.response() {
+            Some(response) => Ok(IdempotentRequest::Replay(response.to_string())),
+            None => Err(IdempotencyError::RequestInProgress.into()),
+        }
+    }
+
+    #[instrument(skip(self, response))]
+    async fn complete(
+        &self,
+        reservation: IdempotencyReservation,
+        response: String,
+    ) -> Result<(), Box<dyn ApplicationError>> {
+        self.idempotency_repository
+            .save_response(
+                reservation.user_id,
+                &reservation.scope,
+                &reservation.key,
+                &response,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to save idempotent response: {}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn release(
+        &self,
+        reservation: IdempotencyReservation,
+    ) -> Result<(), Box<dyn ApplicationError>> {
+        self.idempotency_repository
+            .remove(reservation.user_id, &reservation.scope, &reservation.key)
+            .await
+            .map_err(|e| {
+                error!("Failed to release idempotency key: {}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        Ok(())
+    }
+
+    async fn cleanup_daemon(&self) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+            IDEMPOTENCY_KEY_CLEANUP_INTERVAL_SECONDS,
+        ));
+
+        loop {
+            interval.tick().await;
+
+            let local_now = Local::now();
+            let offset = *local_now.offset();
+            let expire_time =
+                local_now.with_timezone(&offset) - Duration::hours(IDEMPOTENCY_KEY_RETENTION_HOURS);
+
+            match self.idempotency_repository.remove_before(expire_time).await {
+                Ok(removed) => {
+                    info!("Removed {} expired idempotency keys", removed);
+                }
+                Err(e) => {
+                    error!("Failed to remove expired idempotency keys: {}", e);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::RepositoryError;
+    use crate::domain::model::session::Session;
+    use mockall::{mock, predicate::*};
+    use sea_orm::prelude::DateTimeWithTimeZone;
+
+    mock! {
+        SessionManagerService {}
+        #[async_trait]
+        impl SessionManagerService for SessionManagerService {
+            async fn create_session(&self, user_id: UserId) -> Result<Session, RepositoryError>;
+            async fn delete_session(&self, session: Session) -> Result<(), RepositoryError>;
+            async fn get_session(&self, session_id: SessionId) -> Result<Option<Session>, RepositoryError>;
+            async fn get_user_id_by_session(&self, session_id: SessionId) -> Result<Option<UserId>, RepositoryError>;
+            async fn verify_session_id(&self, session_id_str: &str) -> Result<bool, RepositoryError>;
+        }
+    }
+
+    mock! {
+        IdempotencyRepository {}
+        #[async_trait]
+        impl IdempotencyRepository for IdempotencyRepository {
+            async fn find(&self, user_id: UserId, scope: &str, key: &str) -> Result<Option<IdempotencyRecord>, RepositoryError>;
+            async fn reserve(&self, record: &IdempotencyRecord) -> Result<bool, RepositoryError>;
+            async fn save_response(&self, user_id: UserId, scope: &str, key: &str, response: &str) -> Result<(), RepositoryError>;
+            async fn remove(&self, user_id: UserId, scope: &str, key: &str) -> Result<(), RepositoryError>;
+            async fn remove_before(&self, time: DateTimeWithTimeZone) -> Result<u64, RepositoryError>;
+        }
+    }
+
+    const SCOPE: &str = "/payment/recharge";
+    const KEY: &str = "8b0a6c1e-1f0e-4a52-9d3b-5a4f2b7c9e10";
+
+    fn logged_in_session(session_id: SessionId) -> MockSessionManagerService {
+        let mut mock_session = MockSessionManagerService::new();
+
+        mock_session
+            .expect_get_user_id_by_session()
+            .with(eq(session_id))
+            .returning(|_| Ok(Some(UserId::from(1))));
+
+        mock_session
+    }
+
+    fn command(session_id: SessionId, request: &[u8]) -> BeginIdempotentRequestCommand {
+        BeginIdempotentRequestCommand {
+            session_id: session_id.to_string(),
+            key: KEY.to_string(),
+            scope: SCOPE.to_string(),
+            request: request.to_vec(),
+        }
+    }
+
+    fn existing_record(request: &[u8], response: Option<&str>) -> IdempotencyRecord {
+        let pending =
+            IdempotencyRecord::new_pending(
+                UserId::from(1),
+                SCOPE.to_string(),
+                KEY.to_string(),
+                IdempotencyApplicationServiceImpl::<
+                    MockSessionManagerService,
+                    MockIdempotencyRepository,
+                >::request_hash(request),
+            );
+
+        IdempotencyRecord::new(
+            pending.user_id(),
+            pending.scope().to_string(),
+            pending.key().to_string(),
+            pending.request_hash().to_string(),
+            response.map(str::to_string),
+            pending.create_time(),
+            pending.locked_until(),
+        )
+    }
+
+    fn error_code(result: Result<IdempotentRequest, Box<dyn ApplicationError>>) -> u32 {
+        match result {
+            Err(e) => e.error_code(),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_begin_reserves_new_key() {
+        let session_id = SessionId::random();
+        let mut mock_repo = MockIdempotencyRepository::new();
+
+        mock_repo
+            .expect_reserve()
+            .withf(|record| {
+                record.scope() == SCOPE && record.key() == KEY && record.response().is_none()
+            })
+            .return_once(|_| Ok(true));
+        mock_repo.expect_find().never();
+
+        let service = IdempotencyApplicationServiceImpl::new(
+            Arc::new(logged_in_session(session_id)),
+            Arc::new(mock_repo),
+        );
+
+        let result = service.begin(command(session_id, b"{}")).await;
+
+        assert!(matches!(result, Ok(IdempotentRequest::Proceed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_begin_replays_saved_response() {
+        let session_id = SessionId::random();
+        let mut mock_repo = MockIdempotencyRepository::new();
+        let record = existing_record(b"{}", Some(r#"{"id":1}"#));
+
+        mock_repo.expect_reserve().return_once(|_| Ok(false));
+        mock_repo
+            .expect_find()
+            .withf(|user_id, scope, key| {
+                *user_id == UserId::from(1) && scope == SCOPE && key == KEY
+            })
+            .return_once(move |_, _, _| Ok(Some(record)));
+
+        let service = IdempotencyApplicationServiceImpl::new(
+            Arc::new(logged_in_session(session_id)),
+            Arc::new(mock_repo),
+        );
+
+        let result = service.begin(command(session_id, b"{}")).await;
+
+        assert!(
+            matches!(result, Ok(IdempotentRequest::Replay(response)) if response == r#"{"id":1}"#)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_rejects_different_request() {
+        let session_id = SessionId::random();
+        let mut mock_repo = MockIdempotencyRepository::new();
+        let record = existing_record(b"{}", Some(r#"{"id":1}"#));
+
+        mock_repo.expect_reserve().return_once(|_| Ok(false));
+        mock_repo
+            .expect_find()
+            .return_once(move |_, _, _| Ok(Some(record)));
+
+        let service = IdempotencyApplicationServiceImpl::new(
+            Arc::new(logged_in_session(session_id)),
+            Arc::new(mock_repo),
+        );
+
+        let result = service
+            .begin(command(session_id, br#"{"amount":100}"#))
+            .await;
+
+        assert_eq!(
+            error_code(result),
+            IdempotencyError::KeyConflict.error_code()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_rejects_request_in_progress() {
+        let session_id = SessionId::random();
+        let mut mock_repo = MockIdempotencyRepository::new();
+        let record = existing_record(b"{}", None);
+
+        mock_repo.expect_reserve().return_once(|_| Ok(false));
+        mock_repo
+            .expect_find()
+            .return_once(move |_, _, _| Ok(Some(record)));
+
+        let service = IdempotencyApplicationServiceImpl::new(
+            Arc::new(logged_in_session(session_id)),
+            Arc::new(mock_repo),
+        );
+
+        let result = service.begin(command(session_id, b"{}")).await;
+
+        assert_eq!(
+            error_code(result),
+            IdempotencyError::RequestInProgress.error_code()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_begin_rejects_invalid_key() {
+        let session_id = SessionId::random();
+        let mut mock_repo = MockIdempotencyRepository::new();
+
+        mock_repo.expect_reserve().never();
+
+        let service = IdempotencyApplicationServiceImpl::new(
+            Arc::new(MockSessionManagerService::new()),
+            Arc::new(mock_repo),
+        );
+
+        let mut command = command(session_id, b"{}");
+        command.key = "k".repeat(256);
+
+        let result = service.begin(command).await;
+
+        assert_eq!(
+            error_code(result),
+            GeneralError::BadRequest(String::new()).error_code()
+        );
+    }
+}
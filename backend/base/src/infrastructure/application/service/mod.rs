@@ -3,6 +3,7 @@ pub mod geo;
 pub mod hotel;
 pub mod hotel_data;
 pub mod hotel_order;
+pub mod idempotency;
 pub mod message;
 pub mod personal_info;
 pub mod train_data;
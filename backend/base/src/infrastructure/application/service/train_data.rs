@@ -9,10 +9,11 @@
 //!   - 火车站仓储(`StationRepository`)
 //!   - 列车仓储(`TrainRepository`)
 //!   - 路线仓储(`RouteRepository`)
+//! - 车站、线路或列车数据变更后通过`TrainScheduleService`重建内存时刻表
 
 use crate::application::commands::train_data::{
-    LoadCityCommand, LoadDishTakeawayCommand, LoadStationCommand, LoadTrainNumberCommand,
-    LoadTrainTypeCommand,
+    LoadCityCommand, LoadDishTakeawayCommand, LoadIntraCityTransferCommand, LoadStationCommand,
+    LoadTrainNumberCommand, LoadTrainTypeCommand,
 };
 use crate::application::service::train_data::TrainDataService;
 use crate::application::{ApplicationError, GeneralError, ModeError};
@@ -23,6 +24,7 @@ use crate::domain::repository::station::StationRepository;
 use crate::domain::repository::takeaway::TakeawayShopRepository;
 use crate::domain::repository::train::TrainRepository;
 use crate::domain::service::object_storage::ObjectStorageService;
+use crate::domain::service::train_schedule::TrainScheduleService;
 use async_trait::async_trait;
 use shared::data::{DishData, TakeawayData};
 use std::collections::{HashMap, HashSet};
@@ -40,6 +42,7 @@ use tracing::{error, instrument, warn};
 /// - `S`: 火车站仓储类型，需实现`StationRepository` trait
 /// - `T`: 列车仓储类型，需实现`TrainRepository` trait
 /// - `R`: 路线仓储类型，需实现`RouteRepository` trait
+/// - `TSS`: 车次服务类型，需实现`TrainScheduleService` trait
 ///
 /// # 字段
 /// - `debug`: 是否启用调试模式
@@ -47,7 +50,8 @@ use tracing::{error, instrument, warn};
 /// - `station_repository`: 火车站仓储
 /// - `train_repository`: 列车仓储
 /// - `route_repository`: 路线仓储
-pub struct TrainDataServiceImpl<C, S, T, R, D, TS, OSS>
+/// - `train_schedule_service`: 车次服务，用于数据变更后重建时刻表缓存
+pub struct TrainDataServiceImpl<C, S, T, R, D, TS, OSS, TSS>
 where
     C: CityRepository,
     S: StationRepository,
@@ -56,6 +60,7 @@ where
     D: DishRepository,
     TS: TakeawayShopRepository,
     OSS: ObjectStorageService,
+    TSS: TrainScheduleService,
 {
     debug: bool,
     data_path: PathBuf,
@@ -66,9 +71,10 @@ where
     dish_repository: Arc<D>,
     takeaway_shop_repository: Arc<TS>,
     object_storage_service: Arc<OSS>,
+    train_schedule_service: Arc<TSS>,
 }
 
-impl<C, S, T, R, D, TS, OSS> TrainDataServiceImpl<C, S, T, R, D, TS, OSS>
+impl<C, S, T, R, D, TS, OSS, TSS> TrainDataServiceImpl<C, S, T, R, D, TS, OSS, TSS>
 where
     C: CityRepository,
     S: StationRepository,
@@ -77,6 +83,7 @@ where
     D: DishRepository,
     TS: TakeawayShopRepository,
     OSS: ObjectStorageService,
+    TSS: TrainScheduleService,
 {
     /// 创建新的火车数据加载服务实例
     ///
@@ -86,6 +93,7 @@ where
     /// * `station_repository` - 火车站仓储
     /// * `train_repository` - 列车仓储
     /// * `route_repository` - 路线仓储
+    /// * `train_schedule_service` - 车次服务
     ///
     /// # Returns
     /// 返回新的`TrainDataServiceImpl`实例
@@ -100,6 +108,7 @@ where
         dish_repository: Arc<D>,
         takeaway_shop_repository: Arc<TS>,
         object_storage_service: Arc<OSS>,
+        train_schedule_service: Arc<TSS>,
     ) -> Self {
         Self {
             debug,
@@ -111,6 +120,7 @@ where
             dish_repository,
             takeaway_shop_repository,
             object_storage_service,
+            train_schedule_service,
         }
     }
 
@@ -131,10 +141,25 @@ where
             Err(Box::new(ModeError))
         }
     }
+
+    /// 车站、线路或列车数据变更后重建内存时刻表
+    ///
+    /// # Errors
+    /// * `GeneralError::InternalServerError` - 重建时刻表失败
+    async fn rebuild_timetable(&self) -> Result<(), Box<dyn ApplicationError>> {
+        self.train_schedule_service
+            .rebuild_timetable()
+            .await
+            .map_err(|e| {
+                error!("Error rebuilding timetable: {:?}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })
+    }
 }
 
 #[async_trait]
-impl<C, S, T, R, D, TS, OSS> TrainDataService for TrainDataServiceImpl<C, S, T, R, D, TS, OSS>
+impl<C, S, T, R, D, TS, OSS, TSS> TrainDataService
+    for TrainDataServiceImpl<C, S, T, R, D, TS, OSS, TSS>
 where
     C: CityRepository,
     S: StationRepository,
@@ -143,6 +168,7 @@ where
     D: DishRepository,
     TS: TakeawayShopRepository,
     OSS: ObjectStorageService,
+    TSS: TrainScheduleService,
 {
     /// 检查是否启用调试模式
     ///
@@ -202,6 +228,40 @@ where
                 GeneralError::InternalServerError
             })?;
 
+        self.rebuild_timetable().await?;
+
+        Ok(())
+    }
+
+    /// 加载同城换乘时间数据
+    ///
+    /// # Arguments
+    /// * `command` - 加载同城换乘时间命令
+    ///
+    /// # Returns
+    /// * `Ok(())` - 加载成功
+    /// * `Err(Box<dyn ApplicationError>)` - 加载失败及原因
+    ///
+    /// # Errors
+    /// * `ModeError` - 调试模式未启用
+    /// * `GeneralError::InternalServerError` - 底层基础设施错误
+    #[instrument(skip_all)]
+    async fn load_intra_city_transfer(
+        &self,
+        command: LoadIntraCityTransferCommand,
+    ) -> Result<(), Box<dyn ApplicationError>> {
+        self.check_debug_mode()?;
+
+        self.station_repository
+            .save_raw_transfer(command)
+            .await
+            .map_err(|e| {
+                error!("Error saving intra-city transfer: {:?}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        self.rebuild_timetable().await?;
+
         Ok(())
     }
 
@@ -232,6 +292,8 @@ where
                 GeneralError::InternalServerError
             })?;
 
+        self.rebuild_timetable().await?;
+
         Ok(())
     }
 
@@ -262,6 +324,8 @@ where
                 GeneralError::InternalServerError
             })?;
 
+        self.rebuild_timetable().await?;
+
         Ok(())
     }
 
@@ -550,6 +550,7 @@ where
             transaction_id: tx.uuid(),
             amount: tx.amount().to_f64().unwrap(),
             status: tx.status().to_string(),
+            payment_deadline: tx.payment_deadline().map(|dt| dt.to_rfc3339()),
         })
     }
 }
@@ -2,40 +2,53 @@ use crate::application::ApplicationError;
 use crate::application::GeneralError;
 use crate::application::service::train_order::CreateTrainOrderDTO;
 use crate::application::service::train_order::OrderPackDTO;
+use crate::application::service::train_order::RebookTrainOrderDTO;
 use crate::application::service::train_order::TrainOrderService;
 use crate::application::service::train_order::TrainOrderServiceError;
-use crate::application::service::transaction::TransactionInfoDTO;
+use crate::application::service::train_order::{WaitlistInfoDTO, WaitlistRequestDTO};
+use crate::application::service::transaction::{
+    TransactionApplicationServiceError, TransactionInfoDTO,
+};
 use crate::domain::Identifiable;
 use crate::domain::model::order::{
-    BaseOrder, Order, OrderStatus, OrderTimeInfo, PaymentInfo, TrainOrder,
+    BaseOrder, Order, OrderStatus, OrderTimeInfo, PaymentInfo, TicketCategory, TrainOrder,
 };
 use crate::domain::model::session::SessionId;
 use crate::domain::model::train::{SeatTypeName, TrainNumber};
 use crate::domain::model::train_schedule::StationRange;
+use crate::domain::model::transaction::Transaction;
 use crate::domain::model::user::UserId;
+use crate::domain::model::waitlist::{Waitlist, WaitlistSeatOption};
 use crate::domain::repository::order::OrderRepository;
 use crate::domain::repository::personal_info::PersonalInfoRepository;
 use crate::domain::repository::route::RouteRepository;
 use crate::domain::repository::station::StationRepository;
 use crate::domain::repository::train::TrainRepository;
 use crate::domain::repository::train_schedule::TrainScheduleRepository;
+use crate::domain::repository::user::UserRepository;
+use crate::domain::repository::waitlist::WaitlistRepository;
 use crate::domain::service::ServiceError;
+use crate::domain::service::fare::FareService;
+use crate::domain::service::pricing::{PriceQuoteQuery, PricingService};
 use crate::domain::service::session::SessionManagerService;
-use crate::domain::service::train_booking::TrainBookingService;
+use crate::domain::service::train_booking::{TrainBookingService, TrainBookingServiceError};
 use crate::domain::service::train_schedule::TrainScheduleService;
-use crate::domain::service::transaction::TransactionService;
+use crate::domain::service::transaction::{TransactionService, TransactionServiceError};
+use crate::domain::service::user::{UserService, UserServiceError};
 use anyhow::anyhow;
 use async_trait::async_trait;
 use chrono::Timelike;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use sea_orm::prelude::DateTimeWithTimeZone;
+use std::any::Any;
+use std::ops::Deref;
 use std::sync::Arc;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 use uuid::Uuid;
 
 #[derive(Clone)]
-pub struct TrainOrderServiceImpl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS>
+pub struct TrainOrderServiceImpl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS, FS, PS, WR, US, UR>
 where
     TSR: TrainScheduleRepository,
     TBS: TrainBookingService,
@@ -47,6 +60,11 @@ where
     SMS: SessionManagerService,
     PIR: PersonalInfoRepository,
     TSS: TrainScheduleService,
+    FS: FareService,
+    PS: PricingService,
+    WR: WaitlistRepository,
+    US: UserService,
+    UR: UserRepository,
 {
     train_schedule_repository: Arc<TSR>,
     train_booking_service: Arc<TBS>,
@@ -58,10 +76,17 @@ where
     session_manager_service: Arc<SMS>,
     personal_info_repository: Arc<PIR>,
     train_schedule_service: Arc<TSS>,
+    fare_service: Arc<FS>,
+    pricing_service: Arc<PS>,
+    waitlist_repository: Arc<WR>,
+    user_service: Arc<US>,
+    user_repository: Arc<UR>,
+    // 是否在下单时暂留座位，支付后确认；否则在支付后再分配座位
+    seat_hold: bool,
 }
 
-impl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS>
-    TrainOrderServiceImpl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS>
+impl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS, FS, PS, WR, US, UR>
+    TrainOrderServiceImpl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS, FS, PS, WR, US, UR>
 where
     TSR: TrainScheduleRepository,
     TBS: TrainBookingService,
@@ -73,6 +98,11 @@ where
     SMS: SessionManagerService,
     PIR: PersonalInfoRepository,
     TSS: TrainScheduleService,
+    FS: FareService,
+    PS: PricingService,
+    WR: WaitlistRepository,
+    US: UserService,
+    UR: UserRepository,
 {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -86,6 +116,12 @@ where
         session_manager_service: Arc<SMS>,
         personal_info_repository: Arc<PIR>,
         train_schedule_service: Arc<TSS>,
+        fare_service: Arc<FS>,
+        pricing_service: Arc<PS>,
+        waitlist_repository: Arc<WR>,
+        user_service: Arc<US>,
+        user_repository: Arc<UR>,
+        seat_hold: bool,
     ) -> Self {
         Self {
             train_schedule_repository,
@@ -98,15 +134,78 @@ where
             session_manager_service,
             personal_info_repository,
             train_schedule_service,
+            fare_service,
+            pricing_service,
+            waitlist_repository,
+            user_service,
+            user_repository,
+            seat_hold,
         }
     }
 
+    /// 校验用户密码或支付密码，同时提供时使用用户密码
+    async fn verify_password(
+        &self,
+        user_id: UserId,
+        user_password: Option<String>,
+        payment_password: Option<String>,
+    ) -> Result<(), Box<dyn ApplicationError>> {
+        let user = self
+            .user_repository
+            .find(user_id)
+            .await
+            .map_err(|e| {
+                error!("Database error when finding user: {:?}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?
+            .ok_or(TrainOrderServiceError::InvalidSessionId)?;
+
+        let (result, wrong_password_error) = if let Some(user_password) = user_password {
+            (
+                self.user_service
+                    .verify_password(&user, user_password)
+                    .await,
+                TransactionApplicationServiceError::WrongUserPassword,
+            )
+        } else if let Some(payment_password) = payment_password {
+            (
+                self.user_service
+                    .verify_payment_password(&user, payment_password)
+                    .await,
+                TransactionApplicationServiceError::WrongPaymentPassword,
+            )
+        } else {
+            return Err(Box::new(GeneralError::BadRequest(
+                "Neither user password nor payment password was set".to_string(),
+            )));
+        };
+
+        result.map_err(|e| match e {
+            UserServiceError::InvalidPassword => {
+                Box::new(wrong_password_error) as Box<dyn ApplicationError>
+            }
+            e => {
+                error!("Failed to verify password: {:?}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            }
+        })
+    }
+
     async fn validate_and_create_train_order(
         &self,
         dto: &CreateTrainOrderDTO,
         user_id: UserId,
     ) -> Result<Box<dyn Order>, Box<dyn ApplicationError>> {
         // == 验证订单 ==
+        let ticket_category = match &dto.ticket_category {
+            Some(category) => {
+                TicketCategory::try_from(category.as_str()).map_err(|_for_super_earth| {
+                    TrainOrderServiceError::InvalidTicketCategory(category.clone())
+                })?
+            }
+            None => TicketCategory::default(),
+        };
+
         // SAFETY: 正确性将在find_by_train_number中检查
         let train_number = TrainNumber::from_unchecked(dto.train_number.clone());
 
@@ -304,13 +403,21 @@ where
             .get_id()
             .ok_or(TrainOrderServiceError::InvalidPassengerId)?;
 
+        let passenger_age = personal_info
+            .identity_card_id()
+            .age_on(train_schedule.date());
+
+        if !ticket_category.is_eligible(passenger_age) {
+            return Err(Box::new(TrainOrderServiceError::IneligibleTicketCategory(
+                ticket_category.to_string(),
+            )));
+        }
+
         let seat_type = train_details
             .seats()
             .get(&dto.seat_type)
             .ok_or(TrainOrderServiceError::InvalidTrainNumber)?;
 
-        let unit_price = seat_type.unit_price();
-
         let mut departure_index = None;
         let mut arrival_index = None;
 
@@ -331,18 +438,48 @@ where
             }
         }
 
-        let stations_count = match (departure_index, arrival_index) {
-            (Some(d), Some(a)) => {
-                if a > d {
-                    (a - d) as i64
-                } else {
-                    return Err(Box::new(TrainOrderServiceError::InvalidStationId));
-                }
-            }
+        let (departure_index, arrival_index) = match (departure_index, arrival_index) {
+            (Some(d), Some(a)) if a > d => (d, a),
             _ => return Err(Box::new(TrainOrderServiceError::InvalidStationId)),
         };
 
-        let total_price = unit_price * Decimal::from(stations_count);
+        // 与车次查询使用同一票价计算服务，保证报价与扣款一致
+        let base_fare = self
+            .fare_service
+            .calculate_fare(
+                train_details.train_type(),
+                seat_type,
+                &route,
+                departure_index,
+                arrival_index,
+            )
+            .map_err(|e| {
+                error!("Failed to calculate fare: {}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?;
+
+        // 下单时的报价写入订单，此后支付金额不再随动态定价变化
+        let adult_price = self
+            .pricing_service
+            .quote_fares(&[PriceQuoteQuery {
+                train_schedule_id,
+                route_id: train_schedule.route_id(),
+                date: train_schedule.date(),
+                seat_type: seat_type.clone(),
+                station_range,
+                base_fare,
+            }])
+            .await
+            .map_err(|e| {
+                error!("Failed to quote fare: {}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?
+            .pop()
+            .expect("quote_fares should return one price per query");
+
+        let total_price = self
+            .fare_service
+            .apply_ticket_category(adult_price, ticket_category);
 
         let base_order = BaseOrder::new(
             None,
@@ -355,7 +492,7 @@ where
             personal_info_id,
         );
 
-        let train_order = TrainOrder::new(
+        let mut train_order = TrainOrder::new(
             base_order,
             train_schedule
                 .get_id()
@@ -366,9 +503,34 @@ where
             station_range,
         );
 
+        train_order.set_ticket_category(ticket_category);
+
         Ok(Box::new(train_order))
     }
 
+    fn convert_waitlist_to_dto(waitlist: &Waitlist) -> WaitlistInfoDTO {
+        WaitlistInfoDTO {
+            waitlist_id: waitlist.uuid(),
+            order_id: waitlist.train_order_uuid(),
+            transaction_id: waitlist.transaction_uuid(),
+            seat_types: waitlist
+                .seat_options()
+                .iter()
+                .map(|option| option.seat_type_name.deref().to_string())
+                .collect(),
+            prepaid_amount: waitlist
+                .prepaid_amount()
+                .to_f64()
+                .expect("Failed to convert amount to f64"),
+            deadline: waitlist.deadline().to_rfc3339(),
+            create_time: waitlist.create_time().to_rfc3339(),
+            status: waitlist.status().to_string(),
+            fulfilled_seat_type: waitlist
+                .fulfilled_seat_type()
+                .map(|seat_type_name| seat_type_name.deref().to_string()),
+        }
+    }
+
     // 处理订单消息（模拟消息队列消费者处理）
     pub async fn process_order_message(
         &self,
@@ -465,8 +627,8 @@ where
 }
 
 #[async_trait]
-impl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS> TrainOrderService
-    for TrainOrderServiceImpl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS>
+impl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS, FS, PS, WR, US, UR> TrainOrderService
+    for TrainOrderServiceImpl<TSR, TBS, TR, RR, SR, OR, TS, SMS, PIR, TSS, FS, PS, WR, US, UR>
 where
     TSR: TrainScheduleRepository + Send + Sync + 'static,
     TBS: TrainBookingService + Send + Sync + 'static,
@@ -478,12 +640,18 @@ where
     SMS: SessionManagerService + Send + Sync + 'static,
     PIR: PersonalInfoRepository + Send + Sync + 'static,
     TSS: TrainScheduleService + Send + Sync + 'static,
+    FS: FareService,
+    PS: PricingService,
+    WR: WaitlistRepository,
+    US: UserService,
+    UR: UserRepository,
 {
     #[instrument(skip_all)]
     async fn process_train_order_packs(
         &self,
         session_id: String,
         order_packs: Vec<OrderPackDTO>,
+        coupon_code: Option<String>,
     ) -> Result<TransactionInfoDTO, Box<dyn ApplicationError>> {
         let user_id = self
             .session_manager_service
@@ -499,13 +667,14 @@ where
             .ok_or(TrainOrderServiceError::InvalidSessionId)?;
 
         let mut all_train_orders: Vec<Box<dyn Order>> = Vec::new();
-        let mut all_order_uuids: Vec<Uuid> = Vec::new();
+        let mut pack_order_uuids: Vec<(Vec<Uuid>, bool)> = Vec::new();
         let mut all_atomic = true;
-        let mut total_amount: f64 = 0.0;
 
         for pack in order_packs {
             all_atomic &= pack.atomic;
 
+            let mut order_uuids = Vec::with_capacity(pack.order_list.len());
+
             for order_request in pack.order_list {
                 let dto = CreateTrainOrderDTO {
                     train_number: order_request.train_number.clone(),
@@ -514,33 +683,348 @@ where
                     arrival_station: order_request.arrival_station.clone(),
                     personal_id: order_request.personal_id.clone(),
                     seat_type: order_request.seat_type.clone(),
+                    ticket_category: order_request.ticket_category.clone(),
                 };
 
                 let train_order = self.validate_and_create_train_order(&dto, user_id).await?;
 
-                total_amount += (train_order.unit_price() * train_order.amount())
-                    .to_f64()
-                    .expect("Failed to convert amount to f64");
-
-                all_order_uuids.push(train_order.uuid());
+                order_uuids.push(train_order.uuid());
                 all_train_orders.push(train_order);
             }
+
+            pack_order_uuids.push((order_uuids, pack.atomic));
         }
 
-        let transaction_id = self
+        let transaction = self
             .transaction_service
-            .new_transaction(user_id, all_train_orders, all_atomic)
+            .new_transaction(user_id, all_train_orders, all_atomic, coupon_code)
             .await
-            .map_err(|e| {
-                TrainOrderServiceError::InfrastructureError(ServiceError::RelatedServiceError(
-                    e.into(),
-                ))
+            .map_err(|e| -> Box<dyn ApplicationError> {
+                match e {
+                    e @ TransactionServiceError::CouponError(_) => e.into(),
+                    e => Box::new(TrainOrderServiceError::InfrastructureError(
+                        ServiceError::RelatedServiceError(e.into()),
+                    )),
+                }
             })?;
 
+        // 暂留座位至支付截止时间，超时未支付的交易被取消后座位随之释放；
+        // 仅原子订单组暂留失败时丢弃整个交易，非原子订单暂留失败时该订单在支付后按常规流程分配座位
+        if self.seat_hold
+            && let Some(payment_deadline) = transaction.payment_deadline()
+        {
+            match self
+                .train_booking_service
+                .hold_group(pack_order_uuids, payment_deadline)
+                .await
+            {
+                Ok(failed_order_uuids) if !failed_order_uuids.is_empty() => {
+                    warn!(
+                        "Failed to hold seats for train orders {:?} of transaction {}",
+                        failed_order_uuids,
+                        transaction.uuid()
+                    );
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    error!(
+                        "Failed to hold seats for transaction {}: {}",
+                        transaction.uuid(),
+                        err
+                    );
+
+                    if let Err(e) = self
+                        .transaction_service
+                        .discard_transaction(transaction.uuid())
+                        .await
+                    {
+                        error!(
+                            "Failed to discard transaction {}: {}",
+                            transaction.uuid(),
+                            e
+                        );
+                    }
+
+                    return Err(match err {
+                        TrainBookingServiceError::NoAvailableTickets(_) => {
+                            Box::new(TrainOrderServiceError::NoAvailableTickets)
+                        }
+                        err => Box::new(TrainOrderServiceError::InfrastructureError(
+                            ServiceError::RelatedServiceError(err.into()),
+                        )),
+                    });
+                }
+            }
+        }
+
         Ok(TransactionInfoDTO {
-            transaction_id,
-            amount: total_amount,
+            transaction_id: transaction.uuid(),
+            amount: transaction
+                .raw_amount()
+                .to_f64()
+                .expect("Failed to convert amount to f64"),
             status: "unpaid".to_string(),
+            payment_deadline: transaction.payment_deadline().map(|dt| dt.to_rfc3339()),
         })
     }
+
+    #[instrument(skip(self))]
+    async fn rebook_train_order(
+        &self,
+        session_id: String,
+        rebook_dto: RebookTrainOrderDTO,
+    ) -> Result<TransactionInfoDTO, Box<dyn ApplicationError>> {
+        let user_id = self
+            .session_manager_service
+            .get_user_id_by_session(
+                SessionId::try_from(session_id.as_str())
+                    .map_err(|_| TrainOrderServiceError::InvalidSessionId)?,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to get user ID by session: {:?}", e);
+                TrainOrderServiceError::InvalidSessionId
+            })?
+            .ok_or(TrainOrderServiceError::InvalidSessionId)?;
+
+        let replaced_order = self
+            .order_repository
+            .find_train_order_by_uuid(rebook_dto.order_id)
+            .await
+            .map_err(|e| {
+                error!("Database error when finding train order: {:?}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?
+            .ok_or(TrainOrderServiceError::InvalidOrderId(rebook_dto.order_id))?;
+
+        // 通过乘车人确认订单属于当前用户
+        let personal_info = self
+            .personal_info_repository
+            .find_by_user_id(user_id)
+            .await
+            .map_err(|e| {
+                error!("Database error when finding personal info: {:?}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?
+            .into_iter()
+            .find(|info| info.get_id() == Some(replaced_order.personal_info_id()))
+            .ok_or(TrainOrderServiceError::InvalidOrderId(rebook_dto.order_id))?;
+
+        if replaced_order.order_status() != OrderStatus::Ongoing || replaced_order.already_refund()
+        {
+            return Err(Box::new(TrainOrderServiceError::InvalidRebookOrderStatus(
+                rebook_dto.order_id,
+                replaced_order.order_status().to_string(),
+            )));
+        }
+
+        let dto = CreateTrainOrderDTO {
+            train_number: rebook_dto.train_number,
+            origin_departure_time: rebook_dto.origin_departure_time,
+            departure_station: rebook_dto.departure_station,
+            arrival_station: rebook_dto.arrival_station,
+            personal_id: personal_info.uuid().to_string(),
+            seat_type: rebook_dto.seat_type,
+            ticket_category: Some(replaced_order.ticket_category().to_string()),
+        };
+
+        let new_order = self.validate_and_create_train_order(&dto, user_id).await?;
+
+        // 补缴差价直接从余额扣除，与支付订单相同，需用户确认密码
+        self.verify_password(
+            user_id,
+            rebook_dto.user_password,
+            rebook_dto.payment_password,
+        )
+        .await?;
+
+        let transaction = self
+            .transaction_service
+            .new_rebook_transaction(user_id, &replaced_order, new_order)
+            .await
+            .map_err(|e| -> Box<dyn ApplicationError> {
+                match e {
+                    e @ TransactionServiceError::InsufficientFunds { .. } => e.into(),
+                    e => Box::new(TrainOrderServiceError::InfrastructureError(
+                        ServiceError::RelatedServiceError(e.into()),
+                    )),
+                }
+            })?;
+
+        Ok(TransactionInfoDTO {
+            transaction_id: transaction.uuid(),
+            amount: transaction
+                .raw_amount()
+                .to_f64()
+                .expect("Failed to convert amount to f64"),
+            status: transaction.status().to_string(),
+            payment_deadline: transaction.payment_deadline().map(|dt| dt.to_rfc3339()),
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn create_waitlist(
+        &self,
+        session_id: String,
+        waitlist_dto: WaitlistRequestDTO,
+    ) -> Result<WaitlistInfoDTO, Box<dyn ApplicationError>> {
+        let user_id = self
+            .session_manager_service
+            .get_user_id_by_session(
+                SessionId::try_from(session_id.as_str())
+                    .map_err(|_| TrainOrderServiceError::InvalidSessionId)?,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to get user ID by session: {:?}", e);
+                TrainOrderServiceError::InvalidSessionId
+            })?
+            .ok_or(TrainOrderServiceError::InvalidSessionId)?;
+
+        if waitlist_dto.seat_types.is_empty() {
+            return Err(Box::new(GeneralError::BadRequest(
+                "At least one seat type is required".to_string(),
+            )));
+        }
+
+        for (index, seat_type) in waitlist_dto.seat_types.iter().enumerate() {
+            if waitlist_dto.seat_types[..index].contains(seat_type) {
+                return Err(Box::new(GeneralError::BadRequest(format!(
+                    "Duplicate seat type: {}",
+                    seat_type
+                ))));
+            }
+        }
+
+        let deadline = DateTimeWithTimeZone::parse_from_rfc3339(&waitlist_dto.deadline)
+            .map_err(|e| GeneralError::BadRequest(format!("Invalid deadline format: {}", e)))?;
+
+        // 为每个可接受的座位类别创建订单以计算票价，票价最高者作为预付订单
+        let mut train_orders = Vec::with_capacity(waitlist_dto.seat_types.len());
+
+        for seat_type in &waitlist_dto.seat_types {
+            let dto = CreateTrainOrderDTO {
+                train_number: waitlist_dto.train_number.clone(),
+                origin_departure_time: waitlist_dto.origin_departure_time.clone(),
+                departure_station: waitlist_dto.departure_station.clone(),
+                arrival_station: waitlist_dto.arrival_station.clone(),
+                personal_id: waitlist_dto.personal_id.clone(),
+                seat_type: seat_type.clone(),
+                ticket_category: waitlist_dto.ticket_category.clone(),
+            };
+
+            let order = self.validate_and_create_train_order(&dto, user_id).await?;
+
+            let train_order = (order as Box<dyn Any>)
+                .downcast::<TrainOrder>()
+                .expect("validate_and_create_train_order should create train order");
+
+            train_orders.push(*train_order);
+        }
+
+        let departure_time = train_orders[0].order_time_info().active_time();
+
+        if deadline <= Transaction::now() || deadline > departure_time {
+            return Err(Box::new(TrainOrderServiceError::InvalidWaitlistDeadline(
+                waitlist_dto.deadline,
+            )));
+        }
+
+        let seat_options = train_orders
+            .iter()
+            .map(|order| WaitlistSeatOption {
+                seat_type_name: order.order_seat_type_name().clone(),
+                price: order.unit_price(),
+            })
+            .collect::<Vec<_>>();
+
+        let prepaid_order = train_orders
+            .into_iter()
+            .reduce(|prepaid, order| {
+                if order.unit_price() > prepaid.unit_price() {
+                    order
+                } else {
+                    prepaid
+                }
+            })
+            .expect("seat types should not be empty");
+
+        let train_schedule_id = prepaid_order.train_schedule_id();
+        let station_range = prepaid_order.station_range();
+        let order_uuid = prepaid_order.uuid();
+
+        let transaction = self
+            .transaction_service
+            .new_transaction(user_id, vec![Box::new(prepaid_order)], true, None)
+            .await
+            .map_err(|e| {
+                error!("Failed to create waitlist transaction: {:?}", e);
+                Box::new(TrainOrderServiceError::InfrastructureError(
+                    ServiceError::RelatedServiceError(e.into()),
+                )) as Box<dyn ApplicationError>
+            })?;
+
+        let mut waitlist = Waitlist::new(
+            user_id,
+            order_uuid,
+            transaction.uuid(),
+            train_schedule_id,
+            station_range,
+            seat_options,
+            deadline,
+        );
+
+        if let Err(e) = self.waitlist_repository.save(&mut waitlist).await {
+            error!("Failed to save waitlist: {:?}", e);
+
+            if let Err(e) = self
+                .transaction_service
+                .discard_transaction(transaction.uuid())
+                .await
+            {
+                error!(
+                    "Failed to discard transaction {}: {}",
+                    transaction.uuid(),
+                    e
+                );
+            }
+
+            return Err(Box::new(GeneralError::InternalServerError));
+        }
+
+        Ok(Self::convert_waitlist_to_dto(&waitlist))
+    }
+
+    #[instrument(skip(self))]
+    async fn query_waitlist(
+        &self,
+        session_id: String,
+    ) -> Result<Vec<WaitlistInfoDTO>, Box<dyn ApplicationError>> {
+        let user_id = self
+            .session_manager_service
+            .get_user_id_by_session(
+                SessionId::try_from(session_id.as_str())
+                    .map_err(|_| TrainOrderServiceError::InvalidSessionId)?,
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to get user ID by session: {:?}", e);
+                TrainOrderServiceError::InvalidSessionId
+            })?
+            .ok_or(TrainOrderServiceError::InvalidSessionId)?;
+
+        let waitlists = self
+            .waitlist_repository
+            .find_by_user_id(user_id)
+            .await
+            .map_err(|e| {
+                error!("Database error when finding waitlist: {:?}", e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })?;
+
+        Ok(waitlists
+            .iter()
+            .map(Self::convert_waitlist_to_dto)
+            .collect())
+    }
 }
@@ -36,24 +36,29 @@
 use std::sync::Arc;
 
 use crate::application::commands::train_query::{
-    DirectTrainQueryCommand, TrainQueryValidate, TrainScheduleQueryCommand,
-    TransferTrainQueryCommand,
+    DirectTrainQueryCommand, DirectTrainSortKey, FareCalendarQueryCommand, JourneyQueryCommand,
+    TrainQueryValidate, TrainScheduleQueryCommand, TransferTrainQueryCommand,
 };
 use crate::application::service::train_query::{
-    DirectTrainQueryDTO, SeatInfoDTO, StoppingStationInfo, TrainInfoDTO, TrainQueryResponseDTO,
-    TrainQueryService, TrainQueryServiceError, TransferSolutionDTO, TransferTrainQueryDTO,
+    DirectTrainQueryDTO, FareCalendarDTO, FareCalendarDayDTO, JourneyQueryDTO, JourneySolutionDTO,
+    SeatInfoDTO, StoppingStationInfo, TrainInfoDTO, TrainQueryResponseDTO, TrainQueryService,
+    TrainQueryServiceError, TransferSolutionDTO, TransferTrainQueryDTO,
 };
 use crate::application::{ApplicationError, GeneralError};
 use crate::domain::Identifiable;
 use crate::domain::model::station::StationId;
 use crate::domain::model::train::Train;
+use crate::domain::model::train_schedule::StationRange;
 use crate::domain::repository::route::RouteRepository;
 use crate::domain::repository::station::StationRepository;
 use crate::domain::repository::train::TrainRepository;
+use crate::domain::service::fare::FareService;
+use crate::domain::service::pricing::{PriceQuoteQuery, PricingService};
 use crate::domain::service::route::RouteService;
 use crate::domain::service::session::SessionManagerService;
 use crate::domain::service::station::StationService;
 use crate::domain::service::train_schedule::{TrainScheduleService, TrainScheduleServiceError};
+use crate::domain::service::train_seat::{SeatCountQuery, TrainSeatService};
 use async_trait::async_trait;
 use chrono::{Duration, FixedOffset, NaiveDate};
 use rust_decimal::prelude::ToPrimitive;
@@ -62,9 +67,96 @@ use shared::utils::TimeMeter;
 use std::collections::HashMap;
 use tracing::{error, info, instrument};
 
+/// 未指定时的默认最多换乘次数
+const DEFAULT_MAX_TRANSFERS: u32 = 2;
+/// 允许查询的最多换乘次数上限
+const MAX_TRANSFERS_LIMIT: u32 = 3;
+/// 直达车次查询每页数量上限
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// 计算中转方案的总票价
+///
+/// 指定座位类型时使用该座位类型的票价，否则使用各段的最低票价。
+fn transfer_solution_price(solution: &TransferSolutionDTO, seat_type: Option<&String>) -> u32 {
+    [&solution.first_ride, &solution.second_ride]
+        .into_iter()
+        .map(|ride| {
+            seat_type
+                .and_then(|seat_type| ride.seat_info.get(seat_type))
+                .map_or(ride.price, |seat_info| seat_info.price)
+        })
+        .sum()
+}
+
+/// 直达车次查询结果的排序键，同时作为分页游标
+///
+/// 主键相同时依次按车次号、出发时间、到达时间排序，保证排序结果唯一。
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct DirectTrainOrderKey {
+    primary: i64,
+    train_number: String,
+    departure_timestamp: i64,
+    arrival_timestamp: i64,
+}
+
+impl DirectTrainOrderKey {
+    fn new(info: &TrainInfoDTO, sort_by: DirectTrainSortKey) -> Self {
+        let departure_timestamp = DateTimeWithTimeZone::parse_from_rfc3339(&info.departure_time)
+            .unwrap()
+            .timestamp();
+        let arrival_timestamp = DateTimeWithTimeZone::parse_from_rfc3339(&info.arrival_time)
+            .unwrap()
+            .timestamp();
+
+        let primary = match sort_by {
+            DirectTrainSortKey::Departure => departure_timestamp,
+            DirectTrainSortKey::Arrival => arrival_timestamp,
+            DirectTrainSortKey::Duration => info.travel_time as i64,
+            DirectTrainSortKey::Price => info.price as i64,
+        };
+
+        Self {
+            primary,
+            train_number: info.train_number.clone(),
+            departure_timestamp,
+            arrival_timestamp,
+        }
+    }
+
+    /// 编码为分页游标，格式：`{sort_by}.{primary}.{train_number}.{departure}.{arrival}`
+    fn encode(&self, sort_by: DirectTrainSortKey) -> String {
+        format!(
+            "{}.{}.{}.{}.{}",
+            <&'static str>::from(sort_by),
+            self.primary,
+            self.train_number,
+            self.departure_timestamp,
+            self.arrival_timestamp
+        )
+    }
+
+    /// 解析分页游标，格式错误或排序方式与游标不一致时返回`None`
+    fn decode(cursor: &str, sort_by: DirectTrainSortKey) -> Option<Self> {
+        let mut parts = cursor.split('.');
+
+        if parts.next()? != <&'static str>::from(sort_by) {
+            return None;
+        }
+
+        let key = Self {
+            primary: parts.next()?.parse().ok()?,
+            train_number: parts.next()?.to_string(),
+            departure_timestamp: parts.next()?.parse().ok()?,
+            arrival_timestamp: parts.next()?.parse().ok()?,
+        };
+
+        parts.next().is_none().then_some(key)
+    }
+}
+
 // Thinking 1.2.1D - 4: 为何需要使用`+ 'static + Send + Sync`约束泛型参数？
 // Thinking 1.2.1D - 5: 为何需要使用`Arc<T>`存储领域服务？为何无需使用`Arc<Mutex<T>>`？
-pub struct TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR>
+pub struct TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS>
 where
     T: TrainScheduleService + 'static + Send + Sync,
     U: StationService + 'static + Send + Sync,
@@ -73,6 +165,9 @@ where
     RR: RouteRepository,
     TR: TrainRepository,
     SR: StationRepository,
+    TSS: TrainSeatService,
+    FS: FareService,
+    PS: PricingService,
 {
     // Step 3: Store service instance you need using `Arc<T>` and generics parameter
     // HINT: You may refer to `UserManagerServiceImpl` for example
@@ -84,13 +179,18 @@ where
     route_repository: Arc<RR>,
     train_repository: Arc<TR>,
     station_repository: Arc<SR>,
+    train_seat_service: Arc<TSS>,
+    fare_service: Arc<FS>,
+    pricing_service: Arc<PS>,
     tz_offset_hour: i32,
+    auto_schedule_days: i32,
 }
 
 // Step 4: Implement `new` associate function for `TrainQueryServiceImpl`
 // HINT: You may refer to `UserManagerServiceImpl` for example
 // Exercise 1.2.1D - 5: Your code here. (3 / 6)
-impl<T, U, W, SMS, RR, TR, SR> TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR>
+impl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS>
+    TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS>
 where
     T: TrainScheduleService + 'static + Send + Sync,
     U: StationService + 'static + Send + Sync,
@@ -99,6 +199,9 @@ where
     RR: RouteRepository,
     TR: TrainRepository,
     SR: StationRepository,
+    TSS: TrainSeatService,
+    FS: FareService,
+    PS: PricingService,
 {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -109,7 +212,11 @@ where
         route_repository: Arc<RR>,
         train_repository: Arc<TR>,
         station_repository: Arc<SR>,
+        train_seat_service: Arc<TSS>,
+        fare_service: Arc<FS>,
+        pricing_service: Arc<PS>,
         tz_offset_hour: i32,
+        auto_schedule_days: i32,
     ) -> Self {
         TrainQueryServiceImpl {
             train_schedule_service,
@@ -119,7 +226,11 @@ where
             route_repository,
             train_repository,
             station_repository,
+            train_seat_service,
+            fare_service,
+            pricing_service,
             tz_offset_hour,
+            auto_schedule_days,
         }
     }
 
@@ -183,7 +294,8 @@ where
 // HINT: You may refer to `UserManagerServiceImpl` for example
 // Exercise 1.2.1D - 5: Your code here. (4 / 6)
 #[async_trait]
-impl<T, U, W, SMS, RR, TR, SR> TrainQueryService for TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR>
+impl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS> TrainQueryService
+    for TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS>
 where
     T: TrainScheduleService + 'static + Send + Sync,
     U: StationService + 'static + Send + Sync,
@@ -192,6 +304,9 @@ where
     RR: RouteRepository,
     TR: TrainRepository,
     SR: StationRepository,
+    TSS: TrainSeatService,
+    FS: FareService,
+    PS: PricingService,
 {
     #[instrument(skip(self))]
     async fn query_train(
@@ -381,6 +496,41 @@ where
 
         cmd.validate()?;
 
+        for (from, to, name) in [
+            (cmd.departure_time_from, cmd.departure_time_to, "departure"),
+            (cmd.arrival_time_from, cmd.arrival_time_to, "arrival"),
+        ] {
+            if let (Some(from), Some(to)) = (from, to)
+                && from > to
+            {
+                return Err(Box::new(GeneralError::BadRequest(format!(
+                    "invalid {} time window",
+                    name
+                ))));
+            }
+        }
+
+        if cmd
+            .limit
+            .is_some_and(|limit| limit == 0 || limit > MAX_PAGE_SIZE)
+        {
+            return Err(Box::new(GeneralError::BadRequest(format!(
+                "limit should be between 1 and {}",
+                MAX_PAGE_SIZE
+            ))));
+        }
+
+        let sort_by = cmd.sort_by.unwrap_or_default();
+
+        let cursor = cmd
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                DirectTrainOrderKey::decode(cursor, sort_by)
+                    .ok_or(GeneralError::BadRequest("invalid cursor".to_string()))
+            })
+            .transpose()?;
+
         meter.meter("verify session and command");
 
         let from_ids = self
@@ -463,8 +613,16 @@ where
                     GeneralError::InternalServerError
                 })?;
 
-            infos.push(
-                self.build_dto(
+            if cmd.train_type_prefixes.as_ref().is_some_and(|prefixes| {
+                !prefixes
+                    .iter()
+                    .any(|prefix| train.number().starts_with(prefix.as_str()))
+            }) {
+                continue;
+            }
+
+            let info = self
+                .build_dto(
                     &sch,
                     train,
                     &routes,
@@ -473,28 +631,112 @@ where
                     Some(from_station),
                     Some(to_station),
                 )
-                .await?,
-            );
+                .await?;
+
+            let departure_time = DateTimeWithTimeZone::parse_from_rfc3339(&info.departure_time)
+                .unwrap()
+                .time();
+            let arrival_time = DateTimeWithTimeZone::parse_from_rfc3339(&info.arrival_time)
+                .unwrap()
+                .time();
+
+            if cmd.departure_time_from.is_some_and(|t| departure_time < t)
+                || cmd.departure_time_to.is_some_and(|t| departure_time > t)
+                || cmd.arrival_time_from.is_some_and(|t| arrival_time < t)
+                || cmd.arrival_time_to.is_some_and(|t| arrival_time > t)
+            {
+                continue;
+            }
+
+            infos.push((info, sch, from_station, to_station));
         }
 
-        meter.meter("build train info DTOs");
+        meter.meter("build and filter train info DTOs");
+
+        if let Some(seat_types) = cmd.seat_types.as_ref().filter(|v| !v.is_empty()) {
+            let mut seat_queries = Vec::with_capacity(infos.len() * seat_types.len());
+            let mut candidates = Vec::with_capacity(infos.len());
+
+            // 缺少任一要求座位类型的车次直接排除，其余车次每个座位类型对应一项余票查询
+            for (info, sch, from_station, to_station) in infos {
+                let Some(seat_type_list) = seat_types
+                    .iter()
+                    .map(|seat_type| train_id_to_train[&sch.train_id()].seats().get(seat_type))
+                    .collect::<Option<Vec<_>>>()
+                else {
+                    continue;
+                };
+
+                for seat_type in seat_type_list {
+                    seat_queries.push(SeatCountQuery {
+                        train_schedule_id: sch.get_id().expect("Train schedule should have id"),
+                        route_id: sch.route_id(),
+                        seat_type: seat_type.clone(),
+                        station_range: StationRange::from_unchecked(from_station, to_station),
+                    });
+                }
 
-        // infos.sort_by(|a, b| {
-        //     let a_time = DateTimeWithTimeZone::parse_from_rfc3339(&a.departure_time).unwrap();
-        //     let b_time = DateTimeWithTimeZone::parse_from_rfc3339(&b.departure_time).unwrap();
-        //     a_time.cmp(&b_time)
-        // });
+                candidates.push((info, sch, from_station, to_station));
+            }
 
-        // // 限制结果数量为50个，应前端要求
-        // if infos.len() > 50 {
-        //     infos.truncate(50);
-        // }
+            let seat_counts = self
+                .train_seat_service
+                .available_seats_count_batch(&seat_queries)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get available seats count: {:?}", e);
+                    GeneralError::InternalServerError
+                })?;
+
+            // `seat_queries`中每个车次依次对应`seat_types.len()`项
+            infos = candidates
+                .into_iter()
+                .zip(seat_counts.chunks_exact(seat_types.len()))
+                .filter(|(_, counts)| counts.iter().all(|count| *count > 0))
+                .map(|((mut info, sch, from_station, to_station), counts)| {
+                    for (seat_type, count) in seat_types.iter().zip(counts) {
+                        if let Some(seat_info) = info.seat_info.get_mut(seat_type) {
+                            seat_info.left = *count;
+                        }
+                    }
+                    (info, sch, from_station, to_station)
+                })
+                .collect();
+
+            meter.meter("check available seats");
+        }
+
+        let mut infos = infos
+            .into_iter()
+            .map(|(info, ..)| (DirectTrainOrderKey::new(&info, sort_by), info))
+            .collect::<Vec<_>>();
 
-        // meter.meter("sort and limit results");
+        infos.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let total = infos.len() as u32;
+
+        let begin = cursor.map_or(0, |cursor| infos.partition_point(|(key, _)| *key <= cursor));
+        let end = cmd.limit.map_or(infos.len(), |limit| {
+            (begin + limit as usize).min(infos.len())
+        });
+
+        let next_cursor =
+            (end < infos.len() && end > begin).then(|| infos[end - 1].0.encode(sort_by));
+
+        let solutions = infos
+            .drain(begin..end)
+            .map(|(_, info)| info)
+            .collect::<Vec<_>>();
+
+        meter.meter("sort and paginate results");
 
         info!("{}", meter);
 
-        Ok(DirectTrainQueryDTO { solutions: infos })
+        Ok(DirectTrainQueryDTO {
+            solutions,
+            total,
+            next_cursor,
+        })
     }
 
     #[instrument(skip(self))]
@@ -507,6 +749,24 @@ where
         self.verify_session(cmd.session_id.as_str()).await?;
         cmd.validate()?;
 
+        let seat_requirement = match (&cmd.seat_type, cmd.min_seats) {
+            (Some(seat_type), min_seats) => {
+                let min_seats = min_seats.unwrap_or(1);
+                if min_seats == 0 {
+                    return Err(Box::new(GeneralError::BadRequest(
+                        "min seats should be positive".to_string(),
+                    )));
+                }
+                Some((seat_type.clone(), min_seats))
+            }
+            (None, Some(_)) => {
+                return Err(Box::new(GeneralError::BadRequest(
+                    "seat type is required when min seats is specified".to_string(),
+                )));
+            }
+            (None, None) => None,
+        };
+
         meter.meter("verify session and command");
 
         let from_ids = self
@@ -591,12 +851,15 @@ where
             .collect::<HashMap<_, _>>();
 
         let mut solutions = Vec::new();
-        for (schedule_ids, from_station, to_station, mid_station_opt) in transfer_solutions {
-            if schedule_ids.len() != 2 || mid_station_opt.is_none() {
+        let mut seat_queries = Vec::new();
+        for (schedule_ids, from_station, to_station, transfer_point_opt) in transfer_solutions {
+            if schedule_ids.len() != 2 || transfer_point_opt.is_none() {
                 continue;
             }
 
-            let mid_station = mid_station_opt.unwrap();
+            let transfer_point = transfer_point_opt.unwrap();
+            let mid_station = transfer_point.arrival_station;
+            let transfer_station = transfer_point.departure_station;
 
             let station_name = station_id_to_name
                 .get(&mid_station)
@@ -607,6 +870,18 @@ where
                     GeneralError::InternalServerError
                 })?;
 
+            let transfer_station_name = station_id_to_name
+                .get(&transfer_station)
+                .cloned()
+                .ok_or_else(|| {
+                    error!(
+                        "Inconsistent: No station found for id: {}",
+                        transfer_station
+                    );
+
+                    GeneralError::InternalServerError
+                })?;
+
             let first_schedule = match schedule_by_id.get(&schedule_ids[0]) {
                 Some(s) => s,
                 None => continue,
@@ -650,7 +925,7 @@ where
                     &routes,
                     &station_id_to_name,
                     cmd.departure_time,
-                    Some(mid_station),
+                    Some(transfer_station),
                     Some(to_station),
                 )
                 .await
@@ -687,13 +962,13 @@ where
             let second_mid_idx = match second_dto
                 .route
                 .iter()
-                .position(|stop| stop.station_name == station_name)
+                .position(|stop| stop.station_name == transfer_station_name)
             {
                 Some(idx) => idx,
                 None => continue,
             };
 
-            second_dto.departure_station = station_name.clone();
+            second_dto.departure_station = transfer_station_name;
             second_dto.departure_time = second_dto.route[second_mid_idx]
                 .departure_time
                 .clone()
@@ -714,21 +989,83 @@ where
                 (second_dep_dt + Duration::days(1) - first_arr_dt).num_seconds() as u32
             };
 
+            // 需检查余票时，记录两段行程的余票查询，稍后统一批量查询
+            if let Some((seat_type_name, _)) = &seat_requirement {
+                let (Some(first_seat_type), Some(second_seat_type)) = (
+                    train_id_to_train
+                        .get(&first_schedule.train_id())
+                        .and_then(|t| t.seats().get(seat_type_name)),
+                    train_id_to_train
+                        .get(&second_schedule.train_id())
+                        .and_then(|t| t.seats().get(seat_type_name)),
+                ) else {
+                    continue;
+                };
+
+                seat_queries.push(SeatCountQuery {
+                    train_schedule_id: schedule_ids[0],
+                    route_id: first_schedule.route_id(),
+                    seat_type: first_seat_type.clone(),
+                    station_range: StationRange::from_unchecked(from_station, mid_station),
+                });
+                seat_queries.push(SeatCountQuery {
+                    train_schedule_id: schedule_ids[1],
+                    route_id: second_schedule.route_id(),
+                    seat_type: second_seat_type.clone(),
+                    station_range: StationRange::from_unchecked(transfer_station, to_station),
+                });
+            }
+
             solutions.push(TransferSolutionDTO {
                 first_ride: first_dto,
                 second_ride: second_dto,
                 relaxing_time,
+                transfer_type: transfer_point.transfer_type().to_string(),
+                transfer_time: transfer_point.transfer_time,
             });
         }
 
         meter.meter("build transfer solutions");
 
-        solutions.sort_by(|a, b| {
-            let a_total_time =
-                a.first_ride.travel_time + a.relaxing_time + a.second_ride.travel_time;
-            let b_total_time =
-                b.first_ride.travel_time + b.relaxing_time + b.second_ride.travel_time;
-            a_total_time.cmp(&b_total_time)
+        if let Some((seat_type_name, min_seats)) = &seat_requirement {
+            let seat_counts = self
+                .train_seat_service
+                .available_seats_count_batch(&seat_queries)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get available seats count: {:?}", e);
+                    GeneralError::InternalServerError
+                })?;
+
+            // `seat_queries`中每个方案依次对应两项
+            solutions = solutions
+                .into_iter()
+                .zip(seat_counts.chunks_exact(2))
+                .filter(|(_, counts)| counts.iter().all(|count| count >= min_seats))
+                .map(|(mut solution, counts)| {
+                    for (ride, count) in [&mut solution.first_ride, &mut solution.second_ride]
+                        .into_iter()
+                        .zip(counts)
+                    {
+                        if let Some(seat_info) = ride.seat_info.get_mut(seat_type_name) {
+                            seat_info.left = *count;
+                        }
+                    }
+                    solution
+                })
+                .collect();
+
+            meter.meter("check available seats");
+        }
+
+        // 按总耗时排序，总耗时相同时按总票价排序
+        solutions.sort_by_key(|solution| {
+            (
+                solution.first_ride.travel_time
+                    + solution.relaxing_time
+                    + solution.second_ride.travel_time,
+                transfer_solution_price(solution, seat_requirement.as_ref().map(|r| &r.0)),
+            )
         });
 
         // 限制结果数量为50个，应前端要求
@@ -742,9 +1079,410 @@ where
 
         Ok(TransferTrainQueryDTO { solutions })
     }
+
+    #[instrument(skip(self))]
+    async fn query_journeys(
+        &self,
+        cmd: JourneyQueryCommand,
+    ) -> Result<JourneyQueryDTO, Box<dyn ApplicationError>> {
+        let mut meter = TimeMeter::new("JourneyQuery");
+
+        self.verify_session(cmd.session_id.as_str()).await?;
+        cmd.validate()?;
+
+        let max_transfers = cmd.max_transfers.unwrap_or(DEFAULT_MAX_TRANSFERS);
+        if max_transfers > MAX_TRANSFERS_LIMIT {
+            return Err(Box::new(GeneralError::BadRequest(format!(
+                "max transfers should not exceed {}",
+                MAX_TRANSFERS_LIMIT
+            ))));
+        }
+
+        meter.meter("verify session and command");
+
+        let from_ids = self
+            .resolve_station_ids(&cmd.departure_station, &cmd.departure_city)
+            .await?;
+        let to_ids = self
+            .resolve_station_ids(&cmd.arrival_station, &cmd.arrival_city)
+            .await?;
+
+        meter.meter("resolve station ids");
+
+        let journeys = self
+            .train_schedule_service
+            .plan_journeys(cmd.departure_time, &from_ids, &to_ids, max_transfers)
+            .await
+            .map_err(|e| {
+                error!("Failed to plan journeys: {:?}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        meter.meter("plan journeys");
+
+        let routes = self.route_service.get_routes().await.map_err(|e| {
+            error!("Failed to get routes: {:?}", e);
+            GeneralError::InternalServerError
+        })?;
+
+        let schedules = self
+            .train_schedule_service
+            .get_schedules(cmd.departure_time)
+            .await
+            .map_err(|e| {
+                error!("Failed to get schedules: {:?}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        let schedule_by_id: HashMap<_, _> = schedules
+            .iter()
+            .filter_map(|s| s.get_id().map(|id| (id, s)))
+            .collect();
+
+        let station_id_to_name = self
+            .station_repository
+            .load()
+            .await
+            .inspect_err(|e| error!("failed to load stations: {:?}", e))
+            .map_err(|_for_super_earth| GeneralError::InternalServerError)?
+            .into_iter()
+            .map(|s| {
+                (
+                    s.get_id().expect("Station should have id"),
+                    s.name().to_string(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let train_id_to_train = self
+            .train_repository
+            .get_trains()
+            .await
+            .inspect_err(|_for_super_earth| error!("Failed to load trains"))
+            .map_err(|_for_super_earth| GeneralError::InternalServerError)?
+            .into_iter()
+            .map(|t| (t.get_id().expect("Train should have id"), t))
+            .collect::<HashMap<_, _>>();
+
+        meter.meter("load schedules, routes, stations and trains");
+
+        let mut solutions = Vec::with_capacity(journeys.len());
+
+        for journey in journeys {
+            let mut rides = Vec::with_capacity(journey.legs.len());
+
+            for leg in &journey.legs {
+                let schedule = schedule_by_id.get(&leg.train_schedule_id).ok_or_else(|| {
+                    error!(
+                        "Inconsistent: No train schedule found for id: {}",
+                        leg.train_schedule_id
+                    );
+                    GeneralError::InternalServerError
+                })?;
+
+                let train = train_id_to_train
+                    .get(&schedule.train_id())
+                    .cloned()
+                    .ok_or_else(|| {
+                        error!(
+                            "Inconsistent: No train found for schedule id: {}",
+                            leg.train_schedule_id
+                        );
+                        GeneralError::InternalServerError
+                    })?;
+
+                rides.push(
+                    self.build_dto(
+                        schedule,
+                        train,
+                        &routes,
+                        &station_id_to_name,
+                        cmd.departure_time,
+                        Some(leg.departure_station),
+                        Some(leg.arrival_station),
+                    )
+                    .await?,
+                );
+            }
+
+            let relaxing_times = journey
+                .legs
+                .windows(2)
+                .map(|pair| pair[1].departure_time - pair[0].arrival_time)
+                .collect();
+
+            let transfer_types = journey
+                .transfers
+                .iter()
+                .map(|transfer| transfer.transfer_type().to_string())
+                .collect();
+
+            let transfer_times = journey
+                .transfers
+                .iter()
+                .map(|transfer| transfer.transfer_time)
+                .collect();
+
+            let total_time = match (journey.legs.first(), journey.legs.last()) {
+                (Some(first), Some(last)) => last.arrival_time - first.departure_time,
+                _ => 0,
+            };
+
+            let price = rides.iter().map(|ride| ride.price).sum();
+
+            solutions.push(JourneySolutionDTO {
+                rides,
+                relaxing_times,
+                transfer_types,
+                transfer_times,
+                transfer_count: journey.transfer_count(),
+                total_time,
+                price,
+            });
+        }
+
+        meter.meter("build journey solutions");
+
+        // 限制结果数量为50个，应前端要求
+        solutions.truncate(50);
+
+        info!("{}", meter);
+
+        Ok(JourneyQueryDTO { solutions })
+    }
+
+    #[instrument(skip(self))]
+    async fn query_fare_calendar(
+        &self,
+        cmd: FareCalendarQueryCommand,
+    ) -> Result<FareCalendarDTO, Box<dyn ApplicationError>> {
+        let mut meter = TimeMeter::new("FareCalendarQuery");
+
+        self.verify_session(cmd.session_id.as_str()).await?;
+        cmd.validate()?;
+
+        let days = (cmd.end_date - cmd.begin_date).num_days() + 1;
+        if days <= 0 || days > self.auto_schedule_days as i64 {
+            return Err(Box::new(GeneralError::BadRequest(format!(
+                "date range should be within 1 to {} days",
+                self.auto_schedule_days
+            ))));
+        }
+
+        meter.meter("verify session and command");
+
+        let from_ids = self
+            .resolve_station_ids(&cmd.departure_station, &cmd.departure_city)
+            .await?;
+        let to_ids = self
+            .resolve_station_ids(&cmd.arrival_station, &cmd.arrival_city)
+            .await?;
+
+        let station_pairs: Vec<(StationId, StationId)> = from_ids
+            .iter()
+            .flat_map(|f| to_ids.iter().map(move |t| (*f, *t)))
+            .collect();
+
+        meter.meter("resolve station ids");
+
+        let route_by_id = self
+            .route_service
+            .get_routes()
+            .await
+            .map_err(|e| {
+                error!("Failed to get routes: {:?}", e);
+                GeneralError::InternalServerError
+            })?
+            .into_iter()
+            .map(|r| (r.get_id().expect("Route should have id"), r))
+            .collect::<HashMap<_, _>>();
+
+        let train_id_to_train = self
+            .train_repository
+            .get_trains()
+            .await
+            .inspect_err(|_for_super_earth| error!("Failed to load trains"))
+            .map_err(|_for_super_earth| GeneralError::InternalServerError)?
+            .into_iter()
+            .map(|t| (t.get_id().expect("Train should have id"), t))
+            .collect::<HashMap<_, _>>();
+
+        meter.meter("load routes and trains");
+
+        // (日期, [(出发时刻偏移, [座位类型])])，各车次的座位列表依次与`seat_queries`、`quote_queries`一一对应
+        let mut day_infos = Vec::with_capacity(days as usize);
+        let mut seat_queries = Vec::new();
+        let mut quote_queries = Vec::new();
+
+        for date in cmd.begin_date.iter_days().take(days as usize) {
+            let schedules = self
+                .train_schedule_service
+                .direct_schedules(date, &station_pairs)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get direct schedules: {:?}", e);
+                    GeneralError::InternalServerError
+                })?;
+
+            let mut schedule_infos = Vec::with_capacity(schedules.len());
+
+            for (sch, from_station, to_station) in &schedules {
+                let route = route_by_id.get(&sch.route_id()).ok_or_else(|| {
+                    error!(
+                        "Inconsistent: No route found for schedule id: {}",
+                        sch.get_id().unwrap()
+                    );
+                    GeneralError::InternalServerError
+                })?;
+
+                let train = train_id_to_train.get(&sch.train_id()).ok_or_else(|| {
+                    error!(
+                        "Inconsistent: No train found for schedule id: {}",
+                        sch.get_id().unwrap()
+                    );
+                    GeneralError::InternalServerError
+                })?;
+
+                let (Some(from_idx), Some(to_idx)) = (
+                    route
+                        .stops()
+                        .iter()
+                        .position(|stop| stop.station_id() == *from_station),
+                    route
+                        .stops()
+                        .iter()
+                        .position(|stop| stop.station_id() == *to_station),
+                ) else {
+                    continue;
+                };
+
+                let departure_offset =
+                    route.stops()[from_idx].departure_time() + sch.origin_departure_time() as u32;
+
+                let mut seat_type_names = Vec::new();
+
+                for seat_type in train.seats().values() {
+                    let base_fare = self
+                        .fare_service
+                        .calculate_fare(train.train_type(), seat_type, route, from_idx, to_idx)
+                        .map_err(|e| {
+                            error!("Failed to calculate fare: {}", e);
+                            GeneralError::InternalServerError
+                        })?;
+
+                    let train_schedule_id = sch.get_id().expect("Train schedule should have id");
+                    let station_range = StationRange::from_unchecked(*from_station, *to_station);
+
+                    seat_queries.push(SeatCountQuery {
+                        train_schedule_id,
+                        route_id: sch.route_id(),
+                        seat_type: seat_type.clone(),
+                        station_range,
+                    });
+                    quote_queries.push(PriceQuoteQuery {
+                        train_schedule_id,
+                        route_id: sch.route_id(),
+                        date: sch.date(),
+                        seat_type: seat_type.clone(),
+                        station_range,
+                        base_fare,
+                    });
+                    seat_type_names.push(seat_type.name().to_string());
+                }
+
+                schedule_infos.push((departure_offset, seat_type_names));
+            }
+
+            day_infos.push((date, schedule_infos));
+        }
+
+        meter.meter("get direct schedules");
+
+        let seat_counts = self
+            .train_seat_service
+            .available_seats_count_batch(&seat_queries)
+            .await
+            .map_err(|e| {
+                error!("Failed to get available seats count: {:?}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        meter.meter("count available seats");
+
+        let prices = self
+            .pricing_service
+            .quote_fares(&quote_queries)
+            .await
+            .map_err(|e| {
+                error!("Failed to quote fares: {}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        meter.meter("quote fares");
+
+        let mut seat_counts = seat_counts.into_iter().zip(prices);
+        let mut result = Vec::with_capacity(day_infos.len());
+
+        for (date, schedule_infos) in day_infos {
+            let mut train_count = 0;
+            let mut earliest_departure_offset: Option<u32> = None;
+            let mut lowest_price: HashMap<String, u32> = HashMap::new();
+
+            for (departure_offset, seat_type_names) in schedule_infos {
+                let mut contributed = false;
+
+                for (seat_type, (count, price)) in seat_type_names.into_iter().zip(&mut seat_counts)
+                {
+                    if count == 0 {
+                        continue;
+                    }
+
+                    let price = price.to_u32().unwrap_or(0);
+
+                    lowest_price
+                        .entry(seat_type)
+                        .and_modify(|p| *p = (*p).min(price))
+                        .or_insert(price);
+                    contributed = true;
+                }
+
+                // 只统计实际提供了票价（仍有余票）的车次
+                if contributed {
+                    train_count += 1;
+                    earliest_departure_offset = Some(
+                        earliest_departure_offset
+                            .map_or(departure_offset, |t| t.min(departure_offset)),
+                    );
+                }
+            }
+
+            let earliest_departure_time = earliest_departure_offset.map(|offset| {
+                date.and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .checked_add_signed(Duration::seconds(offset as i64))
+                    .unwrap()
+                    .and_local_timezone(FixedOffset::east_opt(self.tz_offset_hour * 3600).unwrap())
+                    .unwrap()
+                    .to_rfc3339()
+            });
+
+            result.push(FareCalendarDayDTO {
+                date: date.to_string(),
+                train_count,
+                earliest_departure_time,
+                has_seats: !lowest_price.is_empty(),
+                lowest_price,
+            });
+        }
+
+        info!("{}", meter);
+
+        Ok(FareCalendarDTO { days: result })
+    }
 }
 
-impl<T, U, W, SMS, RR, TR, SR> TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR>
+impl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS>
+    TrainQueryServiceImpl<T, U, W, SMS, RR, TR, SR, TSS, FS, PS>
 where
     T: TrainScheduleService + 'static + Send + Sync,
     U: StationService + 'static + Send + Sync,
@@ -753,6 +1491,9 @@ where
     RR: RouteRepository,
     TR: TrainRepository,
     SR: StationRepository,
+    TSS: TrainSeatService,
+    FS: FareService,
+    PS: PricingService,
 {
     #[allow(clippy::too_many_arguments)]
     #[instrument(skip(self, routes, station_id_to_name))]
@@ -828,23 +1569,6 @@ where
             });
         }
 
-        // ——— 列车 / 座位 ———
-        let stations_count = stopping.len() - 1;
-
-        let mut seat_info = HashMap::new();
-        for seat in train.seats().values() {
-            let unit_price = seat.unit_price().to_f64().unwrap_or(0.0) as u32;
-            let total_price = unit_price * stations_count as u32;
-            seat_info.insert(
-                seat.name().to_string(),
-                SeatInfoDTO {
-                    seat_type: seat.name().to_string(),
-                    left: seat.capacity(),
-                    price: total_price,
-                },
-            );
-        }
-
         // ——— 其余字段 ———
         let user_dep_station_name = user_departure_station
             .and_then(|id| station_id_to_name.get(&id).cloned())
@@ -864,6 +1588,54 @@ where
             .position(|stop| stop.station_name == user_arr_station_name)
             .expect("arrival station should exist in stopping");
 
+        // ——— 列车 / 座位 ———
+        let seat_types = train.seats().values().collect::<Vec<_>>();
+
+        let mut quote_queries = Vec::with_capacity(seat_types.len());
+        for seat in &seat_types {
+            let base_fare = self
+                .fare_service
+                .calculate_fare(train.train_type(), seat, route, user_dep_idx, user_arr_idx)
+                .map_err(|e| {
+                    error!("Failed to calculate fare: {}", e);
+                    GeneralError::InternalServerError
+                })?;
+
+            quote_queries.push(PriceQuoteQuery {
+                train_schedule_id: sch.get_id().expect("Train schedule should have id"),
+                route_id: sch.route_id(),
+                date: sch.date(),
+                seat_type: (*seat).clone(),
+                station_range: StationRange::from_unchecked(
+                    route.stops()[user_dep_idx].station_id(),
+                    route.stops()[user_arr_idx].station_id(),
+                ),
+                base_fare,
+            });
+        }
+
+        // 与下单时使用同一报价，保证报价与扣款一致
+        let prices = self
+            .pricing_service
+            .quote_fares(&quote_queries)
+            .await
+            .map_err(|e| {
+                error!("Failed to quote fares: {}", e);
+                GeneralError::InternalServerError
+            })?;
+
+        let mut seat_info = HashMap::new();
+        for (seat, price) in seat_types.into_iter().zip(prices) {
+            seat_info.insert(
+                seat.name().to_string(),
+                SeatInfoDTO {
+                    seat_type: seat.name().to_string(),
+                    left: seat.capacity(),
+                    price: price.to_u32().unwrap_or(0),
+                },
+            );
+        }
+
         let dep_time = stopping[user_dep_idx]
             .departure_time
             .as_ref()
@@ -920,3 +1692,55 @@ where
 // Exercise 1.2.1D - 5: Your code here. (6 / 6)
 
 // Good! Next, register your application service in `api::main`
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_train_info(train_number: &str, departure_time: &str, price: u32) -> TrainInfoDTO {
+        TrainInfoDTO {
+            departure_station: "北京南".to_string(),
+            departure_time: departure_time.to_string(),
+            arrival_station: "上海虹桥".to_string(),
+            arrival_time: "2025-05-01T18:00:00+08:00".to_string(),
+            origin_station: "北京南".to_string(),
+            origin_departure_time: departure_time.to_string(),
+            terminal_station: "上海虹桥".to_string(),
+            terminal_arrival_time: "2025-05-01T18:00:00+08:00".to_string(),
+            train_number: train_number.to_string(),
+            travel_time: 0,
+            price,
+            route: Vec::new(),
+            seat_info: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_direct_train_order_key_cursor() {
+        let early = make_train_info("G2", "2025-05-01T08:00:00+08:00", 600);
+        let late = make_train_info("G1", "2025-05-01T09:00:00+08:00", 500);
+
+        let early_key = DirectTrainOrderKey::new(&early, DirectTrainSortKey::Departure);
+        let late_key = DirectTrainOrderKey::new(&late, DirectTrainSortKey::Departure);
+        assert!(early_key < late_key);
+
+        let price_early_key = DirectTrainOrderKey::new(&early, DirectTrainSortKey::Price);
+        let price_late_key = DirectTrainOrderKey::new(&late, DirectTrainSortKey::Price);
+        assert!(price_late_key < price_early_key);
+
+        let cursor = early_key.encode(DirectTrainSortKey::Departure);
+        assert_eq!(
+            DirectTrainOrderKey::decode(&cursor, DirectTrainSortKey::Departure),
+            Some(early_key)
+        );
+        // 排序方式与游标不一致时视为无效游标
+        assert_eq!(
+            DirectTrainOrderKey::decode(&cursor, DirectTrainSortKey::Price),
+            None
+        );
+        assert_eq!(
+            DirectTrainOrderKey::decode("departure.1.G1", DirectTrainSortKey::Departure),
+            None
+        );
+    }
+}
@@ -1,36 +1,197 @@
 use crate::application::commands::transaction::{
-    BalanceQuery, CancelOrderCommand, GenerateDebugTransactionCommand, PayTransactionCommand,
-    RechargeCommand, SetPaymentPasswordCommand, TransactionDetailQuery, TransactionQuery,
+    BalanceQuery, CancelOrderCommand, GenerateDebugTransactionCommand, OrderTimelineQuery,
+    PartialRefundCommand, PayTransactionCommand, PaymentCallbackCommand, RechargeCommand,
+    SetPaymentPasswordCommand, TransactionDetailQuery, TransactionQuery, WalletStatementQuery,
 };
 use crate::application::service::transaction::{
-    BalanceInfoDTO, TransactionApplicationService, TransactionApplicationServiceError,
-    TransactionInfoDTO,
+    BalanceInfoDTO, OrderStatusHistoryDTO, RechargeIntentDTO, StatementEntryDTO,
+    TransactionApplicationService, TransactionApplicationServiceError, TransactionInfoDTO,
+    WalletStatementDTO,
 };
 use crate::application::{ApplicationError, GeneralError, ModeError};
-use crate::domain::Identifiable;
+use crate::domain::model::order::Order;
 use crate::domain::model::session::SessionId;
-use crate::domain::model::transaction::{Transaction, TransactionAmountAbs};
+use crate::domain::model::transaction::{
+    Transaction, TransactionAmountAbs, TransactionId, TransactionKind,
+};
 use crate::domain::model::user::{PaymentPassword, User, UserId};
-use crate::domain::repository::transaction::TransactionRepository;
+use crate::domain::repository::order::OrderRepository;
+use crate::domain::repository::transaction::{
+    StatementCursor, StatementFilter, TransactionRepository,
+};
 use crate::domain::repository::user::UserRepository;
 use crate::domain::service::order::order_dto::TransactionDataDto;
 use crate::domain::service::session::SessionManagerService;
 use crate::domain::service::transaction::{TransactionService, TransactionServiceError};
 use crate::domain::service::user::{UserService, UserServiceError};
+use crate::domain::{DbId, Identifiable};
 use async_trait::async_trait;
+use chrono::{DateTime, Days, FixedOffset, NaiveDate, NaiveTime};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use sea_orm::prelude::DateTimeWithTimeZone;
 use shared::utils::TimeMeter;
 use std::sync::Arc;
 use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+
+/// 单页账单条目的最大数量
+const MAX_STATEMENT_PAGE_SIZE: u32 = 100;
+
+/// 解析`YYYY-MM-DD`格式的日期，返回该日期零点在`offset`时区下的时间
+fn parse_statement_date(
+    date: &str,
+    offset: FixedOffset,
+) -> Result<DateTimeWithTimeZone, GeneralError> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| GeneralError::BadRequest(format!("invalid date: {}", date)))?;
+
+    date.and_time(NaiveTime::MIN)
+        .and_local_timezone(offset)
+        .single()
+        .ok_or_else(|| GeneralError::BadRequest(format!("invalid date: {}", date)))
+}
+
+/// 根据查询条件构造账单过滤条件
+///
+/// 起止日期均为闭区间：`begin_date`当天零点起，至`end_date`次日零点止；
+/// 未指定截止日期时截止到`now`。未指定交易类型时不按类型过滤。
+fn parse_statement_filter(
+    query: &WalletStatementQuery,
+    now: DateTimeWithTimeZone,
+) -> Result<StatementFilter, GeneralError> {
+    let offset = *now.offset();
+
+    let begin = query
+        .begin_date
+        .as_deref()
+        .map(|date| parse_statement_date(date, offset))
+        .transpose()?;
+
+    let end = match query.end_date.as_deref() {
+        Some(date) => {
+            let end = parse_statement_date(date, offset)?;
+            end.checked_add_days(Days::new(1))
+                .ok_or_else(|| GeneralError::BadRequest(format!("invalid date: {}", date)))?
+        }
+        None => now,
+    };
+
+    if begin.is_some_and(|begin| begin > end) {
+        return Err(GeneralError::BadRequest("invalid date range".to_string()));
+    }
+
+    let kinds = query
+        .types
+        .iter()
+        .map(|kind| {
+            TransactionKind::try_from(kind.as_str()).map_err(|_| {
+                GeneralError::BadRequest(format!("invalid transaction type: {}", kind))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(StatementFilter { begin, end, kinds })
+}
+
+/// 以账单中的一笔交易作为分页位置，交易未完成或未持久化时返回`None`
+fn statement_cursor_of(transaction: &Transaction) -> Option<StatementCursor> {
+    Some(StatementCursor {
+        finish_time: transaction.finish_time()?,
+        transaction_id: transaction.get_id()?,
+    })
+}
+
+/// 编码账单分页游标，格式为`{完成时间的微秒时间戳}.{交易 ID}`
+fn encode_statement_cursor(cursor: &StatementCursor) -> String {
+    format!(
+        "{}.{}",
+        cursor.finish_time.timestamp_micros(),
+        cursor.transaction_id.to_db_value()
+    )
+}
+
+/// 解码账单分页游标，格式不正确时返回`None`
+fn decode_statement_cursor(cursor: &str, offset: FixedOffset) -> Option<StatementCursor> {
+    let (micros, id) = cursor.split_once('.')?;
+
+    let finish_time = DateTime::from_timestamp_micros(micros.parse().ok()?)?.with_timezone(&offset);
+    let transaction_id = TransactionId::from_db_value(id.parse().ok()?).ok()?;
+
+    Some(StatementCursor {
+        finish_time,
+        transaction_id,
+    })
+}
+
+/// 将账单中的金额转换为`f64`，无法表示时视为内部错误
+fn statement_amount_to_f64(value: Decimal) -> Result<f64, GeneralError> {
+    value.to_f64().ok_or_else(|| {
+        error!("failed to convert statement amount {} to f64", value);
+        GeneralError::InternalServerError
+    })
+}
+
+/// 交易类型在账单中的显示名称
+fn statement_kind_label(kind: &str) -> &'static str {
+    match TransactionKind::try_from(kind) {
+        Ok(TransactionKind::Recharge) => "充值",
+        Ok(TransactionKind::Pay) => "支付",
+        Ok(TransactionKind::Refund) => "退款",
+        Err(_) => "未知",
+    }
+}
+
+/// 转义 CSV 文本字段
+///
+/// 以`=`、`+`、`-`、`@`开头的文本前加`'`，避免被电子表格软件当作公式执行；
+/// 包含逗号、引号或换行的字段按 RFC 4180 用引号包围。
+fn escape_csv_text(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// 将账单渲染为 CSV，末尾附期初与期末余额
+fn render_statement_csv(statement: &WalletStatementDTO) -> String {
+    let mut csv = String::from("时间,交易号,类型,金额,手续费,优惠金额,说明\r\n");
+
+    for entry in &statement.entries {
+        csv.push_str(&format!(
+            "{},{},{},{:.2},{:.2},{:.2},{}\r\n",
+            entry.time,
+            entry.transaction_id,
+            statement_kind_label(&entry.kind),
+            entry.amount,
+            entry.refund_fee,
+            entry.coupon_discount,
+            escape_csv_text(&entry.description),
+        ));
+    }
+
+    csv.push_str("\r\n");
+    csv.push_str(&format!("期初余额,{:.2}\r\n", statement.opening_balance));
+    csv.push_str(&format!("期末余额,{:.2}\r\n", statement.closing_balance));
 
-pub struct TransactionApplicationServiceImpl<S, T, R, U, UR>
+    csv
+}
+
+pub struct TransactionApplicationServiceImpl<S, T, R, U, UR, OR>
 where
     S: SessionManagerService,
     T: TransactionService,
     R: TransactionRepository,
     U: UserService,
     UR: UserRepository,
+    OR: OrderRepository,
 {
     debug_mode: bool,
     session_manager: Arc<S>,
@@ -38,15 +199,17 @@ where
     transaction_repository: Arc<R>,
     user_service: Arc<U>,
     user_repository: Arc<UR>,
+    order_repository: Arc<OR>,
 }
 
-impl<S, T, R, U, UR> TransactionApplicationServiceImpl<S, T, R, U, UR>
+impl<S, T, R, U, UR, OR> TransactionApplicationServiceImpl<S, T, R, U, UR, OR>
 where
     S: SessionManagerService,
     T: TransactionService,
     R: TransactionRepository,
     U: UserService,
     UR: UserRepository,
+    OR: OrderRepository,
 {
     pub fn new(
         debug_mode: bool,
@@ -55,6 +218,7 @@ where
         transaction_repository: Arc<R>,
         user_service: Arc<U>,
         user_repository: Arc<UR>,
+        order_repository: Arc<OR>,
     ) -> Self {
         Self {
             debug_mode,
@@ -63,6 +227,7 @@ where
             transaction_repository,
             user_service,
             user_repository,
+            order_repository,
         }
     }
     async fn get_user_id_by_session_id(
@@ -117,6 +282,215 @@ where
         Ok(())
     }
 
+    /// 查找用户的订单及其所属交易，订单不属于该用户时返回`NotFound`
+    async fn find_user_order(
+        &self,
+        user_id: UserId,
+        order_uuid: Uuid,
+    ) -> Result<(Transaction, Box<dyn Order>), Box<dyn ApplicationError>> {
+        let tx_list = self
+            .transaction_repository
+            .find_by_user_id(user_id)
+            .await
+            .map_err(|e| {
+                error!("failed to find tx list for user_id {}: {}", user_id, e);
+                GeneralError::InternalServerError
+            })?;
+
+        for tx in tx_list {
+            if let Some(order) = tx.orders().iter().find(|order| order.uuid() == order_uuid) {
+                let order = order.clone();
+                return Ok((tx, order));
+            }
+        }
+
+        warn!("No transaction found for order id {}", order_uuid);
+        Err(Box::new(GeneralError::NotFound))
+    }
+
+    /// 加载满足过滤条件的账单条目与期初、期末余额
+    ///
+    /// 指定`limit`时多取一条，用于判断是否存在下一页。
+    async fn load_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+        after: Option<StatementCursor>,
+        limit: Option<u32>,
+    ) -> Result<WalletStatementDTO, Box<dyn ApplicationError>> {
+        let total = self
+            .transaction_repository
+            .count_statement(user_id, filter)
+            .await
+            .map_err(|e| {
+                error!("failed to count statement for user_id {}: {}", user_id, e);
+                GeneralError::InternalServerError
+            })?;
+
+        let mut transactions = self
+            .find_statement(user_id, filter, after, limit.map(|limit| limit as u64 + 1))
+            .await?;
+
+        let next_cursor = match limit {
+            Some(limit) if transactions.len() > limit as usize => {
+                transactions.truncate(limit as usize);
+                transactions
+                    .last()
+                    .and_then(statement_cursor_of)
+                    .map(|cursor| encode_statement_cursor(&cursor))
+            }
+            _ => None,
+        };
+
+        let mut entries = Vec::with_capacity(transactions.len());
+
+        for transaction in transactions {
+            entries.push(self.make_statement_entry(transaction).await?);
+        }
+
+        self.make_statement(user_id, filter, entries, total as u32, next_cursor)
+            .await
+    }
+
+    /// 按`MAX_STATEMENT_PAGE_SIZE`分批加载满足过滤条件的全部账单条目与期初、期末余额，不统计条目总数
+    async fn load_full_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+    ) -> Result<WalletStatementDTO, Box<dyn ApplicationError>> {
+        let mut entries = Vec::new();
+        let mut after = None;
+
+        loop {
+            let transactions = self
+                .find_statement(user_id, filter, after, Some(MAX_STATEMENT_PAGE_SIZE as u64))
+                .await?;
+
+            let is_last_page = transactions.len() < MAX_STATEMENT_PAGE_SIZE as usize;
+            after = transactions.last().and_then(statement_cursor_of);
+
+            for transaction in transactions {
+                entries.push(self.make_statement_entry(transaction).await?);
+            }
+
+            if is_last_page || after.is_none() {
+                break;
+            }
+        }
+
+        let total = entries.len() as u32;
+
+        self.make_statement(user_id, filter, entries, total, None)
+            .await
+    }
+
+    async fn find_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+        after: Option<StatementCursor>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Transaction>, Box<dyn ApplicationError>> {
+        self.transaction_repository
+            .find_statement(user_id, filter, after, limit)
+            .await
+            .map_err(|e| {
+                error!("failed to find statement for user_id {}: {}", user_id, e);
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })
+    }
+
+    /// 为账单条目补充期初、期末余额
+    async fn make_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+        entries: Vec<StatementEntryDTO>,
+        total: u32,
+        next_cursor: Option<String>,
+    ) -> Result<WalletStatementDTO, Box<dyn ApplicationError>> {
+        let opening_balance = match filter.begin {
+            Some(begin) => self.get_balance_before(user_id, begin).await?,
+            None => Decimal::ZERO,
+        };
+        let closing_balance = self.get_balance_before(user_id, filter.end).await?;
+
+        Ok(WalletStatementDTO {
+            opening_balance: statement_amount_to_f64(opening_balance)?,
+            closing_balance: statement_amount_to_f64(closing_balance)?,
+            entries,
+            total,
+            next_cursor,
+        })
+    }
+
+    async fn get_balance_before(
+        &self,
+        user_id: UserId,
+        time: DateTimeWithTimeZone,
+    ) -> Result<Decimal, Box<dyn ApplicationError>> {
+        self.transaction_repository
+            .get_user_balance_before(user_id, time)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to get balance before {} for user_id {}: {}",
+                    time, user_id, e
+                );
+                Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
+            })
+    }
+
+    async fn make_statement_entry(
+        &self,
+        transaction: Transaction,
+    ) -> Result<StatementEntryDTO, Box<dyn ApplicationError>> {
+        let transaction_id = transaction.uuid();
+        let kind = transaction.kind();
+        let time = transaction
+            .finish_time()
+            .map(|time| time.to_rfc3339())
+            .unwrap_or_default();
+        // 交易金额以支付为正，账单中以余额变动为正
+        let amount = -transaction.amount();
+        let refund_fee = transaction.refund_fee();
+        let coupon_discount = transaction.coupon_discount();
+
+        let orders = self
+            .transaction_service
+            .convert_transaction_to_dto(transaction)
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to convert transaction {} to dto: {}",
+                    transaction_id, e
+                );
+                GeneralError::InternalServerError
+            })?
+            .orders;
+
+        let description = if orders.is_empty() {
+            statement_kind_label(kind.into()).to_string()
+        } else {
+            orders
+                .iter()
+                .map(|order| order.description())
+                .collect::<Vec<_>>()
+                .join("；")
+        };
+
+        Ok(StatementEntryDTO {
+            transaction_id,
+            kind: kind.to_string(),
+            time,
+            amount: statement_amount_to_f64(amount)?,
+            refund_fee: statement_amount_to_f64(refund_fee)?,
+            coupon_discount: statement_amount_to_f64(coupon_discount)?,
+            description,
+            orders,
+        })
+    }
+
     async fn verify_payment_password(
         &self,
         user: &User,
@@ -138,17 +512,21 @@ where
 }
 
 #[async_trait]
-impl<S, T, R, U, UR> TransactionApplicationService
-    for TransactionApplicationServiceImpl<S, T, R, U, UR>
+impl<S, T, R, U, UR, OR> TransactionApplicationService
+    for TransactionApplicationServiceImpl<S, T, R, U, UR, OR>
 where
     S: SessionManagerService,
     T: TransactionService,
     R: TransactionRepository,
     U: UserService,
     UR: UserRepository,
+    OR: OrderRepository,
 {
     #[instrument(skip(self))]
-    async fn recharge(&self, command: RechargeCommand) -> Result<(), Box<dyn ApplicationError>> {
+    async fn recharge(
+        &self,
+        command: RechargeCommand,
+    ) -> Result<RechargeIntentDTO, Box<dyn ApplicationError>> {
         let user_id = self
             .get_user_id_by_session_id(&command.session_id)
             .await
@@ -159,7 +537,8 @@ where
                 );
             })?;
 
-        self.transaction_service
+        let (tx, intent) = self
+            .transaction_service
             .recharge(
                 user_id,
                 TransactionAmountAbs::from(Decimal::from_f64(command.amount).ok_or(
@@ -171,6 +550,27 @@ where
                 error!("failed to recharge user {}: {}", user_id, e);
             })?;
 
+        Ok(RechargeIntentDTO::new(&tx, intent))
+    }
+
+    #[instrument(skip(self, command), fields(provider = %command.provider))]
+    async fn handle_payment_callback(
+        &self,
+        command: PaymentCallbackCommand,
+    ) -> Result<(), Box<dyn ApplicationError>> {
+        let signature = command.signature.ok_or_else(|| {
+            TransactionApplicationServiceError::InvalidPaymentCallback(
+                "missing signature".to_string(),
+            )
+        })?;
+
+        self.transaction_service
+            .handle_payment_callback(&command.provider, &command.payload, &signature)
+            .await
+            .inspect_err(|e| {
+                warn!("failed to handle payment callback: {}", e);
+            })?;
+
         Ok(())
     }
 
@@ -188,6 +588,52 @@ where
         })
     }
 
+    #[instrument(skip(self))]
+    async fn query_statement(
+        &self,
+        query: WalletStatementQuery,
+    ) -> Result<WalletStatementDTO, Box<dyn ApplicationError>> {
+        if let Some(limit) = query.limit
+            && (limit == 0 || limit > MAX_STATEMENT_PAGE_SIZE)
+        {
+            return Err(Box::new(GeneralError::BadRequest(format!(
+                "limit should be between 1 and {}",
+                MAX_STATEMENT_PAGE_SIZE
+            ))));
+        }
+
+        let now = Transaction::now();
+        let filter = parse_statement_filter(&query, now)?;
+
+        let after = query
+            .cursor
+            .as_deref()
+            .map(|cursor| {
+                decode_statement_cursor(cursor, *now.offset())
+                    .ok_or_else(|| GeneralError::BadRequest("invalid cursor".to_string()))
+            })
+            .transpose()?;
+
+        let user_id = self.get_user_id_by_session_id(&query.session_id).await?;
+
+        self.load_statement(user_id, &filter, after, query.limit)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn export_statement(
+        &self,
+        query: WalletStatementQuery,
+    ) -> Result<String, Box<dyn ApplicationError>> {
+        let filter = parse_statement_filter(&query, Transaction::now())?;
+
+        let user_id = self.get_user_id_by_session_id(&query.session_id).await?;
+
+        let statement = self.load_full_statement(user_id, &filter).await?;
+
+        Ok(render_statement_csv(&statement))
+    }
+
     #[instrument(skip(self))]
     async fn query_transactions(
         &self,
@@ -357,39 +803,44 @@ where
     ) -> Result<(), Box<dyn ApplicationError>> {
         let user_id = self.get_user_id_by_session_id(&command.session_id).await?;
 
-        let tx_list = self
-            .transaction_repository
-            .find_by_user_id(user_id)
-            .await
-            .map_err(|e| {
-                error!("failed to find tx list for user_id {}: {}", user_id, e);
-                GeneralError::InternalServerError
-            })?;
-
         let target_order_uuid = command.order_id;
 
-        let mut target_tx = None;
-        let mut target_order = None;
+        let (target_tx, target_order) = self.find_user_order(user_id, target_order_uuid).await?;
 
-        for tx in &tx_list {
-            for order in tx.orders() {
-                if order.uuid() == target_order_uuid {
-                    target_order = Some(order);
-                    target_tx = Some(tx);
-                    break;
+        self.transaction_service
+            .refund_transaction_with_fee(target_tx.uuid(), &[target_order])
+            .await
+            .map_err(|e| match e {
+                TransactionServiceError::RefundError(e) => Box::new(
+                    TransactionApplicationServiceError::RefundError(e.to_string()),
+                )
+                    as Box<dyn ApplicationError>,
+                x => {
+                    error!("Failed to refund order {}: {}", target_order_uuid, x);
+                    Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
                 }
-            }
-        }
+            })?;
 
-        let target_tx = target_tx.ok_or_else(|| {
-            warn!("No transaction found for order id {}", target_order_uuid);
-            GeneralError::NotFound
-        })?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn partial_refund(
+        &self,
+        command: PartialRefundCommand,
+    ) -> Result<(), Box<dyn ApplicationError>> {
+        let user_id = self.get_user_id_by_session_id(&command.session_id).await?;
 
-        let target_order = target_order.unwrap();
+        let target_order_uuid = command.order_id;
+
+        let (target_tx, _) = self.find_user_order(user_id, target_order_uuid).await?;
 
         self.transaction_service
-            .refund_transaction(target_tx.uuid(), &[target_order.clone()])
+            .partial_refund(
+                target_tx.uuid(),
+                target_order_uuid,
+                Decimal::from(command.quantity),
+            )
             .await
             .map_err(|e| match e {
                 TransactionServiceError::RefundError(e) => Box::new(
@@ -397,11 +848,166 @@ where
                 )
                     as Box<dyn ApplicationError>,
                 x => {
-                    error!("Failed to refund order {}: {}", target_order_uuid, x);
+                    error!(
+                        "Failed to partially refund order {}: {}",
+                        target_order_uuid, x
+                    );
                     Box::new(GeneralError::InternalServerError) as Box<dyn ApplicationError>
                 }
             })?;
 
         Ok(())
     }
+
+    #[instrument(skip(self))]
+    async fn query_order_timeline(
+        &self,
+        query: OrderTimelineQuery,
+    ) -> Result<Vec<OrderStatusHistoryDTO>, Box<dyn ApplicationError>> {
+        let user_id = self.get_user_id_by_session_id(&query.session_id).await?;
+
+        let (_, order) = self.find_user_order(user_id, query.order_id).await?;
+
+        let history = self
+            .order_repository
+            .find_status_history(order.uuid())
+            .await
+            .map_err(|e| {
+                error!(
+                    "failed to find status history for order {}: {}",
+                    order.uuid(),
+                    e
+                );
+                GeneralError::InternalServerError
+            })?;
+
+        Ok(history
+            .into_iter()
+            .map(OrderStatusHistoryDTO::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_query(begin_date: Option<&str>, end_date: Option<&str>) -> WalletStatementQuery {
+        WalletStatementQuery {
+            session_id: String::new(),
+            begin_date: begin_date.map(str::to_string),
+            end_date: end_date.map(str::to_string),
+            types: Vec::new(),
+            cursor: None,
+            limit: None,
+        }
+    }
+
+    fn make_now() -> DateTimeWithTimeZone {
+        DateTime::parse_from_rfc3339("2025-06-30T12:00:00+08:00").unwrap()
+    }
+
+    #[test]
+    fn test_statement_filter_covers_whole_days() {
+        let filter = parse_statement_filter(
+            &make_query(Some("2025-06-01"), Some("2025-06-15")),
+            make_now(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            filter.begin,
+            Some(DateTime::parse_from_rfc3339("2025-06-01T00:00:00+08:00").unwrap())
+        );
+        assert_eq!(
+            filter.end,
+            DateTime::parse_from_rfc3339("2025-06-16T00:00:00+08:00").unwrap()
+        );
+        assert!(filter.kinds.is_empty());
+    }
+
+    #[test]
+    fn test_statement_filter_defaults_to_now() {
+        let filter = parse_statement_filter(&make_query(None, None), make_now()).unwrap();
+
+        assert_eq!(filter.begin, None);
+        assert_eq!(filter.end, make_now());
+    }
+
+    #[test]
+    fn test_statement_filter_rejects_invalid_input() {
+        assert!(parse_statement_filter(&make_query(Some("2025/06/01"), None), make_now()).is_err());
+        assert!(
+            parse_statement_filter(
+                &make_query(Some("2025-06-15"), Some("2025-06-01")),
+                make_now()
+            )
+            .is_err()
+        );
+
+        let mut query = make_query(None, None);
+        query.types = vec!["pay".to_string(), "withdraw".to_string()];
+        assert!(parse_statement_filter(&query, make_now()).is_err());
+
+        query.types = vec!["pay".to_string(), "refund".to_string()];
+        assert_eq!(
+            parse_statement_filter(&query, make_now()).unwrap().kinds,
+            vec![TransactionKind::Pay, TransactionKind::Refund]
+        );
+    }
+
+    #[test]
+    fn test_statement_cursor_round_trip() {
+        let cursor = StatementCursor {
+            finish_time: DateTime::parse_from_rfc3339("2025-06-01T08:30:00.123456+08:00").unwrap(),
+            transaction_id: TransactionId::from_db_value(42).unwrap(),
+        };
+
+        let encoded = encode_statement_cursor(&cursor);
+
+        assert_eq!(
+            decode_statement_cursor(&encoded, *make_now().offset()),
+            Some(cursor)
+        );
+        assert_eq!(decode_statement_cursor("abc", *make_now().offset()), None);
+        assert_eq!(decode_statement_cursor("1.x", *make_now().offset()), None);
+    }
+
+    #[test]
+    fn test_escape_csv_text() {
+        assert_eq!(escape_csv_text("火车票 G1"), "火车票 G1");
+        assert_eq!(escape_csv_text("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv_text("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_csv_text("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(escape_csv_text("-1,2"), "\"'-1,2\"");
+    }
+
+    #[test]
+    fn test_render_statement_csv() {
+        let statement = WalletStatementDTO {
+            opening_balance: 100.0,
+            closing_balance: 50.5,
+            entries: vec![StatementEntryDTO {
+                transaction_id: Uuid::nil(),
+                kind: TransactionKind::Pay.to_string(),
+                time: "2025-06-01T08:30:00+08:00".to_string(),
+                amount: -49.5,
+                refund_fee: 0.0,
+                coupon_discount: 0.0,
+                description: "支付".to_string(),
+                orders: Vec::new(),
+            }],
+            total: 1,
+            next_cursor: None,
+        };
+
+        assert_eq!(
+            render_statement_csv(&statement),
+            "时间,交易号,类型,金额,手续费,优惠金额,说明\r\n\
+             2025-06-01T08:30:00+08:00,00000000-0000-0000-0000-000000000000,支付,-49.50,0.00,0.00,支付\r\n\
+             \r\n\
+             期初余额,100.00\r\n\
+             期末余额,50.50\r\n"
+        );
+    }
 }
@@ -5,8 +5,9 @@ use crate::domain::service::order_status::{
     OrderStatusConsumer, OrderStatusConsumerError, OrderStatusMessagePack,
 };
 use crate::domain::service::takeaway_booking::TakeawayBookingService;
-use crate::domain::service::train_booking::TrainBookingService;
+use crate::domain::service::train_booking::{TrainBookingService, TrainBookingServiceError};
 use crate::domain::service::transaction::TransactionService;
+use crate::domain::service::waitlist::WaitlistService;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tracing::{error, info, instrument};
@@ -33,24 +34,32 @@ where
     }
 }
 
-pub struct TrainOrderStatusConsumer<TBS, TS>
+pub struct TrainOrderStatusConsumer<TBS, TS, WS>
 where
     TBS: TrainBookingService,
     TS: TransactionService,
+    WS: WaitlistService,
 {
     train_booking_service: Arc<TBS>,
     transaction_service: Arc<TS>,
+    waitlist_service: Arc<WS>,
 }
 
-impl<TBS, TS> TrainOrderStatusConsumer<TBS, TS>
+impl<TBS, TS, WS> TrainOrderStatusConsumer<TBS, TS, WS>
 where
     TBS: TrainBookingService,
     TS: TransactionService,
+    WS: WaitlistService,
 {
-    pub fn new(train_booking_service: Arc<TBS>, transaction_service: Arc<TS>) -> Self {
+    pub fn new(
+        train_booking_service: Arc<TBS>,
+        transaction_service: Arc<TS>,
+        waitlist_service: Arc<WS>,
+    ) -> Self {
         Self {
             train_booking_service,
             transaction_service,
+            waitlist_service,
         }
     }
 }
@@ -122,10 +131,11 @@ where
 }
 
 #[async_trait]
-impl<TBS, TS> RabbitMQOrderStatusConsumer for TrainOrderStatusConsumer<TBS, TS>
+impl<TBS, TS, WS> RabbitMQOrderStatusConsumer for TrainOrderStatusConsumer<TBS, TS, WS>
 where
     TBS: TrainBookingService,
     TS: TransactionService,
+    WS: WaitlistService,
 {
     fn binding_key(&self) -> &'static str {
         OrderType::Train.message_queue_name()
@@ -140,6 +150,7 @@ where
 
         let mut to_cancel_order_id_list = Vec::new();
         let mut to_booking_order_id_list = Vec::new();
+        let mut to_rebook_order_id_list = Vec::new();
 
         for message in message_pack.messages {
             if message.order_type != OrderType::Train {
@@ -149,21 +160,40 @@ where
                 );
             }
 
-            match message.new_status {
-                OrderStatus::Paid => to_booking_order_id_list.push(message.order_id),
-                OrderStatus::Cancelled => to_cancel_order_id_list.push(message.order_id),
-                x => {
+            match (message.new_status, message.replaced_order_id) {
+                (OrderStatus::Unpaid, Some(replaced_order_id)) => {
+                    to_rebook_order_id_list.push((replaced_order_id, message.order_id))
+                }
+                (OrderStatus::Paid, None) => to_booking_order_id_list.push(message.order_id),
+                (OrderStatus::Cancelled, _) => to_cancel_order_id_list.push(message.order_id),
+                (x, _) => {
                     error!("unexpected order status: {}", x);
                 }
             }
         }
 
         if !to_booking_order_id_list.is_empty() {
-            let tx = self
+            let tx = match self
                 .train_booking_service
                 .booking_group(to_booking_order_id_list, message_pack.atomic)
                 .await
-                .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+            {
+                Ok(tx) => tx,
+                Err(TrainBookingServiceError::RolledBackWithFulfilledWaitlists {
+                    source,
+                    fulfilled_waitlists,
+                }) => {
+                    self.waitlist_service
+                        .settle_fulfilled(fulfilled_waitlists)
+                        .await
+                        .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+
+                    return Err(OrderStatusConsumerError::RelatedServiceError(
+                        (*source).into(),
+                    ));
+                }
+                Err(e) => return Err(OrderStatusConsumerError::RelatedServiceError(e.into())),
+            };
 
             if !tx.is_empty() {
                 let tx_list_boxed = tx
@@ -178,11 +208,86 @@ where
             }
         }
 
+        for (replaced_order_uuid, new_order_uuid) in to_rebook_order_id_list {
+            // 新订单暂留座位成功才结算差价，否则丢弃调整交易，原订单保持不变
+            if let Err(err) = self
+                .train_booking_service
+                .rebook_ticket(replaced_order_uuid, new_order_uuid)
+                .await
+            {
+                error!(
+                    "Failed to rebook train order {} to {}: {}",
+                    replaced_order_uuid, new_order_uuid, err
+                );
+
+                self.transaction_service
+                    .discard_transaction(message_pack.transaction_uuid)
+                    .await
+                    .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+
+                continue;
+            }
+
+            // 差价结算成功后确认新订单暂留的座位；结算失败时取消新订单并丢弃调整交易，原订单保持不变
+            let released_order_uuid = match self
+                .transaction_service
+                .settle_rebook_transaction(message_pack.transaction_uuid, replaced_order_uuid)
+                .await
+            {
+                Ok(()) => {
+                    self.train_booking_service
+                        .booking_ticket(new_order_uuid)
+                        .await
+                        .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+
+                    replaced_order_uuid
+                }
+                Err(err) => {
+                    error!(
+                        "Failed to settle rebook of train order {} to {}: {}",
+                        replaced_order_uuid, new_order_uuid, err
+                    );
+
+                    new_order_uuid
+                }
+            };
+
+            // 释放的座位可能已分配给候补，需为兑现的候补退还差价并通知用户
+            let fulfilled_waitlists = self
+                .train_booking_service
+                .cancel_ticket(released_order_uuid)
+                .await
+                .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+
+            if !fulfilled_waitlists.is_empty() {
+                self.waitlist_service
+                    .settle_fulfilled(fulfilled_waitlists)
+                    .await
+                    .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+            }
+
+            if released_order_uuid == new_order_uuid {
+                self.transaction_service
+                    .discard_transaction(message_pack.transaction_uuid)
+                    .await
+                    .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+            }
+        }
+
         for order_uuid in to_cancel_order_id_list {
-            self.train_booking_service
+            // 释放的座位可能已分配给候补，需为兑现的候补退还差价并通知用户
+            let fulfilled_waitlists = self
+                .train_booking_service
                 .cancel_ticket(order_uuid)
                 .await
                 .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+
+            if !fulfilled_waitlists.is_empty() {
+                self.waitlist_service
+                    .settle_fulfilled(fulfilled_waitlists)
+                    .await
+                    .map_err(|e| OrderStatusConsumerError::RelatedServiceError(e.into()))?;
+            }
         }
 
         Ok(())
@@ -0,0 +1,213 @@
+use crate::domain::model::coupon::{Coupon, CouponDiscount, CouponId, CouponRedemptionStatus};
+use crate::domain::model::order::OrderType;
+use crate::domain::model::user::UserId;
+use crate::domain::repository::coupon::CouponRepository;
+use crate::domain::{DbId, Identifiable, Repository, RepositoryError};
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    FromQueryResult, QueryFilter, Statement,
+};
+use tracing::{error, instrument};
+
+impl_db_id_from_u64!(CouponId, i32, "coupon");
+
+pub struct CouponDataConverter;
+
+impl CouponDataConverter {
+    pub fn make_from_do(coupon_do: crate::models::coupon::Model) -> Result<Coupon, anyhow::Error> {
+        let discount =
+            CouponDiscount::try_new(coupon_do.discount_type.as_str(), coupon_do.discount_value)?;
+
+        let applicable_order_types = coupon_do
+            .applicable_order_types
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| OrderType::try_from(s).map_err(|e| anyhow!(e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let to_limit = |value: Option<i32>| {
+            value
+                .map(u32::try_from)
+                .transpose()
+                .map_err(|e| anyhow!("Invalid coupon limit: {}", e))
+        };
+
+        Ok(Coupon::new(
+            Some(CouponId::from_db_value(coupon_do.id)?),
+            coupon_do.code,
+            discount,
+            coupon_do.min_spend,
+            applicable_order_types,
+            coupon_do.valid_from,
+            coupon_do.expire_time,
+            to_limit(coupon_do.per_user_limit)?,
+            to_limit(coupon_do.global_limit)?,
+            u32::try_from(coupon_do.redeemed_count)
+                .map_err(|e| anyhow!("Invalid coupon redeemed count: {}", e))?,
+        ))
+    }
+
+    /// 转换为数据库模型
+    ///
+    /// `redeemed_count`只在插入时写入，之后仅由交易仓储在核销/返还时原子地增减，避免覆盖并发核销的结果
+    pub fn transform_to_do(coupon: &Coupon) -> crate::models::coupon::ActiveModel {
+        let mut model = crate::models::coupon::ActiveModel {
+            id: ActiveValue::NotSet,
+            code: ActiveValue::Set(coupon.code().to_string()),
+            discount_type: ActiveValue::Set(coupon.discount().discount_type().to_string()),
+            discount_value: ActiveValue::Set(coupon.discount().value()),
+            min_spend: ActiveValue::Set(coupon.min_spend()),
+            applicable_order_types: ActiveValue::Set(
+                coupon
+                    .applicable_order_types()
+                    .iter()
+                    .map(|order_type| order_type.to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+            valid_from: ActiveValue::Set(coupon.valid_from()),
+            expire_time: ActiveValue::Set(coupon.expire_time()),
+            per_user_limit: ActiveValue::Set(coupon.per_user_limit().map(|x| x as i32)),
+            global_limit: ActiveValue::Set(coupon.global_limit().map(|x| x as i32)),
+            redeemed_count: ActiveValue::NotSet,
+        };
+
+        if let Some(id) = coupon.get_id() {
+            model.id = ActiveValue::Set(id.to_db_value());
+        } else {
+            model.redeemed_count = ActiveValue::Set(coupon.redeemed_count() as i32);
+        }
+
+        model
+    }
+}
+
+/// 查询用户已核销指定优惠券的次数，可在数据库事务中调用
+pub async fn count_user_redemptions<C: ConnectionTrait>(
+    db: &C,
+    coupon_id: CouponId,
+    user_id: UserId,
+) -> Result<u32, RepositoryError> {
+    #[derive(Debug, FromQueryResult)]
+    struct CountQueryResult {
+        count: i64,
+    }
+
+    let redeemed: &str = CouponRedemptionStatus::Redeemed.into();
+    let voided: &str = CouponRedemptionStatus::Voided.into();
+
+    let result = CountQueryResult::find_by_statement(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"SELECT
+    COUNT(*) AS "count"
+FROM "transaction"
+WHERE "transaction"."coupon_id" = $1
+  AND "transaction"."user_id" = $2
+  AND "transaction"."coupon_status" IN ($3, $4)"#,
+        [
+            coupon_id.to_db_value().into(),
+            user_id.to_db_value().into(),
+            redeemed.into(),
+            voided.into(),
+        ],
+    ))
+    .one(db)
+    .await
+    .inspect_err(|e| error!("Failed to count coupon redemptions: {}", e))
+    .context(format!(
+        "Failed to count redemptions of coupon {} for user {}",
+        coupon_id, user_id
+    ))?;
+
+    Ok(result.map(|r| r.count as u32).unwrap_or(0))
+}
+
+pub struct CouponRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl CouponRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl Repository<Coupon> for CouponRepositoryImpl {
+    async fn find(&self, id: CouponId) -> Result<Option<Coupon>, RepositoryError> {
+        let coupon_do = crate::models::coupon::Entity::find_by_id(id.to_db_value())
+            .one(&self.db)
+            .await
+            .context(format!("Failed to find coupon for coupon id: {}", id))?;
+
+        coupon_do
+            .map(CouponDataConverter::make_from_do)
+            .transpose()
+            .map_err(RepositoryError::ValidationError)
+    }
+
+    async fn remove(&self, aggregate: Coupon) -> Result<(), RepositoryError> {
+        if let Some(id) = aggregate.get_id() {
+            crate::models::coupon::Entity::delete_by_id(id.to_db_value())
+                .exec(&self.db)
+                .await
+                .context(format!("Failed to delete coupon for coupon id: {}", id))?;
+        }
+
+        Ok(())
+    }
+
+    async fn save(&self, aggregate: &mut Coupon) -> Result<CouponId, RepositoryError> {
+        let model = CouponDataConverter::transform_to_do(aggregate);
+
+        if let Some(id) = aggregate.get_id() {
+            crate::models::coupon::Entity::update(model)
+                .exec(&self.db)
+                .await
+                .context(format!("Failed to update coupon with id: {}", id))?;
+
+            Ok(id)
+        } else {
+            let result = crate::models::coupon::Entity::insert(model)
+                .exec(&self.db)
+                .await
+                .context("Failed to insert coupon")?;
+
+            let id = CouponId::from_db_value(result.last_insert_id)?;
+
+            aggregate.set_id(id);
+
+            Ok(id)
+        }
+    }
+}
+
+#[async_trait]
+impl CouponRepository for CouponRepositoryImpl {
+    #[instrument(skip(self))]
+    async fn find_by_code(&self, code: &str) -> Result<Option<Coupon>, RepositoryError> {
+        let coupon_do = crate::models::coupon::Entity::find()
+            .filter(crate::models::coupon::Column::Code.eq(code))
+            .one(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query coupon: {}", e))
+            .context(format!("Failed to find coupon for code: {}", code))?;
+
+        coupon_do
+            .map(CouponDataConverter::make_from_do)
+            .transpose()
+            .map_err(RepositoryError::ValidationError)
+    }
+
+    #[instrument(skip(self))]
+    async fn count_user_redemptions(
+        &self,
+        coupon_id: CouponId,
+        user_id: UserId,
+    ) -> Result<u32, RepositoryError> {
+        count_user_redemptions(&self.db, coupon_id, user_id).await
+    }
+}
@@ -0,0 +1,165 @@
+use crate::domain::model::idempotency::IdempotencyRecord;
+use crate::domain::model::user::UserId;
+use crate::domain::repository::idempotency::IdempotencyRepository;
+use crate::domain::{DbId, RepositoryError};
+use anyhow::Context;
+use async_trait::async_trait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, EntityTrait,
+    QueryFilter, Statement,
+};
+use tracing::{error, instrument};
+
+pub struct IdempotencyRecordDataConverter;
+
+impl IdempotencyRecordDataConverter {
+    pub fn make_from_do(
+        record_do: crate::models::idempotency_key::Model,
+    ) -> Result<IdempotencyRecord, anyhow::Error> {
+        Ok(IdempotencyRecord::new(
+            UserId::from_db_value(record_do.user_id)?,
+            record_do.scope,
+            record_do.key,
+            record_do.request_hash,
+            record_do.response,
+            record_do.create_time,
+            record_do.locked_until,
+        ))
+    }
+
+    pub fn transform_to_do(
+        record: &IdempotencyRecord,
+    ) -> crate::models::idempotency_key::ActiveModel {
+        crate::models::idempotency_key::ActiveModel {
+            id: ActiveValue::NotSet,
+            user_id: ActiveValue::Set(record.user_id().to_db_value()),
+            scope: ActiveValue::Set(record.scope().to_string()),
+            key: ActiveValue::Set(record.key().to_string()),
+            request_hash: ActiveValue::Set(record.request_hash().to_string()),
+            response: ActiveValue::Set(record.response().map(|x| x.to_string())),
+            create_time: ActiveValue::Set(record.create_time()),
+            locked_until: ActiveValue::Set(record.locked_until()),
+        }
+    }
+}
+
+pub struct IdempotencyRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl IdempotencyRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    fn filter_key<Q: QueryFilter>(query: Q, user_id: UserId, scope: &str, key: &str) -> Q {
+        query
+            .filter(crate::models::idempotency_key::Column::UserId.eq(user_id.to_db_value()))
+            .filter(crate::models::idempotency_key::Column::Scope.eq(scope))
+            .filter(crate::models::idempotency_key::Column::Key.eq(key))
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for IdempotencyRepositoryImpl {
+    #[instrument(skip(self))]
+    async fn find(
+        &self,
+        user_id: UserId,
+        scope: &str,
+        key: &str,
+    ) -> Result<Option<IdempotencyRecord>, RepositoryError> {
+        let query = crate::models::idempotency_key::Entity::find();
+
+        let record_do = Self::filter_key(query, user_id, scope, key)
+            .one(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to load idempotency key: {}", e))
+            .context("failed to load idempotency key from db")?;
+
+        record_do
+            .map(IdempotencyRecordDataConverter::make_from_do)
+            .transpose()
+            .map_err(RepositoryError::InconsistentState)
+    }
+
+    #[instrument(skip(self))]
+    async fn reserve(&self, record: &IdempotencyRecord) -> Result<bool, RepositoryError> {
+        // 已失效的处理中登记（如处理请求时进程退出）由相同请求接管
+        let result = self
+            .db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"INSERT INTO "idempotency_key"
+    ("user_id", "scope", "key", "request_hash", "response", "create_time", "locked_until")
+VALUES ($1, $2, $3, $4, NULL, $5, $6)
+ON CONFLICT ("user_id", "scope", "key") DO UPDATE
+SET "create_time" = EXCLUDED."create_time", "locked_until" = EXCLUDED."locked_until"
+WHERE "idempotency_key"."response" IS NULL
+  AND "idempotency_key"."request_hash" = EXCLUDED."request_hash"
+  AND "idempotency_key"."locked_until" < EXCLUDED."create_time""#,
+                [
+                    record.user_id().to_db_value().into(),
+                    record.scope().into(),
+                    record.key().into(),
+                    record.request_hash().into(),
+                    record.create_time().into(),
+                    record.locked_until().into(),
+                ],
+            ))
+            .await
+            .inspect_err(|e| error!("Failed to reserve idempotency key: {}", e))
+            .context("failed to reserve idempotency key")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[instrument(skip(self, response))]
+    async fn save_response(
+        &self,
+        user_id: UserId,
+        scope: &str,
+        key: &str,
+        response: &str,
+    ) -> Result<(), RepositoryError> {
+        let update = crate::models::idempotency_key::Entity::update_many().col_expr(
+            crate::models::idempotency_key::Column::Response,
+            Expr::value(response),
+        );
+
+        Self::filter_key(update, user_id, scope, key)
+            .exec(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to save idempotent response: {}", e))
+            .context("failed to save idempotent response")?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn remove(&self, user_id: UserId, scope: &str, key: &str) -> Result<(), RepositoryError> {
+        let delete = crate::models::idempotency_key::Entity::delete_many();
+
+        Self::filter_key(delete, user_id, scope, key)
+            .exec(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to remove idempotency key: {}", e))
+            .context("failed to remove idempotency key")?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn remove_before(&self, time: DateTimeWithTimeZone) -> Result<u64, RepositoryError> {
+        let result = crate::models::idempotency_key::Entity::delete_many()
+            .filter(crate::models::idempotency_key::Column::CreateTime.lt(time))
+            .exec(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to remove expired idempotency keys: {}", e))
+            .context("failed to remove expired idempotency keys")?;
+
+        Ok(result.rows_affected)
+    }
+}
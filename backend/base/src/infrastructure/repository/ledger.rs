@@ -0,0 +1,190 @@
+use crate::domain::model::ledger::{JournalEntry, JournalEntryKind, JournalLine, LedgerAccount};
+use crate::domain::model::user::UserId;
+use crate::domain::repository::ledger::LedgerRepository;
+use crate::domain::{DbId, RepositoryError};
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, Statement,
+};
+use std::collections::HashMap;
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+pub struct JournalEntryDataConverter;
+
+impl JournalEntryDataConverter {
+    pub fn make_from_do(
+        entry_do: crate::models::journal_entry::Model,
+        line_dos: Vec<crate::models::journal_line::Model>,
+    ) -> Result<JournalEntry, anyhow::Error> {
+        let lines = line_dos
+            .into_iter()
+            .map(|line_do| {
+                let account = LedgerAccount::from_parts(
+                    &line_do.account,
+                    line_do.user_id.map(UserId::from_db_value).transpose()?,
+                )
+                .map_err(|e| anyhow!(e))?;
+
+                Ok(JournalLine::new(line_do.uuid, account, line_do.amount))
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
+        Ok(JournalEntry::new(
+            entry_do.uuid,
+            entry_do.transaction_uuid,
+            JournalEntryKind::try_from(entry_do.kind.as_str()).map_err(|e| anyhow!(e))?,
+            lines,
+            entry_do.create_time,
+        )?)
+    }
+
+    pub fn transform_to_do(entry: &JournalEntry) -> crate::models::journal_entry::ActiveModel {
+        crate::models::journal_entry::ActiveModel {
+            id: ActiveValue::NotSet,
+            uuid: ActiveValue::Set(entry.uuid()),
+            transaction_uuid: ActiveValue::Set(entry.transaction_uuid()),
+            kind: ActiveValue::Set(entry.kind().to_string()),
+            create_time: ActiveValue::Set(entry.create_time()),
+        }
+    }
+
+    pub fn transform_line_to_do(
+        entry: &JournalEntry,
+        line: &JournalLine,
+    ) -> crate::models::journal_line::ActiveModel {
+        crate::models::journal_line::ActiveModel {
+            id: ActiveValue::NotSet,
+            uuid: ActiveValue::Set(line.uuid()),
+            entry_uuid: ActiveValue::Set(entry.uuid()),
+            account: ActiveValue::Set(line.account().kind().to_string()),
+            user_id: ActiveValue::Set(line.account().user_id().map(|x| x.to_db_value())),
+            amount: ActiveValue::Set(line.amount()),
+        }
+    }
+
+    /// 持久化记账凭证及其分录，已持久化的凭证与分录（按`uuid`判断）将被忽略
+    pub async fn save_all<C: ConnectionTrait>(
+        db: &C,
+        entries: &[JournalEntry],
+    ) -> Result<(), DbErr> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        crate::models::journal_entry::Entity::insert_many(
+            entries.iter().map(Self::transform_to_do),
+        )
+        .on_conflict(
+            OnConflict::column(crate::models::journal_entry::Column::Uuid)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        let lines = entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .lines()
+                    .iter()
+                    .map(|line| Self::transform_line_to_do(entry, line))
+            })
+            .collect::<Vec<_>>();
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        crate::models::journal_line::Entity::insert_many(lines)
+            .on_conflict(
+                OnConflict::column(crate::models::journal_line::Column::Uuid)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub struct LedgerRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl LedgerRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl LedgerRepository for LedgerRepositoryImpl {
+    #[instrument(skip(self))]
+    async fn find_by_transaction_uuid(
+        &self,
+        transaction_uuid: Uuid,
+    ) -> Result<Vec<JournalEntry>, RepositoryError> {
+        let entry_list = crate::models::journal_entry::Entity::find()
+            .filter(crate::models::journal_entry::Column::TransactionUuid.eq(transaction_uuid))
+            .order_by_asc(crate::models::journal_entry::Column::CreateTime)
+            .order_by_asc(crate::models::journal_entry::Column::Id)
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to load journal entries: {}", e))
+            .context("failed to load journal entries from db")?;
+
+        let mut line_map = crate::models::journal_line::Entity::find()
+            .filter(
+                crate::models::journal_line::Column::EntryUuid
+                    .is_in(entry_list.iter().map(|entry| entry.uuid)),
+            )
+            .order_by_asc(crate::models::journal_line::Column::Id)
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to load journal lines: {}", e))
+            .context("failed to load journal lines from db")?
+            .into_iter()
+            .fold(HashMap::<Uuid, Vec<_>>::new(), |mut map, line| {
+                map.entry(line.entry_uuid).or_default().push(line);
+                map
+            });
+
+        entry_list
+            .into_iter()
+            .map(|entry| {
+                let lines = line_map.remove(&entry.uuid).unwrap_or_default();
+                JournalEntryDataConverter::make_from_do(entry, lines)
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::InconsistentState)
+    }
+
+    #[instrument(skip(self))]
+    async fn get_wallet_balances(&self) -> Result<HashMap<UserId, Decimal>, RepositoryError> {
+        #[derive(Debug, FromQueryResult)]
+        struct WalletBalance {
+            user_id: i32,
+            balance: Decimal,
+        }
+
+        let r = WalletBalance::find_by_statement(Statement::from_string(
+            DatabaseBackend::Postgres,
+            r#"SELECT "user_id", SUM("amount") AS "balance" FROM "journal_line" WHERE "account" = 'user_wallet' GROUP BY "user_id""#,
+        ))
+        .all(&self.db)
+        .await
+        .context("Failed to query wallet balances")?;
+
+        r.into_iter()
+            .map(|item| Ok((UserId::from_db_value(item.user_id)?, item.balance)))
+            .collect::<Result<HashMap<_, _>, anyhow::Error>>()
+            .map_err(RepositoryError::InconsistentState)
+    }
+}
@@ -5,6 +5,7 @@ pub mod session;
 pub mod user;
 
 pub mod city;
+pub mod coupon;
 pub mod mock;
 pub mod personal_info;
 pub mod route;
@@ -16,11 +17,14 @@ pub mod transaction;
 pub mod dish;
 pub mod hotel;
 pub mod hotel_rating;
+pub mod idempotency;
+pub mod ledger;
 pub mod notify;
 pub mod occupied_room;
 pub mod order;
 pub mod seat_availability;
 pub mod takeaway;
+pub mod waitlist;
 
 #[instrument(level = "trace", skip_all)]
 pub fn transform_list<T, U, I>(
@@ -27,8 +27,8 @@ mod ser_order {
     use crate::domain::model::dish::DishId;
     use crate::domain::model::hotel::{HotelDateRange, HotelId, HotelRoomTypeId};
     use crate::domain::model::order::{
-        BaseOrder, DishOrder, HotelOrder, Order, OrderId, OrderStatus, OrderTimeInfo, PaymentInfo,
-        TakeawayOrder, TrainOrder,
+        BaseOrder, DishOrder, GroupSeatingStrategy, HotelOrder, Order, OrderId, OrderStatus,
+        OrderTimeInfo, PaymentInfo, TakeawayOrder, TicketCategory, TrainOrder,
     };
     use crate::domain::model::personal_info::{PersonalInfoId, PreferredSeatLocation};
     use crate::domain::model::station::StationId;
@@ -95,6 +95,10 @@ mod ser_order {
     pub struct PaymentInfoDto {
         pub pay_transaction_id: Option<i32>,
         pub refund_transaction_id: Option<i32>,
+        #[serde(default)]
+        pub refunded_amount: Decimal,
+        #[serde(default)]
+        pub refunded_quantity: Decimal,
     }
 
     #[derive(Serialize, Deserialize)]
@@ -117,6 +121,12 @@ mod ser_order {
         pub preferred_seat_location: Option<char>,
         pub station_range: StationRangeDTO,
         pub order_seat_type: String,
+        #[serde(default)]
+        pub group_seating: Option<String>,
+        #[serde(default)]
+        pub ticket_category: Option<String>,
+        #[serde(default)]
+        pub seat_hold_expire_time: Option<String>,
     }
 
     #[derive(Serialize, Deserialize)]
@@ -297,6 +307,8 @@ mod ser_order {
             Self {
                 pay_transaction_id: info.pay_transaction_id().map(|id| id.to_db_value()),
                 refund_transaction_id: info.refund_transaction_id().map(|id| id.to_db_value()),
+                refunded_amount: info.refunded_amount(),
+                refunded_quantity: info.refunded_quantity(),
             }
         }
     }
@@ -313,7 +325,12 @@ mod ser_order {
                 .refund_transaction_id
                 .map(TransactionId::from_db_value)
                 .transpose()?;
-            Ok(PaymentInfo::new(pay_transaction_id, refund_transaction_id))
+            Ok(PaymentInfo::new_full(
+                pay_transaction_id,
+                refund_transaction_id,
+                dto.refunded_amount,
+                dto.refunded_quantity,
+            ))
         }
     }
 
@@ -361,6 +378,9 @@ mod ser_order {
                 preferred_seat_location: order.preferred_seat_location().map(|x| x.into()),
                 station_range: order.station_range().into(),
                 order_seat_type: order.order_seat_type_name().to_string(),
+                group_seating: order.group_seating().map(|x| x.to_string()),
+                ticket_category: Some(order.ticket_category().to_string()),
+                seat_hold_expire_time: order.seat_hold_expire_time().map(|x| x.to_rfc3339()),
             }
         }
     }
@@ -370,7 +390,7 @@ mod ser_order {
 
         fn try_from(dto: TrainOrderDto) -> Result<Self, Self::Error> {
             let base = dto.base.try_into()?;
-            Ok(TrainOrder::new(
+            let mut train_order = TrainOrder::new(
                 base,
                 TrainScheduleId::from_db_value(dto.train_schedule_id)?,
                 dto.seat.map(Seat::try_from).transpose()?,
@@ -380,7 +400,29 @@ mod ser_order {
                     .transpose()
                     .map_err(|e| anyhow!("invalid preferred seat location: {}", e))?,
                 dto.station_range.try_into()?,
-            ))
+            );
+
+            train_order.set_group_seating(
+                dto.group_seating
+                    .map(|x| GroupSeatingStrategy::try_from(x.as_str()))
+                    .transpose()
+                    .map_err(|e| anyhow!("invalid group seating strategy: {}", e))?,
+            );
+
+            if let Some(ticket_category) = dto.ticket_category {
+                train_order.set_ticket_category(
+                    TicketCategory::try_from(ticket_category.as_str())
+                        .map_err(|e| anyhow!("invalid ticket category: {}", e))?,
+                );
+            }
+
+            train_order.set_seat_hold_expire_time(
+                dto.seat_hold_expire_time
+                    .map(|x| DateTimeWithTimeZone::from_str(&x))
+                    .transpose()?,
+            );
+
+            Ok(train_order)
         }
     }
 
@@ -2,6 +2,7 @@ use crate::domain::model::hotel::HotelId;
 use crate::domain::model::order::{
     DishOrder, HotelOrder, Order, OrderId, OrderStatus, TakeawayOrder, TrainOrder,
 };
+use crate::domain::model::order_state_machine::{OrderStatusActor, OrderStatusTransition};
 use crate::domain::model::train_schedule::TrainScheduleId;
 use crate::domain::model::user::UserId;
 use crate::domain::repository::order::{
@@ -14,9 +15,11 @@ use anyhow::{Context, anyhow};
 use async_trait::async_trait;
 use chrono::{FixedOffset, NaiveDate, NaiveTime};
 use sea_orm::ColumnTrait;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::OnConflict;
 use sea_orm::{
-    DatabaseBackend, DatabaseConnection, DbBackend, EntityTrait, FromQueryResult, QueryFilter,
-    Statement,
+    ActiveValue, ConnectionTrait, DatabaseBackend, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, FromQueryResult, QueryFilter, QueryOrder, Statement, TransactionTrait,
 };
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
@@ -66,6 +69,72 @@ struct DepartureTimeQueryResult {
     pub origin_departure_time: i32,
 }
 
+pub struct OrderStatusTransitionDataConverter;
+
+impl OrderStatusTransitionDataConverter {
+    pub fn make_from_do(
+        history_do: crate::models::order_status_history::Model,
+    ) -> Result<OrderStatusTransition, anyhow::Error> {
+        let actor = OrderStatusActor::from_parts(
+            &history_do.actor,
+            history_do
+                .actor_user_id
+                .map(UserId::from_db_value)
+                .transpose()?,
+        )
+        .map_err(|e| anyhow!(e))?;
+
+        Ok(OrderStatusTransition::new(
+            history_do.uuid,
+            history_do.order_uuid,
+            OrderStatus::try_from(history_do.from_status.as_str()).map_err(|e| anyhow!(e))?,
+            OrderStatus::try_from(history_do.to_status.as_str()).map_err(|e| anyhow!(e))?,
+            actor,
+            history_do.reason,
+            history_do.create_time,
+        ))
+    }
+
+    pub fn transform_to_do(
+        transition: &OrderStatusTransition,
+    ) -> crate::models::order_status_history::ActiveModel {
+        crate::models::order_status_history::ActiveModel {
+            id: ActiveValue::NotSet,
+            uuid: ActiveValue::Set(transition.uuid()),
+            order_uuid: ActiveValue::Set(transition.order_uuid()),
+            from_status: ActiveValue::Set(transition.from().to_string()),
+            to_status: ActiveValue::Set(transition.to().to_string()),
+            actor: ActiveValue::Set(transition.actor().kind().to_string()),
+            actor_user_id: ActiveValue::Set(transition.actor().user_id().map(|x| x.to_db_value())),
+            reason: ActiveValue::Set(transition.reason().to_string()),
+            create_time: ActiveValue::Set(transition.create_time()),
+        }
+    }
+
+    /// 持久化订单状态迁移记录，已持久化的记录（按`uuid`判断）将被忽略
+    pub async fn save_all<C: ConnectionTrait>(
+        db: &C,
+        transitions: &[OrderStatusTransition],
+    ) -> Result<(), DbErr> {
+        if transitions.is_empty() {
+            return Ok(());
+        }
+
+        crate::models::order_status_history::Entity::insert_many(
+            transitions.iter().map(Self::transform_to_do),
+        )
+        .on_conflict(
+            OnConflict::column(crate::models::order_status_history::Column::Uuid)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
 pub struct OrderRepositoryImpl {
     db: DatabaseConnection,
 }
@@ -211,7 +280,9 @@ FROM "train_order"
     "hotel_order"."create_time",
     "hotel_order"."active_time",
     "hotel_order"."complete_time",
-    "hotel_order"."status"
+    "hotel_order"."status",
+    "hotel_order"."refunded_amount",
+    "hotel_order"."refunded_quantity"
 FROM "hotel_order"
     INNER JOIN "person_info"
         ON "hotel_order"."person_info_id" = "person_info"."id"
@@ -435,6 +506,15 @@ WHERE "user"."id" = $1 AND "hotel_order"."hotel_id" = $2"#,
     }
 
     async fn update(&self, order: Box<dyn Order>) -> Result<(), RepositoryError> {
+        let status_transitions = order.status_transitions().to_vec();
+
+        let txn = self
+            .db
+            .begin()
+            .await
+            .inspect_err(|e| error!("Failed to start transaction: {}", e))
+            .context("Failed to start transaction")?;
+
         match order.as_ref().type_id() {
             id if id == TypeId::of::<TrainOrder>() => {
                 let train_order = (order as Box<dyn Any>).downcast::<TrainOrder>().unwrap();
@@ -444,7 +524,7 @@ WHERE "user"."id" = $1 AND "hotel_order"."hotel_id" = $2"#,
                 let train_order_do = OrderDataConverter::transform_to_do_train(*train_order);
 
                 crate::models::train_order::Entity::update(train_order_do)
-                    .exec(&self.db)
+                    .exec(&txn)
                     .await
                     .context(format!("failed to update train order uuid: {}", order_uuid))?;
             }
@@ -456,7 +536,7 @@ WHERE "user"."id" = $1 AND "hotel_order"."hotel_id" = $2"#,
                 let hotel_order_do = OrderDataConverter::transform_to_do_hotel(*hotel_order);
 
                 crate::models::hotel_order::Entity::update(hotel_order_do)
-                    .exec(&self.db)
+                    .exec(&txn)
                     .await
                     .context(format!("failed to update hotel order uuid: {}", order_uuid))?;
             }
@@ -468,7 +548,7 @@ WHERE "user"."id" = $1 AND "hotel_order"."hotel_id" = $2"#,
                 let dish_order_do = OrderDataConverter::transform_to_do_dish(*dish_order);
 
                 crate::models::dish_order::Entity::update(dish_order_do)
-                    .exec(&self.db)
+                    .exec(&txn)
                     .await
                     .context(format!("failed to update dish order uuid: {}", order_uuid))?;
             }
@@ -481,7 +561,7 @@ WHERE "user"."id" = $1 AND "hotel_order"."hotel_id" = $2"#,
                     OrderDataConverter::transform_to_do_takeaway(*takeaway_order);
 
                 crate::models::takeaway_order::Entity::update(takeaway_order_do)
-                    .exec(&self.db)
+                    .exec(&txn)
                     .await
                     .context(format!(
                         "failed to update takeaway order uuid: {}",
@@ -491,6 +571,16 @@ WHERE "user"."id" = $1 AND "hotel_order"."hotel_id" = $2"#,
             _ => panic!("Unknown order type"),
         }
 
+        OrderStatusTransitionDataConverter::save_all(&txn, &status_transitions)
+            .await
+            .inspect_err(|e| error!("Failed to save order status history: {}", e))
+            .context("failed to save order status history")?;
+
+        txn.commit()
+            .await
+            .inspect_err(|e| error!("Failed to commit transaction: {}", e))
+            .context("Failed to commit transaction")?;
+
         Ok(())
     }
 
@@ -1006,4 +1096,39 @@ WHERE "takeaway_order"."id" = $1"#,
 
         Ok(order.is_some())
     }
+
+    async fn find_expired_seat_hold_train_order_uuids(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Uuid>, RepositoryError> {
+        let train_order_list = crate::models::train_order::Entity::find()
+            .filter(crate::models::train_order::Column::SeatHoldExpireTime.lte(now))
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to load expired seat hold train orders: {}", e))
+            .context("failed to load expired seat hold train orders from db")?;
+
+        Ok(train_order_list.into_iter().map(|x| x.uuid).collect())
+    }
+
+    #[instrument(skip(self))]
+    async fn find_status_history(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<Vec<OrderStatusTransition>, RepositoryError> {
+        let history_list = crate::models::order_status_history::Entity::find()
+            .filter(crate::models::order_status_history::Column::OrderUuid.eq(order_uuid))
+            .order_by_asc(crate::models::order_status_history::Column::CreateTime)
+            .order_by_asc(crate::models::order_status_history::Column::Id)
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to load order status history: {}", e))
+            .context("failed to load order status history from db")?;
+
+        history_list
+            .into_iter()
+            .map(OrderStatusTransitionDataConverter::make_from_do)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::InconsistentState)
+    }
 }
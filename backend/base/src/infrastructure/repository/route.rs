@@ -85,6 +85,7 @@ impl RouteDataConverter {
                 stop.arrival_time as u32,
                 stop.departure_time as u32,
                 stop.order as u32,
+                stop.distance as u32,
             );
         }
 
@@ -110,6 +111,7 @@ impl RouteDataConverter {
             let arrival_time = stop.arrival_time();
             let departure_time = stop.departure_time();
             let order = stop.order();
+            let distance = stop.distance();
 
             // Convert to ActiveModel
             let mut model = crate::models::route::ActiveModel {
@@ -119,6 +121,7 @@ impl RouteDataConverter {
                 arrival_time: ActiveValue::Set(arrival_time as i32),
                 departure_time: ActiveValue::Set(departure_time as i32),
                 order: ActiveValue::Set(order as i32),
+                distance: ActiveValue::Set(distance as i32),
             };
 
             if let Some(stop_id) = stop_id {
@@ -513,6 +516,7 @@ WHERE "train_schedule"."id" = $1;"#,
                 arrival_time: ActiveValue::Set(stop.arrival_time as i32),
                 departure_time: ActiveValue::Set(stop.departure_time as i32),
                 order: ActiveValue::Set(stop.order as i32),
+                distance: ActiveValue::Set(stop.distance as i32),
             };
 
             model_list.push(model);
@@ -703,6 +703,71 @@ WHERE "seat_availability"."train_schedule_id" = $1;"#,
         Ok(result)
     }
 
+    async fn get_train_schedules_occupied_seat(
+        &self,
+        train_schedule_ids: &[TrainScheduleId],
+    ) -> Result<HashMap<TrainScheduleId, OccupiedSeatInfoMap>, RepositoryError> {
+        #[derive(FromQueryResult)]
+        struct OccupiedSeatInfo {
+            train_schedule_id: i32,
+            seat_type_id: i32,
+            begin_station_id: i32,
+            end_station_id: i32,
+            seat_id: i64,
+        }
+
+        let mut result: HashMap<TrainScheduleId, OccupiedSeatInfoMap> = HashMap::new();
+
+        if train_schedule_ids.is_empty() {
+            return Ok(result);
+        }
+
+        let placeholders = (1..=train_schedule_ids.len())
+            .map(|i| format!("${}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let seat_info_list = OccupiedSeatInfo::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            format!(
+                r#"SELECT
+    "seat_availability"."train_schedule_id" AS "train_schedule_id",
+    "seat_availability"."seat_type_id" AS "seat_type_id",
+    "seat_availability"."begin_station_id" AS "begin_station_id",
+    "seat_availability"."end_station_id" AS "end_station_id",
+    "occupied_seat"."seat_id" AS "seat_id"
+FROM "seat_availability"
+INNER JOIN "occupied_seat"
+    ON "occupied_seat"."seat_availability_id" = "seat_availability"."id"
+WHERE "seat_availability"."train_schedule_id" IN ({});"#,
+                placeholders
+            ),
+            train_schedule_ids
+                .iter()
+                .map(|id| id.to_db_value().into())
+                .collect::<Vec<sea_orm::Value>>(),
+        ))
+        .all(&self.db)
+        .await
+        .context(format!(
+            "failed to get occupied seat info for {} train schedules",
+            train_schedule_ids.len()
+        ))?;
+
+        for seat_info in seat_info_list {
+            let entry = result
+                .entry(TrainScheduleId::from_db_value(seat_info.train_schedule_id)?)
+                .or_default()
+                .entry(seat_info.seat_type_id)
+                .or_default()
+                .entry((seat_info.begin_station_id, seat_info.end_station_id))
+                .or_default();
+            entry.push(seat_info.seat_id);
+        }
+
+        Ok(result)
+    }
+
     async fn find_by_schedule_seat_type_station_range(
         &self,
         train_schedule_id: TrainScheduleId,
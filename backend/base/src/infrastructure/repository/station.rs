@@ -7,6 +7,7 @@
 //! - 车站实体的CRUD操作
 //! - 按城市或名称查询车站
 //! - 批量导入车站数据
+//! - 同城换乘时间表的导入与加载
 //!
 //! # 实现特点
 //! - 使用SeaORM实现数据库操作
@@ -23,7 +24,7 @@ use async_trait::async_trait;
 use sea_orm::sea_query::OnConflict;
 use sea_orm::{ActiveValue, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
 use sea_orm::{ColumnTrait, Select};
-use shared::data::StationData;
+use shared::data::{IntraCityTransferData, StationData};
 use std::collections::HashMap;
 use tracing::{debug, error, instrument, trace};
 
@@ -369,6 +370,176 @@ impl StationRepository for StationRepositoryImpl {
 
         Ok(())
     }
+
+    /// 加载同城换乘时间表
+    ///
+    /// # Returns
+    /// `(出发车站ID, 目标车站ID) -> 换乘时间（秒）`的映射
+    ///
+    /// # Errors
+    /// - 数据库查询错误
+    /// - 数据转换错误
+    #[instrument(skip(self))]
+    async fn load_transfer_times(
+        &self,
+    ) -> Result<HashMap<(StationId, StationId), u32>, RepositoryError> {
+        let models = crate::models::station_transfer::Entity::find()
+            .all(&self.db)
+            .await
+            .context("failed to load station transfer times")
+            .map_err(|e| {
+                error!("Failed to load station transfer times: {:?}", e);
+                RepositoryError::Db(e)
+            })?;
+
+        let mut result = HashMap::with_capacity(models.len());
+
+        for model in models {
+            let from_station_id = StationId::from_db_value(model.from_station_id)
+                .map_err(RepositoryError::ValidationError)?;
+            let to_station_id = StationId::from_db_value(model.to_station_id)
+                .map_err(RepositoryError::ValidationError)?;
+
+            result.insert((from_station_id, to_station_id), model.transfer_time as u32);
+        }
+
+        Ok(result)
+    }
+
+    /// 批量导入原始同城换乘时间数据
+    ///
+    /// # Arguments
+    /// - `transfer_data`: 按城市组织的换乘时间数据
+    ///
+    /// # Notes
+    /// - 使用事务保证原子性
+    /// - 城市默认换乘时间展开为该城市内所有车站对，单独配置的车站对覆盖默认值
+    /// - 车站对视为双向
+    ///
+    /// # Errors
+    /// - 数据库操作错误
+    /// - 城市或车站不存在错误
+    async fn save_raw_transfer(
+        &self,
+        transfer_data: IntraCityTransferData,
+    ) -> Result<(), RepositoryError> {
+        trace!("Begin transaction");
+        let txn = self
+            .db
+            .begin()
+            .await
+            .context("failed to start transaction")
+            .map_err(|e| {
+                error!("Failed to start transaction: {:?}", e);
+                RepositoryError::Db(e)
+            })?;
+
+        let city_name_to_id = crate::models::city::Entity::find()
+            .all(&txn)
+            .await
+            .context("failed to load cities")
+            .map_err(|e| {
+                error!("Failed to load cities: {:?}", e);
+                RepositoryError::Db(e)
+            })?
+            .into_iter()
+            .map(|city| (city.name, city.id))
+            .collect::<HashMap<_, _>>();
+
+        let stations = crate::models::station::Entity::find()
+            .all(&txn)
+            .await
+            .context("failed to load stations")
+            .map_err(|e| {
+                error!("Failed to load stations: {:?}", e);
+                RepositoryError::Db(e)
+            })?;
+
+        let mut transfer_times: HashMap<(i32, i32), i32> = HashMap::new();
+
+        for item in &transfer_data {
+            let Some(&city_id) = city_name_to_id.get(&item.city) else {
+                error!("City {} not found for station transfer", item.city);
+                return Err(RepositoryError::InconsistentState(anyhow!(
+                    "City not found: {}",
+                    item.city
+                )));
+            };
+
+            let station_name_to_id = stations
+                .iter()
+                .filter(|station| station.city_id == city_id)
+                .map(|station| (station.name.as_str(), station.id))
+                .collect::<HashMap<_, _>>();
+
+            if let Some(default_time) = item.default_time {
+                for &from_id in station_name_to_id.values() {
+                    for &to_id in station_name_to_id.values() {
+                        if from_id != to_id {
+                            transfer_times.insert((from_id, to_id), default_time as i32);
+                        }
+                    }
+                }
+            }
+
+            for pair in &item.stations {
+                let (Some(&from_id), Some(&to_id)) = (
+                    station_name_to_id.get(pair.from.as_str()),
+                    station_name_to_id.get(pair.to.as_str()),
+                ) else {
+                    error!(
+                        "Station {} or {} not found in city {}",
+                        pair.from, pair.to, item.city
+                    );
+                    return Err(RepositoryError::InconsistentState(anyhow!(
+                        "Station not found in city {}: {} / {}",
+                        item.city,
+                        pair.from,
+                        pair.to
+                    )));
+                };
+
+                transfer_times.insert((from_id, to_id), pair.time as i32);
+                transfer_times.insert((to_id, from_id), pair.time as i32);
+            }
+        }
+
+        if !transfer_times.is_empty() {
+            let model_list = transfer_times
+                .into_iter()
+                .map(|((from_id, to_id), transfer_time)| {
+                    crate::models::station_transfer::ActiveModel {
+                        id: ActiveValue::NotSet,
+                        from_station_id: ActiveValue::Set(from_id),
+                        to_station_id: ActiveValue::Set(to_id),
+                        transfer_time: ActiveValue::Set(transfer_time),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            crate::models::station_transfer::Entity::insert_many(model_list)
+                .on_conflict(
+                    OnConflict::columns([
+                        crate::models::station_transfer::Column::FromStationId,
+                        crate::models::station_transfer::Column::ToStationId,
+                    ])
+                    .update_column(crate::models::station_transfer::Column::TransferTime)
+                    .to_owned(),
+                )
+                .exec(&txn)
+                .await
+                .context("failed to save raw station transfer data")
+                .map_err(|e| {
+                    error!("Failed to save raw station transfer data: {:?}", e);
+                    RepositoryError::Db(e)
+                })?;
+        }
+
+        trace!("Commit transaction");
+        txn.commit().await.context("failed to commit transaction")?;
+
+        Ok(())
+    }
 }
 
 impl StationRepositoryImpl {
@@ -444,6 +444,7 @@ impl TakeawayShopRepository for TakeawayShopRepositoryImpl {
             route_arrival_time: i32,
             route_departure_time: i32,
             route_order: i32,
+            route_distance: i32,
         }
 
         let r = QueryResult::find_by_statement(Statement::from_sql_and_values(
@@ -465,7 +466,8 @@ impl TakeawayShopRepository for TakeawayShopRepositoryImpl {
     "route"."station_id" AS "route_station_id",
     "route"."arrival_time" AS "route_arrival_time",
     "route"."departure_time" AS "route_departure_time",
-    "route"."order" AS "route_order"
+    "route"."order" AS "route_order",
+    "route"."distance" AS "route_distance"
 FROM "takeaway_shop"
     INNER JOIN "takeaway_dish"
         ON "takeaway_shop"."id" = "takeaway_dish"."takeaway_shop_id"
@@ -526,6 +528,7 @@ WHERE "route"."line_id" = $1;"#,
                 data.route_arrival_time as u32,
                 data.route_departure_time as u32,
                 data.route_order as u32,
+                data.route_distance as u32,
             );
 
             station_id_to_stop.insert(data.route_station_id, stop);
@@ -23,11 +23,12 @@
 //! - 所有操作都在数据库事务中执行以保证数据一致性
 //! - 使用聚合管理器跟踪变更
 //! - 支持四种订单类型的混合处理
+use crate::domain::model::coupon::{CouponId, CouponRedemption, CouponRedemptionStatus};
 use crate::domain::model::dish::DishId;
-use crate::domain::model::hotel::{HotelDateRange, HotelId, HotelRoomTypeId};
+use crate::domain::model::hotel::{HotelDateRange, HotelId, HotelRoomTypeId, OccupiedRoom};
 use crate::domain::model::order::{
-    BaseOrder, DishOrder, HotelOrder, Order, OrderId, OrderStatus, OrderTimeInfo, PaymentInfo,
-    TakeawayOrder, TrainOrder,
+    BaseOrder, DishOrder, GroupSeatingStrategy, HotelOrder, Order, OrderId, OrderStatus,
+    OrderTimeInfo, OrderType, PaymentInfo, TakeawayOrder, TicketCategory, TrainOrder,
 };
 use crate::domain::model::personal_info::{PersonalInfoId, PreferredSeatLocation};
 use crate::domain::model::station::StationId;
@@ -36,21 +37,31 @@ use crate::domain::model::train::{SeatType, SeatTypeId, SeatTypeName};
 use crate::domain::model::train_schedule::{
     Seat, SeatId, SeatLocationInfo, SeatStatus, StationRange, TrainScheduleId,
 };
-use crate::domain::model::transaction::{Transaction, TransactionId, TransactionStatus};
+use crate::domain::model::transaction::{
+    ExternalPayment, RefundLine, Transaction, TransactionId, TransactionKind, TransactionStatus,
+};
 use crate::domain::model::user::UserId;
-use crate::domain::repository::transaction::TransactionRepository;
+use crate::domain::model::waitlist::WaitlistStatus;
+use crate::domain::repository::transaction::{
+    RebookSettlement, StatementCursor, StatementFilter, TransactionRepository,
+};
 use crate::domain::service::{AggregateManagerImpl, DiffInfo};
+use crate::domain::{AggregateManager, DbRepositorySupport, MultiEntityDiff, RepositoryError};
 use crate::domain::{DbId, DiffType, Identifiable, TypedDiff};
-use crate::domain::{DbRepositorySupport, MultiEntityDiff, RepositoryError};
+use crate::infrastructure::repository::coupon::{CouponDataConverter, count_user_redemptions};
+use crate::infrastructure::repository::ledger::JournalEntryDataConverter;
+use crate::infrastructure::repository::order::OrderStatusTransitionDataConverter;
 use anyhow::{Context, anyhow};
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::{One, ToPrimitive};
+use sea_orm::prelude::DateTimeWithTimeZone;
+use sea_orm::sea_query::OnConflict;
 use sea_orm::{
-    ActiveValue, DatabaseBackend, DatabaseConnection, DatabaseTransaction, DbErr, EntityTrait,
-    QueryFilter, Select, Statement, TransactionTrait,
+    ActiveValue, ConnectionTrait, DatabaseBackend, DatabaseConnection, DatabaseTransaction, DbErr,
+    EntityTrait, QueryFilter, QuerySelect, Select, Statement, TransactionTrait,
 };
-use sea_orm::{ColumnTrait, FromQueryResult};
+use sea_orm::{ColumnTrait, Condition, FromQueryResult, PaginatorTrait, QueryOrder};
 use shared::utils::TimeMeter;
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
@@ -104,7 +115,7 @@ impl OrderDataConverter {
         );
         let unit_price = train_order_do.price;
         let amount = Decimal::one();
-        let payment_info = PaymentInfo::new(
+        let payment_info = PaymentInfo::new_full(
             train_order_do
                 .pay_transaction_id
                 .map(TransactionId::try_from)
@@ -113,6 +124,8 @@ impl OrderDataConverter {
                 .refund_transaction_id
                 .map(TransactionId::try_from)
                 .transpose()?,
+            train_order_do.refunded_amount,
+            Decimal::from(train_order_do.refunded_quantity),
         );
         let personal_info_id = PersonalInfoId::from_db_value(train_order_do.person_info_id)?;
 
@@ -197,14 +210,29 @@ impl OrderDataConverter {
             ));
         }
 
-        Ok(TrainOrder::new(
+        let group_seating = train_order_do
+            .group_seating
+            .map(|x| GroupSeatingStrategy::try_from(x.as_str()))
+            .transpose()
+            .map_err(|e| anyhow!(e))?;
+
+        let mut train_order = TrainOrder::new(
             base,
             train_schedule_id,
             seat,
             order_seat_type_name,
             preferred_seat_location,
             station_range,
-        ))
+        );
+
+        train_order.set_group_seating(group_seating);
+        train_order.set_ticket_category(
+            TicketCategory::try_from(train_order_do.ticket_category.as_str())
+                .map_err(|e| anyhow!(e))?,
+        );
+        train_order.set_seat_hold_expire_time(train_order_do.seat_hold_expire_time);
+
+        Ok(train_order)
     }
 
     /// 将火车票订单领域对象转换为数据库模型
@@ -229,6 +257,14 @@ impl OrderDataConverter {
             price: ActiveValue::Set(train_order.unit_price()),
             pay_transaction_id: ActiveValue::NotSet,
             refund_transaction_id: ActiveValue::NotSet,
+            refunded_amount: ActiveValue::Set(train_order.payment_info().refunded_amount()),
+            refunded_quantity: ActiveValue::Set(
+                train_order
+                    .payment_info()
+                    .refunded_quantity()
+                    .to_i32()
+                    .unwrap(),
+            ),
             person_info_id: ActiveValue::Set(train_order.personal_info_id().to_db_value()),
 
             train_schedule_id: ActiveValue::Set(train_order.train_schedule_id().to_db_value()),
@@ -252,6 +288,9 @@ impl OrderDataConverter {
             preferred_seat_location: ActiveValue::Set(
                 train_order.preferred_seat_location().map(|x| x.to_string()),
             ),
+            group_seating: ActiveValue::Set(train_order.group_seating().map(|x| x.to_string())),
+            ticket_category: ActiveValue::Set(train_order.ticket_category().to_string()),
+            seat_hold_expire_time: ActiveValue::Set(train_order.seat_hold_expire_time()),
         };
 
         if let Some(id) = train_order.get_id() {
@@ -306,7 +345,7 @@ impl OrderDataConverter {
         );
         let unit_price = hotel_order_do.price;
         let amount = Decimal::from(hotel_order_do.amount);
-        let payment_info = PaymentInfo::new(
+        let payment_info = PaymentInfo::new_full(
             hotel_order_do
                 .pay_transaction_id
                 .map(TransactionId::try_from)
@@ -315,6 +354,8 @@ impl OrderDataConverter {
                 .refund_transaction_id
                 .map(TransactionId::try_from)
                 .transpose()?,
+            hotel_order_do.refunded_amount,
+            Decimal::from(hotel_order_do.refunded_quantity),
         );
         let personal_info_id = PersonalInfoId::from_db_value(hotel_order_do.person_info_id)?;
 
@@ -366,6 +407,14 @@ impl OrderDataConverter {
             amount: ActiveValue::Set(hotel_order.amount().to_i32().unwrap()),
             pay_transaction_id: ActiveValue::NotSet,
             refund_transaction_id: ActiveValue::NotSet,
+            refunded_amount: ActiveValue::Set(hotel_order.payment_info().refunded_amount()),
+            refunded_quantity: ActiveValue::Set(
+                hotel_order
+                    .payment_info()
+                    .refunded_quantity()
+                    .to_i32()
+                    .unwrap(),
+            ),
             person_info_id: ActiveValue::Set(hotel_order.personal_info_id().to_db_value()),
 
             hotel_id: ActiveValue::Set(hotel_order.hotel_id().to_db_value()),
@@ -413,7 +462,7 @@ impl OrderDataConverter {
         );
         let unit_price = dish_order_do.price;
         let amount = Decimal::from(dish_order_do.amount);
-        let payment_info = PaymentInfo::new(
+        let payment_info = PaymentInfo::new_full(
             dish_order_do
                 .pay_transaction_id
                 .map(TransactionId::try_from)
@@ -422,6 +471,8 @@ impl OrderDataConverter {
                 .refund_transaction_id
                 .map(TransactionId::try_from)
                 .transpose()?,
+            dish_order_do.refunded_amount,
+            Decimal::from(dish_order_do.refunded_quantity),
         );
         let personal_info_id = PersonalInfoId::from_db_value(dish_order_do.person_info_id)?;
 
@@ -469,6 +520,14 @@ impl OrderDataConverter {
             price: ActiveValue::Set(dish_order.unit_price()),
             pay_transaction_id: ActiveValue::NotSet,
             refund_transaction_id: ActiveValue::NotSet,
+            refunded_amount: ActiveValue::Set(dish_order.payment_info().refunded_amount()),
+            refunded_quantity: ActiveValue::Set(
+                dish_order
+                    .payment_info()
+                    .refunded_quantity()
+                    .to_i32()
+                    .unwrap(),
+            ),
             person_info_id: ActiveValue::Set(dish_order.personal_info_id().to_db_value()),
 
             train_order_id: ActiveValue::Set(dish_order.train_order_id().to_db_value()),
@@ -515,7 +574,7 @@ impl OrderDataConverter {
         );
         let unit_price = takeaway_order_do.price;
         let amount = Decimal::from(takeaway_order_do.amount);
-        let payment_info = PaymentInfo::new(
+        let payment_info = PaymentInfo::new_full(
             takeaway_order_do
                 .pay_transaction_id
                 .map(TransactionId::try_from)
@@ -524,6 +583,8 @@ impl OrderDataConverter {
                 .refund_transaction_id
                 .map(TransactionId::try_from)
                 .transpose()?,
+            takeaway_order_do.refunded_amount,
+            Decimal::from(takeaway_order_do.refunded_quantity),
         );
         let personal_info_id = PersonalInfoId::from_db_value(takeaway_order_do.person_info_id)?;
 
@@ -573,6 +634,14 @@ impl OrderDataConverter {
             price: ActiveValue::Set(takeaway_order.unit_price()),
             pay_transaction_id: ActiveValue::NotSet,
             refund_transaction_id: ActiveValue::NotSet,
+            refunded_amount: ActiveValue::Set(takeaway_order.payment_info().refunded_amount()),
+            refunded_quantity: ActiveValue::Set(
+                takeaway_order
+                    .payment_info()
+                    .refunded_quantity()
+                    .to_i32()
+                    .unwrap(),
+            ),
             person_info_id: ActiveValue::Set(takeaway_order.personal_info_id().to_db_value()),
 
             train_order_id: ActiveValue::Set(takeaway_order.train_order_id().to_db_value()),
@@ -1010,6 +1079,28 @@ impl TransactionDataConverter {
 
         let orders: Vec<Box<dyn Order>> = transaction_do_pack.orders.into();
 
+        let coupon_redemption = match (
+            transaction_do_pack.transaction.coupon_id,
+            transaction_do_pack.transaction.coupon_status.as_deref(),
+        ) {
+            (Some(coupon_id), Some(coupon_status)) => Some(CouponRedemption::new(
+                CouponId::from_db_value(coupon_id)?,
+                transaction_do_pack.transaction.coupon_discount,
+                CouponRedemptionStatus::try_from(coupon_status).map_err(|e| anyhow!(e))?,
+            )),
+            _ => None,
+        };
+
+        let external_payment = match (
+            transaction_do_pack.transaction.payment_provider,
+            transaction_do_pack.transaction.external_payment_id,
+        ) {
+            (Some(provider), Some(external_payment_id)) => {
+                Some(ExternalPayment::new(provider, external_payment_id))
+            }
+            _ => None,
+        };
+
         Ok(Transaction::new_full(
             Some(transaction_id),
             transaction_do_pack.transaction.uuid,
@@ -1017,9 +1108,15 @@ impl TransactionDataConverter {
             transaction_do_pack.transaction.finish_time,
             transaction_do_pack.transaction.amount,
             transaction_status,
+            TransactionKind::try_from(transaction_do_pack.transaction.kind.as_str())
+                .map_err(|e| anyhow!(e))?,
             UserId::try_from(transaction_do_pack.transaction.user_id)?,
             orders,
             transaction_do_pack.transaction.atomic,
+            coupon_redemption,
+            transaction_do_pack.transaction.refund_fee,
+            transaction_do_pack.transaction.payment_deadline,
+            external_payment,
         ))
     }
 
@@ -1042,8 +1139,30 @@ impl TransactionDataConverter {
             status: ActiveValue::Set(
                 <TransactionStatus as Into<&str>>::into(transaction.status()).to_string(),
             ),
+            kind: ActiveValue::Set(transaction.kind().to_string()),
             user_id: ActiveValue::Set(transaction.user_id().to_db_value()),
             atomic: ActiveValue::Set(transaction.atomic()),
+            coupon_id: ActiveValue::Set(
+                transaction
+                    .coupon_redemption()
+                    .map(|redemption| redemption.coupon_id().to_db_value()),
+            ),
+            coupon_discount: ActiveValue::Set(transaction.coupon_discount()),
+            coupon_status: ActiveValue::Set(transaction.coupon_redemption().map(|redemption| {
+                <CouponRedemptionStatus as Into<&str>>::into(redemption.status()).to_string()
+            })),
+            refund_fee: ActiveValue::Set(transaction.refund_fee()),
+            payment_deadline: ActiveValue::Set(transaction.payment_deadline()),
+            payment_provider: ActiveValue::Set(
+                transaction
+                    .external_payment()
+                    .map(|payment| payment.provider().to_string()),
+            ),
+            external_payment_id: ActiveValue::Set(
+                transaction
+                    .external_payment()
+                    .map(|payment| payment.external_payment_id()),
+            ),
         };
 
         if let Some(id) = transaction.get_id() {
@@ -1073,6 +1192,57 @@ impl TransactionDataConverter {
     }
 }
 
+/// 退款明细数据转换器
+pub struct RefundLineDataConverter;
+
+impl RefundLineDataConverter {
+    /// 将退款明细转换为数据库模型
+    ///
+    /// # Arguments
+    /// * `transaction_id` - 退款明细所属退款交易的数据库ID
+    /// * `line` - 要转换的退款明细
+    pub fn transform_to_do(
+        transaction_id: i32,
+        line: &RefundLine,
+    ) -> crate::models::refund_line::ActiveModel {
+        crate::models::refund_line::ActiveModel {
+            id: ActiveValue::NotSet,
+            uuid: ActiveValue::Set(line.uuid()),
+            transaction_id: ActiveValue::Set(transaction_id),
+            order_uuid: ActiveValue::Set(line.order_uuid()),
+            quantity: ActiveValue::Set(line.quantity().map(|x| x.to_i32().unwrap())),
+            amount: ActiveValue::Set(line.amount()),
+            fee: ActiveValue::Set(line.fee()),
+        }
+    }
+
+    /// 持久化退款明细，已持久化的明细（按`uuid`判断）将被忽略
+    pub async fn save_all<C: ConnectionTrait>(
+        db: &C,
+        transaction_id: i32,
+        lines: &[RefundLine],
+    ) -> Result<(), DbErr> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        crate::models::refund_line::Entity::insert_many(
+            lines
+                .iter()
+                .map(|line| Self::transform_to_do(transaction_id, line)),
+        )
+        .on_conflict(
+            OnConflict::column(crate::models::refund_line::Column::Uuid)
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await?;
+
+        Ok(())
+    }
+}
+
 /// 交易仓储实现
 ///
 /// 实现`TransactionRepository`和`DbRepositorySupport` trait
@@ -1189,7 +1359,8 @@ impl TransactionRepositoryImpl {
                         && old.amount() == new.amount()
                         && old.status() == new.status()
                         && old.user_id() == new.user_id()
-                        && old.atomic() == new.atomic())
+                        && old.atomic() == new.atomic()
+                        && old.coupon_redemption() == new.coupon_redemption())
                     {
                         result.add_change(TypedDiff::new(DiffType::Modified, Some(old), Some(new)));
                     }
@@ -1340,30 +1511,196 @@ impl TransactionRepositoryImpl {
     }
 }
 
-#[async_trait]
-impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
-    type Manager = AggregateManagerImpl<Transaction>;
+impl TransactionRepositoryImpl {
+    /// 筛选用户账单中的交易：已支付且完成时间在查询时间段内的交易
+    fn filter_statement(
+        select: Select<crate::models::transaction::Entity>,
+        user_id: UserId,
+        filter: &StatementFilter,
+    ) -> Select<crate::models::transaction::Entity> {
+        use crate::models::transaction::Column;
+
+        let paid: &str = TransactionStatus::Paid.into();
+
+        let mut select = select
+            .filter(Column::UserId.eq(user_id.to_db_value()))
+            .filter(Column::Status.eq(paid))
+            .filter(Column::FinishTime.lt(filter.end));
+
+        if let Some(begin) = filter.begin {
+            select = select.filter(Column::FinishTime.gte(begin));
+        }
 
-    fn get_aggregate_manager(&self) -> Arc<Mutex<Self::Manager>> {
-        Arc::clone(&self.aggregate_manager)
+        if !filter.kinds.is_empty() {
+            select =
+                select.filter(Column::Kind.is_in(filter.kinds.iter().map(|kind| kind.to_string())));
+        }
+
+        select
     }
 
-    #[instrument(skip(self))]
-    async fn on_insert(&self, aggregate: Transaction) -> Result<TransactionId, RepositoryError> {
-        debug!("inserting transaction: {:?}", aggregate);
-        let txn = self
-            .db
-            .begin()
+    /// 订单所在的数据表
+    fn order_table(order_type: OrderType) -> &'static str {
+        match order_type {
+            OrderType::Train => "train_order",
+            OrderType::Hotel => "hotel_order",
+            OrderType::Dish => "dish_order",
+            OrderType::Takeaway => "takeaway_order",
+        }
+    }
+
+    /// 在数据库事务中变更交易状态
+    ///
+    /// 仅当数据库中的交易状态仍为加载时的状态才更新，否则说明交易已被并发修改（如重复支付），
+    /// 返回错误使整个事务回滚，避免重复记账。
+    async fn transit_status(
+        txn: &DatabaseTransaction,
+        old_status: TransactionStatus,
+        new: &Transaction,
+    ) -> Result<(), RepositoryError> {
+        let id = new.get_id().ok_or_else(|| {
+            RepositoryError::InconsistentState(anyhow!("transaction {} has no id", new.uuid()))
+        })?;
+        let old_status: &str = old_status.into();
+        let new_status: &str = new.status().into();
+
+        let result = txn
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"UPDATE "transaction" SET "status" = $1 WHERE "id" = $2 AND "status" = $3"#,
+                [
+                    new_status.into(),
+                    id.to_db_value().into(),
+                    old_status.into(),
+                ],
+            ))
             .await
-            .inspect_err(|e| {
-                error!("Failed to start transaction: {}", e);
-            })
-            .context("Failed to start transaction")?;
+            .context(format!(
+                "Failed to update status of transaction {}",
+                new.uuid()
+            ))?;
+
+        if result.rows_affected() != 1 {
+            return Err(RepositoryError::InconsistentState(anyhow!(
+                "transaction {} status changed concurrently",
+                new.uuid()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 在数据库事务中同步优惠券的核销状态
+    ///
+    /// 比较数据库中已持久化的核销状态与聚合根中的状态：
+    /// - 未核销 -> 已核销：锁定优惠券，检查有效期与使用次数限制后增加已核销次数，不满足条件时整个事务回滚
+    /// - 已核销 -> 已返还：减少已核销次数
+    async fn sync_coupon_redemption(
+        txn: &DatabaseTransaction,
+        transaction: &Transaction,
+    ) -> Result<(), RepositoryError> {
+        let Some(redemption) = transaction.coupon_redemption() else {
+            return Ok(());
+        };
+
+        let transaction_id = transaction.get_id().ok_or_else(|| {
+            RepositoryError::InconsistentState(anyhow!(
+                "transaction {} with coupon has no id",
+                transaction.uuid()
+            ))
+        })?;
+
+        #[derive(Debug, FromQueryResult)]
+        struct PersistedCouponStatus {
+            coupon_status: Option<String>,
+        }
+
+        // 锁定交易行，避免同一交易被并发核销两次
+        let persisted = PersistedCouponStatus::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "transaction"."coupon_status" FROM "transaction" WHERE "transaction"."id" = $1 FOR UPDATE"#,
+            [transaction_id.to_db_value().into()],
+        ))
+        .one(txn)
+        .await
+        .context(format!(
+            "Failed to lock transaction {} for coupon redemption",
+            transaction_id
+        ))?
+        .and_then(|item| item.coupon_status)
+        .map(|status| CouponRedemptionStatus::try_from(status.as_str()))
+        .transpose()
+        .map_err(|e| RepositoryError::InconsistentState(anyhow!(e)))?;
+
+        let coupon_id = redemption.coupon_id().to_db_value();
+
+        match (persisted, redemption.status()) {
+            (Some(CouponRedemptionStatus::Pending), CouponRedemptionStatus::Redeemed) => {
+                let coupon_do = crate::models::coupon::Entity::find_by_id(coupon_id)
+                    .lock_exclusive()
+                    .one(txn)
+                    .await
+                    .context(format!("Failed to lock coupon {}", coupon_id))?
+                    .ok_or_else(|| {
+                        RepositoryError::InconsistentState(anyhow!(
+                            "coupon {} of transaction {} not found",
+                            coupon_id,
+                            transaction_id
+                        ))
+                    })?;
+
+                let coupon = CouponDataConverter::make_from_do(coupon_do)
+                    .map_err(RepositoryError::ValidationError)?;
+
+                let user_redeemed_count =
+                    count_user_redemptions(txn, redemption.coupon_id(), transaction.user_id())
+                        .await?;
+
+                coupon
+                    .check_valid_time(transaction.finish_time().unwrap_or_else(Transaction::now))
+                    .and_then(|_| coupon.check_redemption_limit(user_redeemed_count))
+                    .map_err(|e| RepositoryError::ValidationError(e.into()))?;
+
+                txn.execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"UPDATE "coupon" SET "redeemed_count" = "redeemed_count" + 1 WHERE "id" = $1"#,
+                    [coupon_id.into()],
+                ))
+                .await
+                .context(format!("Failed to redeem coupon {}", coupon_id))?;
+            }
+            (Some(CouponRedemptionStatus::Redeemed), CouponRedemptionStatus::Restored) => {
+                txn.execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    r#"UPDATE "coupon" SET "redeemed_count" = "redeemed_count" - 1 WHERE "id" = $1"#,
+                    [coupon_id.into()],
+                ))
+                .await
+                .context(format!("Failed to restore coupon {}", coupon_id))?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// 在数据库事务中插入交易及其订单、退款明细、订单状态历史和会计分录
+    async fn insert_transaction(
+        txn: &DatabaseTransaction,
+        aggregate: Transaction,
+    ) -> Result<TransactionId, RepositoryError> {
+        let status_transitions = aggregate
+            .orders()
+            .iter()
+            .flat_map(|order| order.status_transitions().iter().cloned())
+            .collect::<Vec<_>>();
+        let journal_entries = aggregate.journal_entries().to_vec();
+        let refund_lines = aggregate.refund_lines().to_vec();
 
         let model_pack = TransactionDataConverter::transform_to_do(aggregate);
 
         let result = crate::models::transaction::Entity::insert(model_pack.transaction)
-            .exec(&txn)
+            .exec(txn)
             .await
             .inspect_err(|e| {
                 error!("Failed to insert transaction: {}", e);
@@ -1372,13 +1709,60 @@ impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
 
         model_pack
             .orders
-            .insert_or_update_all(&txn, Some(result.last_insert_id))
+            .insert_or_update_all(txn, Some(result.last_insert_id))
             .await
             .inspect_err(|e| {
                 error!("failed to insert or update orders: {}", e);
             })
             .context("Failed to insert orders")?;
 
+        RefundLineDataConverter::save_all(txn, result.last_insert_id, &refund_lines)
+            .await
+            .inspect_err(|e| {
+                error!("failed to save refund lines: {}", e);
+            })
+            .context("Failed to save refund lines")?;
+
+        OrderStatusTransitionDataConverter::save_all(txn, &status_transitions)
+            .await
+            .inspect_err(|e| {
+                error!("failed to save order status history: {}", e);
+            })
+            .context("Failed to save order status history")?;
+
+        JournalEntryDataConverter::save_all(txn, &journal_entries)
+            .await
+            .inspect_err(|e| {
+                error!("failed to save journal entries: {}", e);
+            })
+            .context("Failed to save journal entries")?;
+
+        Ok(TransactionId::from_db_value(result.last_insert_id)?)
+    }
+}
+
+#[async_trait]
+impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
+    type Manager = AggregateManagerImpl<Transaction>;
+
+    fn get_aggregate_manager(&self) -> Arc<Mutex<Self::Manager>> {
+        Arc::clone(&self.aggregate_manager)
+    }
+
+    #[instrument(skip(self))]
+    async fn on_insert(&self, aggregate: Transaction) -> Result<TransactionId, RepositoryError> {
+        debug!("inserting transaction: {:?}", aggregate);
+        let txn = self
+            .db
+            .begin()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to start transaction: {}", e);
+            })
+            .context("Failed to start transaction")?;
+
+        let id = Self::insert_transaction(&txn, aggregate).await?;
+
         txn.commit()
             .await
             .inspect_err(|e| {
@@ -1386,7 +1770,7 @@ impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
             })
             .context("Failed to commit transaction")?;
 
-        Ok(TransactionId::from_db_value(result.last_insert_id)?)
+        Ok(id)
     }
 
     #[instrument(skip(self))]
@@ -1444,6 +1828,11 @@ impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
             }
         }
 
+        let status_transitions = to_update_orders
+            .iter()
+            .flat_map(|order| order.status_transitions().iter().cloned())
+            .collect::<Vec<_>>();
+
         let to_update_order_pack: OrderPack = to_update_orders.into();
         let to_remove_order_pack: OrderPack = to_remove_orders.into();
 
@@ -1456,6 +1845,13 @@ impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
             })
             .map_err(|e| RepositoryError::Db(e.into()))?;
 
+        OrderStatusTransitionDataConverter::save_all(&txn, &status_transitions)
+            .await
+            .inspect_err(|e| {
+                error!("failed to save order status history: {}", e);
+            })
+            .map_err(|e| RepositoryError::Db(e.into()))?;
+
         to_remove_order_pack
             .delete_all(&txn)
             .await
@@ -1475,6 +1871,14 @@ impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
 
                     debug!("transaction modified: {:?}", new);
 
+                    if let Some(old) = &changes.old_value
+                        && old.status() != new.status()
+                    {
+                        Self::transit_status(&txn, old.status(), &new).await?;
+                    }
+
+                    Self::sync_coupon_redemption(&txn, &new).await?;
+
                     crate::models::transaction::Entity::update(
                         TransactionDataConverter::transform_to_do_transaction_only(&new),
                     )
@@ -1484,6 +1888,26 @@ impl DbRepositorySupport<Transaction> for TransactionRepositoryImpl {
                         error!("failed to update transaction: {}", e);
                     })
                     .map_err(|e| RepositoryError::Db(e.into()))?;
+
+                    JournalEntryDataConverter::save_all(&txn, new.journal_entries())
+                        .await
+                        .inspect_err(|e| {
+                            error!("failed to save journal entries: {}", e);
+                        })
+                        .map_err(|e| RepositoryError::Db(e.into()))?;
+
+                    if let Some(id) = new.get_id() {
+                        RefundLineDataConverter::save_all(
+                            &txn,
+                            id.to_db_value(),
+                            new.refund_lines(),
+                        )
+                        .await
+                        .inspect_err(|e| {
+                            error!("failed to save refund lines: {}", e);
+                        })
+                        .map_err(|e| RepositoryError::Db(e.into()))?;
+                    }
                 }
                 DiffType::Removed => {
                     panic!("Aggregate root transaction should not have diff type of: Removed")
@@ -1537,6 +1961,75 @@ impl TransactionRepository for TransactionRepositoryImpl {
         Ok(r)
     }
 
+    async fn find_by_order_uuid(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<Option<Transaction>, RepositoryError> {
+        #[derive(Debug, FromQueryResult)]
+        struct PayTransactionId {
+            pay_transaction_id: Option<i32>,
+        }
+
+        // 订单 UUID 在各类订单表中唯一，至多命中一行
+        let r = PayTransactionId::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "pay_transaction_id" FROM "train_order" WHERE "uuid" = $1
+UNION ALL SELECT "pay_transaction_id" FROM "hotel_order" WHERE "uuid" = $1
+UNION ALL SELECT "pay_transaction_id" FROM "dish_order" WHERE "uuid" = $1
+UNION ALL SELECT "pay_transaction_id" FROM "takeaway_order" WHERE "uuid" = $1"#,
+            [order_uuid.into()],
+        ))
+        .one(&self.db)
+        .await
+        .context(format!(
+            "Failed to query pay transaction of order: {}",
+            order_uuid
+        ))?;
+
+        let Some(transaction_id) = r.and_then(|item| item.pay_transaction_id) else {
+            return Ok(None);
+        };
+
+        let r = self
+            .query_transaction(|q| {
+                q.filter(crate::models::transaction::Column::Id.eq(transaction_id))
+            })
+            .await?;
+
+        Ok(r.into_iter().next())
+    }
+
+    async fn find_expired_unpaid(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Transaction>, RepositoryError> {
+        let unpaid: &str = TransactionStatus::Unpaid.into();
+
+        self.query_transaction(|q| {
+            q.filter(crate::models::transaction::Column::Status.eq(unpaid))
+                .filter(crate::models::transaction::Column::PaymentDeadline.lte(now))
+        })
+        .await
+    }
+
+    async fn find_by_external_payment_id(
+        &self,
+        provider: &str,
+        external_payment_id: Uuid,
+    ) -> Result<Option<Transaction>, RepositoryError> {
+        let r = self
+            .query_transaction(|q| {
+                q.filter(crate::models::transaction::Column::PaymentProvider.eq(provider))
+                    .filter(
+                        crate::models::transaction::Column::ExternalPaymentId
+                            .eq(external_payment_id),
+                    )
+            })
+            .await?;
+
+        Ok(r.into_iter().next())
+    }
+
     async fn get_user_balance(&self, user_id: UserId) -> Result<Option<Decimal>, RepositoryError> {
         #[derive(Debug, FromQueryResult)]
         struct Balance {
@@ -1554,4 +2047,563 @@ impl TransactionRepository for TransactionRepositoryImpl {
 
         Ok(r.map(|item| item.balance))
     }
+
+    async fn get_all_user_balances(&self) -> Result<HashMap<UserId, Decimal>, RepositoryError> {
+        #[derive(Debug, FromQueryResult)]
+        struct Balance {
+            user_id: i32,
+            balance: Decimal,
+        }
+
+        let r = Balance::find_by_statement(Statement::from_string(
+            DatabaseBackend::Postgres,
+            r#"SELECT "balance"."user_id", "balance"."balance" FROM "balance""#,
+        ))
+        .all(&self.db)
+        .await
+        .context("Failed to query balance for all users")?;
+
+        r.into_iter()
+            .map(|item| Ok((UserId::from_db_value(item.user_id)?, item.balance)))
+            .collect::<Result<HashMap<_, _>, anyhow::Error>>()
+            .map_err(RepositoryError::InconsistentState)
+    }
+
+    async fn find_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+        after: Option<StatementCursor>,
+        limit: Option<u64>,
+    ) -> Result<Vec<Transaction>, RepositoryError> {
+        use crate::models::transaction::Column;
+
+        self.query_transaction(|q| {
+            let mut q = Self::filter_statement(q, user_id, filter)
+                .order_by_asc(Column::FinishTime)
+                .order_by_asc(Column::Id);
+
+            if let Some(after) = after {
+                q = q.filter(
+                    Condition::any()
+                        .add(Column::FinishTime.gt(after.finish_time))
+                        .add(
+                            Condition::all()
+                                .add(Column::FinishTime.eq(after.finish_time))
+                                .add(Column::Id.gt(after.transaction_id.to_db_value())),
+                        ),
+                );
+            }
+
+            if let Some(limit) = limit {
+                q = q.limit(limit);
+            }
+
+            q
+        })
+        .await
+    }
+
+    async fn count_statement(
+        &self,
+        user_id: UserId,
+        filter: &StatementFilter,
+    ) -> Result<u64, RepositoryError> {
+        let count =
+            Self::filter_statement(crate::models::transaction::Entity::find(), user_id, filter)
+                .count(&self.db)
+                .await
+                .context(format!(
+                    "Failed to count statement transactions for user: {}",
+                    user_id
+                ))?;
+
+        Ok(count)
+    }
+
+    async fn get_user_balance_before(
+        &self,
+        user_id: UserId,
+        time: DateTimeWithTimeZone,
+    ) -> Result<Decimal, RepositoryError> {
+        #[derive(Debug, FromQueryResult)]
+        struct Balance {
+            balance: Decimal,
+        }
+
+        // 与`balance`视图一致：余额为已支付交易金额之和的相反数
+        let r = Balance::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT COALESCE(-SUM("transaction"."amount"), 0) AS "balance"
+FROM "transaction"
+WHERE "transaction"."user_id" = $1
+  AND "transaction"."status" = 'paid'
+  AND "transaction"."finish_time" < $2"#,
+            [user_id.to_db_value().into(), time.into()],
+        ))
+        .one(&self.db)
+        .await
+        .context(format!(
+            "Failed to query balance before {} for user: {}",
+            time, user_id
+        ))?;
+
+        Ok(r.map_or(Decimal::ZERO, |item| item.balance))
+    }
+
+    #[instrument(skip_all)]
+    async fn save_partial_refund(
+        &self,
+        transaction: &mut Transaction,
+        refund_transaction: &mut Transaction,
+        released_rooms: &[OccupiedRoom],
+    ) -> Result<TransactionId, RepositoryError> {
+        let transaction_id = transaction.get_id().ok_or_else(|| {
+            RepositoryError::InconsistentState(anyhow!(
+                "partially refunded transaction {} has no id",
+                transaction.uuid()
+            ))
+        })?;
+
+        let txn = self
+            .db
+            .begin()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to start transaction: {}", e);
+            })
+            .context("Failed to start transaction")?;
+
+        // 锁定原交易行，使同一交易的部分退款、取消依次执行
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "transaction"."id" FROM "transaction" WHERE "transaction"."id" = $1 FOR UPDATE"#,
+            [transaction_id.to_db_value().into()],
+        ))
+        .await
+        .context(format!(
+            "Failed to lock transaction {} for partial refund",
+            transaction_id
+        ))?;
+
+        for order in transaction.orders() {
+            let lines = refund_transaction
+                .refund_lines()
+                .iter()
+                .filter(|line| line.order_uuid() == order.uuid())
+                .collect::<Vec<_>>();
+
+            if lines.is_empty() {
+                continue;
+            }
+
+            let payment_info = order.payment_info();
+            let refunded_amount = payment_info.refunded_amount();
+            let refunded_quantity = payment_info.refunded_quantity();
+            let previous_amount =
+                refunded_amount - lines.iter().map(|line| line.amount()).sum::<Decimal>();
+            let previous_quantity = refunded_quantity
+                - lines
+                    .iter()
+                    .filter_map(|line| line.quantity())
+                    .sum::<Decimal>();
+
+            let to_db_quantity = |quantity: Decimal| {
+                quantity.to_i32().ok_or_else(|| {
+                    RepositoryError::InconsistentState(anyhow!(
+                        "invalid refunded quantity {} of order {}",
+                        quantity,
+                        order.uuid()
+                    ))
+                })
+            };
+
+            let table = Self::order_table(order.order_type());
+            let status: &str = order.order_status().into();
+
+            // 仅当订单的累计退款与状态仍与加载时一致才更新，避免并发的部分退款或取消重复退款
+            let result = txn
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    format!(
+                        r#"UPDATE "{table}" SET "refunded_amount" = $1, "refunded_quantity" = $2
+WHERE "uuid" = $3
+  AND "refunded_amount" = $4
+  AND "refunded_quantity" = $5
+  AND "status" = $6
+  AND "refund_transaction_id" IS NULL"#
+                    ),
+                    [
+                        refunded_amount.into(),
+                        to_db_quantity(refunded_quantity)?.into(),
+                        order.uuid().into(),
+                        previous_amount.into(),
+                        to_db_quantity(previous_quantity)?.into(),
+                        status.into(),
+                    ],
+                ))
+                .await
+                .context(format!(
+                    "Failed to record partial refund of order {}",
+                    order.uuid()
+                ))?;
+
+            if result.rows_affected() != 1 {
+                return Err(RepositoryError::InconsistentState(anyhow!(
+                    "order {} was refunded or changed concurrently",
+                    order.uuid()
+                )));
+            }
+        }
+
+        for room in released_rooms {
+            let room_id = room.get_id().ok_or_else(|| {
+                RepositoryError::InconsistentState(anyhow!("released occupied room has no id"))
+            })?;
+
+            let result = crate::models::occupied_room::Entity::delete_by_id(room_id.to_db_value())
+                .exec(&txn)
+                .await
+                .context(format!("Failed to release occupied room {}", room_id))?;
+
+            if result.rows_affected != 1 {
+                return Err(RepositoryError::InconsistentState(anyhow!(
+                    "occupied room {} was already released",
+                    room_id
+                )));
+            }
+        }
+
+        Self::sync_coupon_redemption(&txn, transaction).await?;
+
+        crate::models::transaction::Entity::update(
+            TransactionDataConverter::transform_to_do_transaction_only(transaction),
+        )
+        .exec(&txn)
+        .await
+        .inspect_err(|e| {
+            error!("failed to update transaction: {}", e);
+        })
+        .map_err(|e| RepositoryError::Db(e.into()))?;
+
+        let refund_transaction_id =
+            Self::insert_transaction(&txn, refund_transaction.clone()).await?;
+
+        txn.commit()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to commit transaction: {}", e);
+            })
+            .context("Failed to commit transaction")?;
+
+        refund_transaction.set_id(refund_transaction_id);
+
+        let mut manager = self.aggregate_manager.lock().unwrap();
+        manager.attach(refund_transaction.clone());
+        manager.merge(transaction.clone());
+
+        Ok(refund_transaction_id)
+    }
+
+    async fn save_rebook_settlement(
+        &self,
+        adjustment_transaction: &mut Transaction,
+        replaced_transaction: &mut Transaction,
+        replaced_order_uuid: Uuid,
+    ) -> Result<RebookSettlement, RepositoryError> {
+        let replaced_order = replaced_transaction
+            .orders()
+            .iter()
+            .find(|order| order.uuid() == replaced_order_uuid)
+            .ok_or_else(|| {
+                RepositoryError::InconsistentState(anyhow!(
+                    "transaction {} has no order {}",
+                    replaced_transaction.uuid(),
+                    replaced_order_uuid
+                ))
+            })?;
+        let refund_transaction_id = replaced_order
+            .payment_info()
+            .refund_transaction_id()
+            .ok_or_else(|| {
+                RepositoryError::InconsistentState(anyhow!(
+                    "replaced order {} is not refunded to the adjustment transaction",
+                    replaced_order_uuid
+                ))
+            })?;
+
+        let txn = self
+            .db
+            .begin()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to start transaction: {}", e);
+            })
+            .context("Failed to start transaction")?;
+
+        let user_id = adjustment_transaction.user_id();
+
+        // 锁定用户的全部交易行，使余额在结算期间不被并发的支付修改
+        txn.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "transaction"."id" FROM "transaction" WHERE "transaction"."user_id" = $1 FOR UPDATE"#,
+            [user_id.to_db_value().into()],
+        ))
+        .await
+        .context(format!(
+            "Failed to lock transactions of user {} for rebook settlement",
+            user_id
+        ))?;
+
+        #[derive(Debug, FromQueryResult)]
+        struct Balance {
+            balance: Decimal,
+        }
+
+        let balance = Balance::find_by_statement(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "balance"."balance" FROM "balance" WHERE "balance"."user_id" = $1"#,
+            [user_id.to_db_value().into()],
+        ))
+        .one(&txn)
+        .await
+        .context(format!("Failed to query balance for user: {}", user_id))?
+        .map_or(Decimal::ZERO, |item| item.balance);
+
+        // 补缴差价时调整交易金额为正，退还差价时为负，不受余额限制
+        if balance < adjustment_transaction.raw_amount() {
+            return Ok(RebookSettlement::InsufficientBalance { balance });
+        }
+
+        Self::transit_status(&txn, TransactionStatus::Unpaid, adjustment_transaction).await?;
+
+        crate::models::transaction::Entity::update(
+            TransactionDataConverter::transform_to_do_transaction_only(adjustment_transaction),
+        )
+        .exec(&txn)
+        .await
+        .inspect_err(|e| {
+            error!("failed to update transaction: {}", e);
+        })
+        .map_err(|e| RepositoryError::Db(e.into()))?;
+
+        JournalEntryDataConverter::save_all(&txn, adjustment_transaction.journal_entries())
+            .await
+            .inspect_err(|e| {
+                error!("failed to save journal entries: {}", e);
+            })
+            .map_err(|e| RepositoryError::Db(e.into()))?;
+
+        let unpaid: &str = OrderStatus::Unpaid.into();
+
+        for order in adjustment_transaction.orders() {
+            let table = Self::order_table(order.order_type());
+            let status: &str = order.order_status().into();
+
+            // 新订单仅在结算时离开未支付状态，避免与取消新订单并发
+            let result = txn
+                .execute(Statement::from_sql_and_values(
+                    DatabaseBackend::Postgres,
+                    format!(
+                        r#"UPDATE "{table}" SET "status" = $1 WHERE "uuid" = $2 AND "status" = $3"#
+                    ),
+                    [status.into(), order.uuid().into(), unpaid.into()],
+                ))
+                .await
+                .context(format!("Failed to update status of order {}", order.uuid()))?;
+
+            if result.rows_affected() != 1 {
+                return Err(RepositoryError::InconsistentState(anyhow!(
+                    "order {} status changed concurrently",
+                    order.uuid()
+                )));
+            }
+
+            OrderStatusTransitionDataConverter::save_all(&txn, order.status_transitions())
+                .await
+                .inspect_err(|e| {
+                    error!("failed to save order status history: {}", e);
+                })
+                .map_err(|e| RepositoryError::Db(e.into()))?;
+        }
+
+        let table = Self::order_table(replaced_order.order_type());
+        let ongoing: &str = OrderStatus::Ongoing.into();
+
+        // 原订单在改签期间可能被并发取消或退款，此时不能再将其票款计入调整交易
+        let result = txn
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                format!(
+                    r#"UPDATE "{table}" SET "refund_transaction_id" = $1
+WHERE "uuid" = $2
+  AND "status" = $3
+  AND "refund_transaction_id" IS NULL"#
+                ),
+                [
+                    refund_transaction_id.to_db_value().into(),
+                    replaced_order_uuid.into(),
+                    ongoing.into(),
+                ],
+            ))
+            .await
+            .context(format!(
+                "Failed to record rebook of order {}",
+                replaced_order_uuid
+            ))?;
+
+        if result.rows_affected() != 1 {
+            return Err(RepositoryError::InconsistentState(anyhow!(
+                "order {} was refunded or changed concurrently",
+                replaced_order_uuid
+            )));
+        }
+
+        txn.commit()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to commit transaction: {}", e);
+            })
+            .context("Failed to commit transaction")?;
+
+        let mut manager = self.aggregate_manager.lock().unwrap();
+        manager.merge(adjustment_transaction.clone());
+        manager.merge(replaced_transaction.clone());
+
+        Ok(RebookSettlement::Settled)
+    }
+
+    #[instrument(skip_all)]
+    async fn save_fare_difference_refund(
+        &self,
+        refund_transaction: &mut Transaction,
+        waitlist_uuid: Uuid,
+    ) -> Result<TransactionId, RepositoryError> {
+        let txn = self
+            .db
+            .begin()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to start transaction: {}", e);
+            })
+            .context("Failed to start transaction")?;
+
+        let fulfilled: &str = WaitlistStatus::Fulfilled.into();
+
+        // 仅当差价尚未退还才标记，避免兑现与守护任务重试并发时重复退款
+        let result = txn
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"UPDATE "waitlist" SET "fare_difference_refunded" = TRUE
+WHERE "uuid" = $1
+  AND "status" = $2
+  AND "fare_difference_refunded" = FALSE"#,
+                [waitlist_uuid.into(), fulfilled.into()],
+            ))
+            .await
+            .context(format!(
+                "Failed to record fare difference refund of waitlist {}",
+                waitlist_uuid
+            ))?;
+
+        if result.rows_affected() != 1 {
+            return Err(RepositoryError::InconsistentState(anyhow!(
+                "fare difference of waitlist {} was already refunded",
+                waitlist_uuid
+            )));
+        }
+
+        let refund_transaction_id =
+            Self::insert_transaction(&txn, refund_transaction.clone()).await?;
+
+        txn.commit()
+            .await
+            .inspect_err(|e| {
+                error!("Failed to commit transaction: {}", e);
+            })
+            .context("Failed to commit transaction")?;
+
+        refund_transaction.set_id(refund_transaction_id);
+
+        self.aggregate_manager
+            .lock()
+            .unwrap()
+            .attach(refund_transaction.clone());
+
+        Ok(refund_transaction_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Repository;
+    use sea_orm::Database;
+
+    #[tokio::test]
+    #[ignore = "requires a migrated PostgreSQL database in DATABASE_URL"]
+    async fn test_concurrent_pay_posts_journal_entry_once() {
+        let db = Database::connect(std::env::var("DATABASE_URL").unwrap())
+            .await
+            .unwrap();
+
+        let user_id = db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"INSERT INTO "user" ("username", "hashed_password", "salt", "phone", "name", "identity_card_id")
+VALUES ($1, '', '', $1, 'test', '110101199001011234') RETURNING "id""#,
+                [Uuid::new_v4().to_string().into()],
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get::<i32>("", "id")
+            .unwrap();
+        let user_id = UserId::from_db_value(user_id).unwrap();
+
+        let mut transaction = Transaction::new(user_id, vec![], false);
+        let transaction_id = TransactionRepositoryImpl::new(db.clone())
+            .save(&mut transaction)
+            .await
+            .unwrap();
+
+        let pay = |db: DatabaseConnection| async move {
+            let repository = TransactionRepositoryImpl::new(db);
+            let mut transaction = repository.find(transaction_id).await?.unwrap();
+            transaction.pay().unwrap();
+            repository.save(&mut transaction).await
+        };
+
+        let (first, second) = tokio::join!(pay(db.clone()), pay(db.clone()));
+
+        let entries = db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"SELECT COUNT(*) AS "count" FROM "journal_entry" WHERE "transaction_uuid" = $1"#,
+                [transaction.uuid().into()],
+            ))
+            .await
+            .unwrap()
+            .unwrap()
+            .try_get::<i64>("", "count")
+            .unwrap();
+
+        db.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"DELETE FROM "journal_entry" WHERE "transaction_uuid" = $1"#,
+            [transaction.uuid().into()],
+        ))
+        .await
+        .unwrap();
+        db.execute(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"DELETE FROM "user" WHERE "id" = $1"#,
+            [user_id.to_db_value().into()],
+        ))
+        .await
+        .unwrap();
+
+        assert_eq!(first.is_ok() as u8 + second.is_ok() as u8, 1);
+        assert_eq!(entries, 1);
+    }
 }
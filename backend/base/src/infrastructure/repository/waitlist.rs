@@ -0,0 +1,335 @@
+use crate::domain::model::station::StationId;
+use crate::domain::model::train::SeatTypeName;
+use crate::domain::model::train_schedule::{StationRange, TrainScheduleId};
+use crate::domain::model::user::UserId;
+use crate::domain::model::waitlist::{Waitlist, WaitlistId, WaitlistSeatOption, WaitlistStatus};
+use crate::domain::repository::waitlist::WaitlistRepository;
+use crate::domain::{DbId, Identifiable, Repository, RepositoryError};
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use sea_orm::prelude::{DateTimeWithTimeZone, Decimal};
+use sea_orm::{
+    ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+use std::ops::Deref;
+use tracing::{error, instrument};
+use uuid::Uuid;
+
+impl_db_id_from_u64!(WaitlistId, i32, "waitlist");
+
+/// 可接受座位类型在数据库中的存储形式
+#[derive(Debug, Serialize, Deserialize)]
+struct WaitlistSeatOptionDo {
+    seat_type_name: String,
+    price: Decimal,
+}
+
+pub struct WaitlistDataConverter;
+
+impl WaitlistDataConverter {
+    pub fn make_from_do(
+        waitlist_do: crate::models::waitlist::Model,
+    ) -> Result<Waitlist, anyhow::Error> {
+        let seat_options: Vec<WaitlistSeatOptionDo> =
+            serde_json::from_value(waitlist_do.seat_options)?;
+
+        let seat_options = seat_options
+            .into_iter()
+            .map(|option| WaitlistSeatOption {
+                seat_type_name: SeatTypeName::from_unchecked(option.seat_type_name),
+                price: option.price,
+            })
+            .collect();
+
+        let status =
+            WaitlistStatus::try_from(waitlist_do.status.as_str()).map_err(|e| anyhow!(e))?;
+
+        Ok(Waitlist::new_full(
+            Some(WaitlistId::from_db_value(waitlist_do.id)?),
+            waitlist_do.uuid,
+            UserId::from_db_value(waitlist_do.user_id)?,
+            waitlist_do.train_order_uuid,
+            waitlist_do.transaction_uuid,
+            TrainScheduleId::from_db_value(waitlist_do.train_schedule_id)?,
+            StationRange::from_unchecked(
+                StationId::from_db_value(waitlist_do.begin_station_id)?,
+                StationId::from_db_value(waitlist_do.end_station_id)?,
+            ),
+            seat_options,
+            waitlist_do.deadline,
+            waitlist_do.create_time,
+            status,
+            waitlist_do
+                .fulfilled_seat_type
+                .map(SeatTypeName::from_unchecked),
+            waitlist_do.fare_difference_refunded,
+        ))
+    }
+
+    pub fn transform_to_do(waitlist: &Waitlist) -> crate::models::waitlist::ActiveModel {
+        let seat_options = waitlist
+            .seat_options()
+            .iter()
+            .map(|option| WaitlistSeatOptionDo {
+                seat_type_name: option.seat_type_name.deref().to_string(),
+                price: option.price,
+            })
+            .collect::<Vec<_>>();
+
+        let status: &str = waitlist.status().into();
+
+        let mut model = crate::models::waitlist::ActiveModel {
+            id: ActiveValue::NotSet,
+            uuid: ActiveValue::Set(waitlist.uuid()),
+            user_id: ActiveValue::Set(waitlist.user_id().to_db_value()),
+            train_order_uuid: ActiveValue::Set(waitlist.train_order_uuid()),
+            transaction_uuid: ActiveValue::Set(waitlist.transaction_uuid()),
+            train_schedule_id: ActiveValue::Set(waitlist.train_schedule_id().to_db_value()),
+            begin_station_id: ActiveValue::Set(
+                waitlist.station_range().get_from_station_id().to_db_value(),
+            ),
+            end_station_id: ActiveValue::Set(
+                waitlist.station_range().get_to_station_id().to_db_value(),
+            ),
+            seat_options: ActiveValue::Set(serde_json::to_value(seat_options).unwrap()),
+            deadline: ActiveValue::Set(waitlist.deadline()),
+            create_time: ActiveValue::Set(waitlist.create_time()),
+            status: ActiveValue::Set(status.to_string()),
+            fulfilled_seat_type: ActiveValue::Set(
+                waitlist
+                    .fulfilled_seat_type()
+                    .map(|seat_type_name| seat_type_name.deref().to_string()),
+            ),
+            fare_difference_refunded: ActiveValue::Set(waitlist.fare_difference_refunded()),
+        };
+
+        if let Some(id) = waitlist.get_id() {
+            model.id = ActiveValue::Set(id.to_db_value());
+        }
+
+        model
+    }
+}
+
+pub struct WaitlistRepositoryImpl {
+    db: DatabaseConnection,
+}
+
+impl WaitlistRepositoryImpl {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    fn convert_all(
+        waitlist_do_list: Vec<crate::models::waitlist::Model>,
+    ) -> Result<Vec<Waitlist>, RepositoryError> {
+        waitlist_do_list
+            .into_iter()
+            .map(WaitlistDataConverter::make_from_do)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::ValidationError)
+    }
+}
+
+#[async_trait]
+impl Repository<Waitlist> for WaitlistRepositoryImpl {
+    async fn find(&self, id: WaitlistId) -> Result<Option<Waitlist>, RepositoryError> {
+        let waitlist_do = crate::models::waitlist::Entity::find_by_id(id.to_db_value())
+            .one(&self.db)
+            .await
+            .context(format!("Failed to find waitlist for waitlist id: {}", id))?;
+
+        waitlist_do
+            .map(WaitlistDataConverter::make_from_do)
+            .transpose()
+            .map_err(RepositoryError::ValidationError)
+    }
+
+    async fn remove(&self, aggregate: Waitlist) -> Result<(), RepositoryError> {
+        if let Some(id) = aggregate.get_id() {
+            crate::models::waitlist::Entity::delete_by_id(id.to_db_value())
+                .exec(&self.db)
+                .await
+                .context(format!("Failed to delete waitlist for waitlist id: {}", id))?;
+        }
+
+        Ok(())
+    }
+
+    async fn save(&self, aggregate: &mut Waitlist) -> Result<WaitlistId, RepositoryError> {
+        let model = WaitlistDataConverter::transform_to_do(aggregate);
+
+        if let Some(id) = aggregate.get_id() {
+            crate::models::waitlist::Entity::update(model)
+                .exec(&self.db)
+                .await
+                .context(format!("Failed to update waitlist with id: {}", id))?;
+
+            Ok(id)
+        } else {
+            let result = crate::models::waitlist::Entity::insert(model)
+                .exec(&self.db)
+                .await
+                .context("Failed to insert waitlist")?;
+
+            let id = WaitlistId::from_db_value(result.last_insert_id)?;
+
+            aggregate.set_id(id);
+
+            Ok(id)
+        }
+    }
+}
+
+#[async_trait]
+impl WaitlistRepository for WaitlistRepositoryImpl {
+    #[instrument(skip(self))]
+    async fn find_by_uuid(&self, uuid: Uuid) -> Result<Option<Waitlist>, RepositoryError> {
+        let waitlist_do = crate::models::waitlist::Entity::find()
+            .filter(crate::models::waitlist::Column::Uuid.eq(uuid))
+            .one(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context(format!("Failed to find waitlist for uuid: {}", uuid))?;
+
+        waitlist_do
+            .map(WaitlistDataConverter::make_from_do)
+            .transpose()
+            .map_err(RepositoryError::ValidationError)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_train_order_uuid(
+        &self,
+        train_order_uuid: Uuid,
+    ) -> Result<Option<Waitlist>, RepositoryError> {
+        let waitlist_do = crate::models::waitlist::Entity::find()
+            .filter(crate::models::waitlist::Column::TrainOrderUuid.eq(train_order_uuid))
+            .one(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context(format!(
+                "Failed to find waitlist for train order uuid: {}",
+                train_order_uuid
+            ))?;
+
+        waitlist_do
+            .map(WaitlistDataConverter::make_from_do)
+            .transpose()
+            .map_err(RepositoryError::ValidationError)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_user_id(&self, user_id: UserId) -> Result<Vec<Waitlist>, RepositoryError> {
+        let waitlist_do_list = crate::models::waitlist::Entity::find()
+            .filter(crate::models::waitlist::Column::UserId.eq(user_id.to_db_value()))
+            .order_by_desc(crate::models::waitlist::Column::CreateTime)
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context(format!("Failed to find waitlist for user id: {}", user_id))?;
+
+        Self::convert_all(waitlist_do_list)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_pending_by_train_schedule(
+        &self,
+        train_schedule_id: TrainScheduleId,
+    ) -> Result<Vec<Waitlist>, RepositoryError> {
+        let pending: &str = WaitlistStatus::Pending.into();
+
+        let waitlist_do_list = crate::models::waitlist::Entity::find()
+            .filter(
+                crate::models::waitlist::Column::TrainScheduleId
+                    .eq(train_schedule_id.to_db_value()),
+            )
+            .filter(crate::models::waitlist::Column::Status.eq(pending))
+            .order_by_asc(crate::models::waitlist::Column::CreateTime)
+            .order_by_asc(crate::models::waitlist::Column::Id)
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context(format!(
+                "Failed to find pending waitlist for train schedule id: {}",
+                train_schedule_id
+            ))?;
+
+        Self::convert_all(waitlist_do_list)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_pending_train_schedule_ids(
+        &self,
+    ) -> Result<Vec<TrainScheduleId>, RepositoryError> {
+        let pending: &str = WaitlistStatus::Pending.into();
+
+        let train_schedule_id_list: Vec<i32> = crate::models::waitlist::Entity::find()
+            .select_only()
+            .column(crate::models::waitlist::Column::TrainScheduleId)
+            .filter(crate::models::waitlist::Column::Status.eq(pending))
+            .distinct()
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context("Failed to find train schedules with pending waitlist")?;
+
+        train_schedule_id_list
+            .into_iter()
+            .map(TrainScheduleId::from_db_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RepositoryError::ValidationError)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_expired_pending(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Waitlist>, RepositoryError> {
+        let pending: &str = WaitlistStatus::Pending.into();
+
+        let waitlist_do_list = crate::models::waitlist::Entity::find()
+            .filter(crate::models::waitlist::Column::Status.eq(pending))
+            .filter(crate::models::waitlist::Column::Deadline.lte(now))
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context("Failed to find expired pending waitlist")?;
+
+        Self::convert_all(waitlist_do_list)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_by_status(
+        &self,
+        status: WaitlistStatus,
+    ) -> Result<Vec<Waitlist>, RepositoryError> {
+        let status_str: &str = status.into();
+
+        let waitlist_do_list = crate::models::waitlist::Entity::find()
+            .filter(crate::models::waitlist::Column::Status.eq(status_str))
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context(format!("Failed to find waitlist with status: {}", status))?;
+
+        Self::convert_all(waitlist_do_list)
+    }
+
+    #[instrument(skip(self))]
+    async fn find_fare_difference_unrefunded(&self) -> Result<Vec<Waitlist>, RepositoryError> {
+        let fulfilled: &str = WaitlistStatus::Fulfilled.into();
+
+        let waitlist_do_list = crate::models::waitlist::Entity::find()
+            .filter(crate::models::waitlist::Column::Status.eq(fulfilled))
+            .filter(crate::models::waitlist::Column::FareDifferenceRefunded.eq(false))
+            .all(&self.db)
+            .await
+            .inspect_err(|e| error!("Failed to query waitlist: {}", e))
+            .context("Failed to find fulfilled waitlist with unrefunded fare difference")?;
+
+        Self::convert_all(waitlist_do_list)
+    }
+}
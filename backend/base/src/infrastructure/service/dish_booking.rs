@@ -1,4 +1,5 @@
 use crate::domain::model::order::{DishOrder, Order, OrderStatus};
+use crate::domain::model::order_state_machine::OrderStatusActor;
 use crate::domain::repository::order::OrderRepository;
 use crate::domain::service::dish_booking::{DishBookingService, DishBookingServiceError};
 use async_trait::async_trait;
@@ -35,16 +36,9 @@ where
             .await?
             .ok_or(DishBookingServiceError::InvalidOrder(order_uuid))?;
 
-        if order.order_status() != OrderStatus::Paid {
-            return Err(DishBookingServiceError::InvalidOrderStatus(
-                order_uuid,
-                order.order_status(),
-            ));
-        }
-
         // 火车餐订单总是会成功
 
-        order.set_status(OrderStatus::Ongoing);
+        order.transition_status(OrderStatus::Ongoing, OrderStatusActor::System, "预订成功")?;
 
         self.order_repository
             .update(Box::new(order))
@@ -71,7 +65,11 @@ where
             ));
         }
 
-        order.set_status(OrderStatus::Cancelled);
+        order.transition_status(
+            OrderStatus::Cancelled,
+            OrderStatusActor::System,
+            "订单已取消",
+        )?;
 
         self.order_repository
             .update(Box::new(order))
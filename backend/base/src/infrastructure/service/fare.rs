@@ -0,0 +1,209 @@
+use crate::domain::model::order::TicketCategory;
+use crate::domain::model::route::Route;
+use crate::domain::model::train::SeatType;
+use crate::domain::service::fare::{FareRateTable, FareService, FareServiceError};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// 基于里程的票价计算服务
+pub struct FareServiceImpl {
+    rate_table: FareRateTable,
+}
+
+impl FareServiceImpl {
+    pub fn new(rate_table: FareRateTable) -> Self {
+        Self { rate_table }
+    }
+
+    /// 四舍五入到计价单位的整数倍
+    fn round_fare(&self, fare: Decimal) -> Decimal {
+        let unit = self.rate_table.rounding_unit;
+
+        if unit <= Decimal::ZERO {
+            return fare;
+        }
+
+        (fare / unit).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero) * unit
+    }
+}
+
+impl FareService for FareServiceImpl {
+    fn calculate_fare(
+        &self,
+        train_type: &str,
+        seat_type: &SeatType,
+        route: &Route,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<Decimal, FareServiceError> {
+        if from_index >= to_index || to_index >= route.stops().len() {
+            return Err(FareServiceError::InvalidStationRange(from_index, to_index));
+        }
+
+        let distance = route.distance_between(from_index, to_index);
+
+        // 未录入里程的线路按经停站数计价
+        if distance == 0 {
+            return Ok(seat_type.unit_price() * Decimal::from(to_index - from_index));
+        }
+
+        let rate = self
+            .rate_table
+            .seat_type_rate
+            .get(seat_type.name())
+            .copied()
+            .unwrap_or(self.rate_table.default_seat_type_rate);
+
+        let factor = self
+            .rate_table
+            .train_type_factor
+            .get(train_type)
+            .copied()
+            .unwrap_or(Decimal::ONE);
+
+        let fare = (Decimal::from(distance) * rate * factor).max(self.rate_table.min_fare);
+
+        Ok(self.round_fare(fare))
+    }
+
+    fn apply_ticket_category(&self, fare: Decimal, ticket_category: TicketCategory) -> Decimal {
+        match self.rate_table.ticket_category_rate.get(&ticket_category) {
+            Some(rate) => self.round_fare(fare * rate),
+            None => fare,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::route::RouteId;
+    use crate::domain::model::station::StationId;
+    use crate::domain::model::train::{SeatTypeId, SeatTypeName};
+
+    fn make_route(distances: &[u32]) -> Route {
+        let mut route = Route::new(Some(RouteId::from(1)));
+
+        for (order, distance) in distances.iter().enumerate() {
+            route.add_stop(
+                None,
+                StationId::from(100 + order as u64),
+                0,
+                0,
+                order as u32,
+                *distance,
+            );
+        }
+
+        route
+    }
+
+    fn make_seat_type(name: &str, unit_price: i64) -> SeatType {
+        SeatType::new(
+            Some(SeatTypeId::from(1)),
+            SeatTypeName::from_unchecked(name.to_string()),
+            100,
+            Decimal::from(unit_price),
+        )
+    }
+
+    #[test]
+    fn test_calculate_fare() {
+        let service = FareServiceImpl::new(FareRateTable::default());
+        let second_class = make_seat_type("二等", 300);
+
+        // 20 + 600 公里，不同里程的区间票价不同
+        let route = make_route(&[0, 20, 600, 3]);
+
+        // 20 * 0.5 * 1.2 = 12
+        assert_eq!(
+            service.calculate_fare("G", &second_class, &route, 0, 1),
+            Ok(Decimal::from(12))
+        );
+        // 620 * 0.5 * 1.2 = 372
+        assert_eq!(
+            service.calculate_fare("G", &second_class, &route, 0, 2),
+            Ok(Decimal::from(372))
+        );
+        // 3 * 0.5 * 1.0 = 1.5，不低于最低票价 5
+        assert_eq!(
+            service.calculate_fare("D", &second_class, &route, 2, 3),
+            Ok(Decimal::from(5))
+        );
+        // 603 * 0.5 * 0.7 = 211.05，四舍五入取整
+        assert_eq!(
+            service.calculate_fare_by_station(
+                "P",
+                &second_class,
+                &route,
+                StationId::from(101),
+                StationId::from(103)
+            ),
+            Ok(Decimal::from(211))
+        );
+
+        assert_eq!(
+            service.calculate_fare("G", &second_class, &route, 2, 1),
+            Err(FareServiceError::InvalidStationRange(2, 1))
+        );
+        assert_eq!(
+            service.calculate_fare_by_station(
+                "G",
+                &second_class,
+                &route,
+                StationId::from(100),
+                StationId::from(200)
+            ),
+            Err(FareServiceError::StationNotInRoute(StationId::from(200)))
+        );
+    }
+
+    #[test]
+    fn test_calculate_fare_rounding_unit() {
+        let service = FareServiceImpl::new(FareRateTable {
+            rounding_unit: Decimal::new(5, 1),
+            ..FareRateTable::default()
+        });
+
+        let route = make_route(&[0, 23]);
+
+        // 23 * 0.5 * 1.2 = 13.8，四舍五入到 0.5 的整数倍
+        assert_eq!(
+            service.calculate_fare("G", &make_seat_type("二等", 300), &route, 0, 1),
+            Ok(Decimal::new(140, 1))
+        );
+    }
+
+    #[test]
+    fn test_apply_ticket_category() {
+        let service = FareServiceImpl::new(FareRateTable::default());
+        let fare = Decimal::from(211);
+
+        assert_eq!(
+            service.apply_ticket_category(fare, TicketCategory::Adult),
+            Decimal::from(211)
+        );
+        // 211 * 0.5 = 105.5，四舍五入取整
+        assert_eq!(
+            service.apply_ticket_category(fare, TicketCategory::Child),
+            Decimal::from(106)
+        );
+        // 211 * 0.75 = 158.25
+        assert_eq!(
+            service.apply_ticket_category(fare, TicketCategory::Student),
+            Decimal::from(158)
+        );
+    }
+
+    #[test]
+    fn test_calculate_fare_without_distance() {
+        let service = FareServiceImpl::new(FareRateTable::default());
+
+        // 未录入里程时按经停站数计价
+        let route = make_route(&[0, 0, 0, 0]);
+
+        assert_eq!(
+            service.calculate_fare("G", &make_seat_type("二等", 300), &route, 1, 3),
+            Ok(Decimal::from(600))
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::domain::model::hotel::{
     HotelDateRange, HotelId, HotelRoomStatus, HotelRoomTypeId, OccupiedRoom,
 };
 use crate::domain::model::order::{HotelOrder, Order, OrderStatus};
+use crate::domain::model::order_state_machine::{OrderStateMachine, OrderStatusActor};
 use crate::domain::repository::hotel::HotelRepository;
 use crate::domain::repository::occupied_room::OccupiedRoomRepository;
 use crate::domain::repository::order::OrderRepository;
@@ -179,7 +180,7 @@ where
             .inspect_err(|e| error!("Failed to load hotel order: {}", e))?
             .ok_or(HotelBookingServiceError::InvalidOrder(order_uuid))?;
 
-        if order.order_status() != OrderStatus::Paid {
+        if !OrderStateMachine::can_transition(order.order_status(), OrderStatus::Ongoing) {
             return Err(HotelBookingServiceError::InvalidOrderStatus(
                 order_uuid,
                 order.order_status(),
@@ -217,7 +218,7 @@ where
                 .inspect_err(|e| error!("Failed to save occupied room: {}", e))?;
         }
 
-        order.set_status(OrderStatus::Ongoing);
+        order.transition_status(OrderStatus::Ongoing, OrderStatusActor::System, "预订成功")?;
 
         self.order_repository
             .update(Box::new(order))
@@ -242,16 +243,32 @@ where
             ));
         }
 
-        let to_cancel_occupied_rooms = self
+        let occupied_rooms = self
             .occupied_room_repository
             .find_by_order_uuid(order_uuid)
             .await?;
 
+        // 部分退款时已释放了退还的房间，只释放订单剩余的房间
+        let remaining_count = order.remaining_quantity().to_usize().unwrap_or_default();
+        let to_cancel_occupied_rooms = order
+            .rooms_to_release(occupied_rooms, remaining_count)
+            .ok_or_else(|| {
+                RepositoryError::InconsistentState(anyhow!(
+                    "hotel order {} has less than {} occupied rooms",
+                    order_uuid,
+                    remaining_count
+                ))
+            })?;
+
         self.occupied_room_repository
             .remove_many(to_cancel_occupied_rooms)
             .await?;
 
-        order.set_status(OrderStatus::Cancelled);
+        order.transition_status(
+            OrderStatus::Cancelled,
+            OrderStatusActor::System,
+            "订单已取消，已释放房间",
+        )?;
 
         self.order_repository
             .update(Box::new(order))
@@ -0,0 +1,109 @@
+use crate::LEDGER_RECONCILIATION_INTERVAL_SECONDS;
+use crate::domain::DbId;
+use crate::domain::repository::ledger::LedgerRepository;
+use crate::domain::repository::transaction::TransactionRepository;
+use crate::domain::service::ledger::{LedgerService, LedgerServiceError, WalletDiscrepancy};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tracing::{error, info, instrument, warn};
+
+pub struct LedgerServiceImpl<LR, TR>
+where
+    LR: LedgerRepository,
+    TR: TransactionRepository,
+{
+    ledger_repository: Arc<LR>,
+    transaction_repository: Arc<TR>,
+}
+
+impl<LR, TR> LedgerServiceImpl<LR, TR>
+where
+    LR: LedgerRepository,
+    TR: TransactionRepository,
+{
+    pub fn new(ledger_repository: Arc<LR>, transaction_repository: Arc<TR>) -> Self {
+        Self {
+            ledger_repository,
+            transaction_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl<LR, TR> LedgerService for LedgerServiceImpl<LR, TR>
+where
+    LR: LedgerRepository,
+    TR: TransactionRepository,
+{
+    #[instrument(skip(self))]
+    async fn reconcile(&self) -> Result<Vec<WalletDiscrepancy>, LedgerServiceError> {
+        let ledger_balances = self.ledger_repository.get_wallet_balances().await?;
+        let transaction_balances = self.transaction_repository.get_all_user_balances().await?;
+
+        let user_ids = ledger_balances
+            .keys()
+            .chain(transaction_balances.keys())
+            .copied()
+            .collect::<HashSet<_>>();
+
+        let mut discrepancies = user_ids
+            .into_iter()
+            .filter_map(|user_id| {
+                let ledger_balance = ledger_balances
+                    .get(&user_id)
+                    .copied()
+                    .unwrap_or(Decimal::ZERO);
+                let transaction_balance = transaction_balances
+                    .get(&user_id)
+                    .copied()
+                    .unwrap_or(Decimal::ZERO);
+
+                (ledger_balance != transaction_balance).then_some(WalletDiscrepancy {
+                    user_id,
+                    ledger_balance,
+                    transaction_balance,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        discrepancies.sort_by_key(|discrepancy| discrepancy.user_id.to_db_value());
+
+        Ok(discrepancies)
+    }
+
+    async fn reconciliation_daemon(&self) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+            LEDGER_RECONCILIATION_INTERVAL_SECONDS,
+        ));
+
+        loop {
+            interval.tick().await;
+
+            match self.reconcile().await {
+                Ok(discrepancies) if discrepancies.is_empty() => {
+                    info!("Ledger reconciliation passed");
+                }
+                Ok(discrepancies) => {
+                    for discrepancy in &discrepancies {
+                        warn!(
+                            "Wallet balance mismatch for user {}: ledger {}, transactions {}",
+                            discrepancy.user_id,
+                            discrepancy.ledger_balance,
+                            discrepancy.transaction_balance
+                        );
+                    }
+
+                    error!(
+                        "Ledger reconciliation found {} mismatched wallets",
+                        discrepancies.len()
+                    );
+                }
+                Err(e) => {
+                    error!("Failed to reconcile ledger: {}", e);
+                }
+            }
+        }
+    }
+}
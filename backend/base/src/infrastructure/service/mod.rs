@@ -1,8 +1,10 @@
 pub mod dish_booking;
+pub mod fare;
 pub mod geo;
 pub mod hotel_booking;
 pub mod hotel_query;
 pub mod hotel_rating;
+pub mod ledger;
 pub mod message;
 pub mod object_storage;
 pub mod order;
@@ -10,7 +12,11 @@ pub mod order_status;
 pub mod order_status_consumer_service;
 pub mod order_status_producer_service;
 pub mod password;
+pub mod payment_gateway;
+pub mod pricing;
 pub mod route;
+pub mod seat_assignment;
+pub mod seat_availability_cache;
 pub mod session;
 pub mod station;
 pub mod takeaway_booking;
@@ -20,3 +26,4 @@ pub mod train_seat;
 pub mod train_type;
 pub mod transaction;
 pub mod user;
+pub mod waitlist;
@@ -35,6 +35,11 @@ macro_rules! base_order_fields {
             can_cancel: calculate_can_cancel($order),
             reason: get_reason($order.order_status(), $order.already_refund()),
             order_type: $order_type,
+            // 可退金额需结合所在交易的优惠金额计算，由`TransactionService`填充
+            refundable_amount: None,
+            refund_fee: None,
+            refunded_amount: None,
+            remaining_amount: None,
         }
     };
 }
@@ -121,6 +126,9 @@ where
                     location: String::from(seat.location_info().location),
                     type_name: seat.seat_type().name().to_string(),
                 }),
+                group_seating: train_order.group_seating().map(|x| x.to_string()),
+                ticket_category: train_order.ticket_category().to_string(),
+                seat_hold_expire_time: train_order.seat_hold_expire_time().map(|x| x.to_rfc3339()),
             };
 
             Ok(OrderInfoDto::Train(order_info_dto))
@@ -1,37 +1,104 @@
 use crate::ORDER_STATUS_UPDATE_INTERVAL_SECONDS;
+use crate::domain::model::message::OrderNotify;
 use crate::domain::model::order::{Order, OrderStatus};
+use crate::domain::model::order_state_machine::{OrderStateMachine, OrderStatusActor};
+use crate::domain::model::transaction::Transaction;
+use crate::domain::model::user::UserId;
 use crate::domain::repository::order::OrderRepository;
+use crate::domain::repository::transaction::TransactionRepository;
+use crate::domain::service::message::MessageService;
 use crate::domain::service::order_status::OrderStatusManagerService;
 use crate::domain::service::order_status::{OrderStatusMessage, OrderStatusMessagePack};
 use crate::infrastructure::service::order_status_producer_service::OrderStatusProducerService;
 use async_trait::async_trait;
 use chrono::Local;
 use std::sync::Arc;
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
 use uuid::Uuid;
 
-pub struct OrderStatusManagerServiceImpl<OR>
+pub struct OrderStatusManagerServiceImpl<OR, TR, MS>
 where
     OR: OrderRepository,
+    TR: TransactionRepository,
+    MS: MessageService,
 {
     order_status_producer_service: Arc<OrderStatusProducerService>,
     order_repository: Arc<OR>,
+    transaction_repository: Arc<TR>,
+    message_service: Arc<MS>,
 }
 
-impl<OR> OrderStatusManagerServiceImpl<OR>
+impl<OR, TR, MS> OrderStatusManagerServiceImpl<OR, TR, MS>
 where
     OR: OrderRepository,
+    TR: TransactionRepository,
+    MS: MessageService,
 {
     pub fn new(
         order_status_producer_service: Arc<OrderStatusProducerService>,
         order_repository: Arc<OR>,
+        transaction_repository: Arc<TR>,
+        message_service: Arc<MS>,
     ) -> Self {
         Self {
             order_status_producer_service,
             order_repository,
+            transaction_repository,
+            message_service,
         }
     }
 
+    /// 关闭已超过支付截止时间的交易，取消其订单并通知用户
+    #[instrument(skip(self))]
+    async fn expire_unpaid_transactions(&self) -> Result<(), anyhow::Error> {
+        info!("Expiring unpaid transactions...");
+
+        let expired_transactions = self
+            .transaction_repository
+            .find_expired_unpaid(Transaction::now())
+            .await
+            .inspect_err(|e| error!("Failed to load expired transactions: {}", e))?;
+
+        for mut tx in expired_transactions {
+            let cancelled_orders = match tx.expire() {
+                Ok(orders) => orders,
+                Err(e) => {
+                    warn!("Failed to expire transaction {}: {}", tx.uuid(), e);
+                    continue;
+                }
+            };
+
+            info!(
+                "transaction {} payment expired, cancelling {} orders",
+                tx.uuid(),
+                cancelled_orders.len()
+            );
+
+            if let Err(e) = self.transaction_repository.save(&mut tx).await {
+                error!("Failed to save expired transaction {}: {}", tx.uuid(), e);
+                continue;
+            }
+
+            for order in cancelled_orders {
+                let notify = OrderNotify::new_now(
+                    tx.user_id(),
+                    "订单支付超时，已自动取消".to_string(),
+                    order,
+                );
+
+                if let Err(e) = self
+                    .message_service
+                    .send_to_user(tx.user_id(), Box::new(notify))
+                    .await
+                {
+                    error!("Failed to notify user {}: {}", tx.user_id(), e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip(self))]
     async fn update_order_status(&self) -> Result<(), anyhow::Error> {
         info!("Updating order status...");
@@ -51,19 +118,28 @@ where
         for mut order in active_orders {
             let prev_status = order.order_status();
 
-            if now >= order.order_time_info().active_time()
-                && now < order.order_time_info().complete_time()
-            {
-                order.set_status(OrderStatus::Ongoing)
-            } else if now >= order.order_time_info().complete_time() {
-                order.set_status(OrderStatus::Completed);
+            let (target_status, reason) = if now >= order.order_time_info().complete_time() {
+                (OrderStatus::Completed, "行程已结束")
+            } else if now >= order.order_time_info().active_time() {
+                (OrderStatus::Active, "行程已开始")
             } else {
                 continue; // Skip orders that are not in the active or completed state
+            };
+
+            // 未支付、失败及已取消的订单不会进入行程状态，由状态机拒绝
+            if prev_status == target_status
+                || !OrderStateMachine::can_transition(prev_status, target_status)
+            {
+                continue;
             }
 
-            if prev_status != order.order_status() {
-                to_update_orders.push(order);
+            if let Err(e) = order.transition_status(target_status, OrderStatusActor::System, reason)
+            {
+                warn!("Failed to update order status: {}", e);
+                continue;
             }
+
+            to_update_orders.push(order);
         }
 
         info!("{} orders need status update", to_update_orders.len());
@@ -79,9 +155,11 @@ where
 }
 
 #[async_trait]
-impl<OR> OrderStatusManagerService for OrderStatusManagerServiceImpl<OR>
+impl<OR, TR, MS> OrderStatusManagerService for OrderStatusManagerServiceImpl<OR, TR, MS>
 where
     OR: OrderRepository,
+    TR: TransactionRepository,
+    MS: MessageService,
 {
     #[instrument(skip_all)]
     async fn notify_status_change(
@@ -103,6 +181,7 @@ where
                 order_id: order.uuid(),
                 order_type: order.order_type(),
                 new_status,
+                replaced_order_id: None,
             });
         }
 
@@ -121,6 +200,57 @@ where
         }
     }
 
+    #[instrument(skip_all)]
+    async fn notify_rebook(
+        &self,
+        transaction_uuid: Uuid,
+        replaced_order: &dyn Order,
+        new_order: &dyn Order,
+    ) {
+        info!(
+            "order rebook: transaction_uuid: {}, replaced_order: {}, new_order: {}",
+            transaction_uuid,
+            replaced_order.uuid(),
+            new_order.uuid()
+        );
+
+        let message_pack = OrderStatusMessagePack {
+            transaction_uuid,
+            atomic: true,
+            messages: vec![OrderStatusMessage {
+                order_id: new_order.uuid(),
+                order_type: new_order.order_type(),
+                new_status: OrderStatus::Unpaid,
+                replaced_order_id: Some(replaced_order.uuid()),
+            }],
+        };
+
+        if let Err(e) = self
+            .order_status_producer_service
+            .delivery_message(message_pack)
+            .await
+        {
+            error!("error while producing order rebook: {}", e);
+        }
+    }
+
+    #[instrument(skip(self, order))]
+    async fn notify_partial_refund(&self, user_id: UserId, order: &dyn Order) {
+        let notify = OrderNotify::new_now(
+            user_id,
+            "订单部分退款成功，已释放退还的部分".to_string(),
+            dyn_clone::clone_box(order),
+        );
+
+        if let Err(e) = self
+            .message_service
+            .send_to_user(user_id, Box::new(notify))
+            .await
+        {
+            error!("Failed to notify user {}: {}", user_id, e);
+        }
+    }
+
     #[instrument(skip_all)]
     async fn order_status_daemon(&self) {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
@@ -130,6 +260,10 @@ where
         loop {
             interval.tick().await;
 
+            if let Err(e) = self.expire_unpaid_transactions().await {
+                error!("Failed to expire unpaid transactions: {}", e);
+            }
+
             if let Err(e) = self.update_order_status().await {
                 error!("Failed to update order status: {}", e);
             }
@@ -0,0 +1,300 @@
+use crate::domain::model::transaction::TransactionAmountAbs;
+use crate::domain::service::payment_gateway::{
+    PaymentCallback, PaymentGateway, PaymentGatewayError, PaymentIntent, PaymentIntentStatus,
+};
+use async_trait::async_trait;
+use blake2::Blake2bMac512;
+use blake2::digest::Mac;
+use dashmap::DashMap;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+
+pub const MOCK_PAYMENT_PROVIDER: &str = "mock";
+
+/// 模拟支付网关回调的请求体
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct MockWebhookPayload {
+    external_payment_id: Uuid,
+    amount: String,
+    status: String,
+}
+
+/// 本地模拟支付网关，不访问网络
+///
+/// 支付意图保存在内存中，由`complete_payment`模拟用户完成付款并生成带签名的回调，
+/// 签名为以`secret`为密钥的 BLAKE2b MAC 的十六进制表示。
+///
+/// 支付意图在重启后丢失，仅供调试模式使用。
+pub struct MockPaymentGatewayImpl {
+    secret: Vec<u8>,
+    intents: DashMap<Uuid, PaymentIntent>,
+}
+
+impl MockPaymentGatewayImpl {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            secret: secret.as_bytes().to_vec(),
+            intents: DashMap::new(),
+        }
+    }
+
+    fn new_mac(&self) -> Blake2bMac512 {
+        <Blake2bMac512 as Mac>::new_from_slice(&self.secret)
+            .expect("BLAKE2b MAC should accept key of mock payment secret")
+    }
+
+    /// 计算回调请求体的签名
+    pub fn sign(&self, payload: &[u8]) -> String {
+        let mut mac = self.new_mac();
+        mac.update(payload);
+
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// 模拟用户完成付款（或放弃付款），返回支付网关将发送的回调请求体及签名。
+    ///
+    /// Arguments:
+    /// - `external_payment_id`: 支付网关分配的支付 ID。
+    /// - `succeeded`: 付款是否成功。
+    ///
+    /// Returns:
+    /// - 成功时返回`(payload, signature)`。
+    /// - 支付不存在或已不处于`Pending`状态时返回`PaymentGatewayError`。
+    #[instrument(skip(self))]
+    pub fn complete_payment(
+        &self,
+        external_payment_id: Uuid,
+        succeeded: bool,
+    ) -> Result<(Vec<u8>, String), PaymentGatewayError> {
+        let mut intent = self
+            .intents
+            .get_mut(&external_payment_id)
+            .ok_or(PaymentGatewayError::PaymentNotFound(external_payment_id))?;
+
+        if intent.status != PaymentIntentStatus::Pending {
+            return Err(PaymentGatewayError::InvalidPaymentStatus {
+                op: "complete",
+                status: intent.status,
+                external_payment_id,
+            });
+        }
+
+        intent.status = if succeeded {
+            PaymentIntentStatus::Succeeded
+        } else {
+            PaymentIntentStatus::Failed
+        };
+
+        let payload = serde_json::to_vec(&MockWebhookPayload {
+            external_payment_id,
+            amount: intent.amount.to_string(),
+            status: intent.status.to_string(),
+        })
+        .expect("mock webhook payload should be serializable");
+
+        let signature = self.sign(&payload);
+
+        Ok((payload, signature))
+    }
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[async_trait]
+impl PaymentGateway for MockPaymentGatewayImpl {
+    fn provider(&self) -> &'static str {
+        MOCK_PAYMENT_PROVIDER
+    }
+
+    #[instrument(skip(self))]
+    async fn create_intent(
+        &self,
+        amount: TransactionAmountAbs,
+        description: &str,
+    ) -> Result<PaymentIntent, PaymentGatewayError> {
+        let intent = PaymentIntent {
+            external_payment_id: Uuid::new_v4(),
+            amount: Decimal::from(amount),
+            status: PaymentIntentStatus::Pending,
+            payment_url: None,
+        };
+
+        info!(
+            "created mock payment intent {} for {}",
+            intent.external_payment_id, description
+        );
+
+        self.intents
+            .insert(intent.external_payment_id, intent.clone());
+
+        Ok(intent)
+    }
+
+    async fn query_intent(
+        &self,
+        external_payment_id: Uuid,
+    ) -> Result<PaymentIntent, PaymentGatewayError> {
+        self.intents
+            .get(&external_payment_id)
+            .map(|intent| intent.clone())
+            .ok_or(PaymentGatewayError::PaymentNotFound(external_payment_id))
+    }
+
+    #[instrument(skip(self))]
+    async fn refund(
+        &self,
+        external_payment_id: Uuid,
+    ) -> Result<PaymentIntent, PaymentGatewayError> {
+        let mut intent = self
+            .intents
+            .get_mut(&external_payment_id)
+            .ok_or(PaymentGatewayError::PaymentNotFound(external_payment_id))?;
+
+        if intent.status != PaymentIntentStatus::Succeeded {
+            return Err(PaymentGatewayError::InvalidPaymentStatus {
+                op: "refund",
+                status: intent.status,
+                external_payment_id,
+            });
+        }
+
+        intent.status = PaymentIntentStatus::Refunded;
+
+        Ok(intent.clone())
+    }
+
+    fn verify_webhook(
+        &self,
+        payload: &[u8],
+        signature: &str,
+    ) -> Result<PaymentCallback, PaymentGatewayError> {
+        let signature = decode_hex(signature).ok_or(PaymentGatewayError::InvalidSignature)?;
+
+        let mut mac = self.new_mac();
+        mac.update(payload);
+
+        mac.verify_slice(&signature).map_err(|_for_super_earth| {
+            warn!("mock payment webhook signature mismatch");
+            PaymentGatewayError::InvalidSignature
+        })?;
+
+        let payload: MockWebhookPayload = serde_json::from_slice(payload)
+            .map_err(|e| PaymentGatewayError::InvalidPayload(e.to_string()))?;
+
+        Ok(PaymentCallback {
+            external_payment_id: payload.external_payment_id,
+            amount: payload
+                .amount
+                .parse::<Decimal>()
+                .map_err(|e| PaymentGatewayError::InvalidPayload(e.to_string()))?,
+            status: PaymentIntentStatus::try_from(payload.status.as_str())
+                .map_err(PaymentGatewayError::InvalidPayload)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claims::{assert_err, assert_ok};
+
+    fn amount(value: i64) -> TransactionAmountAbs {
+        TransactionAmountAbs::from(Decimal::from(value))
+    }
+
+    #[tokio::test]
+    async fn test_complete_payment_produces_verified_callback() {
+        let gateway = MockPaymentGatewayImpl::new("For Super Earth!");
+
+        let intent = gateway.create_intent(amount(100), "充值").await.unwrap();
+        assert_eq!(intent.status, PaymentIntentStatus::Pending);
+
+        let (payload, signature) = gateway
+            .complete_payment(intent.external_payment_id, true)
+            .unwrap();
+
+        let callback = gateway.verify_webhook(&payload, &signature).unwrap();
+
+        assert_eq!(callback.external_payment_id, intent.external_payment_id);
+        assert_eq!(callback.amount, Decimal::from(100));
+        assert_eq!(callback.status, PaymentIntentStatus::Succeeded);
+
+        let queried = gateway
+            .query_intent(intent.external_payment_id)
+            .await
+            .unwrap();
+        assert_eq!(queried.status, PaymentIntentStatus::Succeeded);
+
+        // 同一笔支付不能重复完成
+        assert_err!(gateway.complete_payment(intent.external_payment_id, true));
+    }
+
+    #[tokio::test]
+    async fn test_verify_webhook_rejects_forged_callback() {
+        let gateway = MockPaymentGatewayImpl::new("For Super Earth!");
+        let other_gateway = MockPaymentGatewayImpl::new("For Democracy!");
+
+        let intent = gateway.create_intent(amount(100), "充值").await.unwrap();
+
+        let (payload, signature) = gateway
+            .complete_payment(intent.external_payment_id, true)
+            .unwrap();
+
+        let mut tampered = payload.clone();
+        let tampered_len = tampered.len();
+        tampered[tampered_len - 2] = b'x';
+
+        assert!(matches!(
+            gateway.verify_webhook(&tampered, &signature),
+            Err(PaymentGatewayError::InvalidSignature)
+        ));
+        assert!(matches!(
+            gateway.verify_webhook(&payload, "not a signature"),
+            Err(PaymentGatewayError::InvalidSignature)
+        ));
+        assert!(matches!(
+            other_gateway.verify_webhook(&payload, &signature),
+            Err(PaymentGatewayError::InvalidSignature)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_refund_requires_succeeded_payment() {
+        let gateway = MockPaymentGatewayImpl::new("For Super Earth!");
+
+        let failed = gateway.create_intent(amount(50), "充值").await.unwrap();
+        let (payload, signature) = gateway
+            .complete_payment(failed.external_payment_id, false)
+            .unwrap();
+
+        assert_eq!(
+            gateway.verify_webhook(&payload, &signature).unwrap().status,
+            PaymentIntentStatus::Failed
+        );
+        assert_err!(gateway.refund(failed.external_payment_id).await);
+
+        let succeeded = gateway.create_intent(amount(50), "充值").await.unwrap();
+        gateway
+            .complete_payment(succeeded.external_payment_id, true)
+            .unwrap();
+
+        let refunded = assert_ok!(gateway.refund(succeeded.external_payment_id).await);
+        assert_eq!(refunded.status, PaymentIntentStatus::Refunded);
+    }
+}
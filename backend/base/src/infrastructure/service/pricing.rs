@@ -0,0 +1,385 @@
+use crate::domain::service::pricing::{
+    DynamicPricingPolicy, PriceQuoteQuery, PricingService, PricingServiceError,
+};
+use crate::domain::service::train_seat::{SeatCountQuery, TrainSeatService};
+use async_trait::async_trait;
+use chrono::{FixedOffset, NaiveDate, Utc};
+use rust_decimal::{Decimal, RoundingStrategy};
+use std::sync::Arc;
+
+/// 基于上座率与距发车天数的动态定价服务
+pub struct DynamicPricingServiceImpl<TSS>
+where
+    TSS: TrainSeatService,
+{
+    policy: DynamicPricingPolicy,
+    train_seat_service: Arc<TSS>,
+    tz_offset_hour: i32,
+}
+
+impl<TSS> DynamicPricingServiceImpl<TSS>
+where
+    TSS: TrainSeatService,
+{
+    pub fn new(
+        policy: DynamicPricingPolicy,
+        train_seat_service: Arc<TSS>,
+        tz_offset_hour: i32,
+    ) -> Self {
+        Self {
+            policy,
+            train_seat_service,
+            tz_offset_hour,
+        }
+    }
+
+    fn today(&self) -> NaiveDate {
+        Utc::now()
+            .with_timezone(&FixedOffset::east_opt(self.tz_offset_hour * 3600).unwrap())
+            .date_naive()
+    }
+
+    /// 计算调整系数，已限制在`[min_multiplier, max_multiplier]`内
+    fn multiplier(
+        &self,
+        occupied_seats_count: u32,
+        total_seats_count: u32,
+        days_to_departure: i64,
+    ) -> Decimal {
+        let policy = &self.policy;
+        let mut multiplier = Decimal::ONE;
+
+        if total_seats_count > 0 {
+            let load_factor = (Decimal::from(occupied_seats_count)
+                / Decimal::from(total_seats_count))
+            .min(Decimal::ONE);
+
+            if load_factor >= policy.high_load_threshold
+                && policy.high_load_threshold < Decimal::ONE
+            {
+                multiplier += policy.max_load_markup * (load_factor - policy.high_load_threshold)
+                    / (Decimal::ONE - policy.high_load_threshold);
+            } else if load_factor <= policy.low_load_threshold
+                && policy.low_load_threshold > Decimal::ZERO
+            {
+                multiplier -= policy.max_load_discount * (policy.low_load_threshold - load_factor)
+                    / policy.low_load_threshold;
+            }
+        }
+
+        if days_to_departure >= policy.early_bird_days {
+            multiplier -= policy.early_bird_discount;
+        } else if days_to_departure <= policy.last_minute_days {
+            multiplier += policy.last_minute_markup;
+        }
+
+        multiplier.clamp(policy.min_multiplier, policy.max_multiplier)
+    }
+
+    /// 四舍五入到计价单位的整数倍
+    fn round_fare(&self, fare: Decimal) -> Decimal {
+        let unit = self.policy.rounding_unit;
+
+        if unit <= Decimal::ZERO {
+            return fare;
+        }
+
+        (fare / unit).round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero) * unit
+    }
+}
+
+#[async_trait]
+impl<TSS> PricingService for DynamicPricingServiceImpl<TSS>
+where
+    TSS: TrainSeatService,
+{
+    fn adjust_fare(
+        &self,
+        base_fare: Decimal,
+        occupied_seats_count: u32,
+        total_seats_count: u32,
+        days_to_departure: i64,
+    ) -> Decimal {
+        if !self.policy.enabled {
+            return base_fare;
+        }
+
+        self.round_fare(
+            base_fare * self.multiplier(occupied_seats_count, total_seats_count, days_to_departure),
+        )
+    }
+
+    async fn quote_fares(
+        &self,
+        queries: &[PriceQuoteQuery],
+    ) -> Result<Vec<Decimal>, PricingServiceError> {
+        if !self.policy.enabled {
+            return Ok(queries.iter().map(|query| query.base_fare).collect());
+        }
+
+        let seat_queries = queries
+            .iter()
+            .map(|query| SeatCountQuery {
+                train_schedule_id: query.train_schedule_id,
+                route_id: query.route_id,
+                seat_type: query.seat_type.clone(),
+                station_range: query.station_range,
+            })
+            .collect::<Vec<_>>();
+
+        let available_counts = self
+            .train_seat_service
+            .available_seats_count_batch(&seat_queries)
+            .await?;
+
+        let today = self.today();
+
+        Ok(queries
+            .iter()
+            .zip(available_counts)
+            .map(|(query, available)| {
+                let total = query.seat_type.capacity();
+
+                self.adjust_fare(
+                    query.base_fare,
+                    total.saturating_sub(available),
+                    total,
+                    (query.date - today).num_days(),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Verified;
+    use crate::domain::model::personal_info::PersonalInfoId;
+    use crate::domain::model::route::RouteId;
+    use crate::domain::model::station::StationId;
+    use crate::domain::model::train::{SeatType, SeatTypeId, SeatTypeName};
+    use crate::domain::model::train_schedule::{
+        Seat, SeatAvailabilityId, SeatLocationInfo, StationRange, TrainSchedule, TrainScheduleId,
+    };
+    use crate::domain::service::train_seat::TrainSeatServiceError;
+    use chrono::Duration;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 按顺序返回固定余票数的余票服务
+    struct FixedTrainSeatService {
+        available: Vec<u32>,
+        batch_calls: AtomicUsize,
+    }
+
+    impl FixedTrainSeatService {
+        fn new(available: Vec<u32>) -> Self {
+            Self {
+                available,
+                batch_calls: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl TrainSeatService for FixedTrainSeatService {
+        async fn available_seats_count(
+            &self,
+            seat_availability_id: SeatAvailabilityId,
+        ) -> Result<u32, TrainSeatServiceError> {
+            Err(TrainSeatServiceError::InvalidSeatAvailability(
+                seat_availability_id,
+            ))
+        }
+
+        async fn available_seats_count_batch(
+            &self,
+            queries: &[SeatCountQuery],
+        ) -> Result<Vec<u32>, TrainSeatServiceError> {
+            self.batch_calls.fetch_add(1, Ordering::SeqCst);
+            assert_eq!(queries.len(), self.available.len());
+
+            Ok(self.available.clone())
+        }
+
+        async fn reserve_seat(
+            &self,
+            _train_schedule: &mut TrainSchedule,
+            _station_range: StationRange<Verified>,
+            _seat_type: SeatType,
+            _seat_location_info: SeatLocationInfo,
+            _personal_info_id: PersonalInfoId,
+        ) -> Result<Seat, TrainSeatServiceError> {
+            Err(TrainSeatServiceError::NoAvailableSeat)
+        }
+
+        async fn free_seat(
+            &self,
+            _seat_availability_id: SeatAvailabilityId,
+            _seat: Seat,
+        ) -> Result<(), TrainSeatServiceError> {
+            Err(TrainSeatServiceError::UnreservedSeat)
+        }
+
+        async fn rebuild_seat_availability_cache(
+            &self,
+            _begin_date: NaiveDate,
+            _days: i32,
+        ) -> Result<(), TrainSeatServiceError> {
+            Ok(())
+        }
+    }
+
+    fn make_service(
+        enabled: bool,
+        available: Vec<u32>,
+    ) -> DynamicPricingServiceImpl<FixedTrainSeatService> {
+        DynamicPricingServiceImpl::new(
+            DynamicPricingPolicy {
+                enabled,
+                ..DynamicPricingPolicy::default()
+            },
+            Arc::new(FixedTrainSeatService::new(available)),
+            8,
+        )
+    }
+
+    fn make_query(capacity: u32, date: NaiveDate) -> PriceQuoteQuery {
+        PriceQuoteQuery {
+            train_schedule_id: TrainScheduleId::from(1),
+            route_id: RouteId::from(1),
+            date,
+            seat_type: SeatType::new(
+                Some(SeatTypeId::from(1)),
+                SeatTypeName::from_unchecked("二等".to_string()),
+                capacity,
+                Decimal::from(50),
+            ),
+            station_range: StationRange::from_unchecked(StationId::from(1), StationId::from(2)),
+            base_fare: Decimal::from(100),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_quote_fares() {
+        let service = make_service(true, vec![50, 15, 85, 0]);
+        let date = service.today() + Duration::days(3);
+        let queries = vec![make_query(100, date); 4];
+
+        // 上座率依次为 50%、85%、15%、100%，距发车 3 天
+        assert_eq!(
+            service.quote_fares(&queries).await.unwrap(),
+            vec![
+                Decimal::from(100),
+                Decimal::from(110),
+                Decimal::from(95),
+                Decimal::from(120)
+            ]
+        );
+        assert_eq!(
+            service
+                .train_seat_service
+                .batch_calls
+                .load(Ordering::SeqCst),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_fares_disabled() {
+        let service = make_service(false, Vec::new());
+        let queries = vec![make_query(100, service.today()); 2];
+
+        assert_eq!(
+            service.quote_fares(&queries).await.unwrap(),
+            vec![Decimal::from(100); 2]
+        );
+        // 未启用时不查询余票
+        assert_eq!(
+            service
+                .train_seat_service
+                .batch_calls
+                .load(Ordering::SeqCst),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_fares_zero_capacity() {
+        let service = make_service(true, vec![0]);
+        let date = service.today() + Duration::days(10);
+
+        // 座位总数为 0 时不按上座率调整，仅给予提前购票折扣 5%
+        assert_eq!(
+            service.quote_fares(&[make_query(0, date)]).await.unwrap(),
+            vec![Decimal::from(95)]
+        );
+    }
+
+    #[test]
+    fn test_adjust_fare() {
+        let service = make_service(true, Vec::new());
+        let base_fare = Decimal::from(100);
+
+        // 上座率 50%，距发车 3 天，不调整
+        assert_eq!(
+            service.adjust_fare(base_fare, 50, 100, 3),
+            Decimal::from(100)
+        );
+        // 上座率 85%：上浮 0.2 * (0.85 - 0.7) / 0.3 = 10%
+        assert_eq!(
+            service.adjust_fare(base_fare, 85, 100, 3),
+            Decimal::from(110)
+        );
+        // 上座率 15%：折扣 0.1 * (0.3 - 0.15) / 0.3 = 5%
+        assert_eq!(
+            service.adjust_fare(base_fare, 15, 100, 3),
+            Decimal::from(95)
+        );
+        // 提前 10 天购票且上座率 0%：折扣 10% + 5%
+        assert_eq!(
+            service.adjust_fare(base_fare, 0, 100, 10),
+            Decimal::from(85)
+        );
+        // 满座且当天发车：上浮 20% + 5%
+        assert_eq!(
+            service.adjust_fare(base_fare, 100, 100, 0),
+            Decimal::from(125)
+        );
+    }
+
+    #[test]
+    fn test_adjust_fare_bounds() {
+        let service = DynamicPricingServiceImpl::new(
+            DynamicPricingPolicy {
+                enabled: true,
+                min_multiplier: Decimal::new(90, 2),
+                max_multiplier: Decimal::new(110, 2),
+                ..DynamicPricingPolicy::default()
+            },
+            Arc::new(FixedTrainSeatService::new(Vec::new())),
+            8,
+        );
+
+        let base_fare = Decimal::from(100);
+
+        assert_eq!(
+            service.adjust_fare(base_fare, 100, 100, 0),
+            Decimal::from(110)
+        );
+        assert_eq!(
+            service.adjust_fare(base_fare, 0, 100, 10),
+            Decimal::from(90)
+        );
+    }
+
+    #[test]
+    fn test_adjust_fare_disabled() {
+        let service = make_service(false, Vec::new());
+
+        assert_eq!(
+            service.adjust_fare(Decimal::new(1234, 1), 100, 100, 0),
+            Decimal::new(1234, 1)
+        );
+    }
+}
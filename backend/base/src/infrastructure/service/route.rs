@@ -117,6 +117,7 @@ where
                 stop.arrival_time(),
                 stop.departure_time(),
                 stop.order(),
+                stop.distance(),
             );
         }
 
@@ -0,0 +1,484 @@
+use crate::domain::model::order::GroupSeatingStrategy;
+use crate::domain::service::seat_assignment::{
+    SeatAssignmentRequest, SeatAssignmentStrategy, SeatCandidate,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// 首次适配策略
+///
+/// 优先选择第一个满足位置偏好的座位，否则选择第一个空闲座位。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FirstFitSeatAssignmentStrategy;
+
+/// 最佳适配策略
+///
+/// 在满足位置偏好的前提下，选择请求区间两侧剩余空闲区间最短的座位，
+/// 使短途订单尽量拼接在已有占用旁边，从而让全程空闲的座位保留得更久。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BestFitSeatAssignmentStrategy;
+
+/// 计算座位在请求区间两侧连续空闲的区间数之和
+fn calc_free_gap(bitmap: Option<&[bool]>, request: &SeatAssignmentRequest) -> u32 {
+    let Some(bitmap) = bitmap else {
+        return (request.begin_order - request.first_order)
+            + (request.last_order - request.end_order);
+    };
+
+    let mut left = request.begin_order;
+    while left > request.first_order && !bitmap[left as usize - 1] {
+        left -= 1;
+    }
+
+    let mut right = request.end_order;
+    while right < request.last_order && !bitmap[right as usize] {
+        right += 1;
+    }
+
+    (request.begin_order - left) + (right - request.end_order)
+}
+
+fn is_preferred(candidate: &SeatCandidate<'_>, request: &SeatAssignmentRequest) -> bool {
+    request
+        .preferred_location
+        .is_some_and(|location| candidate.location_info.location == location)
+}
+
+impl SeatAssignmentStrategy for FirstFitSeatAssignmentStrategy {
+    fn select_seat(
+        &self,
+        candidates: &[SeatCandidate<'_>],
+        request: &SeatAssignmentRequest,
+    ) -> Option<usize> {
+        candidates
+            .iter()
+            .position(|candidate| is_preferred(candidate, request))
+            .or_else(|| (!candidates.is_empty()).then_some(0))
+    }
+}
+
+impl SeatAssignmentStrategy for BestFitSeatAssignmentStrategy {
+    fn select_seat(
+        &self,
+        candidates: &[SeatCandidate<'_>],
+        request: &SeatAssignmentRequest,
+    ) -> Option<usize> {
+        candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, candidate)| {
+                (
+                    !is_preferred(candidate, request),
+                    calc_free_gap(candidate.occupied_bitmap, request),
+                )
+            })
+            .map(|(index, _)| index)
+    }
+}
+
+/// 在`pool`中逐个使用`strategy`选出`count`个座位，返回选中座位在`candidates`中的下标
+fn select_in_pool<S>(
+    strategy: &S,
+    candidates: &[SeatCandidate<'_>],
+    pool: &[usize],
+    count: usize,
+    request: &SeatAssignmentRequest,
+) -> Option<Vec<usize>>
+where
+    S: SeatAssignmentStrategy + ?Sized,
+{
+    let mut remaining_index = pool.to_vec();
+    let mut remaining = pool
+        .iter()
+        .map(|index| candidates[*index])
+        .collect::<Vec<_>>();
+    let mut indices = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let selected = strategy.select_seat(&remaining, request)?;
+
+        remaining.remove(selected);
+        indices.push(remaining_index.remove(selected));
+    }
+
+    Some(indices)
+}
+
+/// 从满足同一分配方式的多组座位中选出一组并分配`count`个座位
+///
+/// 由`strategy`在全部组的座位中选出第一个座位，所在的组即为采用的组，组内其余座位同样由`strategy`选择。
+fn select_in_groups<S>(
+    strategy: &S,
+    candidates: &[SeatCandidate<'_>],
+    groups: &[Vec<usize>],
+    count: usize,
+    request: &SeatAssignmentRequest,
+) -> Option<Vec<usize>>
+where
+    S: SeatAssignmentStrategy + ?Sized,
+{
+    // 相邻排窗口可能相互重叠，去重时保持座位顺序
+    let mut seen = HashSet::new();
+    let pool = groups
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|index| seen.insert(*index))
+        .collect::<Vec<_>>();
+
+    let first = select_in_pool(strategy, candidates, &pool, 1, request)?[0];
+
+    let group = groups.iter().find(|group| group.contains(&first))?;
+    let rest = group
+        .iter()
+        .copied()
+        .filter(|index| *index != first)
+        .collect::<Vec<_>>();
+
+    let mut indices = vec![first];
+    indices.extend(select_in_pool(
+        strategy,
+        candidates,
+        &rest,
+        count - 1,
+        request,
+    )?);
+
+    Some(indices)
+}
+
+/// 为同行乘客分配一组座位
+///
+/// 依次尝试同一排、同一车厢相邻排、同一车厢，均不满足时退化为逐个使用`strategy`分配座位。
+/// 同一排或相邻排时仅考虑占用排数最少的方案。每种分配方式下均由`strategy`选择具体座位，
+/// 因此在该方式可选的座位中同样遵循位置偏好与`strategy`的适配规则。
+///
+/// # Returns
+/// * `Some((strategy, indices))` - 采用的分配方式及选中座位在`candidates`中的下标
+/// * `None` - 空闲座位数不足`count`
+pub fn select_group_seats<S>(
+    strategy: &S,
+    candidates: &[SeatCandidate<'_>],
+    count: usize,
+    request: &SeatAssignmentRequest,
+) -> Option<(GroupSeatingStrategy, Vec<usize>)>
+where
+    S: SeatAssignmentStrategy + ?Sized,
+{
+    if count == 0 || candidates.len() < count {
+        return None;
+    }
+
+    // carriage -> row -> 候选座位下标（按位置排序）
+    let mut carriage_map: BTreeMap<i32, BTreeMap<i32, Vec<usize>>> = BTreeMap::new();
+
+    for (index, candidate) in candidates.iter().enumerate() {
+        carriage_map
+            .entry(candidate.location_info.carriage)
+            .or_default()
+            .entry(candidate.location_info.row)
+            .or_default()
+            .push(index);
+    }
+
+    for rows in carriage_map.values_mut() {
+        for seats in rows.values_mut() {
+            seats.sort_by_key(|index| candidates[*index].location_info.location);
+        }
+    }
+
+    // 占用排数最少的相邻排窗口，每个窗口为其中的候选座位下标
+    let mut best_span = usize::MAX;
+    let mut best_windows: Vec<Vec<usize>> = Vec::new();
+
+    for rows in carriage_map.values() {
+        let rows = rows.iter().collect::<Vec<_>>();
+
+        for begin in 0..rows.len() {
+            let mut seat_count = 0;
+
+            for end in begin..rows.len() {
+                if end > begin && *rows[end].0 != *rows[end - 1].0 + 1 {
+                    break;
+                }
+
+                seat_count += rows[end].1.len();
+
+                if seat_count >= count {
+                    let span = end - begin + 1;
+
+                    if span < best_span {
+                        best_span = span;
+                        best_windows.clear();
+                    }
+
+                    if span == best_span {
+                        best_windows.push(
+                            rows[begin..=end]
+                                .iter()
+                                .flat_map(|(_, seats)| seats.iter().copied())
+                                .collect(),
+                        );
+                    }
+
+                    break;
+                }
+            }
+        }
+    }
+
+    if let Some(indices) = select_in_groups(strategy, candidates, &best_windows, count, request) {
+        let group_seating = if best_span == 1 {
+            GroupSeatingStrategy::SameRow
+        } else {
+            GroupSeatingStrategy::AdjacentRows
+        };
+
+        return Some((group_seating, indices));
+    }
+
+    let carriages = carriage_map
+        .values()
+        .map(|rows| {
+            rows.values()
+                .flat_map(|seats| seats.iter().copied())
+                .collect::<Vec<_>>()
+        })
+        .filter(|seats| seats.len() >= count)
+        .collect::<Vec<_>>();
+
+    if let Some(indices) = select_in_groups(strategy, candidates, &carriages, count, request) {
+        return Some((GroupSeatingStrategy::SameCarriage, indices));
+    }
+
+    let pool = (0..candidates.len()).collect::<Vec<_>>();
+    let indices = select_in_pool(strategy, candidates, &pool, count, request)?;
+
+    Some((GroupSeatingStrategy::Scattered, indices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::train_schedule::{SeatId, SeatLocationInfo};
+
+    const SEAT_COUNT: usize = 20;
+    const STOP_COUNT: u32 = 8;
+
+    /// 线性同余伪随机数，保证模拟过程可复现
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u32 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 33) as u32
+        }
+    }
+
+    /// 依次售出随机短途票，返回剩余可售的全程票数
+    fn simulate(strategy: &dyn SeatAssignmentStrategy, seed: u64, trips: usize) -> usize {
+        let mut bitmaps = vec![vec![false; STOP_COUNT as usize]; SEAT_COUNT];
+        let mut rng = Lcg(seed);
+
+        for _ in 0..trips {
+            let begin_order = rng.next() % (STOP_COUNT - 1);
+            let max_len = (STOP_COUNT - 1 - begin_order).min(3);
+            let end_order = begin_order + 1 + rng.next() % max_len;
+
+            let request = SeatAssignmentRequest {
+                begin_order,
+                end_order,
+                first_order: 0,
+                last_order: STOP_COUNT - 1,
+                preferred_location: None,
+            };
+
+            let candidates = bitmaps
+                .iter()
+                .enumerate()
+                .filter(|(_, bitmap)| {
+                    !bitmap[begin_order as usize..end_order as usize]
+                        .iter()
+                        .any(|occupied| *occupied)
+                })
+                .map(|(seat_index, bitmap)| SeatCandidate {
+                    seat_id: SeatId::from(seat_index as u64),
+                    location_info: SeatLocationInfo {
+                        carriage: 1,
+                        row: seat_index as i32 / 5 + 1,
+                        location: ['A', 'B', 'C', 'D', 'F'][seat_index % 5],
+                    },
+                    occupied_bitmap: Some(bitmap.as_slice()),
+                })
+                .collect::<Vec<_>>();
+
+            if let Some(index) = strategy.select_seat(&candidates, &request) {
+                let seat_index = u64::from(candidates[index].seat_id) as usize;
+                bitmaps[seat_index][begin_order as usize..end_order as usize].fill(true);
+            }
+        }
+
+        bitmaps
+            .iter()
+            .filter(|bitmap| !bitmap.iter().any(|occupied| *occupied))
+            .count()
+    }
+
+    #[test]
+    fn test_best_fit_keeps_more_full_route_seats() {
+        let mut first_fit_total = 0;
+        let mut best_fit_total = 0;
+
+        for seed in 0..50 {
+            let first_fit = simulate(&FirstFitSeatAssignmentStrategy, seed, 30);
+            let best_fit = simulate(&BestFitSeatAssignmentStrategy, seed, 30);
+
+            assert!(
+                best_fit >= first_fit,
+                "seed {}: {} < {}",
+                seed,
+                best_fit,
+                first_fit
+            );
+
+            first_fit_total += first_fit;
+            best_fit_total += best_fit;
+        }
+
+        assert!(best_fit_total > first_fit_total);
+    }
+
+    #[test]
+    fn test_best_fit_respects_preferred_location() {
+        let occupied = [true, false, false, false];
+        let candidates = [
+            SeatCandidate {
+                seat_id: SeatId::from(1u64),
+                location_info: SeatLocationInfo {
+                    carriage: 1,
+                    row: 1,
+                    location: 'A',
+                },
+                occupied_bitmap: None,
+            },
+            SeatCandidate {
+                seat_id: SeatId::from(2u64),
+                location_info: SeatLocationInfo {
+                    carriage: 1,
+                    row: 1,
+                    location: 'B',
+                },
+                occupied_bitmap: Some(&occupied),
+            },
+        ];
+
+        let mut request = SeatAssignmentRequest {
+            begin_order: 1,
+            end_order: 3,
+            first_order: 0,
+            last_order: 3,
+            preferred_location: None,
+        };
+
+        let strategy = BestFitSeatAssignmentStrategy;
+        assert_eq!(strategy.select_seat(&candidates, &request), Some(1));
+
+        request.preferred_location = Some('A');
+        assert_eq!(strategy.select_seat(&candidates, &request), Some(0));
+    }
+
+    fn make_candidates(locations: &[(i32, i32, char)]) -> Vec<SeatCandidate<'static>> {
+        locations
+            .iter()
+            .enumerate()
+            .map(|(index, (carriage, row, location))| SeatCandidate {
+                seat_id: SeatId::from(index as u64),
+                location_info: SeatLocationInfo {
+                    carriage: *carriage,
+                    row: *row,
+                    location: *location,
+                },
+                occupied_bitmap: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_select_group_seats_fallback_order() {
+        let request = SeatAssignmentRequest {
+            begin_order: 0,
+            end_order: 1,
+            first_order: 0,
+            last_order: 1,
+            preferred_location: None,
+        };
+        let strategy = BestFitSeatAssignmentStrategy;
+
+        let candidates = make_candidates(&[(1, 1, 'A'), (2, 3, 'C'), (2, 3, 'A'), (2, 5, 'F')]);
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 2, &request),
+            Some((GroupSeatingStrategy::SameRow, vec![2, 1]))
+        );
+
+        let candidates = make_candidates(&[(1, 1, 'A'), (2, 3, 'C'), (2, 4, 'A'), (2, 6, 'F')]);
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 2, &request),
+            Some((GroupSeatingStrategy::AdjacentRows, vec![1, 2]))
+        );
+
+        let candidates = make_candidates(&[(1, 1, 'A'), (2, 3, 'C'), (2, 5, 'A'), (3, 6, 'F')]);
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 2, &request),
+            Some((GroupSeatingStrategy::SameCarriage, vec![1, 2]))
+        );
+
+        let candidates = make_candidates(&[(1, 1, 'A'), (2, 3, 'C'), (3, 5, 'A')]);
+        let (group_seating, indices) =
+            select_group_seats(&strategy, &candidates, 3, &request).unwrap();
+        assert_eq!(group_seating, GroupSeatingStrategy::Scattered);
+        assert_eq!(indices.len(), 3);
+
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 4, &request),
+            None
+        );
+    }
+
+    #[test]
+    fn test_select_group_seats_uses_strategy_within_tier() {
+        let mut request = SeatAssignmentRequest {
+            begin_order: 1,
+            end_order: 2,
+            first_order: 0,
+            last_order: 3,
+            preferred_location: None,
+        };
+        let strategy = BestFitSeatAssignmentStrategy;
+
+        // 第 2 排的座位前一区间已被占用，最佳适配应选择第 2 排
+        let occupied = [true, false, false];
+        let mut candidates = make_candidates(&[(1, 1, 'A'), (1, 1, 'B'), (1, 2, 'A'), (1, 2, 'B')]);
+        candidates[2].occupied_bitmap = Some(&occupied);
+        candidates[3].occupied_bitmap = Some(&occupied);
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 2, &request),
+            Some((GroupSeatingStrategy::SameRow, vec![2, 3]))
+        );
+
+        // 同一排可选时优先满足位置偏好
+        let candidates = make_candidates(&[(1, 1, 'A'), (1, 1, 'B'), (1, 2, 'A'), (1, 2, 'F')]);
+        request.preferred_location = Some('F');
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 2, &request),
+            Some((GroupSeatingStrategy::SameRow, vec![3, 2]))
+        );
+
+        let candidates = make_candidates(&[(1, 1, 'A'), (1, 3, 'B'), (2, 1, 'A'), (2, 3, 'F')]);
+        assert_eq!(
+            select_group_seats(&strategy, &candidates, 2, &request),
+            Some((GroupSeatingStrategy::SameCarriage, vec![3, 2]))
+        );
+    }
+}
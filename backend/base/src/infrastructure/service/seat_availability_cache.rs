@@ -0,0 +1,373 @@
+//! 余票缓存模块
+//!
+//! 按`(车次, 座位类型)`缓存各座位的区间占用位图及每个区间的剩余座位数，
+//! 由`TrainSeatServiceImpl`在占座、释放座位时增量维护，使余票查询无需访问数据库。
+//!
+//! 缓存仅是数据库的派生视图：未命中时由调用方从`SeatAvailabilityRepository`加载后写入，
+//! 写入时通过版本号丢弃加载期间已发生变更的结果，保证不会覆盖增量更新。
+use crate::Verified;
+use crate::domain::model::train::{SeatType, SeatTypeId};
+use crate::domain::model::train_schedule::{StationRange, TrainScheduleId};
+use crate::domain::{DbId, Identifiable};
+use crate::infrastructure::service::train_seat::calc_available_seat_count;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// 某车次某座位类型的座位占用情况
+#[derive(Debug, Clone)]
+pub struct SeatOccupancy {
+    capacity: u32,
+    /// 车站ID -> 停靠顺序
+    station_id_to_order_map: Arc<HashMap<i32, u32>>,
+    /// `segment_free[i]`为第`i`站到下一站之间区间的剩余座位数
+    segment_free: Vec<u32>,
+    /// 座位ID -> 区间占用位图，全程空闲的座位不出现在其中
+    seat_to_occupied_bitmap: HashMap<i64, Vec<bool>>,
+}
+
+impl SeatOccupancy {
+    /// 由座位占用位图创建占用情况
+    ///
+    /// # Arguments
+    /// * `capacity` - 该座位类型座位总数
+    /// * `station_id_to_order_map` - 线路车站ID到停靠顺序的映射
+    /// * `route_stops_count` - 线路停靠站数量
+    /// * `seat_to_occupied_bitmap` - 座位ID到区间占用位图的映射
+    pub fn new(
+        capacity: u32,
+        station_id_to_order_map: Arc<HashMap<i32, u32>>,
+        route_stops_count: usize,
+        seat_to_occupied_bitmap: HashMap<i64, Vec<bool>>,
+    ) -> Self {
+        let mut segment_free = vec![capacity; route_stops_count];
+
+        for bitmap in seat_to_occupied_bitmap.values() {
+            for (free, occupied) in segment_free.iter_mut().zip(bitmap) {
+                if *occupied {
+                    *free = free.saturating_sub(1);
+                }
+            }
+        }
+
+        Self {
+            capacity,
+            station_id_to_order_map,
+            segment_free,
+            seat_to_occupied_bitmap,
+        }
+    }
+
+    /// 将车站区间转换为停靠顺序区间`[begin_order, end_order)`
+    fn order_range(&self, station_range: StationRange<Verified>) -> Option<(u32, u32)> {
+        let begin_order = *self
+            .station_id_to_order_map
+            .get(&station_range.get_from_station_id().to_db_value())?;
+        let end_order = *self
+            .station_id_to_order_map
+            .get(&station_range.get_to_station_id().to_db_value())?;
+
+        (begin_order < end_order).then_some((begin_order, end_order))
+    }
+
+    /// 各区间的剩余座位数
+    pub fn segment_free(&self) -> &[u32] {
+        &self.segment_free
+    }
+
+    /// 计算`station_range`内全程空闲的座位数，区间不在线路上时返回`None`
+    pub fn available_count(&self, station_range: StationRange<Verified>) -> Option<u32> {
+        let (begin_order, end_order) = self.order_range(station_range)?;
+
+        // 任一区间已无余票时无需逐座位检查
+        if self.segment_free[begin_order as usize..end_order as usize].contains(&0) {
+            return Some(0);
+        }
+
+        Some(calc_available_seat_count(
+            self.capacity,
+            &self.seat_to_occupied_bitmap,
+            begin_order,
+            end_order,
+        ))
+    }
+
+    /// 标记座位在`station_range`内被占用
+    fn occupy(&mut self, seat_id: i64, station_range: StationRange<Verified>) {
+        let Some((begin_order, end_order)) = self.order_range(station_range) else {
+            return;
+        };
+
+        let route_stops_count = self.segment_free.len();
+
+        let bitmap = self
+            .seat_to_occupied_bitmap
+            .entry(seat_id)
+            .or_insert_with(|| vec![false; route_stops_count]);
+
+        let range = begin_order as usize..end_order as usize;
+
+        for (occupied, free) in bitmap[range.clone()]
+            .iter_mut()
+            .zip(&mut self.segment_free[range])
+        {
+            if !*occupied {
+                *occupied = true;
+                *free = free.saturating_sub(1);
+            }
+        }
+    }
+
+    /// 释放座位在`station_range`内的占用
+    fn release(&mut self, seat_id: i64, station_range: StationRange<Verified>) {
+        let Some((begin_order, end_order)) = self.order_range(station_range) else {
+            return;
+        };
+
+        let Some(bitmap) = self.seat_to_occupied_bitmap.get_mut(&seat_id) else {
+            return;
+        };
+
+        let range = begin_order as usize..end_order as usize;
+
+        for (occupied, free) in bitmap[range.clone()]
+            .iter_mut()
+            .zip(&mut self.segment_free[range])
+        {
+            if *occupied {
+                *occupied = false;
+                *free = (*free + 1).min(self.capacity);
+            }
+        }
+
+        if !bitmap.contains(&true) {
+            self.seat_to_occupied_bitmap.remove(&seat_id);
+        }
+    }
+}
+
+/// 余票缓存
+///
+/// 以`(TrainScheduleId, SeatType)`为键（内部使用座位类型ID），线程安全。
+pub struct SeatAvailabilityCache {
+    entries: DashMap<(TrainScheduleId, SeatTypeId), SeatOccupancy>,
+    /// 每次增量更新或清空时递增，用于丢弃加载期间已过时的结果
+    version: AtomicU64,
+}
+
+impl Default for SeatAvailabilityCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SeatAvailabilityCache {
+    pub fn new() -> Self {
+        Self {
+            entries: DashMap::new(),
+            version: AtomicU64::new(0),
+        }
+    }
+
+    fn key(
+        train_schedule_id: TrainScheduleId,
+        seat_type: &SeatType,
+    ) -> (TrainScheduleId, SeatTypeId) {
+        (
+            train_schedule_id,
+            seat_type.get_id().expect("seat type id should be present"),
+        )
+    }
+
+    /// 当前版本号，从数据库加载前读取，写入时传给`insert_if_unchanged`
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// 缓存的车次座位类型数量
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 查询`station_range`内的可用座位数，未缓存或区间无效时返回`None`
+    pub fn available_count(
+        &self,
+        train_schedule_id: TrainScheduleId,
+        seat_type: &SeatType,
+        station_range: StationRange<Verified>,
+    ) -> Option<u32> {
+        self.entries
+            .get(&Self::key(train_schedule_id, seat_type))?
+            .available_count(station_range)
+    }
+
+    /// 若自`version`读取以来缓存未发生变更，则写入从数据库加载的占用情况
+    ///
+    /// 已存在的条目不会被覆盖，其已由增量更新保持最新。
+    pub fn insert_if_unchanged(
+        &self,
+        version: u64,
+        train_schedule_id: TrainScheduleId,
+        seat_type: &SeatType,
+        occupancy: SeatOccupancy,
+    ) {
+        // 先持有条目锁再检查版本号：检查之后到达的增量更新会阻塞在该条目上，
+        // 待写入完成后再应用到新条目，不会被过时的加载结果覆盖
+        let entry = self.entries.entry(Self::key(train_schedule_id, seat_type));
+
+        if self.version() != version {
+            return;
+        }
+
+        entry.or_insert(occupancy);
+    }
+
+    /// 占座成功后增量更新，未缓存的车次仅递增版本号
+    pub fn occupy(
+        &self,
+        train_schedule_id: TrainScheduleId,
+        seat_type: &SeatType,
+        seat_id: i64,
+        station_range: StationRange<Verified>,
+    ) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+
+        if let Some(mut occupancy) = self
+            .entries
+            .get_mut(&Self::key(train_schedule_id, seat_type))
+        {
+            occupancy.occupy(seat_id, station_range);
+        }
+    }
+
+    /// 释放座位后增量更新，未缓存的车次仅递增版本号
+    pub fn release(
+        &self,
+        train_schedule_id: TrainScheduleId,
+        seat_type: &SeatType,
+        seat_id: i64,
+        station_range: StationRange<Verified>,
+    ) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+
+        if let Some(mut occupancy) = self
+            .entries
+            .get_mut(&Self::key(train_schedule_id, seat_type))
+        {
+            occupancy.release(seat_id, station_range);
+        }
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::station::StationId;
+    use crate::domain::model::train::SeatTypeName;
+    use rust_decimal::Decimal;
+
+    fn station_range(from: u64, to: u64) -> StationRange<Verified> {
+        StationRange::from_unchecked(StationId::from(from), StationId::from(to))
+    }
+
+    #[test]
+    fn test_seat_occupancy_incremental_update() {
+        // 站点 101..=105 顺序为 0..=4，座位 1 占用 101->103
+        let station_id_to_order_map: Arc<HashMap<i32, u32>> =
+            Arc::new((0..5).map(|order| (101 + order as i32, order)).collect());
+
+        let mut bitmap = vec![false; 5];
+        bitmap[0..2].fill(true);
+
+        let mut occupancy = SeatOccupancy::new(
+            2,
+            Arc::clone(&station_id_to_order_map),
+            5,
+            HashMap::from([(1, bitmap)]),
+        );
+
+        assert_eq!(occupancy.segment_free()[..4], [1, 1, 2, 2]);
+        assert_eq!(occupancy.available_count(station_range(101, 105)), Some(1));
+        assert_eq!(occupancy.available_count(station_range(103, 105)), Some(2));
+        assert_eq!(occupancy.available_count(station_range(105, 101)), None);
+
+        // 座位 2 占用 102->104 后，101->102 区间仍有余票，102->103 区间售罄
+        occupancy.occupy(2, station_range(102, 104));
+        assert_eq!(occupancy.segment_free()[..4], [1, 0, 1, 2]);
+        assert_eq!(occupancy.available_count(station_range(101, 103)), Some(0));
+        assert_eq!(occupancy.available_count(station_range(101, 102)), Some(1));
+        // 座位 1 可继续售卖 103->105
+        assert_eq!(occupancy.available_count(station_range(103, 105)), Some(1));
+
+        occupancy.release(1, station_range(101, 103));
+        assert_eq!(occupancy.segment_free()[..4], [2, 1, 1, 2]);
+        assert_eq!(occupancy.available_count(station_range(101, 105)), Some(1));
+
+        // 重复释放不影响计数
+        occupancy.release(1, station_range(101, 103));
+        occupancy.release(2, station_range(102, 104));
+        assert_eq!(occupancy.segment_free()[..4], [2, 2, 2, 2]);
+        assert_eq!(occupancy.available_count(station_range(101, 105)), Some(2));
+    }
+
+    #[test]
+    fn test_insert_discarded_after_interleaved_update() {
+        let station_id_to_order_map: Arc<HashMap<i32, u32>> =
+            Arc::new((0..3).map(|order| (101 + order as i32, order)).collect());
+        let seat_type = SeatType::new(
+            Some(SeatTypeId::from(1)),
+            SeatTypeName::from_unchecked("二等座".to_string()),
+            2,
+            Decimal::from(10),
+        );
+        let train_schedule_id = TrainScheduleId::from(1);
+        let cache = SeatAvailabilityCache::new();
+
+        // 加载期间座位 1 被占用，加载结果中尚未包含该占用，不应写入缓存
+        let version = cache.version();
+        let stale = SeatOccupancy::new(2, Arc::clone(&station_id_to_order_map), 3, HashMap::new());
+        cache.occupy(train_schedule_id, &seat_type, 1, station_range(101, 103));
+        cache.insert_if_unchanged(version, train_schedule_id, &seat_type, stale);
+
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache.available_count(train_schedule_id, &seat_type, station_range(101, 103)),
+            None
+        );
+
+        // 重新加载后写入，之后的增量更新应用到该条目上
+        let version = cache.version();
+        let mut bitmap = vec![false; 3];
+        bitmap[0..2].fill(true);
+        let fresh = SeatOccupancy::new(
+            2,
+            Arc::clone(&station_id_to_order_map),
+            3,
+            HashMap::from([(1, bitmap)]),
+        );
+        cache.insert_if_unchanged(version, train_schedule_id, &seat_type, fresh);
+
+        assert_eq!(
+            cache.available_count(train_schedule_id, &seat_type, station_range(101, 103)),
+            Some(1)
+        );
+
+        cache.release(train_schedule_id, &seat_type, 1, station_range(101, 103));
+
+        assert_eq!(
+            cache.available_count(train_schedule_id, &seat_type, station_range(101, 103)),
+            Some(2)
+        );
+    }
+}
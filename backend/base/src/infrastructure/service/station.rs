@@ -236,10 +236,14 @@ where
         to_city: &str,
     ) -> Result<Vec<(StationId, StationId)>, StationServiceError> {
         let from_list = self.get_station_by_city_name(from_city).await?;
-        let to_list   = self.get_station_by_city_name(to_city).await?;
+        let to_list = self.get_station_by_city_name(to_city).await?;
         Ok(from_list
             .iter()
-            .flat_map(|f| to_list.iter().map(move |t| (f.get_id().unwrap(), t.get_id().unwrap())))
+            .flat_map(|f| {
+                to_list
+                    .iter()
+                    .map(move |t| (f.get_id().unwrap(), t.get_id().unwrap()))
+            })
             .collect())
     }
 }
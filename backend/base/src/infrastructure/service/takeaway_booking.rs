@@ -1,4 +1,5 @@
 use crate::domain::model::order::{Order, OrderStatus, TakeawayOrder};
+use crate::domain::model::order_state_machine::OrderStatusActor;
 use crate::domain::repository::order::OrderRepository;
 use crate::domain::service::takeaway_booking::{
     TakeawayBookingService, TakeawayBookingServiceError,
@@ -37,16 +38,9 @@ where
             .await?
             .ok_or(TakeawayBookingServiceError::InvalidOrder(order_uuid))?;
 
-        if order.order_status() != OrderStatus::Paid {
-            return Err(TakeawayBookingServiceError::InvalidOrderStatus(
-                order_uuid,
-                order.order_status(),
-            ));
-        }
-
         // 外卖订单总是会成功
 
-        order.set_status(OrderStatus::Ongoing);
+        order.transition_status(OrderStatus::Ongoing, OrderStatusActor::System, "预订成功")?;
 
         self.order_repository
             .update(Box::new(order))
@@ -73,7 +67,11 @@ where
             ));
         }
 
-        order.set_status(OrderStatus::Cancelled);
+        order.transition_status(
+            OrderStatus::Cancelled,
+            OrderStatusActor::System,
+            "订单已取消",
+        )?;
 
         self.order_repository
             .update(Box::new(order))
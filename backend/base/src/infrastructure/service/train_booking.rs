@@ -1,21 +1,44 @@
-use crate::domain::model::order::{Order, OrderStatus, TrainOrder};
+use crate::SEAT_HOLD_RELEASE_INTERVAL_SECONDS;
+use crate::TRANSACTION_PAYMENT_TIMEOUT_MINUTES;
+use crate::Verified;
+use crate::domain::model::order::{GroupSeatingStrategy, Order, OrderStatus, TrainOrder};
+use crate::domain::model::order_state_machine::{OrderStateMachine, OrderStatusActor};
+use crate::domain::model::train::SeatType;
+use crate::domain::model::train_schedule::{
+    SeatId, SeatLocationInfo, StationRange, TrainSchedule, TrainScheduleId,
+};
+use crate::domain::model::waitlist::{Waitlist, WaitlistStatus};
 use crate::domain::repository::order::OrderRepository;
+use crate::domain::repository::route::RouteRepository;
 use crate::domain::repository::seat_availability::SeatAvailabilityRepository;
 use crate::domain::repository::train::TrainRepository;
 use crate::domain::repository::train_schedule::TrainScheduleRepository;
+use crate::domain::repository::waitlist::WaitlistRepository;
 use crate::domain::service::ServiceError;
+use crate::domain::service::seat_assignment::{
+    SeatAssignmentRequest, SeatAssignmentStrategy, SeatCandidate,
+};
 use crate::domain::service::train_booking::{TrainBookingService, TrainBookingServiceError};
 use crate::domain::service::train_seat::{TrainSeatService, TrainSeatServiceError};
 use crate::domain::service::train_type::TrainTypeConfigurationService;
 use crate::domain::{DbId, Identifiable, RepositoryError};
+use crate::infrastructure::service::seat_assignment::select_group_seats;
+use crate::infrastructure::service::train_seat::{
+    calc_seat_occupied_bitmap_map, calc_station_id_to_order_map, is_seat_free_in_range,
+};
 use anyhow::anyhow;
 use async_trait::async_trait;
+use chrono::{Local, TimeDelta};
+use dashmap::DashMap;
+use sea_orm::prelude::DateTimeWithTimeZone;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
+use tokio::sync::Mutex;
 use tracing::{error, info, instrument};
 use uuid::Uuid;
 
-pub struct TrainBookingServiceImpl<TSR, TSS, TRR, OR, SAR, TTCS>
+pub struct TrainBookingServiceImpl<TSR, TSS, TRR, OR, SAR, TTCS, RR, SAS, WR>
 where
     TSR: TrainScheduleRepository,
     TSS: TrainSeatService,
@@ -23,6 +46,9 @@ where
     OR: OrderRepository,
     SAR: SeatAvailabilityRepository,
     TTCS: TrainTypeConfigurationService,
+    RR: RouteRepository,
+    SAS: SeatAssignmentStrategy,
+    WR: WaitlistRepository,
 {
     train_schedule_repository: Arc<TSR>,
     train_seat_service: Arc<TSS>,
@@ -30,9 +56,15 @@ where
     order_repository: Arc<OR>,
     seat_availability_repository: Arc<SAR>,
     train_type_configuration_service: Arc<TTCS>,
+    route_repository: Arc<RR>,
+    seat_assignment_strategy: Arc<SAS>,
+    waitlist_repository: Arc<WR>,
+    // 同一车次排班的选座与占座需串行执行，避免并发订单占用同一座位的重叠区间
+    schedule_lock_map: DashMap<TrainScheduleId, Arc<Mutex<()>>>,
 }
 
-impl<TSR, TSS, TRR, OR, SAR, TTCS> TrainBookingServiceImpl<TSR, TSS, TRR, OR, SAR, TTCS>
+impl<TSR, TSS, TRR, OR, SAR, TTCS, RR, SAS, WR>
+    TrainBookingServiceImpl<TSR, TSS, TRR, OR, SAR, TTCS, RR, SAS, WR>
 where
     TSR: TrainScheduleRepository,
     TSS: TrainSeatService,
@@ -40,6 +72,9 @@ where
     OR: OrderRepository,
     SAR: SeatAvailabilityRepository,
     TTCS: TrainTypeConfigurationService,
+    RR: RouteRepository,
+    SAS: SeatAssignmentStrategy,
+    WR: WaitlistRepository,
 {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -49,6 +84,9 @@ where
         order_repository: Arc<OR>,
         seat_availability_repository: Arc<SAR>,
         train_type_configuration_service: Arc<TTCS>,
+        route_repository: Arc<RR>,
+        seat_assignment_strategy: Arc<SAS>,
+        waitlist_repository: Arc<WR>,
     ) -> Self {
         Self {
             train_schedule_repository,
@@ -57,26 +95,45 @@ where
             order_repository,
             seat_availability_repository,
             train_type_configuration_service,
+            route_repository,
+            seat_assignment_strategy,
+            waitlist_repository,
+            schedule_lock_map: DashMap::new(),
         }
     }
-}
 
-#[async_trait]
-impl<TSR, TSS, TRR, OR, SAR, TTCS> TrainBookingService
-    for TrainBookingServiceImpl<TSR, TSS, TRR, OR, SAR, TTCS>
-where
-    TSR: TrainScheduleRepository,
-    TSS: TrainSeatService,
-    TRR: TrainRepository,
-    OR: OrderRepository,
-    SAR: SeatAvailabilityRepository,
-    TTCS: TrainTypeConfigurationService,
-{
-    #[instrument(skip(self))]
-    async fn booking_ticket(&self, order_uuid: Uuid) -> Result<(), TrainBookingServiceError> {
-        info!("Booking train order: {}", order_uuid);
+    fn get_schedule_lock(&self, train_schedule_id: TrainScheduleId) -> Arc<Mutex<()>> {
+        Arc::clone(
+            self.schedule_lock_map
+                .entry(train_schedule_id)
+                .or_insert_with(|| Arc::new(Mutex::new(())))
+                .value(),
+        )
+    }
 
-        let mut train_order = match self
+    /// 按UUID查找火车订单
+    async fn find_train_order(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<TrainOrder, TrainBookingServiceError> {
+        match self
+            .order_repository
+            .find_train_order_by_uuid(order_uuid)
+            .await
+        {
+            Ok(Some(order)) => Ok(order),
+            Ok(None) => Err(TrainBookingServiceError::InvalidOrder(order_uuid)),
+            Err(err) => Err(TrainBookingServiceError::InfrastructureError(err.into())),
+        }
+    }
+
+    /// 加载订票所需的订单、车次排班、座位类型及路线信息，订单需处于`expected_status`状态
+    async fn load_booking_context(
+        &self,
+        order_uuid: Uuid,
+        expected_status: OrderStatus,
+    ) -> Result<BookingContext, TrainBookingServiceError> {
+        let train_order = match self
             .order_repository
             .find_train_order_by_uuid(order_uuid)
             .await
@@ -88,17 +145,25 @@ where
 
         info!("Found train order: {:?}", train_order);
 
-        if train_order.order_status() != OrderStatus::Paid {
+        if train_order.order_status() != expected_status {
             return Err(TrainBookingServiceError::InvalidOrderStatus(
                 order_uuid,
                 train_order.order_status(),
             ));
         }
+
+        self.build_booking_context(train_order).await
+    }
+
+    /// 按订单当前的座位类型构建订票上下文，不检查订单状态
+    async fn build_booking_context(
+        &self,
+        train_order: TrainOrder,
+    ) -> Result<BookingContext, TrainBookingServiceError> {
         let station_range = train_order.station_range();
         let train_schedule_id = train_order.train_schedule_id();
 
-        let mut train_schedule = match self.train_schedule_repository.find(train_schedule_id).await
-        {
+        let train_schedule = match self.train_schedule_repository.find(train_schedule_id).await {
             Ok(Some(schedule)) => schedule,
             Ok(None) => {
                 return Err(TrainBookingServiceError::InfrastructureError(
@@ -134,9 +199,8 @@ where
                 ));
             }
         };
-        let preferred_location = train_order.preferred_seat_location();
 
-        let seat_id_map = match self
+        let mut seat_id_map = match self
             .train_type_configuration_service
             .get_seat_id_map(train_id)
             .await
@@ -152,23 +216,38 @@ where
             }
         };
 
-        let train_schedule_occupied_seat = match self
-            .seat_availability_repository
-            .get_train_schedule_occupied_seat(train_schedule_id)
-            .await
-        {
-            Ok(seat) => seat,
-            Err(err) => {
+        let route = match self.route_repository.find(train_schedule.route_id()).await {
+            Ok(Some(route)) => route,
+            Ok(None) => {
                 return Err(TrainBookingServiceError::InfrastructureError(
                     ServiceError::RelatedServiceError(anyhow!(
-                        "Failed to get occupied seat: {}",
-                        err
+                        "Route not found for id {}",
+                        train_schedule.route_id()
+                    )),
+                ));
+            }
+            Err(err) => return Err(TrainBookingServiceError::InfrastructureError(err.into())),
+        };
+
+        let station_id_to_order_map = calc_station_id_to_order_map(&route);
+
+        let (begin_order, end_order) = match (
+            station_id_to_order_map.get(&station_range.get_from_station_id().to_db_value()),
+            station_id_to_order_map.get(&station_range.get_to_station_id().to_db_value()),
+        ) {
+            (Some(begin_order), Some(end_order)) => (*begin_order, *end_order),
+            _ => {
+                return Err(TrainBookingServiceError::InfrastructureError(
+                    ServiceError::RelatedServiceError(anyhow!(
+                        "Station range {:?} not in route {}",
+                        station_range,
+                        train_schedule.route_id()
                     )),
                 ));
             }
         };
 
-        let seat_locations = match seat_id_map.get(seat_type_name) {
+        let seat_locations = match seat_id_map.remove(seat_type_name) {
             Some(seats) => seats,
             None => {
                 return Err(TrainBookingServiceError::InfrastructureError(
@@ -180,54 +259,87 @@ where
             }
         };
 
-        let seat_type_id = seat_type.get_id().expect("Seat type should have ID");
-        let station_begin_id = station_range.get_from_station_id();
-        let station_end_id = station_range.get_to_station_id();
-
-        let occupied_seats = match train_schedule_occupied_seat
-            .get(&seat_type_id.to_db_value())
-            .and_then(|map| {
-                map.get(&(station_begin_id.to_db_value(), station_end_id.to_db_value()))
-            }) {
-            Some(seats) => seats,
-            None => &Vec::new(),
-        };
-
-        let available_seats: Vec<_> = seat_locations
-            .iter()
-            .filter(|(id, _)| !occupied_seats.contains(&id.to_db_value()))
-            .collect();
-
-        if available_seats.is_empty() {
-            train_order.set_status(OrderStatus::Failed);
-
-            return Err(TrainBookingServiceError::NoAvailableTickets(order_uuid));
-        }
-
-        let selected_seat = match preferred_location {
-            Some(pref_loc) => {
-                let pref_char = char::from(*pref_loc);
+        Ok(BookingContext {
+            train_order,
+            train_schedule,
+            seat_type,
+            seat_locations,
+            route_stops_count: route.stops().len(),
+            first_order: station_id_to_order_map
+                .values()
+                .copied()
+                .min()
+                .unwrap_or_default(),
+            last_order: station_id_to_order_map
+                .values()
+                .copied()
+                .max()
+                .unwrap_or_default(),
+            begin_order,
+            end_order,
+            station_id_to_order_map,
+        })
+    }
 
-                available_seats
-                    .iter()
-                    .find(|(_, info)| info.location == pref_char)
-                    .or_else(|| available_seats.first()) // 没有匹配的就选第一个可用的
-                    // SAFETY: 前面的检查保证了 available_seats 不为空
-                    .unwrap()
+    /// 加载车次排班当前的座位区间占用位图，调用方需持有排班锁
+    async fn load_occupied_bitmap_map(
+        &self,
+        context: &BookingContext,
+    ) -> Result<HashMap<i64, Vec<bool>>, TrainBookingServiceError> {
+        let train_schedule_occupied_seat = match self
+            .seat_availability_repository
+            .get_train_schedule_occupied_seat(context.train_order.train_schedule_id())
+            .await
+        {
+            Ok(seat) => seat,
+            Err(err) => {
+                return Err(TrainBookingServiceError::InfrastructureError(
+                    ServiceError::RelatedServiceError(anyhow!(
+                        "Failed to get occupied seat: {}",
+                        err
+                    )),
+                ));
             }
-            None => available_seats.first().unwrap(),
         };
 
-        info!("Selected seat: {:?}", selected_seat);
+        // 座位在任意与目标区间重叠的区间上被占用，均视为不可用
+        Ok(calc_seat_occupied_bitmap_map(
+            context
+                .seat_type
+                .get_id()
+                .expect("Seat type should have ID")
+                .to_db_value(),
+            &train_schedule_occupied_seat,
+            &context.station_id_to_order_map,
+            context.route_stops_count,
+        ))
+    }
 
-        let seat_location_info = selected_seat.1;
+    /// 为订单占用指定座位，并将订单状态更新为`Ongoing`，调用方需持有排班锁
+    ///
+    /// `hold_expire_time`不为`None`时仅暂留座位，订单状态保持不变，支付后再确认
+    async fn commit_seat(
+        &self,
+        context: BookingContext,
+        seat_location_info: SeatLocationInfo,
+        group_seating: Option<GroupSeatingStrategy>,
+        hold_expire_time: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), TrainBookingServiceError> {
+        let BookingContext {
+            mut train_order,
+            mut train_schedule,
+            seat_type,
+            ..
+        } = context;
+
+        let order_uuid = train_order.uuid();
 
         let seat = match self
             .train_seat_service
             .reserve_seat(
                 &mut train_schedule,
-                station_range,
-                seat_type.clone(),
+                train_order.station_range(),
+                seat_type,
                 seat_location_info,
                 train_order.personal_info_id(),
             )
@@ -236,8 +348,6 @@ where
             Ok(seat) => seat,
             Err(err) => {
                 if let TrainSeatServiceError::NoAvailableSeat = err {
-                    train_order.set_status(OrderStatus::Failed);
-
                     return Err(TrainBookingServiceError::NoAvailableTickets(order_uuid));
                 }
 
@@ -247,95 +357,677 @@ where
             }
         };
 
-        train_order.set_status(OrderStatus::Ongoing);
+        if hold_expire_time.is_none() {
+            train_order.transition_status(
+                OrderStatus::Ongoing,
+                OrderStatusActor::System,
+                "订票成功，已分配座位",
+            )?;
+        }
         train_order.set_seat(Some(seat.clone()));
+        train_order.set_group_seating(group_seating);
+        train_order.set_seat_hold_expire_time(hold_expire_time);
 
         self.order_repository
             .update(Box::new(train_order))
             .await
             .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
 
-        info!(
-            "Train order {} successfully booked with seat: {:?}",
-            order_uuid, seat
-        );
+        match hold_expire_time {
+            Some(hold_expire_time) => info!(
+                "Train order {} successfully held seat {:?} until {}",
+                order_uuid, seat, hold_expire_time
+            ),
+            None => info!(
+                "Train order {} successfully booked with seat: {:?}",
+                order_uuid, seat
+            ),
+        }
         Ok(())
     }
 
-    #[instrument(skip(self))]
-    async fn cancel_ticket(&self, order_uuid: Uuid) -> Result<(), TrainBookingServiceError> {
-        let mut train_order = match self
-            .order_repository
-            .find_train_order_by_uuid(order_uuid)
+    /// 确认订单支付前暂留的座位，并将订单状态更新为`Ongoing`，调用方需持有排班锁
+    async fn confirm_held_seat(
+        &self,
+        mut train_order: TrainOrder,
+    ) -> Result<(), TrainBookingServiceError> {
+        let order_uuid = train_order.uuid();
+
+        train_order.transition_status(
+            OrderStatus::Ongoing,
+            OrderStatusActor::System,
+            "订票成功，已确认支付前暂留的座位",
+        )?;
+        train_order.set_seat_hold_expire_time(None);
+
+        self.order_repository
+            .update(Box::new(train_order))
             .await
-        {
-            Ok(Some(order)) => order,
-            Ok(None) => return Err(TrainBookingServiceError::InvalidOrder(order_uuid)),
+            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+        info!("Train order {} confirmed held seat", order_uuid);
+        Ok(())
+    }
+
+    /// 释放订单占用的座位，不修改订单，调用方需持有排班锁
+    async fn free_train_order_seat(
+        &self,
+        train_order: &TrainOrder,
+    ) -> Result<(), TrainBookingServiceError> {
+        let train_schedule_id = train_order.train_schedule_id();
+
+        let seat = match train_order.seat() {
+            Some(seat) => seat,
+            None => {
+                return Err(TrainBookingServiceError::InfrastructureError(
+                    ServiceError::RelatedServiceError(anyhow!("Seat information is missing")),
+                ));
+            }
+        };
+        let seat_type = seat.seat_type();
+
+        let station_range = train_order.station_range();
+
+        let train_schedule = match self.train_schedule_repository.find(train_schedule_id).await {
+            Ok(Some(schedule)) => schedule,
+            Ok(None) => {
+                return Err(TrainBookingServiceError::InfrastructureError(
+                    ServiceError::RelatedServiceError(anyhow!("Train schedule not found")),
+                ));
+            }
             Err(err) => return Err(TrainBookingServiceError::InfrastructureError(err.into())),
         };
 
+        let seat_availability_id =
+            train_schedule.get_seat_availability_id(station_range, seat_type.clone());
+
+        if let Err(err) = self
+            .train_seat_service
+            .free_seat(seat_availability_id, seat.clone())
+            .await
+        {
+            return Err(TrainBookingServiceError::InfrastructureError(
+                ServiceError::RelatedServiceError(anyhow!("Failed to release seat: {}", err)),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// 释放订单暂留的座位，订单状态保持不变，调用方需持有排班锁
+    async fn release_seat_hold(
+        &self,
+        mut train_order: TrainOrder,
+    ) -> Result<(), TrainBookingServiceError> {
+        info!("Releasing seat hold of train order: {:?}", train_order);
+
+        self.free_train_order_seat(&train_order).await?;
+
+        train_order.set_seat(None);
+        train_order.set_group_seating(None);
+        train_order.set_seat_hold_expire_time(None);
+
+        self.order_repository
+            .update(Box::new(train_order))
+            .await
+            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+        Ok(())
+    }
+
+    /// 加锁后释放指定订单暂留的座位，订单未暂留座位时不做处理
+    async fn release_held_order(&self, order_uuid: Uuid) -> Result<(), TrainBookingServiceError> {
+        let train_schedule_id = self.find_train_order(order_uuid).await?.train_schedule_id();
+
+        let schedule_lock = self.get_schedule_lock(train_schedule_id);
+        let _guard = schedule_lock.lock().await;
+
+        let train_order = self.find_train_order(order_uuid).await?;
+
+        if train_order.is_seat_held() {
+            self.release_seat_hold(train_order).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 释放已过期的暂留座位
+    ///
+    /// 仅处理已取消或失败的订单：未支付订单在交易超时取消后才释放座位，
+    /// 已支付订单则由`booking_ticket`确认座位，避免与支付流程并发修改订单
+    async fn release_expired_seat_holds(&self) -> Result<(), TrainBookingServiceError> {
+        let order_uuid_list = self
+            .order_repository
+            .find_expired_seat_hold_train_order_uuids(Local::now().fixed_offset())
+            .await
+            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+        for order_uuid in order_uuid_list {
+            let train_order = self.find_train_order(order_uuid).await?;
+
+            let schedule_lock = self.get_schedule_lock(train_order.train_schedule_id());
+            let _guard = schedule_lock.lock().await;
+
+            // 持锁后重新加载订单，确保其状态未被并发修改
+            let train_order = self.find_train_order(order_uuid).await?;
+
+            if !train_order.is_seat_held()
+                || !matches!(
+                    train_order.order_status(),
+                    OrderStatus::Cancelled | OrderStatus::Failed
+                )
+            {
+                continue;
+            }
+
+            if let Err(err) = self.release_seat_hold(train_order).await {
+                error!(
+                    "Failed to release seat hold of train order {}: {}",
+                    order_uuid, err
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 回滚同组中已处理的订单：暂留座位时释放暂留，否则取消订单，调用方需持有排班锁
+    async fn rollback_booked_orders(&self, booked_order_uuid_list: Vec<Uuid>, hold: bool) {
+        for booked_order_uuid in booked_order_uuid_list {
+            let result = match self.find_train_order(booked_order_uuid).await {
+                Ok(order) if hold => self.release_seat_hold(order).await,
+                Ok(order) => self.cancel_train_order(order).await,
+                Err(e) => Err(e),
+            };
+
+            if let Err(e) = result {
+                error!("Failed to cancel train order {}: {}", booked_order_uuid, e);
+            }
+        }
+    }
+
+    /// 取消订单并释放已占用的座位，调用方需持有排班锁
+    async fn cancel_train_order(
+        &self,
+        mut train_order: TrainOrder,
+    ) -> Result<(), TrainBookingServiceError> {
         info!("Cancelling train order: {:?}", train_order);
 
         let status = train_order.order_status();
-        // 只有Unpaid（未支付）、Paid（已支付）、Ongoing（未出行）状态的订单可以取消
-        if !(status == OrderStatus::Unpaid
-            || status == OrderStatus::Paid
-            || status == OrderStatus::Ongoing)
-        {
+        // 须在释放座位前校验订单能否取消
+        if !OrderStateMachine::can_transition(status, OrderStatus::Cancelled) {
             return Err(TrainBookingServiceError::InvalidOrderStatus(
-                order_uuid, status,
+                train_order.uuid(),
+                status,
             ));
         }
 
-        // 释放座位
-        if status == OrderStatus::Ongoing {
-            let train_schedule_id = train_order.train_schedule_id();
+        // 释放座位，包括支付前暂留的座位
+        if status == OrderStatus::Ongoing || train_order.is_seat_held() {
+            self.free_train_order_seat(&train_order).await?;
+        }
 
-            let seat = match train_order.seat() {
-                Some(seat) => seat,
-                None => {
-                    return Err(TrainBookingServiceError::InfrastructureError(
-                        ServiceError::RelatedServiceError(anyhow!("Seat information is missing")),
-                    ));
-                }
-            };
-            let seat_type = seat.seat_type();
+        train_order.transition_status(
+            OrderStatus::Cancelled,
+            OrderStatusActor::System,
+            "订单已取消，已释放座位",
+        )?;
+        train_order.set_seat_hold_expire_time(None);
+
+        self.order_repository
+            .update(Box::new(train_order))
+            .await
+            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+        Ok(())
+    }
+
+    /// 加锁后取消订单并释放座位
+    ///
+    /// `fulfil_waitlist`为`true`且释放了座位时，随即为可接受该座位类型的候补分配座位，返回已兑现的候补
+    async fn cancel_ticket_locked(
+        &self,
+        order_uuid: Uuid,
+        fulfil_waitlist: bool,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError> {
+        let train_schedule_id = self.find_train_order(order_uuid).await?.train_schedule_id();
+
+        let schedule_lock = self.get_schedule_lock(train_schedule_id);
+        let _guard = schedule_lock.lock().await;
 
-            let station_range = train_order.station_range();
+        // 持锁后重新加载订单，确保其状态未被并发修改
+        let train_order = self.find_train_order(order_uuid).await?;
 
-            let train_schedule = match self.train_schedule_repository.find(train_schedule_id).await
+        let freed_seat_type = train_order
+            .seat()
+            .as_ref()
+            .filter(|_for_super_earth| {
+                train_order.order_status() == OrderStatus::Ongoing || train_order.is_seat_held()
+            })
+            .map(|seat| seat.seat_type().name().to_string());
+
+        self.cancel_train_order(train_order).await?;
+
+        match freed_seat_type {
+            Some(seat_type) if fulfil_waitlist => {
+                self.fulfil_pending_waitlists(train_schedule_id, Some(&seat_type))
+                    .await
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 按提交顺序为车次排班中等待兑现的候补分配座位，返回已兑现的候补，调用方需持有排班锁
+    ///
+    /// `freed_seat_type`不为`None`时，仅处理可接受该座位类型的候补。单个候补兑现失败不影响其他候补。
+    async fn fulfil_pending_waitlists(
+        &self,
+        train_schedule_id: TrainScheduleId,
+        freed_seat_type: Option<&str>,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError> {
+        let waitlists = self
+            .waitlist_repository
+            .find_pending_by_train_schedule(train_schedule_id)
+            .await
+            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+        let now = Local::now().fixed_offset();
+        let mut fulfilled_waitlists = Vec::new();
+
+        for mut waitlist in waitlists {
+            if waitlist.is_expired(now) {
+                continue;
+            }
+
+            if let Some(seat_type) = freed_seat_type
+                && !waitlist
+                    .seat_options()
+                    .iter()
+                    .any(|option| option.seat_type_name.deref() == seat_type)
             {
-                Ok(Some(schedule)) => schedule,
-                Ok(None) => {
-                    return Err(TrainBookingServiceError::InfrastructureError(
-                        ServiceError::RelatedServiceError(anyhow!("Train schedule not found")),
-                    ));
-                }
-                Err(err) => return Err(TrainBookingServiceError::InfrastructureError(err.into())),
+                continue;
+            }
+
+            match self.try_fulfil_waitlist(&mut waitlist).await {
+                Ok(true) => fulfilled_waitlists.push(waitlist),
+                Ok(false) => {}
+                Err(err) => error!("Failed to fulfil waitlist {}: {}", waitlist.uuid(), err),
+            }
+        }
+
+        Ok(fulfilled_waitlists)
+    }
+
+    /// 按可接受座位类型的顺序为候补订单选座，成功时更新订单座位类型与票价，调用方需持有排班锁
+    ///
+    /// Returns:
+    /// - 兑现成功时返回`true`；尚未完成预付或暂无可用座位时返回`false`。
+    async fn try_fulfil_waitlist(
+        &self,
+        waitlist: &mut Waitlist,
+    ) -> Result<bool, TrainBookingServiceError> {
+        let train_order = self.find_train_order(waitlist.train_order_uuid()).await?;
+
+        // 尚未完成预付或已取消的候补不参与兑现
+        if train_order.order_status() != OrderStatus::Paid {
+            return Ok(false);
+        }
+
+        for option in waitlist.seat_options().to_vec() {
+            let mut candidate_order = train_order.clone();
+            candidate_order.set_order_seat_type_name(option.seat_type_name.clone());
+            candidate_order.set_unit_price(option.price);
+
+            let context = self.build_booking_context(candidate_order).await?;
+
+            let seat_to_occupied_bitmap = self.load_occupied_bitmap_map(&context).await?;
+            let available_seats = context.available_seats(&seat_to_occupied_bitmap);
+
+            let selected_seat = match self
+                .seat_assignment_strategy
+                .select_seat(&available_seats, &context.assignment_request())
+            {
+                Some(index) => available_seats[index],
+                None => continue,
             };
 
-            let seat_availability_id =
-                train_schedule.get_seat_availability_id(station_range, seat_type.clone());
+            waitlist.fulfil(&option.seat_type_name).map_err(|err| {
+                TrainBookingServiceError::InfrastructureError(ServiceError::RelatedServiceError(
+                    anyhow!(err),
+                ))
+            })?;
+
+            self.commit_seat(context, selected_seat.location_info, None, None)
+                .await?;
+
+            self.waitlist_repository
+                .save(waitlist)
+                .await
+                .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+            info!(
+                "Waitlist {} fulfilled with seat type {}",
+                waitlist.uuid(),
+                option.seat_type_name.deref()
+            );
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// 为单个订单选座并占座，`hold_expire_time`不为`None`时仅暂留座位
+    ///
+    /// 确认占座时，若订单在支付前已暂留座位，则直接确认该座位。
+    async fn assign_seat(
+        &self,
+        order_uuid: Uuid,
+        hold_expire_time: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), TrainBookingServiceError> {
+        let expected_status = match hold_expire_time {
+            Some(_) => OrderStatus::Unpaid,
+            None => OrderStatus::Paid,
+        };
+
+        let train_schedule_id = self.find_train_order(order_uuid).await?.train_schedule_id();
+
+        // 持有排班锁直到占座完成，保证"检查-占座"过程不被其他订单打断
+        let schedule_lock = self.get_schedule_lock(train_schedule_id);
+        let _guard = schedule_lock.lock().await;
+
+        // 持锁后加载订单，确保其状态未被并发修改
+        let context = self
+            .load_booking_context(order_uuid, expected_status)
+            .await?;
+
+        if hold_expire_time.is_none() && context.train_order.is_seat_held() {
+            return self.confirm_held_seat(context.train_order).await;
+        }
+
+        let seat_to_occupied_bitmap = self.load_occupied_bitmap_map(&context).await?;
+        let available_seats = context.available_seats(&seat_to_occupied_bitmap);
+
+        let selected_seat = match self
+            .seat_assignment_strategy
+            .select_seat(&available_seats, &context.assignment_request())
+        {
+            Some(index) => available_seats[index],
+            None => {
+                return Err(TrainBookingServiceError::NoAvailableTickets(order_uuid));
+            }
+        };
+
+        info!("Selected seat: {:?}", selected_seat);
+
+        self.commit_seat(context, selected_seat.location_info, None, hold_expire_time)
+            .await
+    }
+
+    /// 为同一车次、座位类型、区间的一组订单分配相邻座位，`hold_expire_time`不为`None`时仅暂留座位
+    ///
+    /// 已暂留座位的订单直接确认；任一订单占座失败时，释放本组已占用的座位。
+    #[instrument(skip(self))]
+    async fn booking_ticket_together(
+        &self,
+        order_uuid_list: &[Uuid],
+        hold_expire_time: Option<DateTimeWithTimeZone>,
+    ) -> Result<(), TrainBookingServiceError> {
+        let expected_status = match hold_expire_time {
+            Some(_) => OrderStatus::Unpaid,
+            None => OrderStatus::Paid,
+        };
+
+        // 同组订单属于同一车次排班
+        let train_schedule_id = self
+            .find_train_order(order_uuid_list[0])
+            .await?
+            .train_schedule_id();
+
+        let schedule_lock = self.get_schedule_lock(train_schedule_id);
+        let _guard = schedule_lock.lock().await;
+
+        // 持锁后加载订单，确保其状态未被并发修改
+        let mut contexts = Vec::with_capacity(order_uuid_list.len());
+
+        for order_uuid in order_uuid_list {
+            contexts.push(
+                self.load_booking_context(*order_uuid, expected_status)
+                    .await?,
+            );
+        }
+
+        let mut booked_order_uuid_list = Vec::with_capacity(contexts.len());
+
+        if hold_expire_time.is_none() {
+            let (held_contexts, unheld_contexts): (Vec<_>, Vec<_>) = contexts
+                .into_iter()
+                .partition(|context| context.train_order.is_seat_held());
+
+            for context in held_contexts {
+                let order_uuid = context.train_order.uuid();
+
+                if let Err(err) = self.confirm_held_seat(context.train_order).await {
+                    self.rollback_booked_orders(booked_order_uuid_list, false)
+                        .await;
+                    return Err(err);
+                }
+
+                booked_order_uuid_list.push(order_uuid);
+            }
+
+            contexts = unheld_contexts;
+        }
+
+        if contexts.is_empty() {
+            return Ok(());
+        }
+
+        let seat_to_occupied_bitmap = self.load_occupied_bitmap_map(&contexts[0]).await?;
+        let available_seats = contexts[0].available_seats(&seat_to_occupied_bitmap);
+
+        let (group_seating, selected_indices) = match select_group_seats(
+            self.seat_assignment_strategy.as_ref(),
+            &available_seats,
+            contexts.len(),
+            &contexts[0].assignment_request(),
+        ) {
+            Some(selected) => selected,
+            None => {
+                let order_uuid = contexts[0].train_order.uuid();
+
+                self.rollback_booked_orders(booked_order_uuid_list, hold_expire_time.is_some())
+                    .await;
+                return Err(TrainBookingServiceError::NoAvailableTickets(order_uuid));
+            }
+        };
+
+        info!(
+            "Selected {} seats for group {:?}",
+            group_seating, order_uuid_list
+        );
+
+        let seat_location_info_list = selected_indices
+            .into_iter()
+            .map(|index| available_seats[index].location_info)
+            .collect::<Vec<_>>();
+
+        for (context, seat_location_info) in contexts.into_iter().zip(seat_location_info_list) {
+            let order_uuid = context.train_order.uuid();
 
             if let Err(err) = self
-                .train_seat_service
-                .free_seat(seat_availability_id, seat.clone())
+                .commit_seat(
+                    context,
+                    seat_location_info,
+                    Some(group_seating),
+                    hold_expire_time,
+                )
                 .await
             {
-                return Err(TrainBookingServiceError::InfrastructureError(
-                    ServiceError::RelatedServiceError(anyhow!("Failed to release seat: {}", err)),
-                ));
+                self.rollback_booked_orders(booked_order_uuid_list, hold_expire_time.is_some())
+                    .await;
+                return Err(err);
             }
+
+            booked_order_uuid_list.push(order_uuid);
         }
 
-        train_order.set_status(OrderStatus::Cancelled);
+        Ok(())
+    }
+}
 
-        self.order_repository
-            .update(Box::new(train_order))
-            .await
-            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+/// 单个订单的订票上下文
+struct BookingContext {
+    train_order: TrainOrder,
+    train_schedule: TrainSchedule,
+    seat_type: SeatType,
+    seat_locations: Vec<(SeatId, SeatLocationInfo)>,
+    station_id_to_order_map: HashMap<i32, u32>,
+    route_stops_count: usize,
+    first_order: u32,
+    last_order: u32,
+    begin_order: u32,
+    end_order: u32,
+}
 
-        Ok(())
+impl BookingContext {
+    fn available_seats<'a>(
+        &self,
+        seat_to_occupied_bitmap: &'a HashMap<i64, Vec<bool>>,
+    ) -> Vec<SeatCandidate<'a>> {
+        self.seat_locations
+            .iter()
+            .filter(|(id, _)| {
+                is_seat_free_in_range(
+                    seat_to_occupied_bitmap.get(&id.to_db_value()),
+                    self.begin_order,
+                    self.end_order,
+                )
+            })
+            .map(|(id, info)| SeatCandidate {
+                seat_id: *id,
+                location_info: *info,
+                occupied_bitmap: seat_to_occupied_bitmap
+                    .get(&id.to_db_value())
+                    .map(|bitmap| bitmap.as_slice()),
+            })
+            .collect()
+    }
+
+    fn assignment_request(&self) -> SeatAssignmentRequest {
+        SeatAssignmentRequest {
+            begin_order: self.begin_order,
+            end_order: self.end_order,
+            first_order: self.first_order,
+            last_order: self.last_order,
+            preferred_location: self.train_order.preferred_seat_location().map(char::from),
+        }
+    }
+}
+
+#[async_trait]
+impl<TSR, TSS, TRR, OR, SAR, TTCS, RR, SAS, WR> TrainBookingService
+    for TrainBookingServiceImpl<TSR, TSS, TRR, OR, SAR, TTCS, RR, SAS, WR>
+where
+    TSR: TrainScheduleRepository,
+    TSS: TrainSeatService,
+    TRR: TrainRepository,
+    OR: OrderRepository,
+    SAR: SeatAvailabilityRepository,
+    TTCS: TrainTypeConfigurationService,
+    RR: RouteRepository,
+    SAS: SeatAssignmentStrategy,
+    WR: WaitlistRepository,
+{
+    #[instrument(skip(self))]
+    async fn booking_ticket(&self, order_uuid: Uuid) -> Result<(), TrainBookingServiceError> {
+        info!("Booking train order: {}", order_uuid);
+
+        self.assign_seat(order_uuid, None).await
+    }
+
+    #[instrument(skip(self))]
+    async fn cancel_ticket(
+        &self,
+        order_uuid: Uuid,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError> {
+        self.cancel_ticket_locked(order_uuid, true).await
+    }
+
+    #[instrument(skip(self))]
+    async fn rebook_ticket(
+        &self,
+        replaced_order_uuid: Uuid,
+        new_order_uuid: Uuid,
+    ) -> Result<(), TrainBookingServiceError> {
+        info!(
+            "Rebooking train order {} to {}",
+            replaced_order_uuid, new_order_uuid
+        );
+
+        let replaced_schedule_id = self
+            .find_train_order(replaced_order_uuid)
+            .await?
+            .train_schedule_id();
+        let new_schedule_id = self
+            .find_train_order(new_order_uuid)
+            .await?
+            .train_schedule_id();
+
+        // 同时持有新旧车次排班的锁，按Id顺序加锁避免与其他改签操作死锁
+        let mut schedule_id_list = vec![replaced_schedule_id, new_schedule_id];
+        schedule_id_list.sort_by_key(|id| id.to_db_value());
+        schedule_id_list.dedup();
+
+        let schedule_lock_list = schedule_id_list
+            .into_iter()
+            .map(|id| self.get_schedule_lock(id))
+            .collect::<Vec<_>>();
+
+        let mut _guard_list = Vec::with_capacity(schedule_lock_list.len());
+        for schedule_lock in &schedule_lock_list {
+            _guard_list.push(schedule_lock.lock().await);
+        }
+
+        // 持锁后加载新旧订单，确保其状态未被并发修改
+        let context = self
+            .load_booking_context(new_order_uuid, OrderStatus::Unpaid)
+            .await?;
+        let replaced_order = self.find_train_order(replaced_order_uuid).await?;
+
+        if replaced_order.order_status() != OrderStatus::Ongoing {
+            return Err(TrainBookingServiceError::InvalidOrderStatus(
+                replaced_order_uuid,
+                replaced_order.order_status(),
+            ));
+        }
+
+        let seat_to_occupied_bitmap = self.load_occupied_bitmap_map(&context).await?;
+        let available_seats = context.available_seats(&seat_to_occupied_bitmap);
+
+        let selected_seat = match self
+            .seat_assignment_strategy
+            .select_seat(&available_seats, &context.assignment_request())
+        {
+            Some(index) => available_seats[index],
+            None => {
+                return Err(TrainBookingServiceError::NoAvailableTickets(new_order_uuid));
+            }
+        };
+
+        info!("Selected seat for rebooking: {:?}", selected_seat);
+
+        // 差价结算前仅暂留座位，结算成功后由`booking_ticket`确认
+        let hold_expire_time =
+            Local::now().fixed_offset() + TimeDelta::minutes(TRANSACTION_PAYMENT_TIMEOUT_MINUTES);
+
+        self.commit_seat(
+            context,
+            selected_seat.location_info,
+            None,
+            Some(hold_expire_time),
+        )
+        .await
     }
 
     #[instrument(skip(self))]
@@ -350,6 +1042,8 @@ where
 
         let mut failed_orders: Vec<TrainOrder> = Vec::new();
 
+        let mut orders = Vec::with_capacity(order_uuid_list.len());
+
         for order_uuid in order_uuid_list.iter() {
             let order = self
                 .order_repository
@@ -370,21 +1064,92 @@ where
                     ))
                 })?;
 
-            let result = self.booking_ticket(*order_uuid).await;
+            orders.push(order);
+        }
 
-            if let Err(err) = result {
-                error!("Failed to booking ticket {}: {}", order_uuid, err);
+        // 候补订单由候补兑现流程分配座位；候补已结束后才完成支付的订单直接退款
+        let mut booking_orders = Vec::with_capacity(orders.len());
 
-                failed_orders.push(order);
+        for order in orders {
+            match self
+                .waitlist_repository
+                .find_by_train_order_uuid(order.uuid())
+                .await
+                .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?
+            {
+                Some(waitlist) if waitlist.status() == WaitlistStatus::Pending => {
+                    info!(
+                        "Train order {} is waiting for waitlist {}",
+                        order.uuid(),
+                        waitlist.uuid()
+                    );
+                }
+                Some(waitlist) => {
+                    info!(
+                        "Train order {} paid after waitlist {} closed with status {}",
+                        order.uuid(),
+                        waitlist.uuid(),
+                        waitlist.status()
+                    );
+
+                    failed_orders.push(order);
+                }
+                None => booking_orders.push(order),
+            }
+        }
+
+        let orders = booking_orders;
+
+        if atomic {
+            for group in group_train_orders(orders) {
+                let group_uuid_list = group.iter().map(|order| order.uuid()).collect::<Vec<_>>();
+
+                let result = if group_uuid_list.len() > 1 {
+                    self.booking_ticket_together(&group_uuid_list, None).await
+                } else {
+                    self.booking_ticket(group_uuid_list[0]).await
+                };
+
+                if let Err(err) = result {
+                    error!("Failed to booking tickets {:?}: {}", group_uuid_list, err);
+
+                    // 与取消订单相同，释放座位后为候补分配座位
+                    let mut fulfilled_waitlists = Vec::new();
 
-                if atomic {
                     for order in &successful_orders {
-                        let _ = self.cancel_ticket(order.uuid()).await;
+                        match self.cancel_ticket_locked(order.uuid(), true).await {
+                            Ok(waitlists) => fulfilled_waitlists.extend(waitlists),
+                            Err(e) => {
+                                error!("Failed to cancel train order {}: {}", order.uuid(), e)
+                            }
+                        }
                     }
-                    return Err(err);
-                } else {
-                    continue;
+
+                    if fulfilled_waitlists.is_empty() {
+                        return Err(err);
+                    }
+
+                    return Err(TrainBookingServiceError::RolledBackWithFulfilledWaitlists {
+                        source: Box::new(err),
+                        fulfilled_waitlists,
+                    });
                 }
+
+                successful_orders.extend(group);
+            }
+
+            return Ok(failed_orders);
+        }
+
+        for order in orders {
+            let order_uuid = order.uuid();
+
+            let result = self.booking_ticket(order_uuid).await;
+
+            if let Err(err) = result {
+                error!("Failed to booking ticket {}: {}", order_uuid, err);
+
+                failed_orders.push(order);
             } else {
                 successful_orders.push(order);
             }
@@ -392,4 +1157,254 @@ where
 
         Ok(failed_orders)
     }
+
+    #[instrument(skip(self))]
+    async fn hold_group(
+        &self,
+        order_packs: Vec<(Vec<Uuid>, bool)>,
+        hold_expire_time: DateTimeWithTimeZone,
+    ) -> Result<Vec<Uuid>, TrainBookingServiceError> {
+        info!("Holding seats for train order packs: {:?}", order_packs);
+
+        let mut held_order_uuid_list: Vec<Uuid> = Vec::new();
+        let mut failed_order_uuid_list: Vec<Uuid> = Vec::new();
+
+        for (order_uuid_list, atomic) in order_packs {
+            let mut orders = Vec::with_capacity(order_uuid_list.len());
+
+            for order_uuid in order_uuid_list {
+                orders.push(self.find_train_order(order_uuid).await?);
+            }
+
+            let groups = if atomic {
+                group_train_orders(orders)
+            } else {
+                orders.into_iter().map(|order| vec![order]).collect()
+            };
+
+            for group in groups {
+                let group_uuid_list = group.iter().map(|order| order.uuid()).collect::<Vec<_>>();
+
+                let result = if group_uuid_list.len() > 1 {
+                    self.booking_ticket_together(&group_uuid_list, Some(hold_expire_time))
+                        .await
+                } else {
+                    self.assign_seat(group_uuid_list[0], Some(hold_expire_time))
+                        .await
+                };
+
+                match result {
+                    Ok(()) => held_order_uuid_list.extend(group_uuid_list),
+                    Err(err) if !atomic => {
+                        error!("Failed to hold seat {:?}: {}", group_uuid_list, err);
+
+                        failed_order_uuid_list.extend(group_uuid_list);
+                    }
+                    Err(err) => {
+                        error!("Failed to hold seats {:?}: {}", group_uuid_list, err);
+
+                        for order_uuid in held_order_uuid_list {
+                            if let Err(e) = self.release_held_order(order_uuid).await {
+                                error!(
+                                    "Failed to release seat hold of train order {}: {}",
+                                    order_uuid, e
+                                );
+                            }
+                        }
+
+                        return Err(err);
+                    }
+                }
+            }
+        }
+
+        Ok(failed_order_uuid_list)
+    }
+
+    #[instrument(skip_all)]
+    async fn seat_hold_daemon(&self) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+            SEAT_HOLD_RELEASE_INTERVAL_SECONDS,
+        ));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.release_expired_seat_holds().await {
+                error!("Failed to release expired seat holds: {}", e);
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn fulfil_waitlist(
+        &self,
+        train_schedule_id: TrainScheduleId,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError> {
+        let schedule_lock = self.get_schedule_lock(train_schedule_id);
+        let _guard = schedule_lock.lock().await;
+
+        self.fulfil_pending_waitlists(train_schedule_id, None).await
+    }
+
+    #[instrument(skip(self))]
+    async fn expire_waitlist(
+        &self,
+        now: DateTimeWithTimeZone,
+    ) -> Result<Vec<Waitlist>, TrainBookingServiceError> {
+        let waitlists = self
+            .waitlist_repository
+            .find_expired_pending(now)
+            .await
+            .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+        let mut expired_waitlists = Vec::with_capacity(waitlists.len());
+
+        for waitlist in waitlists {
+            let schedule_lock = self.get_schedule_lock(waitlist.train_schedule_id());
+            let _guard = schedule_lock.lock().await;
+
+            // 持锁后重新加载候补，确保其未被并发兑现
+            let mut waitlist = match self
+                .waitlist_repository
+                .find_by_uuid(waitlist.uuid())
+                .await
+                .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?
+            {
+                Some(waitlist) if waitlist.is_expired(now) => waitlist,
+                _ => continue,
+            };
+
+            if let Err(err) = waitlist.expire() {
+                error!("Failed to expire waitlist {}: {}", waitlist.uuid(), err);
+                continue;
+            }
+
+            self.waitlist_repository
+                .save(&mut waitlist)
+                .await
+                .map_err(|err| TrainBookingServiceError::InfrastructureError(err.into()))?;
+
+            expired_waitlists.push(waitlist);
+        }
+
+        Ok(expired_waitlists)
+    }
+}
+
+/// 将同一车次、座位类型、区间的订单视为同行乘客分为一组，以便尽量安排在相邻座位
+fn group_train_orders(orders: Vec<TrainOrder>) -> Vec<Vec<TrainOrder>> {
+    let mut group_index_map: HashMap<(TrainScheduleId, String, StationRange<Verified>), usize> =
+        HashMap::new();
+    let mut groups: Vec<Vec<TrainOrder>> = Vec::new();
+
+    for order in orders {
+        let key = (
+            order.train_schedule_id(),
+            order.order_seat_type_name().to_string(),
+            order.station_range(),
+        );
+
+        let index = *group_index_map.entry(key).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+
+        groups[index].push(order);
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::order::{BaseOrder, OrderTimeInfo, PaymentInfo};
+    use crate::domain::model::personal_info::PersonalInfoId;
+    use crate::domain::model::station::StationId;
+    use crate::domain::model::train::{SeatTypeId, SeatTypeName};
+    use crate::domain::model::train_schedule::{Seat, SeatStatus};
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+
+    fn make_train_order(train_schedule_id: u64, seat_type: &str, to_station_id: u64) -> TrainOrder {
+        let now = Local::now().fixed_offset();
+
+        TrainOrder::new(
+            BaseOrder::new(
+                None,
+                Uuid::new_v4(),
+                OrderStatus::Unpaid,
+                OrderTimeInfo::new(now, now, now),
+                Decimal::from(100),
+                Decimal::from(1),
+                PaymentInfo::new(None, None),
+                PersonalInfoId::from(1u64),
+            ),
+            TrainScheduleId::from(train_schedule_id),
+            None,
+            SeatTypeName::from_unchecked(seat_type.to_string()),
+            None,
+            StationRange::from_unchecked(StationId::from(1u64), StationId::from(to_station_id)),
+        )
+    }
+
+    #[test]
+    fn test_group_train_orders() {
+        let orders = vec![
+            make_train_order(1, "二等座", 2),
+            make_train_order(1, "一等座", 2),
+            make_train_order(1, "二等座", 2),
+            make_train_order(2, "二等座", 2),
+            make_train_order(1, "二等座", 3),
+        ];
+        let uuid_list = orders.iter().map(|order| order.uuid()).collect::<Vec<_>>();
+
+        let groups = group_train_orders(orders)
+            .into_iter()
+            .map(|group| group.iter().map(|order| order.uuid()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // 仅车次、座位类型、区间均相同的订单分为一组，组内及组间保持原有顺序
+        assert_eq!(
+            groups,
+            vec![
+                vec![uuid_list[0], uuid_list[2]],
+                vec![uuid_list[1]],
+                vec![uuid_list[3]],
+                vec![uuid_list[4]],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_seat_held() {
+        let mut train_order = make_train_order(1, "二等座", 2);
+        let hold_expire_time = Local::now().fixed_offset() + Duration::minutes(15);
+
+        train_order.set_seat_hold_expire_time(Some(hold_expire_time));
+        // 尚未分配座位
+        assert!(!train_order.is_seat_held());
+
+        train_order.set_seat(Some(Seat::new(
+            SeatId::from(1u64),
+            SeatType::new(
+                Some(SeatTypeId::from(1u64)),
+                SeatTypeName::from_unchecked("二等座".to_string()),
+                100,
+                Decimal::from(100),
+            ),
+            SeatLocationInfo {
+                carriage: 1,
+                row: 1,
+                location: 'A',
+            },
+            SeatStatus::Occupied,
+        )));
+        assert!(train_order.is_seat_held());
+
+        // 支付后确认座位，清除暂留时间
+        train_order.set_seat_hold_expire_time(None);
+        assert!(!train_order.is_seat_held());
+    }
 }
@@ -1,33 +1,42 @@
 use crate::Verified;
 use crate::domain::model::route::{Route, RouteId};
-use crate::domain::model::station::StationId;
-use crate::domain::model::train::{TrainId, TrainNumber};
+use crate::domain::model::station::{Station, StationId};
+use crate::domain::model::train::{Train, TrainId, TrainNumber};
 use crate::domain::model::train_schedule::{TrainSchedule, TrainScheduleId};
 use crate::domain::repository::route::RouteRepository;
+use crate::domain::repository::station::StationRepository;
 use crate::domain::repository::train::TrainRepository;
 use crate::domain::repository::train_schedule::TrainScheduleRepository;
 use crate::domain::service::ServiceError;
-use crate::domain::service::route::{RouteGraph, RouteService};
-use crate::domain::service::train_schedule::{TrainScheduleService, TrainScheduleServiceError};
+use crate::domain::service::fare::FareService;
+use crate::domain::service::route::RouteService;
+use crate::domain::service::train_schedule::{
+    Journey, JourneyLeg, TrainScheduleService, TrainScheduleServiceError, TransferPoint,
+};
 use crate::domain::{Identifiable, RepositoryError};
 use anyhow::anyhow;
 use async_trait::async_trait;
 use chrono::{FixedOffset, NaiveDate, TimeDelta};
+use dashmap::DashMap;
+use rust_decimal::Decimal;
 use sea_orm::prelude::DateTimeWithTimeZone;
 use shared::utils::TimeMeter;
-use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::Mutex;
 use tracing::{error, info, instrument, warn};
 
 // Step 1: Define generics parameter over `RouteService` service
 // Exercise 1.2.1D - 3: Your code here. (1 / 6)
-pub struct TrainScheduleServiceImpl<RS, TR, TSR, RR>
+pub struct TrainScheduleServiceImpl<RS, TR, TSR, RR, SR, FS>
 where
     RS: RouteService + 'static + Send + Sync,
     TR: TrainRepository + 'static + Send + Sync,
     TSR: TrainScheduleRepository,
     RR: RouteRepository,
+    SR: StationRepository,
+    FS: FareService,
 {
     // Step 2: Add struct filed to store an implementation of `RouteService` service
     // Exercise 1.2.1D - 3: Your code here. (2 / 6)
@@ -35,21 +44,35 @@ where
     train_repository: Arc<TR>,
     train_schedule_repository: Arc<TSR>,
     route_repository: Arc<RR>,
+    station_repository: Arc<SR>,
+    fare_service: Arc<FS>,
     tz_offset_hour: i32,
+    /// 按日期缓存的内存时刻表
+    timetable_cache: DashMap<NaiveDate, Arc<DailyTimetable>>,
+    /// 同城换站索引缓存
+    transfer_index_cache: RwLock<Option<Arc<TransferIndex>>>,
+    /// 缓存代数，每次失效时递增，用于丢弃失效前开始构建的结果
+    timetable_generation: AtomicU64,
+    /// 串行化时刻表构建，避免并发查询重复加载同一日期
+    timetable_build_lock: Mutex<()>,
 }
 
-impl<RS, TR, TSR, RR> TrainScheduleServiceImpl<RS, TR, TSR, RR>
+impl<RS, TR, TSR, RR, SR, FS> TrainScheduleServiceImpl<RS, TR, TSR, RR, SR, FS>
 where
     RS: RouteService + 'static + Send + Sync,
     TR: TrainRepository + 'static + Send + Sync,
     TSR: TrainScheduleRepository,
     RR: RouteRepository,
+    SR: StationRepository,
+    FS: FareService,
 {
     pub fn new(
         route_service: Arc<RS>,
         train_repository: Arc<TR>,
         train_schedule_repository: Arc<TSR>,
         route_repository: Arc<RR>,
+        station_repository: Arc<SR>,
+        fare_service: Arc<FS>,
         tz_offset_hour: i32,
     ) -> Self {
         Self {
@@ -57,7 +80,13 @@ where
             train_repository,
             train_schedule_repository,
             route_repository,
+            station_repository,
+            fare_service,
             tz_offset_hour,
+            timetable_cache: DashMap::new(),
+            transfer_index_cache: RwLock::new(None),
+            timetable_generation: AtomicU64::new(0),
+            timetable_build_lock: Mutex::new(()),
         }
     }
 }
@@ -72,26 +101,45 @@ struct Connection {
     arrival_station: StationId,
     arrival_time: u32,
     train_schedule_id: TrainScheduleId,
+    /// 该区间各席别的最低基础票价，由`FareService`计算，与查询结果中的票价一致；
+    /// 列车不存在或无法计价时为`None`
+    fare: Option<Decimal>,
 }
 
-fn build_connections(
+fn build_connections<FS: FareService>(
     train_schedules: &[TrainSchedule],
     route_map_by_id: &HashMap<RouteId, Route>,
+    train_map_by_id: &HashMap<TrainId, Train>,
+    fare_service: &FS,
 ) -> Vec<Connection> {
     let mut connections = Vec::new();
 
     for schedule in train_schedules {
         let origin_offset = schedule.origin_departure_time() as u32;
+        let train = train_map_by_id.get(&schedule.train_id());
 
         if let Some(route) = route_map_by_id.get(&schedule.route_id()) {
             for (i, from_stop) in route.stops().iter().enumerate() {
-                for to_stop in route.stops().iter().skip(i + 1) {
+                for (j, to_stop) in route.stops().iter().enumerate().skip(i + 1) {
+                    let fare = train.and_then(|train| {
+                        train
+                            .seats()
+                            .values()
+                            .filter_map(|seat| {
+                                fare_service
+                                    .calculate_fare(train.train_type(), seat, route, i, j)
+                                    .ok()
+                            })
+                            .min()
+                    });
+
                     connections.push(Connection {
                         departure_station: from_stop.station_id(),
                         departure_time: origin_offset + from_stop.departure_time(),
                         arrival_station: to_stop.station_id(),
                         arrival_time: origin_offset + to_stop.arrival_time(),
                         train_schedule_id: schedule.get_id().unwrap(),
+                        fare,
                     });
                 }
             }
@@ -101,14 +149,6 @@ fn build_connections(
     connections
 }
 
-fn build_station_index(graph: &RouteGraph) -> HashMap<StationId, usize> {
-    graph
-        .node_indices()
-        .enumerate()
-        .map(|(index, node)| (graph[node], index))
-        .collect()
-}
-
 /*--------------------------------------------------------*
 |                    辅助索引：按站点分组的出发连接         |
 *--------------------------------------------------------*/
@@ -129,33 +169,397 @@ fn build_outgoing_index(connections: &[Connection]) -> HashMap<StationId, Vec<us
     map
 }
 
+/// 同城换站索引：车站 -> [(同城其它车站, 换乘时间)]
+type TransferIndex = HashMap<StationId, Vec<(StationId, u32)>>;
+
+/// 为每个车站建立可步行/乘地铁换乘的同城车站列表
+///
+/// 仅保留两端车站均存在且属于同一城市的配置项。
+fn build_transfer_index(
+    stations: &[Station],
+    transfer_times: &HashMap<(StationId, StationId), u32>,
+) -> TransferIndex {
+    let station_city = stations
+        .iter()
+        .filter_map(|station| station.get_id().map(|id| (id, station.city_id())))
+        .collect::<HashMap<_, _>>();
+
+    let mut map: HashMap<StationId, Vec<(StationId, u32)>> = HashMap::new();
+
+    for (&(from, to), &transfer_time) in transfer_times {
+        match (station_city.get(&from), station_city.get(&to)) {
+            (Some(from_city), Some(to_city)) if from != to && from_city == to_city => {
+                map.entry(from).or_default().push((to, transfer_time));
+            }
+            _ => warn!("ignore invalid intra-city transfer: {} -> {}", from, to),
+        }
+    }
+
+    map
+}
+
+/*--------------------------------------------------------*
+|                    按日期缓存的内存时刻表                  |
+*--------------------------------------------------------*/
+/// 某一日期的只读时刻表
+///
+/// 由当日全部车次及其线路派生出查询所需的各类索引，构建一次后由直达、换乘
+/// 及多次换乘查询共享，避免每次查询都从数据库加载车次与线路。
+///
+/// 注意：其中的`TrainSchedule`仅用于时刻信息，座位占用须另行查询。
+struct DailyTimetable {
+    schedules: Vec<TrainSchedule>,
+    /// 车次ID -> 在`schedules`中的下标
+    schedule_index: HashMap<TrainScheduleId, usize>,
+    route_map: HashMap<RouteId, Route>,
+    /// 线路ID -> (车站ID -> 停靠序号)
+    route_pos_map: HashMap<RouteId, HashMap<StationId, usize>>,
+    connections: Vec<Connection>,
+    /// 车站 -> 出发 Connection 索引表
+    outgoing_index: HashMap<StationId, Vec<usize>>,
+}
+
+impl DailyTimetable {
+    fn new<FS: FareService>(
+        schedules: Vec<TrainSchedule>,
+        routes: Vec<Route>,
+        trains: &HashMap<TrainId, Train>,
+        fare_service: &FS,
+    ) -> Self {
+        let route_map = routes
+            .into_iter()
+            .map(|r| (r.get_id().unwrap(), r))
+            .collect::<HashMap<_, _>>();
+
+        let route_pos_map = route_map
+            .iter()
+            .map(|(&route_id, route)| {
+                let pos_map = route
+                    .stops()
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, stop)| (stop.station_id(), idx))
+                    .collect::<HashMap<_, _>>();
+                (route_id, pos_map)
+            })
+            .collect();
+
+        let schedule_index = schedules
+            .iter()
+            .enumerate()
+            .map(|(idx, s)| (s.get_id().unwrap(), idx))
+            .collect();
+
+        let connections = build_connections(&schedules, &route_map, trains, fare_service);
+        let outgoing_index = build_outgoing_index(&connections);
+
+        Self {
+            schedules,
+            schedule_index,
+            route_map,
+            route_pos_map,
+            connections,
+            outgoing_index,
+        }
+    }
+
+    fn schedule(&self, train_schedule_id: TrainScheduleId) -> Option<&TrainSchedule> {
+        self.schedule_index
+            .get(&train_schedule_id)
+            .map(|&idx| &self.schedules[idx])
+    }
+}
+
 const MIN_TRANSFER_SEC: u32 = 10 * 60; // ≥10 分钟
 const MAX_TRANSFER_SEC: u32 = 3 * 60 * 60; // ≤3 小时
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/*--------------------------------------------------------*
+|                多准则换乘规划（RAPTOR 风格）              |
+*--------------------------------------------------------*/
+/// 规划过程中到达某站的一个标签
+struct JourneyLabel {
+    station: StationId,
+    arrival_time: u32,
+    price: Decimal,
+    /// 所属轮次，即已乘坐的段数
+    round: u32,
+    /// 到达该站所乘坐的 Connection，起点标签为`None`
+    connection: Option<usize>,
+    /// 乘坐该 Connection 前同城换站所需时间
+    transfer_time: u32,
+    /// 上一段的标签
+    parent: Option<usize>,
+}
+
+/// 判断`bag`中是否存在不劣于`(arrival_time, price)`的标签
+///
+/// 标签按轮次递增加入，`bag`中已有标签的换乘次数不会多于新标签。
+fn is_dominated(labels: &[JourneyLabel], bag: &[usize], arrival_time: u32, price: Decimal) -> bool {
+    bag.iter().any(|&idx| {
+        let label = &labels[idx];
+        label.arrival_time <= arrival_time && label.price <= price
+    })
+}
+
+/// 移除`bag`中与新标签同一轮次且被其支配的标签
+fn remove_dominated(
+    labels: &[JourneyLabel],
+    bag: &mut Vec<usize>,
+    round: u32,
+    arrival_time: u32,
+    price: Decimal,
+) {
+    bag.retain(|&idx| {
+        let label = &labels[idx];
+        label.round != round || label.arrival_time < arrival_time || label.price < price
+    });
+}
+
+/// 沿`parent`回溯，判断行程是否已经过`station`或乘坐过`train_schedule_id`
+fn is_visited(
+    labels: &[JourneyLabel],
+    connections: &[Connection],
+    mut label_idx: usize,
+    station: StationId,
+    train_schedule_id: TrainScheduleId,
+) -> bool {
+    loop {
+        let label = &labels[label_idx];
+
+        if label.station == station {
+            return true;
+        }
+
+        if label
+            .connection
+            .is_some_and(|conn_idx| connections[conn_idx].train_schedule_id == train_schedule_id)
+        {
+            return true;
+        }
+
+        match label.parent {
+            Some(parent) => label_idx = parent,
+            None => return false,
+        }
+    }
+}
+
+/// 按轮次扩展标签，求换乘不超过`max_transfers`次的 Pareto 最优行程
+///
+/// 第`k`轮只扩展第`k - 1`轮新产生的标签，因此同一车站的标签集合中轮次单调不减，
+/// 新标签只需与已有标签比较到达时间与票价即可保证三准则下的 Pareto 最优。
+/// 首段须在查询日期当天出发，换乘等待时间须在`[MIN_TRANSFER_SEC, MAX_TRANSFER_SEC]`内；
+/// 同城换站时等待时间窗口整体后移换乘时间。
+fn plan_pareto_journeys(
+    connections: &[Connection],
+    outgoing_index: &HashMap<StationId, Vec<usize>>,
+    transfer_index: &HashMap<StationId, Vec<(StationId, u32)>>,
+    origins: &[StationId],
+    destinations: &[StationId],
+    max_transfers: u32,
+) -> Vec<Journey> {
+    let origin_set: HashSet<StationId> = origins.iter().copied().collect();
+    let destination_set: HashSet<StationId> = destinations.iter().copied().collect();
+
+    let mut labels: Vec<JourneyLabel> = Vec::new();
+    let mut bags: HashMap<StationId, Vec<usize>> = HashMap::new();
+    // 所有终点标签，同时用于目标剪枝
+    let mut target_bag: Vec<usize> = Vec::new();
+
+    let mut marked = Vec::with_capacity(origin_set.len());
+    for &origin in &origin_set {
+        labels.push(JourneyLabel {
+            station: origin,
+            arrival_time: 0,
+            price: Decimal::ZERO,
+            round: 0,
+            connection: None,
+            transfer_time: 0,
+            parent: None,
+        });
+        marked.push(labels.len() - 1);
+    }
+
+    for round in 1..=max_transfers + 1 {
+        let mut next_marked = Vec::new();
+
+        for &label_idx in &marked {
+            let station = labels[label_idx].station;
+
+            // 首段只能从起点出发，之后可在同城车站之间换乘
+            let transfer_candidates = std::iter::once((station, 0)).chain(
+                transfer_index
+                    .get(&station)
+                    .filter(|_| round > 1)
+                    .into_iter()
+                    .flatten()
+                    .copied(),
+            );
+
+            for (transfer_station, transfer_time) in transfer_candidates {
+                let Some(outgoing) = outgoing_index.get(&transfer_station) else {
+                    continue;
+                };
+
+                let (earliest, latest) = if round == 1 {
+                    (0, SECONDS_PER_DAY - 1)
+                } else {
+                    let ready_time = labels[label_idx].arrival_time + transfer_time;
+                    (ready_time + MIN_TRANSFER_SEC, ready_time + MAX_TRANSFER_SEC)
+                };
+
+                let start = outgoing.partition_point(|&i| connections[i].departure_time < earliest);
+
+                for &conn_idx in &outgoing[start..] {
+                    let conn = &connections[conn_idx];
+
+                    if conn.departure_time > latest {
+                        break;
+                    }
+
+                    let Some(fare) = conn.fare else {
+                        continue;
+                    };
+
+                    if origin_set.contains(&conn.arrival_station)
+                        || is_visited(
+                            &labels,
+                            connections,
+                            label_idx,
+                            conn.arrival_station,
+                            conn.train_schedule_id,
+                        )
+                    {
+                        continue;
+                    }
+
+                    let arrival_time = conn.arrival_time;
+                    let price = labels[label_idx].price + fare;
+
+                    let bag = bags.entry(conn.arrival_station).or_default();
+
+                    if is_dominated(&labels, bag, arrival_time, price)
+                        || is_dominated(&labels, &target_bag, arrival_time, price)
+                    {
+                        continue;
+                    }
+
+                    remove_dominated(&labels, bag, round, arrival_time, price);
 
-impl<RS, TR, TSR, RR> TrainScheduleServiceImpl<RS, TR, TSR, RR>
+                    labels.push(JourneyLabel {
+                        station: conn.arrival_station,
+                        arrival_time,
+                        price,
+                        round,
+                        connection: Some(conn_idx),
+                        transfer_time,
+                        parent: Some(label_idx),
+                    });
+                    let new_idx = labels.len() - 1;
+                    bag.push(new_idx);
+
+                    if destination_set.contains(&conn.arrival_station) {
+                        remove_dominated(&labels, &mut target_bag, round, arrival_time, price);
+                        target_bag.push(new_idx);
+                    } else {
+                        next_marked.push(new_idx);
+                    }
+                }
+            }
+        }
+
+        // 被同轮标签支配而移出集合的标签无需继续扩展
+        next_marked.retain(|idx| {
+            bags.get(&labels[*idx].station)
+                .is_some_and(|bag| bag.contains(idx))
+        });
+
+        if next_marked.is_empty() {
+            break;
+        }
+
+        marked = next_marked;
+    }
+
+    let mut journeys = target_bag
+        .into_iter()
+        .map(|mut label_idx| {
+            let price = labels[label_idx].price;
+            let mut legs = Vec::new();
+            let mut transfers = Vec::new();
+
+            while let Some(conn_idx) = labels[label_idx].connection {
+                let conn = &connections[conn_idx];
+                let transfer_time = labels[label_idx].transfer_time;
+
+                legs.push(JourneyLeg {
+                    train_schedule_id: conn.train_schedule_id,
+                    departure_station: conn.departure_station,
+                    departure_time: conn.departure_time,
+                    arrival_station: conn.arrival_station,
+                    arrival_time: conn.arrival_time,
+                });
+                label_idx = labels[label_idx]
+                    .parent
+                    .expect("non-origin label should have parent");
+
+                if labels[label_idx].connection.is_some() {
+                    transfers.push(TransferPoint {
+                        arrival_station: labels[label_idx].station,
+                        departure_station: conn.departure_station,
+                        transfer_time,
+                    });
+                }
+            }
+
+            legs.reverse();
+            transfers.reverse();
+
+            Journey {
+                legs,
+                transfers,
+                price,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    journeys.sort_by(|a, b| {
+        (a.arrival_time(), a.transfer_count(), a.price).cmp(&(
+            b.arrival_time(),
+            b.transfer_count(),
+            b.price,
+        ))
+    });
+
+    journeys
+}
+
+impl<RS, TR, TSR, RR, SR, FS> TrainScheduleServiceImpl<RS, TR, TSR, RR, SR, FS>
 where
     RS: RouteService + 'static + Send + Sync,
     TR: TrainRepository + 'static + Send + Sync,
     TSR: TrainScheduleRepository,
     RR: RouteRepository,
+    SR: StationRepository,
+    FS: FareService,
 {
+    /// 从数据库加载指定日期的车次、线路与票价，构建内存时刻表
     #[instrument(skip(self))]
-    async fn load_daily_context(
+    async fn build_timetable(
         &self,
         date: NaiveDate,
-    ) -> Result<
-        (
-            Vec<TrainSchedule>,
-            HashMap<RouteId, Route>,
-            Vec<Connection>,
-            RouteGraph,
-            HashMap<StationId, usize>,
-        ),
-        TrainScheduleServiceError,
-    > {
-        let mut meter = TimeMeter::new("load_daily_context");
+    ) -> Result<DailyTimetable, TrainScheduleServiceError> {
+        let mut meter = TimeMeter::new("build_timetable");
 
-        let schedules = self.get_schedules(date).await?;
+        let schedules = self
+            .train_schedule_repository
+            .find_by_date(date)
+            .await
+            .inspect_err(|e| error!("Failed to load train schedule for date {}: {}", date, e))
+            .map_err(|e| {
+                TrainScheduleServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?;
 
         meter.meter("get schedules");
 
@@ -167,40 +571,128 @@ where
 
         meter.meter("get routes");
 
-        let mut route_map = HashMap::with_capacity(routes.len());
-        for r in routes {
-            route_map.insert(r.get_id().unwrap(), r);
+        let trains = self
+            .train_repository
+            .get_trains()
+            .await
+            .inspect_err(|e| error!("Failed to load trains: {}", e))
+            .map_err(|e| {
+                TrainScheduleServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?
+            .into_iter()
+            .map(|train| (train.get_id().expect("train should have id"), train))
+            .collect::<HashMap<_, _>>();
+
+        meter.meter("load trains");
+
+        let timetable = DailyTimetable::new(schedules, routes, &trains, self.fare_service.as_ref());
+
+        meter.meter("build indexes");
+
+        info!("{}", meter);
+
+        Ok(timetable)
+    }
+
+    /// 获取指定日期的内存时刻表，未命中缓存时构建并缓存
+    async fn get_timetable(
+        &self,
+        date: NaiveDate,
+    ) -> Result<Arc<DailyTimetable>, TrainScheduleServiceError> {
+        if let Some(timetable) = self.timetable_cache.get(&date) {
+            return Ok(Arc::clone(timetable.value()));
         }
 
-        let connections = build_connections(&schedules, &route_map);
+        let _guard = self.timetable_build_lock.lock().await;
 
-        meter.meter("build connections");
+        // 等待锁期间可能已由其它查询构建完成
+        if let Some(timetable) = self.timetable_cache.get(&date) {
+            return Ok(Arc::clone(timetable.value()));
+        }
 
-        let graph = self.route_service.get_route_map().await.map_err(|e| {
-            TrainScheduleServiceError::InfrastructureError(ServiceError::RelatedServiceError(
-                e.into(),
-            ))
-        })?;
+        let generation = self.timetable_generation.load(Ordering::Acquire);
 
-        meter.meter("get route graph");
+        let timetable = Arc::new(self.build_timetable(date).await?);
 
-        let index_map = build_station_index(&graph);
+        // 构建期间缓存被失效，则本次结果可能已过时，仅返回而不缓存
+        if self.timetable_generation.load(Ordering::Acquire) == generation {
+            self.timetable_cache.insert(date, Arc::clone(&timetable));
+        }
 
-        meter.meter("build station index");
+        Ok(timetable)
+    }
 
-        info!("{}", meter);
+    /// 获取同城换站索引，未命中缓存时从数据库加载并缓存
+    async fn get_transfer_index(&self) -> Result<Arc<TransferIndex>, TrainScheduleServiceError> {
+        if let Some(index) = self.transfer_index_cache.read().unwrap().as_ref() {
+            return Ok(Arc::clone(index));
+        }
+
+        let generation = self.timetable_generation.load(Ordering::Acquire);
 
-        Ok((schedules, route_map, connections, graph, index_map))
+        let index = Arc::new(self.load_transfer_index().await?);
+
+        if self.timetable_generation.load(Ordering::Acquire) == generation {
+            *self.transfer_index_cache.write().unwrap() = Some(Arc::clone(&index));
+        }
+
+        Ok(index)
+    }
+
+    /// 预热`[begin_date, begin_date + days)`内各日期的时刻表，并淘汰早于`begin_date`的缓存
+    #[instrument(skip(self))]
+    async fn warm_up_timetables(
+        &self,
+        begin_date: NaiveDate,
+        days: i32,
+    ) -> Result<(), TrainScheduleServiceError> {
+        self.timetable_cache.retain(|&date, _| date >= begin_date);
+
+        for day in 0..days {
+            self.get_timetable(begin_date + chrono::Duration::days(day as i64))
+                .await?;
+        }
+
+        info!("timetable cached dates: {}", self.timetable_cache.len());
+
+        Ok(())
+    }
+
+    /// 加载同城换站索引：车站 -> [(同城其它车站, 换乘时间)]
+    #[instrument(skip(self))]
+    async fn load_transfer_index(&self) -> Result<TransferIndex, TrainScheduleServiceError> {
+        let stations = self
+            .station_repository
+            .load()
+            .await
+            .inspect_err(|e| error!("Failed to load stations: {}", e))
+            .map_err(|e| {
+                TrainScheduleServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?;
+
+        let transfer_times = self
+            .station_repository
+            .load_transfer_times()
+            .await
+            .inspect_err(|e| error!("Failed to load station transfer times: {}", e))
+            .map_err(|e| {
+                TrainScheduleServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?;
+
+        Ok(build_transfer_index(&stations, &transfer_times))
     }
 }
 
 #[async_trait]
-impl<RS, TR, TSR, RR> TrainScheduleService for TrainScheduleServiceImpl<RS, TR, TSR, RR>
+impl<RS, TR, TSR, RR, SR, FS> TrainScheduleService
+    for TrainScheduleServiceImpl<RS, TR, TSR, RR, SR, FS>
 where
     RS: RouteService + 'static + Send + Sync,
     TR: TrainRepository + 'static + Send + Sync,
     TSR: TrainScheduleRepository,
     RR: RouteRepository,
+    SR: StationRepository,
+    FS: FareService,
 {
     #[instrument(skip(self))]
     async fn add_schedule(
@@ -234,6 +726,8 @@ where
                 TrainScheduleServiceError::InfrastructureError(ServiceError::RepositoryError(e))
             })?;
 
+        self.invalidate_timetable(Some(date));
+
         Ok(())
     }
 
@@ -242,13 +736,7 @@ where
         &self,
         date: NaiveDate,
     ) -> Result<Vec<TrainSchedule>, TrainScheduleServiceError> {
-        self.train_schedule_repository
-            .find_by_date(date)
-            .await
-            .inspect_err(|e| error!("Failed to load train schedule for date {}: {}", date, e))
-            .map_err(|e| {
-                TrainScheduleServiceError::InfrastructureError(ServiceError::RepositoryError(e))
-            })
+        Ok(self.get_timetable(date).await?.schedules.clone())
     }
 
     #[instrument(skip(self))]
@@ -291,6 +779,8 @@ where
     ) -> Result<(), TrainScheduleServiceError> {
         info!("Auto plan schedule begin");
 
+        let window_days = days;
+
         let latest_date = self
             .train_schedule_repository
             .get_latest_schedule_date()
@@ -318,7 +808,7 @@ where
 
         if days <= 0 {
             info!("No new schedules to add, exiting auto plan.");
-            return Ok(());
+            return self.warm_up_timetables(begin_date, window_days).await;
         }
 
         let trains = self
@@ -361,6 +851,50 @@ where
             })?;
 
         info!("schedules saved");
+
+        // 新增车次的日期可能已缓存了不完整的时刻表
+        for day in 0..days {
+            self.invalidate_timetable(Some(latest_date + chrono::Duration::days(day as i64)));
+        }
+
+        self.warm_up_timetables(begin_date, window_days).await
+    }
+
+    fn invalidate_timetable(&self, date: Option<NaiveDate>) {
+        self.timetable_generation.fetch_add(1, Ordering::AcqRel);
+
+        match date {
+            Some(date) => {
+                self.timetable_cache.remove(&date);
+            }
+            None => {
+                self.timetable_cache.clear();
+                *self.transfer_index_cache.write().unwrap() = None;
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn rebuild_timetable(&self) -> Result<(), TrainScheduleServiceError> {
+        let mut cached_dates = self
+            .timetable_cache
+            .iter()
+            .map(|entry| *entry.key())
+            .collect::<Vec<_>>();
+
+        cached_dates.sort();
+
+        self.invalidate_timetable(None);
+
+        for date in cached_dates {
+            self.get_timetable(date).await?;
+        }
+
+        info!(
+            "timetable rebuilt, cached dates: {}",
+            self.timetable_cache.len()
+        );
+
         Ok(())
     }
 
@@ -482,49 +1016,22 @@ where
     ) -> Result<Vec<(TrainSchedule, StationId, StationId)>, TrainScheduleServiceError> {
         let mut meter = TimeMeter::new("direct_schedules");
 
-        let schedules = self.get_schedules(date).await?;
-
-        meter.meter("get schedules");
-
-        let route_map_by_id = self
-            .route_service
-            .get_routes()
-            .await
-            .map_err(|e| {
-                TrainScheduleServiceError::InfrastructureError(ServiceError::RelatedServiceError(
-                    e.into(),
-                ))
-            })?
-            .into_iter()
-            .map(|r| (r.get_id().unwrap(), r))
-            .collect::<HashMap<_, _>>();
+        let timetable = self.get_timetable(date).await?;
 
-        meter.meter("get routes");
+        meter.meter("get timetable");
 
         let want: HashSet<_> = pairs.iter().copied().collect();
 
         let mut result = Vec::new();
 
-        let mut route_pos_cache: HashMap<RouteId, HashMap<StationId, usize>> =
-            HashMap::with_capacity(route_map_by_id.len());
-
-        for schedule in &schedules {
+        for schedule in &timetable.schedules {
             let route_id = schedule.route_id();
 
-            let route = match route_map_by_id.get(&route_id) {
-                Some(r) => r,
-                None => continue,
-            };
-
-            let pos_map: HashMap<StationId, usize> = match route_pos_cache.entry(route_id) {
-                Entry::Occupied(o) => o.get().clone(),
-                Entry::Vacant(v) => {
-                    let mut m = HashMap::with_capacity(route.stops().len());
-                    for (idx, stop) in route.stops().iter().enumerate() {
-                        m.insert(stop.station_id(), idx);
-                    }
-                    v.insert(m).clone()
-                }
+            let (Some(route), Some(pos_map)) = (
+                timetable.route_map.get(&route_id),
+                timetable.route_pos_map.get(&route_id),
+            ) else {
+                continue;
             };
 
             for &(from, to) in &want {
@@ -557,9 +1064,11 @@ where
     //--------------------------------------------------------//
     //  换乘查询 (k = 1) —— 列举 **所有** 满足条件的方案
     //--------------------------------------------------------//
-    /// 返回值含义：`(两段列车ID, Some(中转站))`
+    /// 返回值含义：`(两段列车ID, Some(换乘点))`
     ///
     /// * **只允许一次换乘**（两段列车，且必须在同一方向继续前进）。
+    /// * 换乘可以在同站进行，也可以换到按同城换乘时间表可达的同城其它车站，
+    ///   此时第二程的出发时间窗口整体后移该换乘时间。
     /// * 对于同一出发/到达车站对，**列举所有可行方案**，并去重。
     /// * 为保证性能：
     ///   1. 预先对 `Connection` 构建 **出发索引**（O(M) 内存，M≈连接数）。
@@ -574,49 +1083,28 @@ where
             Vec<TrainScheduleId>,
             StationId,
             StationId,
-            Option<StationId>,
+            Option<TransferPoint>,
         )>,
         TrainScheduleServiceError,
     > {
         let mut meter = TimeMeter::new("transfer_schedules");
 
-        let schedules = self.get_schedules(date).await?;
+        let timetable = self.get_timetable(date).await?;
 
-        meter.meter("get schedules");
+        meter.meter("get timetable");
 
-        let (_, _, connections, _graph, _index_map) = self.load_daily_context(date).await?;
+        let transfer_index = self.get_transfer_index().await?;
 
-        meter.meter("load daily context");
+        meter.meter("get transfer index");
 
-        // 站点 -> 出发 Connection 索引表
-        let outgoing_index = build_outgoing_index(&connections);
-
-        meter.meter("build outgoing index");
-
-        let schedule_map: HashMap<TrainScheduleId, &TrainSchedule> =
-            schedules.iter().map(|s| (s.get_id().unwrap(), s)).collect();
-
-        let route_map_by_id = self
-            .route_service
-            .get_routes()
-            .await
-            .map_err(|e| {
-                TrainScheduleServiceError::InfrastructureError(ServiceError::RelatedServiceError(
-                    e.into(),
-                ))
-            })?
-            .into_iter()
-            .map(|r| (r.get_id().unwrap(), r))
-            .collect::<HashMap<_, _>>();
-
-        let mut route_pos_cache: HashMap<RouteId, HashMap<StationId, usize>> =
-            HashMap::with_capacity(route_map_by_id.len());
+        let connections = &timetable.connections;
+        let outgoing_index = &timetable.outgoing_index;
 
         let mut all_solutions = Vec::<(
             Vec<TrainScheduleId>,
             StationId,
             StationId,
-            Option<StationId>,
+            Option<TransferPoint>,
         )>::new();
 
         for &(origin, dest) in pairs {
@@ -625,30 +1113,23 @@ where
             };
 
             // 用于去重，避免同方案多次加入
-            let mut seen: HashSet<(TrainScheduleId, TrainScheduleId, StationId)> = HashSet::new();
+            let mut seen: HashSet<(TrainScheduleId, TrainScheduleId, StationId, StationId)> =
+                HashSet::new();
 
             for &i in first_leg_indices {
                 let first = &connections[i];
 
-                let first_schedule = match schedule_map.get(&first.train_schedule_id) {
-                    Some(s) => *s,
-                    None => continue,
+                let Some(first_schedule) = timetable.schedule(first.train_schedule_id) else {
+                    continue;
                 };
 
-                let first_route = match route_map_by_id.get(&first_schedule.route_id()) {
-                    Some(r) => r,
-                    None => continue,
-                };
+                let route_id = first_schedule.route_id();
 
-                let pos_map = match route_pos_cache.entry(first_schedule.route_id()) {
-                    Entry::Occupied(o) => o.get().clone(),
-                    Entry::Vacant(v) => {
-                        let mut m = HashMap::with_capacity(first_route.stops().len());
-                        for (idx, stop) in first_route.stops().iter().enumerate() {
-                            m.insert(stop.station_id(), idx);
-                        }
-                        v.insert(m).clone()
-                    }
+                let (Some(first_route), Some(pos_map)) = (
+                    timetable.route_map.get(&route_id),
+                    timetable.route_pos_map.get(&route_id),
+                ) else {
+                    continue;
                 };
 
                 let &origin_idx = match pos_map.get(&origin) {
@@ -666,54 +1147,71 @@ where
                     continue;
                 }
 
-                let earliest_next_dep = first.arrival_time + MIN_TRANSFER_SEC;
-                let latest_next_dep = first.arrival_time + MAX_TRANSFER_SEC;
+                let mid_station = first.arrival_station;
 
-                let Some(second_leg_indices) = outgoing_index.get(&first.arrival_station) else {
-                    continue;
-                };
+                // 同站换乘及同城换站换乘的候选出发站
+                let transfer_candidates = std::iter::once((mid_station, 0)).chain(
+                    transfer_index
+                        .get(&mid_station)
+                        .into_iter()
+                        .flatten()
+                        .copied(),
+                );
 
-                // ---------- 二分定位可行第二段起始位置 ---------- //
-                let start_idx = match second_leg_indices
-                    .binary_search_by_key(&earliest_next_dep, |&idx| {
-                        connections[idx].departure_time
-                    }) {
-                    Ok(pos) | Err(pos) => pos,
-                };
+                for (transfer_station, transfer_time) in transfer_candidates {
+                    let earliest_next_dep = first.arrival_time + transfer_time + MIN_TRANSFER_SEC;
+                    let latest_next_dep = first.arrival_time + transfer_time + MAX_TRANSFER_SEC;
 
-                for &j in &second_leg_indices[start_idx..] {
-                    let second = &connections[j];
+                    let Some(second_leg_indices) = outgoing_index.get(&transfer_station) else {
+                        continue;
+                    };
 
-                    if second.departure_time > latest_next_dep {
-                        break;
-                    }
+                    // ---------- 二分定位可行第二段起始位置 ---------- //
+                    let start_idx = match second_leg_indices
+                        .binary_search_by_key(&earliest_next_dep, |&idx| {
+                            connections[idx].departure_time
+                        }) {
+                        Ok(pos) | Err(pos) => pos,
+                    };
 
-                    if second.departure_station != first.arrival_station {
-                        continue;
-                    }
+                    for &j in &second_leg_indices[start_idx..] {
+                        let second = &connections[j];
 
-                    if second.arrival_station != dest {
-                        continue;
-                    }
+                        if second.departure_time > latest_next_dep {
+                            break;
+                        }
 
-                    if first.train_schedule_id == second.train_schedule_id {
-                        continue;
-                    }
+                        if second.departure_station != transfer_station {
+                            continue;
+                        }
 
-                    let mid_station = first.arrival_station;
-                    let key = (
-                        first.train_schedule_id,
-                        second.train_schedule_id,
-                        mid_station,
-                    );
-
-                    if seen.insert(key) {
-                        all_solutions.push((
-                            vec![first.train_schedule_id, second.train_schedule_id],
-                            origin,
-                            dest,
-                            Some(mid_station),
-                        ));
+                        if second.arrival_station != dest {
+                            continue;
+                        }
+
+                        if first.train_schedule_id == second.train_schedule_id {
+                            continue;
+                        }
+
+                        let key = (
+                            first.train_schedule_id,
+                            second.train_schedule_id,
+                            mid_station,
+                            transfer_station,
+                        );
+
+                        if seen.insert(key) {
+                            all_solutions.push((
+                                vec![first.train_schedule_id, second.train_schedule_id],
+                                origin,
+                                dest,
+                                Some(TransferPoint {
+                                    arrival_station: mid_station,
+                                    departure_station: transfer_station,
+                                    transfer_time,
+                                }),
+                            ));
+                        }
                     }
                 }
             }
@@ -726,6 +1224,40 @@ where
         Ok(all_solutions)
     }
 
+    #[instrument(skip(self, origins, destinations))]
+    async fn plan_journeys(
+        &self,
+        date: NaiveDate,
+        origins: &[StationId],
+        destinations: &[StationId],
+        max_transfers: u32,
+    ) -> Result<Vec<Journey>, TrainScheduleServiceError> {
+        let mut meter = TimeMeter::new("plan_journeys");
+
+        let timetable = self.get_timetable(date).await?;
+
+        meter.meter("get timetable");
+
+        let transfer_index = self.get_transfer_index().await?;
+
+        meter.meter("get transfer index");
+
+        let journeys = plan_pareto_journeys(
+            &timetable.connections,
+            &timetable.outgoing_index,
+            &transfer_index,
+            origins,
+            destinations,
+            max_transfers,
+        );
+
+        meter.meter("calc");
+
+        info!("{}", meter);
+
+        Ok(journeys)
+    }
+
     async fn get_station_arrival_time(
         &self,
         train_schedule_id: TrainScheduleId,
@@ -866,3 +1398,286 @@ where
         Ok(arrival_time)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::model::train::{SeatType, SeatTypeId, SeatTypeName, TrainType};
+    use crate::domain::service::fare::FareRateTable;
+    use crate::domain::service::train_schedule::TransferType;
+    use crate::infrastructure::service::fare::FareServiceImpl;
+
+    const HOUR: u32 = 60 * 60;
+
+    fn make_connection(
+        train_schedule_id: u64,
+        departure_station: u64,
+        departure_time: u32,
+        arrival_station: u64,
+        arrival_time: u32,
+        fare: u32,
+    ) -> Connection {
+        Connection {
+            departure_station: StationId::from(departure_station),
+            departure_time,
+            arrival_station: StationId::from(arrival_station),
+            arrival_time,
+            train_schedule_id: TrainScheduleId::from(train_schedule_id),
+            fare: Some(Decimal::from(fare)),
+        }
+    }
+
+    fn make_train(train_id: u64, route_id: u64) -> Train {
+        let seat_type = SeatType::new(
+            Some(SeatTypeId::from(1)),
+            SeatTypeName::from_unchecked("二等".to_string()),
+            100,
+            Decimal::from(50),
+        );
+
+        Train::new(
+            Some(TrainId::from(train_id)),
+            TrainNumber::from_unchecked(format!("G{}", train_id)),
+            TrainType::from_unchecked("G".to_string()),
+            HashMap::from([(seat_type.name().to_string(), seat_type)]),
+            RouteId::from(route_id),
+            0,
+        )
+    }
+
+    fn journey_schedules(journey: &Journey) -> Vec<u64> {
+        journey
+            .legs
+            .iter()
+            .map(|leg| u64::from(leg.train_schedule_id))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_pareto_journeys() {
+        let connections = vec![
+            // 直达，最晚到达但无需换乘
+            make_connection(1, 1, 8 * HOUR, 4, 14 * HOUR, 300),
+            // 经 2 换乘，最早到达
+            make_connection(2, 1, 7 * HOUR, 2, 8 * HOUR, 50),
+            make_connection(3, 2, 8 * HOUR + 30 * 60, 4, 12 * HOUR, 80),
+            // 换乘时间不足
+            make_connection(4, 2, 8 * HOUR + 5 * 60, 4, 9 * HOUR, 10),
+            // 经 3 换乘，最便宜
+            make_connection(5, 1, 7 * HOUR, 3, 9 * HOUR, 10),
+            make_connection(6, 3, 10 * HOUR, 4, 13 * HOUR, 10),
+            // 被 5 -> 6 支配
+            make_connection(7, 3, 10 * HOUR, 4, 15 * HOUR, 20),
+            // 经 2 同城换站至 5
+            make_connection(8, 5, 9 * HOUR, 4, 11 * HOUR, 30),
+        ];
+
+        let outgoing_index = build_outgoing_index(&connections);
+        let no_transfer_index = HashMap::new();
+        let transfer_index = HashMap::from([
+            (StationId::from(2), vec![(StationId::from(5), 20 * 60)]),
+            (StationId::from(5), vec![(StationId::from(2), 20 * 60)]),
+        ]);
+
+        let origins = [StationId::from(1)];
+        let destinations = [StationId::from(4)];
+
+        let direct = plan_pareto_journeys(
+            &connections,
+            &outgoing_index,
+            &transfer_index,
+            &origins,
+            &destinations,
+            0,
+        );
+
+        assert_eq!(direct.len(), 1);
+        assert_eq!(journey_schedules(&direct[0]), vec![1]);
+        assert_eq!(direct[0].price, Decimal::from(300));
+
+        let journeys = plan_pareto_journeys(
+            &connections,
+            &outgoing_index,
+            &no_transfer_index,
+            &origins,
+            &destinations,
+            2,
+        );
+
+        let schedules = journeys.iter().map(journey_schedules).collect::<Vec<_>>();
+        assert_eq!(schedules, vec![vec![2, 3], vec![5, 6], vec![1]]);
+        assert_eq!(journeys[0].price, Decimal::from(130));
+        assert_eq!(journeys[1].price, Decimal::from(20));
+        assert_eq!(journeys[1].transfer_count(), 1);
+        assert_eq!(journeys[1].arrival_time(), 13 * HOUR);
+        assert_eq!(
+            journeys[1].transfers[0].transfer_type(),
+            TransferType::SameStation
+        );
+
+        let journeys = plan_pareto_journeys(
+            &connections,
+            &outgoing_index,
+            &transfer_index,
+            &origins,
+            &destinations,
+            2,
+        );
+
+        let schedules = journeys.iter().map(journey_schedules).collect::<Vec<_>>();
+        assert_eq!(schedules, vec![vec![2, 8], vec![5, 6], vec![1]]);
+        assert_eq!(journeys[0].price, Decimal::from(80));
+        assert_eq!(
+            journeys[0].transfers,
+            vec![TransferPoint {
+                arrival_station: StationId::from(2),
+                departure_station: StationId::from(5),
+                transfer_time: 20 * 60,
+            }]
+        );
+        assert_eq!(
+            journeys[0].transfers[0].transfer_type(),
+            TransferType::SameCity
+        );
+    }
+
+    #[test]
+    fn test_daily_timetable_indexes() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+
+        let mut route = Route::new(Some(RouteId::from(1)));
+        route.add_stop(None, StationId::from(1), 0, 0, 0, 0);
+        route.add_stop(None, StationId::from(2), HOUR, HOUR + 5 * 60, 1, 100);
+        route.add_stop(None, StationId::from(3), 2 * HOUR, 2 * HOUR, 2, 100);
+
+        let schedules = vec![
+            TrainSchedule::new(
+                Some(TrainScheduleId::from(10)),
+                TrainId::from(1),
+                date,
+                (8 * HOUR) as i32,
+                RouteId::from(1),
+            ),
+            // 线路不存在，不应生成连接
+            TrainSchedule::new(
+                Some(TrainScheduleId::from(11)),
+                TrainId::from(2),
+                date,
+                (9 * HOUR) as i32,
+                RouteId::from(2),
+            ),
+        ];
+
+        let trains = HashMap::from([(TrainId::from(1), make_train(1, 1))]);
+        let fare_service = FareServiceImpl::new(FareRateTable::default());
+
+        let timetable = DailyTimetable::new(schedules, vec![route], &trains, &fare_service);
+
+        assert_eq!(timetable.connections.len(), 3);
+        assert_eq!(
+            timetable.route_pos_map[&RouteId::from(1)],
+            HashMap::from([
+                (StationId::from(1), 0),
+                (StationId::from(2), 1),
+                (StationId::from(3), 2),
+            ])
+        );
+        assert_eq!(timetable.outgoing_index[&StationId::from(1)].len(), 2);
+        assert_eq!(timetable.outgoing_index[&StationId::from(2)].len(), 1);
+        assert!(!timetable.outgoing_index.contains_key(&StationId::from(3)));
+
+        assert_eq!(
+            timetable
+                .schedule(TrainScheduleId::from(11))
+                .map(|s| s.train_id()),
+            Some(TrainId::from(2))
+        );
+        assert!(timetable.schedule(TrainScheduleId::from(12)).is_none());
+
+        // 二等座 0.5 SC/km，G 字头系数 1.2
+        let through = timetable
+            .connections
+            .iter()
+            .find(|c| {
+                c.departure_station == StationId::from(1) && c.arrival_station == StationId::from(3)
+            })
+            .unwrap();
+        assert_eq!(through.fare, Some(Decimal::from(120)));
+    }
+
+    #[test]
+    fn test_plan_pareto_journeys_uses_fare_service() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+
+        // 经停 2，里程短但区间多
+        let mut short_route = Route::new(Some(RouteId::from(1)));
+        short_route.add_stop(None, StationId::from(1), 0, 0, 0, 0);
+        short_route.add_stop(None, StationId::from(2), HOUR, HOUR + 5 * 60, 1, 10);
+        short_route.add_stop(None, StationId::from(3), 3 * HOUR, 3 * HOUR, 2, 10);
+
+        // 直达，里程长但仅一个区间
+        let mut long_route = Route::new(Some(RouteId::from(2)));
+        long_route.add_stop(None, StationId::from(1), 0, 0, 0, 0);
+        long_route.add_stop(None, StationId::from(3), 2 * HOUR, 2 * HOUR, 1, 500);
+
+        let schedules = vec![
+            TrainSchedule::new(
+                Some(TrainScheduleId::from(10)),
+                TrainId::from(1),
+                date,
+                (8 * HOUR) as i32,
+                RouteId::from(1),
+            ),
+            TrainSchedule::new(
+                Some(TrainScheduleId::from(11)),
+                TrainId::from(2),
+                date,
+                (8 * HOUR) as i32,
+                RouteId::from(2),
+            ),
+        ];
+
+        let trains = HashMap::from([
+            (TrainId::from(1), make_train(1, 1)),
+            (TrainId::from(2), make_train(2, 2)),
+        ]);
+        let fare_service = FareServiceImpl::new(FareRateTable::default());
+
+        let timetable = DailyTimetable::new(
+            schedules,
+            vec![short_route.clone(), long_route.clone()],
+            &trains,
+            &fare_service,
+        );
+
+        let journeys = plan_pareto_journeys(
+            &timetable.connections,
+            &timetable.outgoing_index,
+            &HashMap::new(),
+            &[StationId::from(1)],
+            &[StationId::from(3)],
+            0,
+        );
+
+        // 按区间数计价时 10 号车次（2 × 50）被 11 号车次（1 × 50）支配，
+        // 按里程计价时 10 号车次更便宜，二者均为 Pareto 最优
+        let schedules = journeys.iter().map(journey_schedules).collect::<Vec<_>>();
+        assert_eq!(schedules, vec![vec![11], vec![10]]);
+
+        let seat_type = trains[&TrainId::from(1)].seats()["二等"].clone();
+        assert_eq!(
+            journeys[0].price,
+            fare_service
+                .calculate_fare("G", &seat_type, &long_route, 0, 1)
+                .unwrap()
+        );
+        assert_eq!(
+            journeys[1].price,
+            fare_service
+                .calculate_fare("G", &seat_type, &short_route, 0, 2)
+                .unwrap()
+        );
+        assert_eq!(journeys[0].price, Decimal::from(300));
+        assert_eq!(journeys[1].price, Decimal::from(12));
+    }
+}
@@ -1,10 +1,10 @@
 use crate::Verified;
 use crate::domain::model::personal_info::PersonalInfoId;
-use crate::domain::model::route::Route;
-use crate::domain::model::train::{SeatType, SeatTypeName};
+use crate::domain::model::route::{Route, RouteId};
+use crate::domain::model::train::{SeatType, SeatTypeId, SeatTypeName};
 use crate::domain::model::train_schedule::{
     Seat, SeatAvailability, SeatAvailabilityId, SeatLocationInfo, SeatStatus, StationRange,
-    TrainSchedule,
+    TrainSchedule, TrainScheduleId,
 };
 use crate::domain::repository::route::RouteRepository;
 use crate::domain::repository::seat_availability::{
@@ -12,12 +12,16 @@ use crate::domain::repository::seat_availability::{
 };
 use crate::domain::repository::train_schedule::TrainScheduleRepository;
 use crate::domain::service::ServiceError;
-use crate::domain::service::train_seat::{TrainSeatService, TrainSeatServiceError};
+use crate::domain::service::train_seat::{SeatCountQuery, TrainSeatService, TrainSeatServiceError};
 use crate::domain::service::train_type::TrainTypeConfigurationService;
 use crate::domain::{DbId, Identifiable, RepositoryError};
+use crate::infrastructure::service::seat_availability_cache::{
+    SeatAvailabilityCache, SeatOccupancy,
+};
 use anyhow::{Context, anyhow};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 use std::sync::Arc;
 use tracing::{error, info, instrument};
@@ -33,28 +37,33 @@ where
     route_repository: Arc<RR>,
     train_type_configuration_service: Arc<TTCS>,
     train_schedule_repository: Arc<TSR>,
+    seat_availability_cache: SeatAvailabilityCache,
 }
 
-fn calc_station_id_to_order_map(route: &Route) -> HashMap<i32, u32> {
+pub(crate) fn calc_station_id_to_order_map(route: &Route) -> HashMap<i32, u32> {
     route
         .stops()
         .iter()
         .map(|stop| (stop.station_id().to_db_value(), stop.order()))
         .collect()
 }
-fn calc_available_seat_count_map(
+
+/// 计算指定座位类型下各座位的区间占用位图
+///
+/// 返回座位ID到占用位图的映射，位图下标为停靠顺序，
+/// `bitmap[i] == true`表示该座位在第`i`站到下一站之间的区间已被占用。
+/// 未出现在返回值中的座位在全程均空闲。
+pub(crate) fn calc_seat_occupied_bitmap_map(
     seat_type_id: i32,
     occupied_seat_info_map: &OccupiedSeatInfoMap,
     station_id_to_order_map: &HashMap<i32, u32>,
     route_stops_count: usize,
-) -> HashMap<(u32, u32), u32> {
-    let mut result: HashMap<(u32, u32), u32> = HashMap::new();
-
+) -> HashMap<i64, Vec<bool>> {
     let mut seat_to_occupied_bitmap: HashMap<i64, Vec<bool>> = HashMap::new();
 
-    let inner_map = occupied_seat_info_map
-        .get(&seat_type_id)
-        .expect("seat type id should in occupied_seat_info_map");
+    let Some(inner_map) = occupied_seat_info_map.get(&seat_type_id) else {
+        return seat_to_occupied_bitmap;
+    };
 
     for ((begin_station_id, end_station_id), seat_list) in inner_map {
         let begin_order = *station_id_to_order_map
@@ -73,62 +82,40 @@ fn calc_available_seat_count_map(
         }
     }
 
-    #[derive(Clone, Copy, PartialEq, Eq)]
-    enum Status {
-        Begin,
-        InOccupied,
-        InAvailable,
-    }
-
-    for (_, occupied_bitmap) in seat_to_occupied_bitmap {
-        let mut begin_order = 0;
-        let mut end_order;
-
-        let mut current_status = Status::Begin;
-
-        for (i, occupied) in occupied_bitmap.iter().enumerate() {
-            match current_status {
-                Status::Begin => {
-                    if *occupied {
-                        current_status = Status::InOccupied;
-                    } else {
-                        current_status = Status::InAvailable;
-                        begin_order = i;
-                    }
-                }
-                Status::InAvailable => {
-                    if *occupied {
-                        current_status = Status::InOccupied;
-                        end_order = i - 1;
-
-                        let entry = result
-                            .entry((begin_order as u32, end_order as u32))
-                            .or_default();
-
-                        *entry += 1;
-                    }
-                }
-                Status::InOccupied => {
-                    if !*occupied {
-                        current_status = Status::InAvailable;
-                        begin_order = i;
-                    }
-                }
-            }
-        }
-
-        if current_status == Status::InAvailable {
-            end_order = occupied_bitmap.len() - 1;
-
-            let entry = result
-                .entry((begin_order as u32, end_order as u32))
-                .or_default();
+    seat_to_occupied_bitmap
+}
 
-            *entry += 1;
-        }
+/// 判断座位在`[begin_order, end_order)`区间内是否全部空闲
+///
+/// `occupied_bitmap`为`None`表示该座位在全程均未被占用。
+pub(crate) fn is_seat_free_in_range(
+    occupied_bitmap: Option<&Vec<bool>>,
+    begin_order: u32,
+    end_order: u32,
+) -> bool {
+    match occupied_bitmap {
+        Some(bitmap) => !bitmap[begin_order as usize..end_order as usize]
+            .iter()
+            .any(|occupied| *occupied),
+        None => true,
     }
+}
 
-    result
+/// 计算座位在`[begin_order, end_order)`区间内的可用数量
+///
+/// 区间内任一段被占用的座位均不可用，其余座位（包括全程未被占用的座位）均可用。
+pub(crate) fn calc_available_seat_count(
+    capacity: u32,
+    seat_to_occupied_bitmap: &HashMap<i64, Vec<bool>>,
+    begin_order: u32,
+    end_order: u32,
+) -> u32 {
+    let occupied_count = seat_to_occupied_bitmap
+        .values()
+        .filter(|bitmap| !is_seat_free_in_range(Some(bitmap), begin_order, end_order))
+        .count() as u32;
+
+    capacity.saturating_sub(occupied_count)
 }
 
 impl<SAR, RR, TTCS, TSR> TrainSeatServiceImpl<SAR, RR, TTCS, TSR>
@@ -149,7 +136,199 @@ where
             route_repository,
             train_type_configuration_service,
             train_schedule_repository,
+            seat_availability_cache: SeatAvailabilityCache::new(),
+        }
+    }
+
+    /// 从数据库加载指定车次的座位占用情况，构建并写入余票缓存
+    ///
+    /// `schedules`为`(车次ID, 线路ID, 座位类型列表)`，返回值以`(车次ID, 座位类型ID)`为键，
+    /// 即使加载期间缓存发生变更而未写入，也可直接用于本次计算。
+    async fn load_seat_occupancy(
+        &self,
+        schedules: &[(TrainScheduleId, RouteId, Vec<SeatType>)],
+    ) -> Result<HashMap<(TrainScheduleId, SeatTypeId), SeatOccupancy>, TrainSeatServiceError> {
+        let mut result = HashMap::new();
+
+        if schedules.is_empty() {
+            return Ok(result);
+        }
+
+        let version = self.seat_availability_cache.version();
+
+        let train_schedule_ids = schedules
+            .iter()
+            .map(|(train_schedule_id, _, _)| *train_schedule_id)
+            .collect::<Vec<_>>();
+
+        let occupied_seat_info_by_schedule = self
+            .seat_availability_repository
+            .get_train_schedules_occupied_seat(&train_schedule_ids)
+            .await
+            .inspect_err(|e| error!("failed to get occupied seat info: {}", e))
+            .map_err(|e| {
+                TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?;
+
+        let route_id_set = schedules
+            .iter()
+            .map(|(_, route_id, _)| *route_id)
+            .collect::<HashSet<_>>();
+
+        let station_id_to_order_by_route = self
+            .route_repository
+            .load()
+            .await
+            .inspect_err(|e| error!("failed to load routes: {}", e))
+            .map_err(|e| {
+                TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?
+            .into_iter()
+            .filter_map(|route| {
+                let route_id = route.get_id().expect("route id should be present");
+                route_id_set.contains(&route_id).then(|| {
+                    (
+                        route_id,
+                        (
+                            Arc::new(calc_station_id_to_order_map(&route)),
+                            route.stops().len(),
+                        ),
+                    )
+                })
+            })
+            .collect::<HashMap<_, _>>();
+
+        let empty_occupied_seat_info_map = OccupiedSeatInfoMap::new();
+
+        for (train_schedule_id, route_id, seat_types) in schedules {
+            let (station_id_to_order_map, route_stops_count) = station_id_to_order_by_route
+                .get(route_id)
+                .ok_or(TrainSeatServiceError::InfrastructureError(
+                    ServiceError::RepositoryError(RepositoryError::InconsistentState(anyhow!(
+                        "no route for route id: {}",
+                        route_id
+                    ))),
+                ))?;
+
+            let occupied_seat_info_map = occupied_seat_info_by_schedule
+                .get(train_schedule_id)
+                .unwrap_or(&empty_occupied_seat_info_map);
+
+            for seat_type in seat_types {
+                let seat_type_id = seat_type.get_id().expect("seat type id should be present");
+
+                let occupancy = SeatOccupancy::new(
+                    seat_type.capacity(),
+                    Arc::clone(station_id_to_order_map),
+                    *route_stops_count,
+                    calc_seat_occupied_bitmap_map(
+                        seat_type_id.to_db_value(),
+                        occupied_seat_info_map,
+                        station_id_to_order_map,
+                        *route_stops_count,
+                    ),
+                );
+
+                self.seat_availability_cache.insert_if_unchanged(
+                    version,
+                    *train_schedule_id,
+                    seat_type,
+                    occupancy.clone(),
+                );
+
+                result.insert((*train_schedule_id, seat_type_id), occupancy);
+            }
         }
+
+        Ok(result)
+    }
+
+    /// 在提交占座前，基于区间占用位图检查座位在目标区间内是否仍然空闲
+    async fn check_seat_free_in_range(
+        &self,
+        train_schedule: &TrainSchedule,
+        seat_type: &SeatType,
+        seat: &Seat,
+        station_range: StationRange<Verified>,
+    ) -> Result<bool, TrainSeatServiceError> {
+        let train_schedule_id = train_schedule
+            .get_id()
+            .expect("train schedule id should be present");
+
+        let route = self
+            .route_repository
+            .find(train_schedule.route_id())
+            .await
+            .inspect_err(|e| {
+                error!(
+                    "failed to find route for id {}: {}",
+                    train_schedule.route_id(),
+                    e
+                )
+            })
+            .map_err(|e| {
+                TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?
+            .ok_or(TrainSeatServiceError::InfrastructureError(
+                ServiceError::RepositoryError(RepositoryError::InconsistentState(anyhow!(
+                    "no route for train schedule id: {}",
+                    train_schedule_id
+                ))),
+            ))?;
+
+        let station_id_to_order_map = calc_station_id_to_order_map(&route);
+
+        let occupied_seat_info_map = self
+            .seat_availability_repository
+            .get_train_schedule_occupied_seat(train_schedule_id)
+            .await
+            .inspect_err(|e| {
+                error!(
+                    "failed to get occupied seat info map for train schedule id {}: {}",
+                    train_schedule_id, e
+                )
+            })
+            .map_err(|e| {
+                TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+            })?;
+
+        let seat_to_occupied_bitmap = calc_seat_occupied_bitmap_map(
+            seat_type
+                .get_id()
+                .expect("seat type id should be present")
+                .to_db_value(),
+            &occupied_seat_info_map,
+            &station_id_to_order_map,
+            route.stops().len(),
+        );
+
+        let (Some(begin_order), Some(end_order)) = (
+            station_id_to_order_map
+                .get(&station_range.get_from_station_id().to_db_value())
+                .copied(),
+            station_id_to_order_map
+                .get(&station_range.get_to_station_id().to_db_value())
+                .copied(),
+        ) else {
+            return Err(TrainSeatServiceError::InfrastructureError(
+                ServiceError::RepositoryError(RepositoryError::InconsistentState(anyhow!(
+                    "station range {:?} not in route of train schedule id: {}",
+                    station_range,
+                    train_schedule_id
+                ))),
+            ));
+        };
+
+        Ok(is_seat_free_in_range(
+            seat_to_occupied_bitmap.get(
+                &seat
+                    .get_id()
+                    .expect("seat id should be present")
+                    .to_db_value(),
+            ),
+            begin_order,
+            end_order,
+        ))
     }
 }
 
@@ -180,75 +359,137 @@ where
                 seat_availability_id,
             ))?;
 
-        let route = self
-            .route_repository
-            .get_by_train_schedule(seat_availability.train_schedule_id())
+        let train_schedule_id = seat_availability.train_schedule_id();
+        let seat_type = seat_availability.seat_type();
+        let station_range = seat_availability.station_range();
+
+        if let Some(count) = self.seat_availability_cache.available_count(
+            train_schedule_id,
+            seat_type,
+            station_range,
+        ) {
+            return Ok(count);
+        }
+
+        let train_schedule = self
+            .train_schedule_repository
+            .find(train_schedule_id)
             .await
             .context(format!(
-                "failed to get route for train schedule id: {}",
-                seat_availability.train_schedule_id()
+                "failed to find train schedule for id: {}",
+                train_schedule_id
             ))
             .map_err(|e| {
                 TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e.into()))
             })?
             .ok_or(TrainSeatServiceError::InfrastructureError(
                 ServiceError::RepositoryError(RepositoryError::InconsistentState(anyhow!(
-                    "no route for train schedule id: {}",
-                    seat_availability.train_schedule_id()
+                    "no train schedule find for train schedule id: {}",
+                    train_schedule_id
                 ))),
             ))?;
 
-        let station_id_to_order_map = calc_station_id_to_order_map(&route);
-
-        let occupied_seat_info_map = self
-            .seat_availability_repository
-            .get_train_schedule_occupied_seat(seat_availability.train_schedule_id())
-            .await
-            .context(format!(
-                "failed to get occupied seat info map for train schedule id: {}",
-                seat_availability.train_schedule_id()
+        let occupancy_map = self
+            .load_seat_occupancy(&[(
+                train_schedule_id,
+                train_schedule.route_id(),
+                vec![seat_type.clone()],
+            )])
+            .await?;
+
+        occupancy_map
+            .get(&(
+                train_schedule_id,
+                seat_type.get_id().expect("seat type id should be present"),
             ))
-            .map_err(|e| {
-                TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e.into()))
-            })?;
+            .and_then(|occupancy| occupancy.available_count(station_range))
+            .ok_or(TrainSeatServiceError::InfrastructureError(
+                ServiceError::RepositoryError(RepositoryError::InconsistentState(anyhow!(
+                    "station range {:?} not in route of train schedule id: {}",
+                    station_range,
+                    train_schedule_id
+                ))),
+            ))
+    }
 
-        let seat_type_id = seat_availability
-            .seat_type()
-            .get_id()
-            .expect("seat type id should be present")
-            .to_db_value();
+    #[instrument(skip(self, queries))]
+    async fn available_seats_count_batch(
+        &self,
+        queries: &[SeatCountQuery],
+    ) -> Result<Vec<u32>, TrainSeatServiceError> {
+        if queries.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        let route_stops_count = route.stops().len();
+        // 收集未命中缓存的车次及其座位类型，一次性从数据库加载
+        let mut missing: HashMap<TrainScheduleId, (RouteId, Vec<SeatType>)> = HashMap::new();
 
-        let available_seat_count_map = calc_available_seat_count_map(
-            seat_type_id,
-            &occupied_seat_info_map,
-            &station_id_to_order_map,
-            route_stops_count,
+        let mut result = queries
+            .iter()
+            .map(|query| {
+                let count = self.seat_availability_cache.available_count(
+                    query.train_schedule_id,
+                    &query.seat_type,
+                    query.station_range,
+                );
+
+                if count.is_none() {
+                    let (_, seat_types) = missing
+                        .entry(query.train_schedule_id)
+                        .or_insert_with(|| (query.route_id, Vec::new()));
+
+                    if !seat_types.contains(&query.seat_type) {
+                        seat_types.push(query.seat_type.clone());
+                    }
+                }
+
+                count
+            })
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(result.into_iter().flatten().collect());
+        }
+
+        info!(
+            "seat availability cache missed for {} train schedules",
+            missing.len()
         );
 
-        let begin_order = *station_id_to_order_map
-            .get(
-                &seat_availability
-                    .station_range()
-                    .get_from_station_id()
-                    .to_db_value(),
-            )
-            .expect("begin station should present in station_id_to_order_map");
+        let missing = missing
+            .into_iter()
+            .map(|(train_schedule_id, (route_id, seat_types))| {
+                (train_schedule_id, route_id, seat_types)
+            })
+            .collect::<Vec<_>>();
 
-        let end_order = *station_id_to_order_map
-            .get(
-                &seat_availability
-                    .station_range()
-                    .get_to_station_id()
-                    .to_db_value(),
-            )
-            .expect("end station should present in station_id_to_order_map");
+        let occupancy_map = self.load_seat_occupancy(&missing).await?;
 
-        Ok(available_seat_count_map
-            .get(&(begin_order, end_order))
-            .copied()
-            .unwrap_or_default())
+        for (count, query) in result.iter_mut().zip(queries) {
+            if count.is_some() {
+                continue;
+            }
+
+            let seat_type_id = query
+                .seat_type
+                .get_id()
+                .expect("seat type id should be present");
+
+            *count = Some(
+                occupancy_map
+                    .get(&(query.train_schedule_id, seat_type_id))
+                    .and_then(|occupancy| occupancy.available_count(query.station_range))
+                    .ok_or(TrainSeatServiceError::InfrastructureError(
+                        ServiceError::RepositoryError(RepositoryError::InconsistentState(anyhow!(
+                            "station range {:?} not in route of train schedule id: {}",
+                            query.station_range,
+                            query.train_schedule_id
+                        ))),
+                    ))?,
+            );
+        }
+
+        Ok(result.into_iter().flatten().collect())
     }
 
     #[instrument(skip(self))]
@@ -384,6 +625,24 @@ where
         }
 
         if let Some(allocated_seat) = allocated_seat {
+            // 占座提交前再次检查区间是否重叠，防止与其他区间的已占座位冲突
+            if !self
+                .check_seat_free_in_range(
+                    &train_schedule,
+                    seat_availability.seat_type(),
+                    &allocated_seat,
+                    station_range,
+                )
+                .await?
+            {
+                info!(
+                    "seat {:?} is already occupied in overlapping station range",
+                    allocated_seat
+                );
+
+                return Err(TrainSeatServiceError::NoAvailableSeat);
+            }
+
             info!(
                 "allocated seat: {:?} for seat availability id: {}",
                 allocated_seat,
@@ -402,6 +661,16 @@ where
                     TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
                 })?;
 
+            self.seat_availability_cache.occupy(
+                seat_availability.train_schedule_id(),
+                seat_availability.seat_type(),
+                allocated_seat
+                    .get_id()
+                    .expect("seat id should be present")
+                    .to_db_value(),
+                station_range,
+            );
+
             Ok(allocated_seat)
         } else {
             Err(TrainSeatServiceError::NoAvailableSeat)
@@ -437,6 +706,11 @@ where
                 seat_availability_id,
             ))?;
 
+        let seat_id = seat
+            .get_id()
+            .expect("seat id should be present")
+            .to_db_value();
+
         seat_availability.remove_occupied_seat(seat);
 
         self.seat_availability_repository
@@ -452,6 +726,128 @@ where
                 TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
             })?;
 
+        self.seat_availability_cache.release(
+            seat_availability.train_schedule_id(),
+            seat_availability.seat_type(),
+            seat_id,
+            seat_availability.station_range(),
+        );
+
         Ok(())
     }
+
+    #[instrument(skip(self))]
+    async fn rebuild_seat_availability_cache(
+        &self,
+        begin_date: NaiveDate,
+        days: i32,
+    ) -> Result<(), TrainSeatServiceError> {
+        self.seat_availability_cache.clear();
+
+        let seat_types_by_train = self
+            .train_type_configuration_service
+            .get_trains()
+            .await
+            .inspect_err(|e| error!("failed to load trains: {}", e))
+            .context("failed to load trains")
+            .map_err(|e| {
+                TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e.into()))
+            })?
+            .into_iter()
+            .map(|train| {
+                (
+                    train.get_id().expect("train id should be present"),
+                    train.seats().values().cloned().collect::<Vec<_>>(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        for day in 0..days {
+            let date = begin_date + chrono::Duration::days(day as i64);
+
+            let schedules = self
+                .train_schedule_repository
+                .find_by_date(date)
+                .await
+                .inspect_err(|e| error!("failed to load train schedules for {}: {}", date, e))
+                .map_err(|e| {
+                    TrainSeatServiceError::InfrastructureError(ServiceError::RepositoryError(e))
+                })?
+                .into_iter()
+                .filter_map(|schedule| {
+                    let seat_types = seat_types_by_train.get(&schedule.train_id())?.clone();
+                    Some((
+                        schedule
+                            .get_id()
+                            .expect("train schedule id should be present"),
+                        schedule.route_id(),
+                        seat_types,
+                    ))
+                })
+                .collect::<Vec<_>>();
+
+            self.load_seat_occupancy(&schedules).await?;
+        }
+
+        info!(
+            "seat availability cache rebuilt, entries: {}",
+            self.seat_availability_cache.len()
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlapping_range_is_occupied() {
+        // 站点 101..=105 顺序为 0..=4，座位 1 占用 101->103，座位 2 占用 104->105
+        let station_id_to_order_map: HashMap<i32, u32> =
+            (0..5).map(|order| (101 + order as i32, order)).collect();
+
+        let mut occupied_seat_info_map: OccupiedSeatInfoMap = HashMap::new();
+        let inner_map = occupied_seat_info_map.entry(1).or_default();
+        inner_map.insert((101, 103), vec![1]);
+        inner_map.insert((104, 105), vec![2]);
+
+        let bitmap_map =
+            calc_seat_occupied_bitmap_map(1, &occupied_seat_info_map, &station_id_to_order_map, 5);
+
+        // 102->104 与座位 1 的 101->103 重叠
+        assert!(!is_seat_free_in_range(bitmap_map.get(&1), 1, 3));
+        // 103->104 与座位 1、座位 2 均不重叠
+        assert!(is_seat_free_in_range(bitmap_map.get(&1), 2, 3));
+        assert!(is_seat_free_in_range(bitmap_map.get(&2), 2, 3));
+        // 座位 3 未被占用
+        assert!(is_seat_free_in_range(bitmap_map.get(&3), 0, 4));
+        // 不存在的座位类型视为全程空闲
+        assert!(
+            calc_seat_occupied_bitmap_map(2, &occupied_seat_info_map, &station_id_to_order_map, 5)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_available_seat_count_in_range() {
+        // 站点 101..=105 顺序为 0..=4，座位 1 占用 101->103，座位 2 占用 102->105
+        let station_id_to_order_map: HashMap<i32, u32> =
+            (0..5).map(|order| (101 + order as i32, order)).collect();
+
+        let mut occupied_seat_info_map: OccupiedSeatInfoMap = HashMap::new();
+        let inner_map = occupied_seat_info_map.entry(1).or_default();
+        inner_map.insert((101, 103), vec![1]);
+        inner_map.insert((102, 105), vec![2]);
+
+        let bitmap_map =
+            calc_seat_occupied_bitmap_map(1, &occupied_seat_info_map, &station_id_to_order_map, 5);
+
+        // 全程未被占用的座位也计入可用数量
+        assert_eq!(calc_available_seat_count(10, &bitmap_map, 0, 1), 9);
+        assert_eq!(calc_available_seat_count(10, &bitmap_map, 1, 2), 8);
+        assert_eq!(calc_available_seat_count(10, &bitmap_map, 3, 4), 9);
+        assert_eq!(calc_available_seat_count(1, &bitmap_map, 0, 4), 0);
+    }
 }
@@ -1,69 +1,270 @@
-use crate::domain::model::order::{Order, OrderStatus};
-use crate::domain::model::transaction::{Transaction, TransactionAmountAbs, TransactionError};
+use crate::domain::model::coupon::{CouponError, CouponRedemptionStatus};
+use crate::domain::model::hotel::OccupiedRoom;
+use crate::domain::model::order::{HotelOrder, Order, OrderStatus};
+use crate::domain::model::order_state_machine::{OrderStateError, OrderStatusActor};
+use crate::domain::model::refund_policy::RefundPolicySet;
+use crate::domain::model::transaction::{
+    ExternalPayment, PartialRefundRequest, RefundPortion, Transaction, TransactionAmountAbs,
+    TransactionError, TransactionStatus,
+};
 use crate::domain::model::user::UserId;
-use crate::domain::repository::transaction::TransactionRepository;
+use crate::domain::repository::coupon::CouponRepository;
+use crate::domain::repository::occupied_room::OccupiedRoomRepository;
+use crate::domain::repository::transaction::{RebookSettlement, TransactionRepository};
 use crate::domain::repository::user::UserRepository;
 use crate::domain::service::order::OrderService;
 use crate::domain::service::order::order_dto::TransactionDataDto;
 use crate::domain::service::order_status::OrderStatusManagerService;
+use crate::domain::service::payment_gateway::{
+    PaymentGateway, PaymentGatewayError, PaymentIntent, PaymentIntentStatus, RechargeChannel,
+};
 use crate::domain::service::transaction::{TransactionService, TransactionServiceError};
+use crate::domain::{Identifiable, RepositoryError};
+use anyhow::anyhow;
 use async_trait::async_trait;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::{ToPrimitive, Zero};
+use std::any::Any;
 use std::collections::HashSet;
 use std::sync::Arc;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 use uuid::Uuid;
 
-pub struct TransactionServiceImpl<U, R, O, OS>
+pub struct TransactionServiceImpl<U, R, O, OS, C, G, ORR>
 where
     U: UserRepository,
     R: TransactionRepository,
     O: OrderService,
     OS: OrderStatusManagerService,
+    C: CouponRepository,
+    G: PaymentGateway,
+    ORR: OccupiedRoomRepository,
 {
     user_repository: Arc<U>,
     transaction_repository: Arc<R>,
     order_service: Arc<O>,
     order_status_manager_service: Arc<OS>,
+    coupon_repository: Arc<C>,
+    recharge_channel: RechargeChannel<G>,
+    occupied_room_repository: Arc<ORR>,
+    refund_policy: RefundPolicySet,
 }
 
-impl<U, R, O, OS> TransactionServiceImpl<U, R, O, OS>
+impl<U, R, O, OS, C, G, ORR> TransactionServiceImpl<U, R, O, OS, C, G, ORR>
 where
     U: UserRepository,
     R: TransactionRepository,
     O: OrderService,
     OS: OrderStatusManagerService,
+    C: CouponRepository,
+    G: PaymentGateway,
+    ORR: OccupiedRoomRepository,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_repository: Arc<U>,
         transaction_repository: Arc<R>,
         order_service: Arc<O>,
         order_status_manager_service: Arc<OS>,
+        coupon_repository: Arc<C>,
+        recharge_channel: RechargeChannel<G>,
+        occupied_room_repository: Arc<ORR>,
+        refund_policy: RefundPolicySet,
     ) -> Self {
         Self {
             user_repository,
             transaction_repository,
             order_service,
             order_status_manager_service,
+            coupon_repository,
+            recharge_channel,
+            occupied_room_repository,
+            refund_policy,
         }
     }
+
+    fn payment_gateway(&self) -> Result<&G, TransactionServiceError> {
+        match &self.recharge_channel {
+            RechargeChannel::Gateway(payment_gateway) => Ok(payment_gateway),
+            RechargeChannel::Direct | RechargeChannel::Disabled => {
+                Err(TransactionServiceError::PaymentGatewayNotConfigured)
+            }
+        }
+    }
+
+    /// 部分退款交易中的一个订单，按数量退款时在同一数据库事务中释放退还的房间
+    async fn refund_order_portion(
+        &self,
+        transaction_id: Uuid,
+        order_uuid: Uuid,
+        portion: RefundPortion,
+    ) -> Result<(Transaction, Transaction), TransactionServiceError> {
+        let mut tx = self
+            .transaction_repository
+            .find_by_uuid(transaction_id)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to find transaction: {:?}", e);
+            })?
+            .ok_or(TransactionServiceError::InvalidTransactionId(
+                transaction_id,
+            ))?;
+
+        if !tx.orders().iter().any(|o| o.uuid() == order_uuid) {
+            return Err(TransactionServiceError::InvalidOrder {
+                order_id: order_uuid,
+                transaction_id,
+            });
+        }
+
+        let mut refund_tx = tx.refund_transaction_lines_with_fee(
+            &[PartialRefundRequest {
+                order_uuid,
+                portion,
+            }],
+            &self.refund_policy,
+            Transaction::now(),
+        )?;
+
+        let released_rooms = match portion {
+            RefundPortion::Quantity(quantity) => {
+                self.rooms_to_release(&tx, order_uuid, quantity).await?
+            }
+            RefundPortion::Amount(_) => Vec::new(),
+        };
+
+        self.transaction_repository
+            .save_partial_refund(&mut tx, &mut refund_tx, &released_rooms)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to save partial refund: {:?}", e);
+            })?;
+
+        Ok((tx, refund_tx))
+    }
+
+    /// 查找按数量部分退款时需释放的房间，非酒店订单没有需释放的房间
+    async fn rooms_to_release(
+        &self,
+        tx: &Transaction,
+        order_uuid: Uuid,
+        quantity: Decimal,
+    ) -> Result<Vec<OccupiedRoom>, TransactionServiceError> {
+        let Some(order) = tx
+            .orders()
+            .iter()
+            .find(|o| o.uuid() == order_uuid)
+            .and_then(|o| (o.as_ref() as &dyn Any).downcast_ref::<HotelOrder>())
+        else {
+            return Ok(Vec::new());
+        };
+
+        let occupied_rooms = self
+            .occupied_room_repository
+            .find_by_order_uuid(order_uuid)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to find occupied rooms: {:?}", e);
+            })?;
+
+        let quantity = quantity.to_usize().unwrap_or_default();
+
+        order
+            .rooms_to_release(occupied_rooms, quantity)
+            .ok_or_else(|| {
+                RepositoryError::InconsistentState(anyhow!(
+                    "hotel order {} has less than {} occupied rooms",
+                    order_uuid,
+                    quantity
+                ))
+                .into()
+            })
+    }
+
+    /// 退款并通知订单取消，按`refund_policy`扣除手续费
+    async fn refund_orders(
+        &self,
+        transaction_id: Uuid,
+        to_refund_orders: &[Box<dyn Order>],
+        refund_policy: &RefundPolicySet,
+    ) -> Result<Uuid, TransactionServiceError> {
+        let mut tx = self
+            .transaction_repository
+            .find_by_uuid(transaction_id)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to find transaction: {:?}", e);
+            })?
+            .ok_or(TransactionServiceError::InvalidTransactionId(
+                transaction_id,
+            ))?;
+
+        let to_refund_order_uuid_set = to_refund_orders
+            .iter()
+            .map(|o| o.uuid())
+            .collect::<HashSet<_>>();
+
+        let mut refund_tx = tx.refund_transaction_partial_with_fee(
+            to_refund_orders,
+            refund_policy,
+            Transaction::now(),
+        )?;
+
+        let refund_tx_id = self
+            .transaction_repository
+            .save(&mut refund_tx)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to save transaction: {:?}", e);
+            })?;
+
+        for order in tx.orders_mut() {
+            if to_refund_order_uuid_set.contains(&order.uuid()) {
+                order
+                    .payment_info_mut()
+                    .set_refund_transaction_id(refund_tx_id);
+            }
+        }
+
+        self.transaction_repository
+            .save(&mut tx)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to save transaction: {:?}", e);
+            })?;
+
+        let orders = tx
+            .orders()
+            .iter()
+            .filter(|order| to_refund_order_uuid_set.contains(&order.uuid()))
+            .map(|order| order.as_ref())
+            .collect::<Vec<_>>();
+
+        self.order_status_manager_service
+            .notify_status_change(transaction_id, tx.atomic(), &orders, OrderStatus::Cancelled)
+            .await;
+
+        Ok(refund_tx.uuid())
+    }
 }
 
 #[async_trait]
-impl<U, R, O, OS> TransactionService for TransactionServiceImpl<U, R, O, OS>
+impl<U, R, O, OS, C, G, ORR> TransactionService for TransactionServiceImpl<U, R, O, OS, C, G, ORR>
 where
     U: UserRepository,
     R: TransactionRepository,
     O: OrderService,
     OS: OrderStatusManagerService,
+    C: CouponRepository,
+    G: PaymentGateway,
+    ORR: OccupiedRoomRepository,
 {
     #[instrument(skip(self))]
     async fn recharge(
         &self,
         user_id: UserId,
         amount: TransactionAmountAbs,
-    ) -> Result<Uuid, TransactionServiceError> {
+    ) -> Result<(Transaction, Option<PaymentIntent>), TransactionServiceError> {
         if self
             .user_repository
             .find(user_id)
@@ -76,14 +277,122 @@ where
             return Err(TransactionServiceError::InvalidUser(user_id));
         }
 
-        let mut tx = Transaction::new_recharge(user_id, amount);
+        let payment_gateway = match &self.recharge_channel {
+            RechargeChannel::Gateway(payment_gateway) => payment_gateway,
+            RechargeChannel::Direct => {
+                let mut tx = Transaction::new_recharge(user_id, amount);
+
+                self.transaction_repository
+                    .save(&mut tx)
+                    .await
+                    .inspect_err(|e| error!("failed to save transaction: {}", e))?;
+
+                return Ok((tx, None));
+            }
+            RechargeChannel::Disabled => return Err(TransactionServiceError::RechargeDisabled),
+        };
+
+        let intent = payment_gateway
+            .create_intent(amount, "SwiftJourney 余额充值")
+            .await
+            .inspect_err(|e| error!("Failed to create payment intent: {}", e))?;
+
+        let mut tx = Transaction::new_external_recharge(
+            user_id,
+            amount,
+            ExternalPayment::new(
+                payment_gateway.provider().to_string(),
+                intent.external_payment_id,
+            ),
+        );
 
         self.transaction_repository
             .save(&mut tx)
             .await
             .inspect_err(|e| error!("failed to save transaction: {}", e))?;
 
-        Ok(tx.uuid())
+        Ok((tx, Some(intent)))
+    }
+
+    #[instrument(skip(self, payload))]
+    async fn handle_payment_callback(
+        &self,
+        provider: &str,
+        payload: &[u8],
+        signature: &str,
+    ) -> Result<(), TransactionServiceError> {
+        let payment_gateway = self.payment_gateway()?;
+
+        if provider != payment_gateway.provider() {
+            return Err(TransactionServiceError::UnknownPaymentProvider(
+                provider.to_string(),
+            ));
+        }
+
+        let callback = payment_gateway.verify_webhook(payload, signature)?;
+
+        let mut tx = self
+            .transaction_repository
+            .find_by_external_payment_id(provider, callback.external_payment_id)
+            .await
+            .inspect_err(|e| error!("Failed to find transaction: {:?}", e))?
+            .ok_or(PaymentGatewayError::PaymentNotFound(
+                callback.external_payment_id,
+            ))?;
+
+        let result = match callback.status {
+            PaymentIntentStatus::Succeeded => {
+                // 支付网关可能重复发送回调，已入账的交易直接确认
+                if tx.status() == TransactionStatus::Paid {
+                    info!("Transaction {} already settled", tx.uuid());
+                    return Ok(());
+                }
+
+                let expected = tx.raw_amount().abs();
+                if callback.amount != expected {
+                    return Err(TransactionServiceError::PaymentAmountMismatch {
+                        transaction_id: tx.uuid(),
+                        expected,
+                        actual: callback.amount,
+                    });
+                }
+
+                tx.complete_external_payment()
+            }
+            PaymentIntentStatus::Failed => {
+                if tx.status() == TransactionStatus::Failed {
+                    return Ok(());
+                }
+
+                tx.fail_external_payment()
+            }
+            status => {
+                warn!(
+                    "Ignoring payment callback with status {} for transaction {}",
+                    status,
+                    tx.uuid()
+                );
+                return Ok(());
+            }
+        };
+
+        result.map_err(|e| match e {
+            TransactionError::AlreadyPaid(_) | TransactionError::PaymentExpired(_) => {
+                TransactionServiceError::InvalidTransactionStatus {
+                    op: "settle",
+                    status: tx.status(),
+                    transaction_id: tx.uuid(),
+                }
+            }
+            _ => panic!("Unexpected error: {:?}", e),
+        })?;
+
+        self.transaction_repository
+            .save(&mut tx)
+            .await
+            .inspect_err(|e| error!("Failed to save transaction: {:?}", e))?;
+
+        Ok(())
     }
 
     #[instrument(skip(self))]
@@ -104,7 +413,8 @@ where
         user_id: UserId,
         orders: Vec<Box<dyn Order>>,
         atomic: bool,
-    ) -> Result<Uuid, TransactionServiceError> {
+        coupon_code: Option<String>,
+    ) -> Result<Transaction, TransactionServiceError> {
         for order in &*orders {
             if order.order_status() != OrderStatus::Unpaid {
                 return Err(TransactionServiceError::InvalidOrderStatus {
@@ -128,6 +438,27 @@ where
 
         let mut tx = Transaction::new(user_id, orders.clone(), atomic);
 
+        if let Some(coupon_code) = coupon_code {
+            let coupon = self
+                .coupon_repository
+                .find_by_code(&coupon_code)
+                .await
+                .inspect_err(|e| error!("Failed to find coupon: {:?}", e))?
+                .ok_or(CouponError::NotFound(coupon_code))?;
+
+            let user_redeemed_count = self
+                .coupon_repository
+                .count_user_redemptions(coupon.get_id().unwrap(), user_id)
+                .await
+                .inspect_err(|e| error!("Failed to count coupon redemptions: {:?}", e))?;
+
+            tx.apply_coupon(&coupon, user_redeemed_count)
+                .map_err(|e| match e {
+                    TransactionError::CouponError(e) => TransactionServiceError::CouponError(e),
+                    _ => panic!("Unexpected error: {:?}", e),
+                })?;
+        }
+
         self.transaction_repository
             .save(&mut tx)
             .await
@@ -135,7 +466,7 @@ where
                 error!("Failed to save transaction: {:?}", e);
             })?;
 
-        Ok(tx.uuid())
+        Ok(tx)
     }
 
     #[instrument(skip(self))]
@@ -152,6 +483,10 @@ where
                 transaction_id,
             ))?;
 
+        if tx.is_payment_expired(Transaction::now()) {
+            return Err(TransactionServiceError::PaymentExpired(tx.uuid()));
+        }
+
         let available_balance = self.get_balance(tx.user_id()).await.inspect_err(|e| {
             error!("Failed to get user balance: {:?}", e);
         })?;
@@ -165,19 +500,61 @@ where
             });
         }
 
+        // 提前检查优惠券是否仍可核销以返回明确的错误，最终检查在保存交易的数据库事务中进行
+        if let Some(redemption) = tx.coupon_redemption()
+            && redemption.status() == CouponRedemptionStatus::Pending
+        {
+            let coupon = self
+                .coupon_repository
+                .find(redemption.coupon_id())
+                .await
+                .inspect_err(|e| error!("Failed to find coupon: {:?}", e))?
+                .ok_or(CouponError::NotFound(redemption.coupon_id().to_string()))?;
+
+            let user_redeemed_count = self
+                .coupon_repository
+                .count_user_redemptions(redemption.coupon_id(), tx.user_id())
+                .await
+                .inspect_err(|e| error!("Failed to count coupon redemptions: {:?}", e))?;
+
+            coupon.check_valid_time(Transaction::now())?;
+            coupon.check_redemption_limit(user_redeemed_count)?;
+        }
+
         tx.pay().map_err(|e| match e {
             TransactionError::AlreadyPaid(_) => TransactionServiceError::InvalidTransactionStatus {
                 op: "pay",
                 status: tx.status(),
                 transaction_id: tx.uuid(),
             },
+            TransactionError::PaymentExpired(x) => TransactionServiceError::PaymentExpired(x),
+            TransactionError::ExternalPaymentRequired(_) => {
+                TransactionServiceError::InvalidTransactionStatus {
+                    op: "pay",
+                    status: tx.status(),
+                    transaction_id: tx.uuid(),
+                }
+            }
             _ => panic!("Unexpected error: {:?}", e),
         })?;
 
         debug!("saving paid transaction: {:?}", tx);
 
+        let actor = OrderStatusActor::User(tx.user_id());
+
         for order in tx.orders_mut() {
-            order.set_status(OrderStatus::Paid);
+            order
+                .transition_status(OrderStatus::Paid, actor, "订单支付成功")
+                .map_err(|e| match e {
+                    OrderStateError::IllegalTransition {
+                        order_uuid, from, ..
+                    } => TransactionServiceError::InvalidOrderStatus {
+                        op: "pay",
+                        status: from,
+                        order_id: order_uuid,
+                        transaction_id: Some(transaction_id),
+                    },
+                })?;
         }
 
         self.transaction_repository
@@ -206,6 +583,121 @@ where
         transaction_id: Uuid,
         to_refund_orders: &[Box<dyn Order>],
     ) -> Result<Uuid, TransactionServiceError> {
+        self.refund_orders(transaction_id, to_refund_orders, &RefundPolicySet::free())
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn refund_transaction_with_fee(
+        &self,
+        transaction_id: Uuid,
+        to_refund_orders: &[Box<dyn Order>],
+    ) -> Result<Uuid, TransactionServiceError> {
+        self.refund_orders(transaction_id, to_refund_orders, &self.refund_policy)
+            .await
+    }
+
+    #[instrument(skip(self))]
+    async fn partial_refund(
+        &self,
+        transaction_id: Uuid,
+        order_uuid: Uuid,
+        quantity: Decimal,
+    ) -> Result<Uuid, TransactionServiceError> {
+        let (tx, refund_tx) = self
+            .refund_order_portion(
+                transaction_id,
+                order_uuid,
+                RefundPortion::Quantity(quantity),
+            )
+            .await?;
+
+        if let Some(order) = tx.orders().iter().find(|o| o.uuid() == order_uuid) {
+            self.order_status_manager_service
+                .notify_partial_refund(tx.user_id(), order.as_ref())
+                .await;
+        }
+
+        Ok(refund_tx.uuid())
+    }
+
+    #[instrument(skip(self))]
+    async fn adjust_order_refund(
+        &self,
+        transaction_id: Uuid,
+        order_uuid: Uuid,
+        amount: Decimal,
+    ) -> Result<Uuid, TransactionServiceError> {
+        let (_, refund_tx) = self
+            .refund_order_portion(transaction_id, order_uuid, RefundPortion::Amount(amount))
+            .await?;
+
+        Ok(refund_tx.uuid())
+    }
+
+    #[instrument(skip(self, replaced_order, new_order))]
+    async fn new_rebook_transaction(
+        &self,
+        user_id: UserId,
+        replaced_order: &dyn Order,
+        new_order: Box<dyn Order>,
+    ) -> Result<Transaction, TransactionServiceError> {
+        if new_order.order_status() != OrderStatus::Unpaid {
+            return Err(TransactionServiceError::InvalidOrderStatus {
+                op: "rebook",
+                status: new_order.order_status(),
+                order_id: new_order.uuid(),
+                transaction_id: None,
+            });
+        }
+
+        if replaced_order.order_status() != OrderStatus::Ongoing || replaced_order.already_refund()
+        {
+            return Err(TransactionServiceError::InvalidOrderStatus {
+                op: "rebook",
+                status: replaced_order.order_status(),
+                order_id: replaced_order.uuid(),
+                transaction_id: None,
+            });
+        }
+
+        // 新订单以`Unpaid`状态进入调整交易，消费者为其暂留座位后结算差价，结算成功才进入`Paid`状态
+        let mut tx = Transaction::new_adjustment(user_id, replaced_order, new_order);
+
+        let available_balance = self.get_balance(user_id).await.inspect_err(|e| {
+            error!("Failed to get user balance: {:?}", e);
+        })?;
+
+        if available_balance < tx.raw_amount() {
+            return Err(TransactionServiceError::InsufficientFunds {
+                transaction_id: tx.uuid(),
+                balance: available_balance,
+                amount: TransactionAmountAbs::from(tx.raw_amount()),
+            });
+        }
+
+        self.transaction_repository
+            .save(&mut tx)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to save transaction: {:?}", e);
+            })?;
+
+        self.order_status_manager_service
+            .notify_rebook(tx.uuid(), replaced_order, tx.orders()[0].as_ref())
+            .await;
+
+        Ok(tx)
+    }
+
+    #[instrument(skip(self))]
+    async fn settle_rebook_transaction(
+        &self,
+        transaction_id: Uuid,
+        replaced_order_id: Uuid,
+    ) -> Result<(), TransactionServiceError> {
+        info!("Settling rebook transaction: {}", transaction_id);
+
         let mut tx = self
             .transaction_repository
             .find_by_uuid(transaction_id)
@@ -217,48 +709,132 @@ where
                 transaction_id,
             ))?;
 
-        let to_refund_order_uuid_set = to_refund_orders
-            .iter()
-            .map(|o| o.uuid())
-            .collect::<HashSet<_>>();
+        // 余额在保存结算的数据库事务中加锁后重新检查
+        tx.pay().map_err(|e| match e {
+            TransactionError::AlreadyPaid(_) => TransactionServiceError::InvalidTransactionStatus {
+                op: "settle rebook",
+                status: tx.status(),
+                transaction_id: tx.uuid(),
+            },
+            TransactionError::PaymentExpired(x) => TransactionServiceError::PaymentExpired(x),
+            _ => panic!("Unexpected error: {:?}", e),
+        })?;
 
-        let mut refund_tx = tx.refund_transaction_partial(to_refund_orders)?;
+        let actor = OrderStatusActor::User(tx.user_id());
 
-        let refund_tx_id = self
+        for order in tx.orders_mut() {
+            order
+                .transition_status(OrderStatus::Paid, actor, "改签订单支付成功")
+                .map_err(|e| match e {
+                    OrderStateError::IllegalTransition {
+                        order_uuid, from, ..
+                    } => TransactionServiceError::InvalidOrderStatus {
+                        op: "settle rebook",
+                        status: from,
+                        order_id: order_uuid,
+                        transaction_id: Some(transaction_id),
+                    },
+                })?;
+        }
+
+        let adjustment_tx_id = tx
+            .get_id()
+            .ok_or(TransactionServiceError::InvalidTransactionId(
+                transaction_id,
+            ))?;
+
+        let mut replaced_tx = self
             .transaction_repository
-            .save(&mut refund_tx)
+            .find_by_order_uuid(replaced_order_id)
             .await
             .inspect_err(|e| {
-                error!("Failed to save transaction: {:?}", e);
+                error!("Failed to find transaction: {:?}", e);
+            })?
+            .filter(|t| t.uuid() != transaction_id && t.user_id() == tx.user_id())
+            .ok_or(TransactionServiceError::InvalidOrder {
+                order_id: replaced_order_id,
+                transaction_id,
             })?;
 
-        for order in tx.orders_mut() {
-            if to_refund_order_uuid_set.contains(&order.uuid()) {
+        // 原订单的票款已计入调整交易，此后取消新订单时按新订单票价全额退款
+        for order in replaced_tx.orders_mut() {
+            if order.uuid() == replaced_order_id {
                 order
                     .payment_info_mut()
-                    .set_refund_transaction_id(refund_tx_id);
+                    .set_refund_transaction_id(adjustment_tx_id);
             }
         }
 
+        // 调整交易的支付、新订单的状态与原订单的退款标记在同一数据库事务中保存，结算失败时均保持不变
+        match self
+            .transaction_repository
+            .save_rebook_settlement(&mut tx, &mut replaced_tx, replaced_order_id)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to save rebook settlement: {:?}", e);
+            })? {
+            RebookSettlement::Settled => Ok(()),
+            RebookSettlement::InsufficientBalance { balance } => {
+                Err(TransactionServiceError::InsufficientFunds {
+                    transaction_id,
+                    balance,
+                    amount: TransactionAmountAbs::from(tx.raw_amount()),
+                })
+            }
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn discard_transaction(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<(), TransactionServiceError> {
+        info!("Discarding transaction: {}", transaction_id);
+
+        let tx = self
+            .transaction_repository
+            .find_by_uuid(transaction_id)
+            .await
+            .inspect_err(|e| {
+                error!("Failed to find transaction: {:?}", e);
+            })?
+            .ok_or(TransactionServiceError::InvalidTransactionId(
+                transaction_id,
+            ))?;
+
+        if tx.status() != TransactionStatus::Unpaid {
+            return Err(TransactionServiceError::InvalidTransactionStatus {
+                op: "discard",
+                status: tx.status(),
+                transaction_id,
+            });
+        }
+
         self.transaction_repository
-            .save(&mut tx)
+            .remove(tx)
             .await
             .inspect_err(|e| {
-                error!("Failed to save transaction: {:?}", e);
+                error!("Failed to remove transaction: {:?}", e);
             })?;
 
-        let orders = tx
-            .orders()
-            .iter()
-            .filter(|order| to_refund_order_uuid_set.contains(&order.uuid()))
-            .map(|order| order.as_ref())
-            .collect::<Vec<_>>();
+        Ok(())
+    }
 
-        self.order_status_manager_service
-            .notify_status_change(transaction_id, tx.atomic(), &orders, OrderStatus::Cancelled)
-            .await;
+    #[instrument(skip(self))]
+    async fn refund_fare_difference(
+        &self,
+        user_id: UserId,
+        amount: TransactionAmountAbs,
+        waitlist_uuid: Uuid,
+    ) -> Result<Uuid, TransactionServiceError> {
+        let mut tx = Transaction::new_fare_refund(user_id, amount);
 
-        Ok(refund_tx.uuid())
+        self.transaction_repository
+            .save_fare_difference_refund(&mut tx, waitlist_uuid)
+            .await
+            .inspect_err(|e| error!("failed to save fare difference refund: {}", e))?;
+
+        Ok(tx.uuid())
     }
 
     #[instrument(skip(self))]
@@ -271,17 +847,49 @@ where
             status: transaction.status().to_string(),
             create_time: transaction.create_time().to_rfc3339(),
             pay_time: transaction.finish_time().map(|dt| dt.to_rfc3339()),
+            payment_deadline: transaction.payment_deadline().map(|dt| dt.to_rfc3339()),
             amount: transaction.amount().to_f64().unwrap_or(0.0),
+            coupon_discount: transaction.coupon_discount().to_f64().unwrap_or(0.0),
             orders: Vec::new(),
         };
 
+        let now = Transaction::now();
+        let refund_quotes = transaction
+            .orders()
+            .iter()
+            .map(|order| transaction.refund_quote(order.uuid(), &self.refund_policy, now))
+            .collect::<Vec<_>>();
+        let refund_summaries = transaction
+            .orders()
+            .iter()
+            .map(|order| transaction.order_refund_summary(order.uuid()))
+            .collect::<Vec<_>>();
+
         let origin_orders = transaction.into_orders();
 
         let mut orders = Vec::with_capacity(origin_orders.len());
 
-        for order in origin_orders {
+        for ((order, refund_quote), refund_summary) in origin_orders
+            .into_iter()
+            .zip(refund_quotes)
+            .zip(refund_summaries)
+        {
             debug!("Converting order to DTO: {:?}", order);
-            orders.push(self.order_service.convert_order_to_dto(order).await?)
+            let mut order_dto = self.order_service.convert_order_to_dto(order).await?;
+
+            let base = order_dto.base_mut();
+            if base.can_cancel
+                && let Some(refund_quote) = refund_quote
+            {
+                base.refundable_amount = refund_quote.refundable_amount().to_f64();
+                base.refund_fee = refund_quote.refund_fee.to_f64();
+            }
+            if let Some(refund_summary) = refund_summary {
+                base.refunded_amount = refund_summary.refunded_amount.to_f64();
+                base.remaining_amount = refund_summary.remaining_amount.to_f64();
+            }
+
+            orders.push(order_dto)
         }
 
         dto.orders = orders;
@@ -0,0 +1,239 @@
+use crate::WAITLIST_PROCESS_INTERVAL_SECONDS;
+use crate::domain::model::message::OrderNotify;
+use crate::domain::model::order::{Order, OrderStatus, TrainOrder};
+use crate::domain::model::transaction::{Transaction, TransactionAmountAbs};
+use crate::domain::model::waitlist::{Waitlist, WaitlistStatus};
+use crate::domain::repository::order::OrderRepository;
+use crate::domain::repository::waitlist::WaitlistRepository;
+use crate::domain::service::message::MessageService;
+use crate::domain::service::train_booking::TrainBookingService;
+use crate::domain::service::transaction::TransactionService;
+use crate::domain::service::waitlist::{WaitlistService, WaitlistServiceError};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::ops::Deref;
+use std::sync::Arc;
+use tracing::{error, info, instrument};
+use uuid::Uuid;
+
+pub struct WaitlistServiceImpl<TBS, WR, TS, OR, MS>
+where
+    TBS: TrainBookingService,
+    WR: WaitlistRepository,
+    TS: TransactionService,
+    OR: OrderRepository,
+    MS: MessageService,
+{
+    train_booking_service: Arc<TBS>,
+    waitlist_repository: Arc<WR>,
+    transaction_service: Arc<TS>,
+    order_repository: Arc<OR>,
+    message_service: Arc<MS>,
+}
+
+impl<TBS, WR, TS, OR, MS> WaitlistServiceImpl<TBS, WR, TS, OR, MS>
+where
+    TBS: TrainBookingService,
+    WR: WaitlistRepository,
+    TS: TransactionService,
+    OR: OrderRepository,
+    MS: MessageService,
+{
+    pub fn new(
+        train_booking_service: Arc<TBS>,
+        waitlist_repository: Arc<WR>,
+        transaction_service: Arc<TS>,
+        order_repository: Arc<OR>,
+        message_service: Arc<MS>,
+    ) -> Self {
+        Self {
+            train_booking_service,
+            waitlist_repository,
+            transaction_service,
+            order_repository,
+            message_service,
+        }
+    }
+
+    async fn find_train_order(&self, order_uuid: Uuid) -> Result<TrainOrder, WaitlistServiceError> {
+        self.order_repository
+            .find_train_order_by_uuid(order_uuid)
+            .await?
+            .ok_or(WaitlistServiceError::InvalidOrder(order_uuid))
+    }
+
+    async fn notify_user(&self, waitlist: &Waitlist, title: String, order: TrainOrder) {
+        let notify = OrderNotify::new_now(waitlist.user_id(), title, Box::new(order));
+
+        if let Err(e) = self
+            .message_service
+            .send_to_user(waitlist.user_id(), Box::new(notify))
+            .await
+        {
+            error!("Failed to notify user {}: {}", waitlist.user_id(), e);
+        }
+    }
+
+    /// 退还候补兑现后的差价并通知用户，差价已退还的候补仅通知用户
+    async fn settle_one(&self, mut waitlist: Waitlist) -> Result<(), WaitlistServiceError> {
+        let fare_difference = waitlist.fare_difference().unwrap_or(Decimal::ZERO);
+
+        if let Some(pending_fare_difference) = waitlist.pending_fare_difference() {
+            self.transaction_service
+                .refund_fare_difference(
+                    waitlist.user_id(),
+                    TransactionAmountAbs::from(pending_fare_difference),
+                    waitlist.uuid(),
+                )
+                .await?;
+
+            waitlist.mark_fare_difference_refunded()?;
+        }
+
+        let order = self.find_train_order(waitlist.train_order_uuid()).await?;
+
+        let seat_type_name = waitlist
+            .fulfilled_seat_type()
+            .map(|seat_type_name| seat_type_name.deref().to_string())
+            .unwrap_or_default();
+
+        let title = if fare_difference > Decimal::ZERO {
+            format!(
+                "候补兑现成功，已为您分配{}，退还差价{}元",
+                seat_type_name, fare_difference
+            )
+        } else {
+            format!("候补兑现成功，已为您分配{}", seat_type_name)
+        };
+
+        self.notify_user(&waitlist, title, order).await;
+
+        Ok(())
+    }
+
+    /// 处理已过截止时间的候补：已预付的全额退款并通知用户，未预付的直接关闭
+    async fn refund_expired(&self, mut waitlist: Waitlist) -> Result<(), WaitlistServiceError> {
+        let order = self.find_train_order(waitlist.train_order_uuid()).await?;
+
+        if order.order_status() != OrderStatus::Paid || order.already_refund() {
+            waitlist.cancel()?;
+            self.waitlist_repository.save(&mut waitlist).await?;
+
+            info!("Waitlist {} closed without prepayment", waitlist.uuid());
+            return Ok(());
+        }
+
+        self.transaction_service
+            .refund_transaction(
+                waitlist.transaction_uuid(),
+                &[Box::new(order.clone()) as Box<dyn Order>],
+            )
+            .await?;
+
+        waitlist.refund()?;
+        self.waitlist_repository.save(&mut waitlist).await?;
+
+        info!("Waitlist {} expired and refunded", waitlist.uuid());
+
+        self.notify_user(
+            &waitlist,
+            format!(
+                "候补未能在截止时间前兑现，已退还预付款{}元",
+                waitlist.prepaid_amount()
+            ),
+            order,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn process_waitlists(&self) -> Result<(), WaitlistServiceError> {
+        for train_schedule_id in self
+            .waitlist_repository
+            .find_pending_train_schedule_ids()
+            .await?
+        {
+            match self
+                .train_booking_service
+                .fulfil_waitlist(train_schedule_id)
+                .await
+            {
+                Ok(fulfilled_waitlists) => self.settle_fulfilled(fulfilled_waitlists).await?,
+                Err(e) => error!(
+                    "Failed to fulfil waitlist for train schedule {}: {}",
+                    train_schedule_id, e
+                ),
+            }
+        }
+
+        // 此前兑现后差价退还失败的候补
+        self.settle_fulfilled(
+            self.waitlist_repository
+                .find_fare_difference_unrefunded()
+                .await?,
+        )
+        .await?;
+
+        let expired_waitlists = self
+            .train_booking_service
+            .expire_waitlist(Transaction::now())
+            .await?;
+
+        info!("{} waitlists expired", expired_waitlists.len());
+
+        // 包括此前退款失败、仍处于`Expired`状态的候补
+        for waitlist in self
+            .waitlist_repository
+            .find_by_status(WaitlistStatus::Expired)
+            .await?
+        {
+            let waitlist_uuid = waitlist.uuid();
+
+            if let Err(e) = self.refund_expired(waitlist).await {
+                error!("Failed to refund expired waitlist {}: {}", waitlist_uuid, e);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<TBS, WR, TS, OR, MS> WaitlistService for WaitlistServiceImpl<TBS, WR, TS, OR, MS>
+where
+    TBS: TrainBookingService,
+    WR: WaitlistRepository,
+    TS: TransactionService,
+    OR: OrderRepository,
+    MS: MessageService,
+{
+    #[instrument(skip(self))]
+    async fn settle_fulfilled(&self, waitlists: Vec<Waitlist>) -> Result<(), WaitlistServiceError> {
+        for waitlist in waitlists {
+            let waitlist_uuid = waitlist.uuid();
+
+            if let Err(e) = self.settle_one(waitlist).await {
+                error!("Failed to settle waitlist {}: {}", waitlist_uuid, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[instrument(skip_all)]
+    async fn waitlist_daemon(&self) {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+            WAITLIST_PROCESS_INTERVAL_SECONDS,
+        ));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = self.process_waitlists().await {
+                error!("Failed to process waitlists: {}", e);
+            }
+        }
+    }
+}
@@ -43,3 +43,17 @@ pub const DB_CHUNK_SIZE: usize = 4096;
 pub const MAX_CONCURRENT_WEBSOCKET_SESSION_PER_USER: usize = 3;
 
 pub const ORDER_STATUS_UPDATE_INTERVAL_SECONDS: u64 = 60; // seconds
+
+pub const TRANSACTION_PAYMENT_TIMEOUT_MINUTES: i64 = 15; // minutes
+
+pub const SEAT_HOLD_RELEASE_INTERVAL_SECONDS: u64 = 60; // seconds
+
+pub const WAITLIST_PROCESS_INTERVAL_SECONDS: u64 = 60; // seconds
+
+pub const LEDGER_RECONCILIATION_INTERVAL_SECONDS: u64 = 3600; // seconds
+
+pub const IDEMPOTENCY_KEY_LOCK_SECONDS: i64 = 60; // seconds
+
+pub const IDEMPOTENCY_KEY_RETENTION_HOURS: i64 = 24; // hours
+
+pub const IDEMPOTENCY_KEY_CLEANUP_INTERVAL_SECONDS: u64 = 3600; // seconds
@@ -22,6 +22,9 @@ pub struct Model {
     pub active_time: DateTimeWithTimeZone,
     pub complete_time: DateTimeWithTimeZone,
     pub status: String,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub refunded_amount: Decimal,
+    pub refunded_quantity: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -3,22 +3,29 @@
 pub mod prelude;
 
 pub mod city;
+pub mod coupon;
 pub mod dish;
 pub mod dish_order;
 pub mod hotel;
 pub mod hotel_order;
 pub mod hotel_rating;
 pub mod hotel_room_type;
+pub mod idempotency_key;
+pub mod journal_entry;
+pub mod journal_line;
 pub mod message;
 pub mod occupied_room;
 pub mod occupied_seat;
+pub mod order_status_history;
 pub mod person_info;
+pub mod refund_line;
 pub mod route;
 pub mod seat_availability;
 pub mod seat_type;
 pub mod seat_type_in_train_type;
 pub mod seat_type_mapping;
 pub mod station;
+pub mod station_transfer;
 pub mod takeaway_dish;
 pub mod takeaway_order;
 pub mod takeaway_shop;
@@ -28,3 +35,4 @@ pub mod train_schedule;
 pub mod train_type;
 pub mod transaction;
 pub mod user;
+pub mod waitlist;
@@ -12,6 +12,7 @@ pub struct Model {
     pub arrival_time: i32,
     pub departure_time: i32,
     pub order: i32,
+    pub distance: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
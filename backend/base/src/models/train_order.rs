@@ -24,6 +24,12 @@ pub struct Model {
     pub status: String,
     pub order_seat_type: String,
     pub preferred_seat_location: Option<String>,
+    pub group_seating: Option<String>,
+    pub ticket_category: String,
+    pub seat_hold_expire_time: Option<DateTimeWithTimeZone>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub refunded_amount: Decimal,
+    pub refunded_quantity: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
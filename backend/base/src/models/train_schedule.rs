@@ -27,6 +27,8 @@ pub enum Relation {
     Train,
     #[sea_orm(has_many = "super::train_order::Entity")]
     TrainOrder,
+    #[sea_orm(has_many = "super::waitlist::Entity")]
+    Waitlist,
 }
 
 impl Related<super::seat_availability::Entity> for Entity {
@@ -47,4 +49,10 @@ impl Related<super::train_order::Entity> for Entity {
     }
 }
 
+impl Related<super::waitlist::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Waitlist.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -15,10 +15,31 @@ pub struct Model {
     pub user_id: i32,
     pub atomic: bool,
     pub uuid: Uuid,
+    pub coupon_id: Option<i32>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub coupon_discount: Decimal,
+    pub coupon_status: Option<String>,
+    #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
+    pub refund_fee: Decimal,
+    pub payment_deadline: Option<DateTimeWithTimeZone>,
+    pub payment_provider: Option<String>,
+    #[sea_orm(unique)]
+    pub external_payment_id: Option<Uuid>,
+    pub kind: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::coupon::Entity",
+        from = "Column::CouponId",
+        to = "super::coupon::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Coupon,
+    #[sea_orm(has_many = "super::refund_line::Entity")]
+    RefundLine,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -29,6 +50,18 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::coupon::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Coupon.def()
+    }
+}
+
+impl Related<super::refund_line::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::RefundLine.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
@@ -28,12 +28,20 @@ pub struct Model {
 pub enum Relation {
     #[sea_orm(has_many = "super::hotel_rating::Entity")]
     HotelRating,
+    #[sea_orm(has_many = "super::idempotency_key::Entity")]
+    IdempotencyKey,
+    #[sea_orm(has_many = "super::journal_line::Entity")]
+    JournalLine,
     #[sea_orm(has_many = "super::message::Entity")]
     Message,
+    #[sea_orm(has_many = "super::order_status_history::Entity")]
+    OrderStatusHistory,
     #[sea_orm(has_many = "super::person_info::Entity")]
     PersonInfo,
     #[sea_orm(has_many = "super::transaction::Entity")]
     Transaction,
+    #[sea_orm(has_many = "super::waitlist::Entity")]
+    Waitlist,
 }
 
 impl Related<super::hotel_rating::Entity> for Entity {
@@ -42,12 +50,30 @@ impl Related<super::hotel_rating::Entity> for Entity {
     }
 }
 
+impl Related<super::idempotency_key::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::IdempotencyKey.def()
+    }
+}
+
+impl Related<super::journal_line::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::JournalLine.def()
+    }
+}
+
 impl Related<super::message::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Message.def()
     }
 }
 
+impl Related<super::order_status_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrderStatusHistory.def()
+    }
+}
+
 impl Related<super::person_info::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::PersonInfo.def()
@@ -60,4 +86,10 @@ impl Related<super::transaction::Entity> for Entity {
     }
 }
 
+impl Related<super::waitlist::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Waitlist.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
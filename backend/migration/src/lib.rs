@@ -45,6 +45,23 @@ mod m20250501_034231_modify_train_add_default_line_id;
 mod m20250503_032006_modify_transaction_add_uuid;
 mod m20250503_052335_create_balance_view;
 mod m20250607_074636_create_hotel_trigger;
+mod m20250612_061530_modify_train_order_add_group_seating;
+mod m20250613_023417_create_station_transfer;
+mod m20250615_021030_modify_route_add_distance;
+mod m20250616_034512_modify_train_order_add_ticket_category;
+mod m20250617_021508_create_coupon;
+mod m20250618_030412_modify_transaction_add_refund_fee;
+mod m20250619_024517_modify_transaction_add_payment_deadline;
+mod m20250620_031742_modify_train_order_add_seat_hold_expire_time;
+mod m20250621_022036_create_waitlist;
+mod m20250622_015324_create_order_status_history;
+mod m20250623_021407_modify_transaction_add_external_payment;
+mod m20250624_023145_create_journal_entry;
+mod m20250625_013052_create_idempotency_key;
+mod m20250626_022418_create_refund_line;
+mod m20250627_021846_modify_transaction_add_kind;
+mod m20250628_014203_modify_idempotency_key_add_locked_until;
+mod m20250629_013517_modify_waitlist_add_fare_difference_refunded;
 
 pub struct Migrator;
 
@@ -97,6 +114,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20250503_032006_modify_transaction_add_uuid::Migration),
             Box::new(m20250503_052335_create_balance_view::Migration),
             Box::new(m20250607_074636_create_hotel_trigger::Migration),
+            Box::new(m20250612_061530_modify_train_order_add_group_seating::Migration),
+            Box::new(m20250613_023417_create_station_transfer::Migration),
+            Box::new(m20250615_021030_modify_route_add_distance::Migration),
+            Box::new(m20250616_034512_modify_train_order_add_ticket_category::Migration),
+            Box::new(m20250617_021508_create_coupon::Migration),
+            Box::new(m20250618_030412_modify_transaction_add_refund_fee::Migration),
+            Box::new(m20250619_024517_modify_transaction_add_payment_deadline::Migration),
+            Box::new(m20250620_031742_modify_train_order_add_seat_hold_expire_time::Migration),
+            Box::new(m20250621_022036_create_waitlist::Migration),
+            Box::new(m20250622_015324_create_order_status_history::Migration),
+            Box::new(m20250623_021407_modify_transaction_add_external_payment::Migration),
+            Box::new(m20250624_023145_create_journal_entry::Migration),
+            Box::new(m20250625_013052_create_idempotency_key::Migration),
+            Box::new(m20250626_022418_create_refund_line::Migration),
+            Box::new(m20250627_021846_modify_transaction_add_kind::Migration),
+            Box::new(m20250628_014203_modify_idempotency_key_add_locked_until::Migration),
+            Box::new(m20250629_013517_modify_waitlist_add_fare_difference_refunded::Migration),
         ]
     }
 }
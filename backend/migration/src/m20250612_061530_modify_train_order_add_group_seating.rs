@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum TrainOrder {
+    Table,
+    GroupSeating,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TrainOrder::Table)
+                    .add_column(ColumnDef::new(TrainOrder::GroupSeating).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TrainOrder::Table)
+                    .drop_column(TrainOrder::GroupSeating)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::m20250411_010614_create_station::Station;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum StationTransfer {
+    Table,
+    Id,
+    FromStationId,
+    ToStationId,
+    TransferTime,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(StationTransfer::Table)
+                    .if_not_exists()
+                    .col(pk_auto(StationTransfer::Id))
+                    .col(integer(StationTransfer::FromStationId).not_null())
+                    .col(integer(StationTransfer::ToStationId).not_null())
+                    .col(integer(StationTransfer::TransferTime).not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StationTransfer::Table, StationTransfer::FromStationId)
+                            .to(Station::Table, Station::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(StationTransfer::Table, StationTransfer::ToStationId)
+                            .to(Station::Table, Station::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_station_transfer_from_to_unique")
+                    .table(StationTransfer::Table)
+                    .col(StationTransfer::FromStationId)
+                    .col(StationTransfer::ToStationId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(StationTransfer::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum TrainOrder {
+    Table,
+    TicketCategory,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TrainOrder::Table)
+                    .add_column(
+                        ColumnDef::new(TrainOrder::TicketCategory)
+                            .string()
+                            .not_null()
+                            .default("adult"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TrainOrder::Table)
+                    .drop_column(TrainOrder::TicketCategory)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Coupon {
+    Table,
+    Id,
+    Code,
+    DiscountType,
+    DiscountValue,
+    MinSpend,
+    ApplicableOrderTypes,
+    ValidFrom,
+    ExpireTime,
+    PerUserLimit,
+    GlobalLimit,
+    RedeemedCount,
+}
+
+#[derive(DeriveIden)]
+pub enum Transaction {
+    Table,
+    CouponId,
+    CouponDiscount,
+    CouponStatus,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Coupon::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Coupon::Id))
+                    .col(string(Coupon::Code).not_null().unique_key())
+                    .col(string(Coupon::DiscountType).not_null())
+                    .col(
+                        decimal_len(Coupon::DiscountValue, 10, 2)
+                            .not_null()
+                            .check(Expr::col(Coupon::DiscountValue).gt(0)),
+                    )
+                    .col(decimal_len(Coupon::MinSpend, 10, 2).not_null().default(0))
+                    .col(string(Coupon::ApplicableOrderTypes).not_null().default(""))
+                    .col(timestamp_with_time_zone(Coupon::ValidFrom).not_null())
+                    .col(timestamp_with_time_zone(Coupon::ExpireTime).not_null())
+                    .col(integer_null(Coupon::PerUserLimit))
+                    .col(integer_null(Coupon::GlobalLimit))
+                    .col(
+                        integer(Coupon::RedeemedCount)
+                            .not_null()
+                            .default(0)
+                            .check(Expr::col(Coupon::RedeemedCount).gte(0)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(ColumnDef::new(Transaction::CouponId).integer().null())
+                    .add_column(
+                        ColumnDef::new(Transaction::CouponDiscount)
+                            .decimal_len(10, 2)
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(Transaction::CouponStatus).string().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_transaction_coupon_id")
+                            .from_tbl(Transaction::Table)
+                            .from_col(Transaction::CouponId)
+                            .to_tbl(Coupon::Table)
+                            .to_col(Coupon::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_foreign_key(Alias::new("fk_transaction_coupon_id"))
+                    .drop_column(Transaction::CouponId)
+                    .drop_column(Transaction::CouponDiscount)
+                    .drop_column(Transaction::CouponStatus)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Coupon::Table).to_owned())
+            .await
+    }
+}
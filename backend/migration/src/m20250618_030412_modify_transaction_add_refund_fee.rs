@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Transaction {
+    Table,
+    RefundFee,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(
+                        ColumnDef::new(Transaction::RefundFee)
+                            .decimal_len(10, 2)
+                            .not_null()
+                            .default(0)
+                            .check(Expr::col(Transaction::RefundFee).gte(0)),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(Transaction::RefundFee)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
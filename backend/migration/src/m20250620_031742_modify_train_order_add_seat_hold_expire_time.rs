@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum TrainOrder {
+    Table,
+    SeatHoldExpireTime,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TrainOrder::Table)
+                    .add_column(
+                        ColumnDef::new(TrainOrder::SeatHoldExpireTime)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TrainOrder::Table)
+                    .drop_column(TrainOrder::SeatHoldExpireTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
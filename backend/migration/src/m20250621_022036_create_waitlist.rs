@@ -0,0 +1,94 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Waitlist {
+    Table,
+    Id,
+    Uuid,
+    UserId,
+    TrainOrderUuid,
+    TransactionUuid,
+    TrainScheduleId,
+    BeginStationId,
+    EndStationId,
+    SeatOptions,
+    Deadline,
+    CreateTime,
+    Status,
+    FulfilledSeatType,
+}
+
+#[derive(DeriveIden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum TrainSchedule {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Waitlist::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Waitlist::Id))
+                    .col(uuid(Waitlist::Uuid).unique_key())
+                    .col(integer(Waitlist::UserId))
+                    .col(uuid(Waitlist::TrainOrderUuid).unique_key())
+                    .col(uuid(Waitlist::TransactionUuid))
+                    .col(integer(Waitlist::TrainScheduleId))
+                    .col(integer(Waitlist::BeginStationId))
+                    .col(integer(Waitlist::EndStationId))
+                    .col(json(Waitlist::SeatOptions))
+                    .col(timestamp_with_time_zone(Waitlist::Deadline))
+                    .col(timestamp_with_time_zone(Waitlist::CreateTime))
+                    .col(string(Waitlist::Status))
+                    .col(string_null(Waitlist::FulfilledSeatType))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Waitlist::Table, Waitlist::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Waitlist::Table, Waitlist::TrainScheduleId)
+                            .to(TrainSchedule::Table, TrainSchedule::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_waitlist_train_schedule_id_status")
+                    .table(Waitlist::Table)
+                    .col(Waitlist::TrainScheduleId)
+                    .col(Waitlist::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Waitlist::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
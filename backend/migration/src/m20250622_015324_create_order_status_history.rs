@@ -0,0 +1,73 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum OrderStatusHistory {
+    Table,
+    Id,
+    Uuid,
+    OrderUuid,
+    FromStatus,
+    ToStatus,
+    Actor,
+    ActorUserId,
+    Reason,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrderStatusHistory::Table)
+                    .if_not_exists()
+                    .col(pk_auto(OrderStatusHistory::Id))
+                    .col(uuid(OrderStatusHistory::Uuid).unique_key())
+                    .col(uuid(OrderStatusHistory::OrderUuid))
+                    .col(string(OrderStatusHistory::FromStatus))
+                    .col(string(OrderStatusHistory::ToStatus))
+                    .col(string(OrderStatusHistory::Actor))
+                    .col(integer_null(OrderStatusHistory::ActorUserId))
+                    .col(string(OrderStatusHistory::Reason))
+                    .col(timestamp_with_time_zone(OrderStatusHistory::CreateTime))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(OrderStatusHistory::Table, OrderStatusHistory::ActorUserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_order_status_history_order_uuid")
+                    .table(OrderStatusHistory::Table)
+                    .col(OrderStatusHistory::OrderUuid)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OrderStatusHistory::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
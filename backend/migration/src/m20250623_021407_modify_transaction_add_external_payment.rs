@@ -0,0 +1,47 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Transaction {
+    Table,
+    PaymentProvider,
+    ExternalPaymentId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(ColumnDef::new(Transaction::PaymentProvider).string().null())
+                    .add_column(
+                        ColumnDef::new(Transaction::ExternalPaymentId)
+                            .uuid()
+                            .null()
+                            .unique_key(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(Transaction::ExternalPaymentId)
+                    .drop_column(Transaction::PaymentProvider)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::sea_orm::{DatabaseBackend, Statement};
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum JournalEntry {
+    Table,
+    Id,
+    Uuid,
+    TransactionUuid,
+    Kind,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+pub enum JournalLine {
+    Table,
+    Id,
+    Uuid,
+    EntryUuid,
+    Account,
+    UserId,
+    Amount,
+}
+
+#[derive(DeriveIden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(JournalEntry::Table)
+                    .if_not_exists()
+                    .col(pk_auto(JournalEntry::Id))
+                    .col(uuid(JournalEntry::Uuid).unique_key())
+                    .col(uuid_null(JournalEntry::TransactionUuid))
+                    .col(string(JournalEntry::Kind))
+                    .col(timestamp_with_time_zone(JournalEntry::CreateTime))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_journal_entry_transaction_uuid")
+                    .table(JournalEntry::Table)
+                    .col(JournalEntry::TransactionUuid)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(JournalLine::Table)
+                    .if_not_exists()
+                    .col(pk_auto(JournalLine::Id))
+                    .col(uuid(JournalLine::Uuid).unique_key())
+                    .col(uuid(JournalLine::EntryUuid))
+                    .col(string(JournalLine::Account))
+                    .col(integer_null(JournalLine::UserId))
+                    .col(decimal_len(JournalLine::Amount, 10, 2))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JournalLine::Table, JournalLine::EntryUuid)
+                            .to(JournalEntry::Table, JournalEntry::Uuid)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JournalLine::Table, JournalLine::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_journal_line_entry_uuid")
+                    .table(JournalLine::Table)
+                    .col(JournalLine::EntryUuid)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_journal_line_account_user_id")
+                    .table(JournalLine::Table)
+                    .col(JournalLine::Account)
+                    .col(JournalLine::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // 将上线账本前已存在的余额导入为期初余额凭证
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                DatabaseBackend::Postgres,
+                "WITH opening AS (
+    SELECT user_id, balance, gen_random_uuid() AS entry_uuid
+    FROM balance
+    WHERE balance <> 0
+), entry AS (
+    INSERT INTO journal_entry (uuid, transaction_uuid, kind, create_time)
+    SELECT entry_uuid, NULL, 'opening', now()
+    FROM opening
+)
+INSERT INTO journal_line (uuid, entry_uuid, account, user_id, amount)
+SELECT gen_random_uuid(), entry_uuid, 'user_wallet', user_id, balance
+FROM opening
+UNION ALL
+SELECT gen_random_uuid(), entry_uuid, 'opening_balance', NULL, -balance
+FROM opening",
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JournalLine::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(JournalEntry::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
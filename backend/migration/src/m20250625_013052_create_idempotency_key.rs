@@ -0,0 +1,72 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum IdempotencyKey {
+    Table,
+    Id,
+    UserId,
+    Scope,
+    Key,
+    RequestHash,
+    Response,
+    CreateTime,
+}
+
+#[derive(DeriveIden)]
+pub enum User {
+    Table,
+    Id,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdempotencyKey::Table)
+                    .if_not_exists()
+                    .col(pk_auto(IdempotencyKey::Id))
+                    .col(integer(IdempotencyKey::UserId))
+                    .col(string(IdempotencyKey::Scope))
+                    .col(string(IdempotencyKey::Key))
+                    .col(string(IdempotencyKey::RequestHash))
+                    .col(text_null(IdempotencyKey::Response))
+                    .col(timestamp_with_time_zone(IdempotencyKey::CreateTime))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(IdempotencyKey::Table, IdempotencyKey::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_idempotency_key_user_id_scope_key")
+                    .table(IdempotencyKey::Table)
+                    .col(IdempotencyKey::UserId)
+                    .col(IdempotencyKey::Scope)
+                    .col(IdempotencyKey::Key)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IdempotencyKey::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::sea_orm::{DatabaseBackend, Statement};
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum RefundLine {
+    Table,
+    Id,
+    Uuid,
+    TransactionId,
+    OrderUuid,
+    Quantity,
+    Amount,
+    Fee,
+}
+
+#[derive(DeriveIden)]
+pub enum Transaction {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+pub enum TrainOrder {
+    Table,
+}
+
+#[derive(DeriveIden)]
+pub enum HotelOrder {
+    Table,
+}
+
+#[derive(DeriveIden)]
+pub enum DishOrder {
+    Table,
+}
+
+#[derive(DeriveIden)]
+pub enum TakeawayOrder {
+    Table,
+}
+
+#[derive(DeriveIden)]
+pub enum Order {
+    RefundedAmount,
+    RefundedQuantity,
+}
+
+/// 将上线部分退款前已全额退款的订单的累计退款设置为其实付金额（按价格比例扣除优惠金额）与数量
+fn backfill_refunded_sql(table: &str, quantity: &str) -> String {
+    format!(
+        r#"WITH order_gross AS (
+    SELECT pay_transaction_id, price AS gross FROM train_order
+    UNION ALL
+    SELECT pay_transaction_id, price * amount FROM hotel_order
+    UNION ALL
+    SELECT pay_transaction_id, price * amount FROM dish_order
+    UNION ALL
+    SELECT pay_transaction_id, price * amount FROM takeaway_order
+), transaction_gross AS (
+    SELECT pay_transaction_id, SUM(gross) AS gross
+    FROM order_gross
+    WHERE pay_transaction_id IS NOT NULL
+    GROUP BY pay_transaction_id
+)
+UPDATE {table} o
+SET refunded_quantity = {quantity},
+    refunded_amount = o.price * {quantity} - CASE
+        WHEN t.coupon_discount = 0 OR g.gross = 0 THEN 0
+        ELSE ROUND(t.coupon_discount * o.price * {quantity} / g.gross, 2)
+    END
+FROM "transaction" t
+    INNER JOIN transaction_gross g ON g.pay_transaction_id = t.id
+WHERE o.pay_transaction_id = t.id
+  AND o.refund_transaction_id IS NOT NULL"#
+    )
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RefundLine::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RefundLine::Id))
+                    .col(uuid(RefundLine::Uuid).unique_key())
+                    .col(integer(RefundLine::TransactionId))
+                    .col(uuid(RefundLine::OrderUuid))
+                    .col(integer_null(RefundLine::Quantity))
+                    .col(decimal_len(RefundLine::Amount, 10, 2))
+                    .col(decimal_len(RefundLine::Fee, 10, 2))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(RefundLine::Table, RefundLine::TransactionId)
+                            .to(Transaction::Table, Transaction::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_refund_line_order_uuid")
+                    .table(RefundLine::Table)
+                    .col(RefundLine::OrderUuid)
+                    .to_owned(),
+            )
+            .await?;
+
+        for table in [
+            TrainOrder::Table.into_iden(),
+            HotelOrder::Table.into_iden(),
+            DishOrder::Table.into_iden(),
+            TakeawayOrder::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .add_column(
+                            ColumnDef::new(Order::RefundedAmount)
+                                .decimal_len(10, 2)
+                                .not_null()
+                                .default(0),
+                        )
+                        .add_column(
+                            ColumnDef::new(Order::RefundedQuantity)
+                                .integer()
+                                .not_null()
+                                .default(0),
+                        )
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        for (table, quantity) in [
+            ("train_order", "1"),
+            ("hotel_order", "o.amount"),
+            ("dish_order", "o.amount"),
+            ("takeaway_order", "o.amount"),
+        ] {
+            manager
+                .get_connection()
+                .execute(Statement::from_string(
+                    DatabaseBackend::Postgres,
+                    backfill_refunded_sql(table, quantity),
+                ))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [
+            TrainOrder::Table.into_iden(),
+            HotelOrder::Table.into_iden(),
+            DishOrder::Table.into_iden(),
+            TakeawayOrder::Table.into_iden(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(table)
+                        .drop_column(Order::RefundedAmount)
+                        .drop_column(Order::RefundedQuantity)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .drop_table(Table::drop().table(RefundLine::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,112 @@
+use crate::sea_orm::{DatabaseBackend, Statement};
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Transaction {
+    Table,
+    Kind,
+    UserId,
+    FinishTime,
+}
+
+/// 按已有数据推断交易类型：
+/// - 金额非负的交易为支付
+/// - 通过外部支付网关完成的交易为充值
+/// - 有记账凭证的交易按凭证类型确定
+/// - 收取了手续费、有退款明细或关联了订单的交易为退款
+/// - 其余交易为充值（记账凭证上线前的差价退款交易无法与充值区分，按充值处理）
+const BACKFILL_KIND_SQL: &str = r#"UPDATE "transaction" t
+SET kind = CASE
+    WHEN t.amount >= 0 THEN 'pay'
+    WHEN t.payment_provider IS NOT NULL THEN 'recharge'
+    WHEN EXISTS (
+        SELECT 1 FROM journal_entry e WHERE e.transaction_uuid = t.uuid AND e.kind = 'recharge'
+    ) THEN 'recharge'
+    WHEN EXISTS (
+        SELECT 1 FROM journal_entry e WHERE e.transaction_uuid = t.uuid AND e.kind = 'refund'
+    ) THEN 'refund'
+    WHEN t.refund_fee > 0
+        OR EXISTS (SELECT 1 FROM refund_line l WHERE l.transaction_id = t.id)
+        OR EXISTS (
+            SELECT 1 FROM train_order o
+            WHERE o.pay_transaction_id = t.id OR o.refund_transaction_id = t.id
+        )
+        OR EXISTS (
+            SELECT 1 FROM hotel_order o
+            WHERE o.pay_transaction_id = t.id OR o.refund_transaction_id = t.id
+        )
+        OR EXISTS (
+            SELECT 1 FROM dish_order o
+            WHERE o.pay_transaction_id = t.id OR o.refund_transaction_id = t.id
+        )
+        OR EXISTS (
+            SELECT 1 FROM takeaway_order o
+            WHERE o.pay_transaction_id = t.id OR o.refund_transaction_id = t.id
+        ) THEN 'refund'
+    ELSE 'recharge'
+END"#;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .add_column(
+                        ColumnDef::new(Transaction::Kind)
+                            .string()
+                            .not_null()
+                            .default("pay"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute(Statement::from_string(
+                DatabaseBackend::Postgres,
+                BACKFILL_KIND_SQL,
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_transaction_user_id_finish_time")
+                    .table(Transaction::Table)
+                    .col(Transaction::UserId)
+                    .col(Transaction::FinishTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_transaction_user_id_finish_time")
+                    .table(Transaction::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Transaction::Table)
+                    .drop_column(Transaction::Kind)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum IdempotencyKey {
+    Table,
+    LockedUntil,
+    CreateTime,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 已有的处理中登记视为已失效，允许相同请求重新登记
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IdempotencyKey::Table)
+                    .add_column(
+                        ColumnDef::new(IdempotencyKey::LockedUntil)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_idempotency_key_create_time")
+                    .table(IdempotencyKey::Table)
+                    .col(IdempotencyKey::CreateTime)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_idempotency_key_create_time")
+                    .table(IdempotencyKey::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(IdempotencyKey::Table)
+                    .drop_column(IdempotencyKey::LockedUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum Waitlist {
+    Table,
+    Status,
+    FareDifferenceRefunded,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Waitlist::Table)
+                    .add_column(
+                        ColumnDef::new(Waitlist::FareDifferenceRefunded)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 已兑现的候补此前在兑现时已尝试退还差价，视为已退还，避免重复退款
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Waitlist::Table)
+                    .value(Waitlist::FareDifferenceRefunded, true)
+                    .and_where(Expr::col(Waitlist::Status).eq("fulfilled"))
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Waitlist::Table)
+                    .drop_column(Waitlist::FareDifferenceRefunded)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
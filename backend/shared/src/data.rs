@@ -4,6 +4,7 @@ use std::collections::HashMap;
 // city -> province
 pub type CityData = HashMap<String, String>;
 pub type StationData = Vec<StationDataItem>;
+pub type IntraCityTransferData = Vec<IntraCityTransferItem>;
 pub type TrainTypeData = Vec<TrainTypeInfoItem>;
 pub type TrainNumberData = Vec<TrainNumberInfoItem>;
 
@@ -23,6 +24,25 @@ pub struct StationDataItem {
     pub city: String,
 }
 
+// 同城两站之间的换乘时间（单位：秒），视为双向
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct StationTransferTime {
+    pub from: String,
+    pub to: String,
+    pub time: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct IntraCityTransferItem {
+    pub city: String,
+    // 未在`stations`中列出的同城车站之间的换乘时间，为空则不允许换站
+    pub default_time: Option<u32>,
+    #[serde(default)]
+    pub stations: Vec<StationTransferTime>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct SeatLocationInfo {
     pub carriage: i32,
@@ -55,6 +75,9 @@ pub struct RouteStationInfo {
     pub arrival_time: u32,
     #[serde(rename = "depatureTime")]
     pub departure_time: u32,
+    /// 距上一停靠站的里程（公里），始发站为0
+    #[serde(default)]
+    pub distance: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]